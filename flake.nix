@@ -85,6 +85,7 @@
             postInstall = ''
               # Install desktop file
               install -Dm644 resources/app.desktop $out/share/applications/com.marcos.RadioApplet.desktop
+              install -Dm644 resources/deeplink.desktop $out/share/applications/com.marcos.RadioApplet.DeepLink.desktop
 
               # Install metainfo
               install -Dm644 resources/app.metainfo.xml $out/share/appdata/com.marcos.RadioApplet.metainfo.xml
@@ -1,17 +1,351 @@
 app-title = Radio for COSMIC
 window-title = Radio for COSMIC
-search-placeholder = Search stations (e.g., Jazz)...
+search-placeholder = Search stations (e.g., Jazz or tag:jazz country:BR)...
 search-button = Search
+search-field-name = Name
+search-field-tag = Tag
+search-field-country = Country
+search-field-language = Language
 searching-status = Searching...
+searching-next-mirror = Trying another server ({ $host })...
+searching-retry = { $host } is busy, retrying ({ $attempt }/{ $max })...
+cancel-search = Cancel
 error-message = Error:
+error-timeout = The station directory did not answer within { $seconds } seconds.
+error-rate-limited = Too many requests. Please wait a moment.
+error-server = The station directory returned an error ({ $status }).
+error-network = Could not reach the station directory. Check your connection.
+error-bad-response = The station directory sent a response that could not be read.
+error-station-not-found = The station could not be found.
+error-invalid-url = The address is not a valid http(s) URL.
+error-invalid-icon-url = The icon address is not a valid http(s) URL.
+retry = Retry
+connecting = Connecting...
+buffering = Buffering... { $percent }%
+live = ● Live
+stream-offline = Station appears offline (HTTP { $status }).
+stream-timeout = Station did not respond within { $seconds } seconds.
+stream-tls = Secure connection to the station failed.
+stream-unreachable = Could not connect to the station.
+stream-rejected = This stream address is not allowed.
+player-failed = Could not start the player. Is mpv installed?
+stream-ended = The stream stopped.
 favorites-header = My Favorites:
 no-favorites = No favorites saved.
-back-to-favorites = ← Back to Favorites
+suggestions-genres = Start with a genre:
+suggestions-nearby = Popular near you:
+suggestions-popular = Popular right now:
+genre-pop = Pop
+genre-rock = Rock
+genre-jazz = Jazz
+genre-news = News
+genre-classical = Classical
+genre-electronic = Electronic
+genre-lofi = Lo-fi
+favorites-page = Page { $page } of { $pages }
+favorites-filter-placeholder = Filter favorites by name or tag
+favorites-filter-empty = No favorites match.
+favorite-added = Added { $name } to favorites
+favorite-removed = Removed { $name } from favorites
+folder-picker = Add { $name } to a folder?
+folder-none = No folder
+manage-favorites = Manage
+manage-done = Done
+custom-station-name = Station name (optional)
+custom-station-url = Stream URL
+custom-station-icon = Icon URL (optional)
+custom-station-add = Add station
+custom-station-cancel = Cancel
+custom-station-exists = This station is already in your favorites.
+select-all = Select all
+bulk-tag-placeholder = Tag
+bulk-add-tag = Add tag
+bulk-remove-tag = Remove tag
+bulk-folder-placeholder = Folder (empty to clear)
+bulk-move-folder = Move
+bulk-archive = Archive
+bulk-export = Export
+bulk-remove = Remove
+bulk-remove-confirm = { $count ->
+    [one] Remove 1 favorite?
+   *[other] Remove { $count } favorites?
+}
+refresh-favorites = Refresh details
+archived-header = Archived:
+restore-favorite = Restore
+edit-favorite-save = Save
+edit-favorite-cancel = Cancel
+save-search-placeholder = Name for this search
+save-search = Save search
+saved-searches-header = Saved Searches:
+clear-search = Clear
+search-hint = Type to search, or narrow it down with tag:jazz country:BR bitrate>128
 search-results-header = Search Results:
+filter-any-country = Any country
+filter-any-language = Any language
+filter-clear = Clear filters
+station-variants = { $count ->
+    [one] 1 variant
+   *[other] { $count } variants
+}
+station-votes = { $count ->
+    [one] 1 vote
+   *[other] { $count } votes
+}
+station-clicks = { $count ->
+    [one] 1 play
+   *[other] { $count } plays
+}
+
+# Tabs
+tab-favorites = Favorites
+tab-favorites-count = Favorites ({ $count })
+tab-search = Search
+tab-browse = Browse
+tab-history = History
+tab-settings = Settings
+loading-popular = Loading popular stations...
+popular-nearby = Stations Near Me:
+popular-top-voted = Top Voted:
+popular-top-clicked = Most Played:
 
 # Volume control
 volume = Volume:
+volume-hint = { $percent }%
 not-playing = No station playing
 
 # Keyboard shortcuts
-shortcuts-hint = Space: play/pause • ↑↓: volume • Esc: close
+shortcuts-hint = Press ? for keyboard shortcuts
+shortcuts-title = Keyboard shortcuts
+shortcut-key-space = Space
+shortcut-play-pause = Play or stop
+shortcut-volume-up = Volume up
+shortcut-volume-down = Volume down
+shortcut-close = Close this overlay, a dialog or the popup
+shortcut-help = Show or hide this list
+
+# Settings
+settings-search = Search
+settings-min-bitrate = Minimum bitrate
+settings-preferred-codec = Preferred format
+settings-show-station-stats = Show votes, plays and bitrate under station names
+settings-row-actions-on-hover = Show station buttons only on hover or long press
+settings-show-broken = Show stations that failed their last health check
+settings-report-clicks = Report plays to radio-browser.info
+settings-cache-on-disk = Keep recent results on disk for offline use
+settings-api-url = Directory server
+settings-api-url-placeholder = Public mirrors (e.g., http://192.168.1.10:8080)
+settings-proxy = HTTP proxy
+settings-proxy-placeholder = From environment (e.g., http://proxy:3128)
+settings-location = Location for nearby stations
+settings-location-placeholder = From locale country (or "lat, long")
+settings-location-invalid = Enter coordinates as "latitude, longitude", e.g. 52.52, 13.40
+settings-timeout = Give up on a server after
+settings-timeout-seconds = { $seconds } s
+settings-apply = Apply
+settings-playback = Playback
+settings-resume = Resume the last station on login
+settings-autoplay = Start playing when the applet starts
+settings-shuffle-favorites = Skip to a random favorite
+settings-loop-favorites = Skip from the last favorite back to the first
+settings-inhibit-idle = Keep the session from suspending while playing
+settings-inhibit-sleep = Block suspend entirely while playing
+settings-pause-on-output-loss = Stop when headphones or the audio device disconnect
+settings-resume-on-output-return = Resume when the device comes back
+settings-duck = When another app plays audio
+duck-off = Keep playing
+duck-lower = Lower the volume
+duck-pause = Stop until it ends
+settings-duck-volume = Volume while lowered
+settings-duck-delay = Wait before reacting
+settings-autoplay-station = Station to start
+autoplay-last-station = Last station played
+settings-max-volume = Maximum volume
+settings-max-volume-percent = { $percent }%
+settings-history-limit = Stations kept in the history
+settings-history-entries = { $entries }
+settings-backend = Audio engine
+settings-lite = Lite mode (for low-power devices)
+lite-auto = Automatic
+lite-on = On
+lite-off = Off
+backend-mpv = mpv
+backend-gstreamer = GStreamer (lighter)
+settings-notifications = Notifications
+settings-notify-station = When a station starts playing
+settings-notify-song = When the song changes
+settings-notify-errors = When a stream fails
+settings-scrobbling = Scrobbling
+settings-listenbrainz-token = ListenBrainz user token
+settings-listenbrainz-placeholder = From listenbrainz.org/settings; leave empty to turn off
+settings-panel = Panel icon
+settings-left-click = Left click
+settings-middle-click = Middle click
+settings-scroll-volume = Scroll to change the volume, scroll sideways to switch favorites
+settings-scroll-step = Volume change per scroll step
+settings-scroll-step-percent = { $percent }%
+panel-toggle-popup = Open the popup
+panel-play-pause = Play or stop
+panel-nothing = Nothing
+settings-panel-text = Text next to the icon
+settings-panel-text-width = Text width
+settings-panel-text-chars = { $chars } characters
+settings-panel-text-scroll = Scroll text that doesn't fit
+settings-panel-text-vertical = Text is only shown on horizontal panels
+panel-text-off = None
+panel-text-station = Station
+panel-text-song = Song
+panel-text-both = Station and song
+settings-popup = Popup
+settings-popup-size = Size
+settings-popup-custom = Width × list height (pixels)
+settings-popup-width = Width
+settings-popup-height = Height
+settings-popup-size-invalid = Width must be 300–1200 and height 150–1200 pixels
+popup-size-small = Small
+popup-size-medium = Medium
+popup-size-large = Large
+popup-size-custom = Custom
+pin-popup = Keep open when clicking elsewhere
+unpin-popup = Close when clicking elsewhere
+settings-profiles = Profiles
+profile-add = New profile
+profile-name-placeholder = Name, e.g. Work or Kids
+profile-default = Default
+
+# Quality preferences
+quality-any = Any
+quality-min-bitrate = ≥ { $kbps } kbps
+quality-codec-first = { $codec } first
+quality-filter-active = Filtered: { $filter }
+
+# Import and export
+settings-favorites = Favorites
+import-favorites = Import stations from OPML or JSON
+import-choose-file = Choose file…
+import-done = { $count ->
+    [one] Imported 1 new station
+   *[other] Imported { $count } new stations
+}
+import-failed = Import failed: { $error }
+export-favorites = Export favorites as OPML
+export-choose-file = Save as…
+export-done = Favorites saved to { $path }
+export-failed = Export failed: { $error }
+export-playlist = Export favorites as a playlist (VLC, car radios)
+file-not-local = Only local files are supported
+file-not-found = The file does not exist
+file-permission-denied = Not allowed to open the file
+file-io-failed = The file could not be read or written
+file-dialog-failed = The file dialog could not be opened
+file-not-station-list = The file is not an OPML or JSON station list
+settings-sync-file = Sync file (e.g. in a Syncthing or Nextcloud folder)
+settings-sync-file-placeholder = Off (path to a .json or .opml file)
+settings-sync-file-invalid = Enter an absolute path in an existing folder
+sync-file-malformed = The sync file is not a station list it can read
+settings-sync-file-paused = Only the default profile is synced
+
+# Settings backup
+settings-backup = Backup
+backup-settings = Back up all settings and favorites
+backup-done = Settings saved to { $path }
+backup-failed = Backup failed: { $error }
+backup-not-a-backup = The file is not a radio settings backup
+backup-too-new = The backup is from a newer version of the applet
+backup-malformed = The backup file is damaged
+restore-settings = Restore from a backup
+restore-confirm = { $count ->
+    [one] The backup has 1 favorite. Merge it into your current favorites or replace all settings?
+   *[other] The backup has { $count } favorites. Merge them into your current favorites or replace all settings?
+}
+restore-merge = Merge
+restore-replace = Replace everything
+restore-cancel = Cancel
+restore-merged = { $count ->
+    [one] Restored 1 new favorite
+   *[other] Restored { $count } new favorites
+}
+restore-replaced = Settings restored with { $count } favorites
+restore-failed = Restore failed: { $error }
+
+# Hidden stations
+hidden-stations-header = Hidden stations
+hidden-stations-empty = Stations you hide are left out of search and browse results
+hidden-station-unhide = Show again
+
+# Genre chips
+genre-chips-header = Genre chips
+genre-chip-placeholder = Tag, e.g. ambient
+genre-chip-add = Add
+
+# Subscriptions
+subscriptions-header = Subscribed station lists
+subscription-name-placeholder = Name
+subscription-url-placeholder = OPML or JSON list URL
+subscription-add = Subscribe
+subscription-exists = Already subscribed to this list
+subscription-pending = Not downloaded yet
+subscription-stations = { $count ->
+    [one] 1 station
+   *[other] { $count } stations
+}
+subscription-updated = { $name } changed: { $count ->
+    [one] 1 station added, removed or renamed
+   *[other] { $count } stations added, removed or renamed
+}
+
+# Favorites change journal
+journal-header = Recent favorite changes
+journal-empty = No changes recorded yet
+journal-added = Added
+journal-removed = Removed
+journal-renamed = Renamed
+journal-revert = Revert
+history-header = Recently played:
+history-empty = Nothing played yet.
+history-clear = Clear history
+history-playing = Playing now
+history-unknown-duration = Duration unknown
+history-minutes = { $minutes } min
+
+# Sharing
+share-title = Share { $name }
+share-homepage = Share homepage instead of stream
+share-too-long = This address is too long for a QR code.
+share-deep-link = Applet link: { $link }
+
+# Deep links
+deeplink-invalid = That radio link could not be understood.
+deeplink-not-found = The linked station is no longer in the directory.
+deeplink-no-favorite = No favorite is called { $name }.
+open-uri-invalid = Only http(s) stream links can be played.
+network-waiting = Waiting for the network to come back…
+output-disconnected = Stopped because the audio device was disconnected.
+duck-paused = Stopped while another app plays audio; it resumes once that ends.
+notification-now-playing = Now playing
+notification-stream-failed = { $name } stopped
+notification-stop = Stop
+notification-next = Next
+
+# Screen reader names of icon-only buttons
+a11y-play = Play { $name }
+a11y-pause = Pause { $name }
+a11y-stop = Stop { $name }
+a11y-add-favorite = Add { $name } to favorites
+a11y-remove-favorite = Remove { $name } from favorites
+a11y-previous-favorite = Previous favorite
+a11y-next-favorite = Next favorite
+a11y-hide-station = Hide { $name }
+a11y-share-station = Share { $name }
+a11y-edit-station = Edit { $name }
+a11y-move-up = Move { $name } up
+a11y-move-down = Move { $name } down
+a11y-run-search = Search { $name }
+a11y-remove = Remove { $name }
+a11y-add-station = Add a station
+a11y-add-profile = Add profile
+a11y-list-layout = Change list layout
+a11y-previous-page = Previous page
+a11y-next-page = Next page
+a11y-close = Close
+a11y-now-playing = Now playing { $name }
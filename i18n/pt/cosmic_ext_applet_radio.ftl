@@ -0,0 +1,351 @@
+app-title = Rádio para o COSMIC
+window-title = Rádio para o COSMIC
+search-placeholder = Buscar estações (ex.: Jazz ou tag:jazz country:BR)...
+search-button = Buscar
+search-field-name = Nome
+search-field-tag = Tag
+search-field-country = País
+search-field-language = Idioma
+searching-status = Buscando...
+searching-next-mirror = Tentando outro servidor ({ $host })...
+searching-retry = { $host } está ocupado, tentando de novo ({ $attempt }/{ $max })...
+cancel-search = Cancelar
+error-message = Erro:
+error-timeout = O diretório de estações não respondeu em { $seconds } segundos.
+error-rate-limited = Muitas solicitações. Aguarde um momento.
+error-server = O diretório de estações retornou um erro ({ $status }).
+error-network = Não foi possível acessar o diretório de estações. Verifique sua conexão.
+error-bad-response = O diretório de estações enviou uma resposta que não pôde ser lida.
+error-station-not-found = A estação não foi encontrada.
+error-invalid-url = O endereço não é uma URL http(s) válida.
+error-invalid-icon-url = O endereço do ícone não é uma URL http(s) válida.
+retry = Tentar novamente
+connecting = Conectando...
+buffering = Carregando... { $percent }%
+live = ● Ao vivo
+stream-offline = A estação parece estar fora do ar (HTTP { $status }).
+stream-timeout = A estação não respondeu em { $seconds } segundos.
+stream-tls = A conexão segura com a estação falhou.
+stream-unreachable = Não foi possível conectar à estação.
+stream-rejected = Este endereço de transmissão não é permitido.
+player-failed = Não foi possível iniciar o reprodutor. O mpv está instalado?
+stream-ended = A transmissão parou.
+favorites-header = Meus Favoritos:
+no-favorites = Nenhum favorito salvo.
+suggestions-genres = Comece por um gênero:
+suggestions-nearby = Populares perto de você:
+suggestions-popular = Populares agora:
+genre-pop = Pop
+genre-rock = Rock
+genre-jazz = Jazz
+genre-news = Notícias
+genre-classical = Clássica
+genre-electronic = Eletrônica
+genre-lofi = Lo-fi
+favorites-page = Página { $page } de { $pages }
+favorites-filter-placeholder = Filtrar favoritos por nome ou tag
+favorites-filter-empty = Nenhum favorito corresponde.
+favorite-added = { $name } adicionada aos favoritos
+favorite-removed = { $name } removida dos favoritos
+folder-picker = Adicionar { $name } a uma pasta?
+folder-none = Sem pasta
+manage-favorites = Gerenciar
+manage-done = Concluir
+custom-station-name = Nome da estação (opcional)
+custom-station-url = URL do stream
+custom-station-icon = URL do ícone (opcional)
+custom-station-add = Adicionar estação
+custom-station-cancel = Cancelar
+custom-station-exists = Esta estação já está nos seus favoritos.
+select-all = Selecionar tudo
+bulk-tag-placeholder = Tag
+bulk-add-tag = Adicionar tag
+bulk-remove-tag = Remover tag
+bulk-folder-placeholder = Pasta (vazio para limpar)
+bulk-move-folder = Mover
+bulk-archive = Arquivar
+bulk-export = Exportar
+bulk-remove = Remover
+bulk-remove-confirm = { $count ->
+    [one] Remover 1 favorita?
+   *[other] Remover { $count } favoritas?
+}
+refresh-favorites = Atualizar detalhes
+archived-header = Arquivados:
+restore-favorite = Restaurar
+edit-favorite-save = Salvar
+edit-favorite-cancel = Cancelar
+save-search-placeholder = Nome para esta busca
+save-search = Salvar busca
+saved-searches-header = Buscas Salvas:
+clear-search = Limpar
+search-hint = Digite para buscar ou refine com tag:jazz country:BR bitrate>128
+search-results-header = Resultados da Busca:
+filter-any-country = Qualquer país
+filter-any-language = Qualquer idioma
+filter-clear = Limpar filtros
+station-variants = { $count ->
+    [one] 1 variante
+   *[other] { $count } variantes
+}
+station-votes = { $count ->
+    [one] 1 voto
+   *[other] { $count } votos
+}
+station-clicks = { $count ->
+    [one] 1 reprodução
+   *[other] { $count } reproduções
+}
+
+# Tabs
+tab-favorites = Favoritos
+tab-favorites-count = Favoritos ({ $count })
+tab-search = Buscar
+tab-browse = Explorar
+tab-history = Histórico
+tab-settings = Configurações
+loading-popular = Carregando estações populares...
+popular-nearby = Estações Perto de Mim:
+popular-top-voted = Mais Votadas:
+popular-top-clicked = Mais Tocadas:
+
+# Volume control
+volume = Volume:
+volume-hint = { $percent }%
+not-playing = Nenhuma estação tocando
+
+# Keyboard shortcuts
+shortcuts-hint = Pressione ? para ver os atalhos de teclado
+shortcuts-title = Atalhos de teclado
+shortcut-key-space = Espaço
+shortcut-play-pause = Tocar ou parar
+shortcut-volume-up = Aumentar volume
+shortcut-volume-down = Diminuir volume
+shortcut-close = Fechar esta lista, um diálogo ou o popup
+shortcut-help = Mostrar ou ocultar esta lista
+
+# Settings
+settings-search = Busca
+settings-min-bitrate = Taxa de bits mínima
+settings-preferred-codec = Formato preferido
+settings-show-station-stats = Mostrar votos, reproduções e taxa de bits sob os nomes das estações
+settings-row-actions-on-hover = Mostrar os botões da estação só ao passar o mouse ou manter pressionado
+settings-show-broken = Mostrar estações que falharam na última verificação
+settings-report-clicks = Informar reproduções ao radio-browser.info
+settings-cache-on-disk = Manter resultados recentes no disco para uso offline
+settings-api-url = Servidor do diretório
+settings-api-url-placeholder = Espelhos públicos (ex.: http://192.168.1.10:8080)
+settings-proxy = Proxy HTTP
+settings-proxy-placeholder = Do ambiente (ex.: http://proxy:3128)
+settings-location = Localização para estações próximas
+settings-location-placeholder = Do país do sistema (ou "lat, long")
+settings-location-invalid = Informe as coordenadas como "latitude, longitude", ex.: -23.55, -46.63
+settings-timeout = Desistir de um servidor após
+settings-timeout-seconds = { $seconds } s
+settings-apply = Aplicar
+settings-playback = Reprodução
+settings-resume = Retomar a última estação ao entrar
+settings-autoplay = Começar a tocar quando o applet iniciar
+settings-shuffle-favorites = Pular para um favorito aleatório
+settings-loop-favorites = Voltar do último favorito para o primeiro ao pular
+settings-inhibit-idle = Impedir que a sessão suspenda enquanto toca
+settings-inhibit-sleep = Bloquear totalmente a suspensão enquanto toca
+settings-pause-on-output-loss = Parar quando os fones ou o dispositivo de áudio se desconectarem
+settings-resume-on-output-return = Retomar quando o dispositivo voltar
+settings-duck = Quando outro app tocar áudio
+duck-off = Continuar tocando
+duck-lower = Baixar o volume
+duck-pause = Parar até terminar
+settings-duck-volume = Volume enquanto baixado
+settings-duck-delay = Esperar antes de reagir
+settings-autoplay-station = Estação inicial
+autoplay-last-station = Última estação tocada
+settings-max-volume = Volume máximo
+settings-max-volume-percent = { $percent }%
+settings-history-limit = Estações mantidas no histórico
+settings-history-entries = { $entries }
+settings-backend = Mecanismo de áudio
+settings-lite = Modo leve (para dispositivos de baixo consumo)
+lite-auto = Automático
+lite-on = Ligado
+lite-off = Desligado
+backend-mpv = mpv
+backend-gstreamer = GStreamer (mais leve)
+settings-notifications = Notificações
+settings-notify-station = Quando uma estação começa a tocar
+settings-notify-song = Quando a música muda
+settings-notify-errors = Quando um stream falha
+settings-scrobbling = Scrobbling
+settings-listenbrainz-token = Token de usuário do ListenBrainz
+settings-listenbrainz-placeholder = Em listenbrainz.org/settings; deixe vazio para desativar
+settings-panel = Ícone do painel
+settings-left-click = Clique esquerdo
+settings-middle-click = Clique do meio
+settings-scroll-volume = Rolar para mudar o volume, rolar para o lado para trocar de favorita
+settings-scroll-step = Alteração de volume por passo de rolagem
+settings-scroll-step-percent = { $percent }%
+panel-toggle-popup = Abrir o popup
+panel-play-pause = Tocar ou parar
+panel-nothing = Nada
+settings-panel-text = Texto ao lado do ícone
+settings-panel-text-width = Largura do texto
+settings-panel-text-chars = { $chars } caracteres
+settings-panel-text-scroll = Rolar o texto que não couber
+settings-panel-text-vertical = O texto só aparece em painéis horizontais
+panel-text-off = Nenhum
+panel-text-station = Estação
+panel-text-song = Música
+panel-text-both = Estação e música
+settings-popup = Popup
+settings-popup-size = Tamanho
+settings-popup-custom = Largura × altura da lista (pixels)
+settings-popup-width = Largura
+settings-popup-height = Altura
+settings-popup-size-invalid = A largura deve ter 300–1200 e a altura 150–1200 pixels
+popup-size-small = Pequeno
+popup-size-medium = Médio
+popup-size-large = Grande
+popup-size-custom = Personalizado
+pin-popup = Manter aberto ao clicar fora
+unpin-popup = Fechar ao clicar fora
+settings-profiles = Perfis
+profile-add = Novo perfil
+profile-name-placeholder = Nome, ex.: Trabalho ou Crianças
+profile-default = Padrão
+
+# Quality preferences
+quality-any = Qualquer
+quality-min-bitrate = ≥ { $kbps } kbps
+quality-codec-first = { $codec } primeiro
+quality-filter-active = Filtrado: { $filter }
+
+# Import and export
+settings-favorites = Favoritos
+import-favorites = Importar estações de OPML ou JSON
+import-choose-file = Escolher arquivo…
+import-done = { $count ->
+    [one] 1 nova estação importada
+   *[other] { $count } novas estações importadas
+}
+import-failed = Falha ao importar: { $error }
+export-favorites = Exportar favoritos como OPML
+export-choose-file = Salvar como…
+export-done = Favoritos salvos em { $path }
+export-failed = Falha ao exportar: { $error }
+export-playlist = Exportar favoritos como playlist (VLC, rádios de carro)
+file-not-local = Apenas arquivos locais são suportados
+file-not-found = O arquivo não existe
+file-permission-denied = Sem permissão para abrir o arquivo
+file-io-failed = Não foi possível ler ou gravar o arquivo
+file-dialog-failed = Não foi possível abrir o seletor de arquivos
+file-not-station-list = O arquivo não é uma lista de estações OPML ou JSON
+settings-sync-file = Arquivo de sincronização (ex.: numa pasta do Syncthing ou Nextcloud)
+settings-sync-file-placeholder = Desligado (caminho para um arquivo .json ou .opml)
+settings-sync-file-invalid = Informe um caminho absoluto numa pasta existente
+sync-file-malformed = O arquivo de sincronização não é uma lista de estações legível
+settings-sync-file-paused = Apenas o perfil padrão é sincronizado
+
+# Settings backup
+settings-backup = Backup
+backup-settings = Fazer backup de todas as configurações e favoritos
+backup-done = Configurações salvas em { $path }
+backup-failed = Falha no backup: { $error }
+backup-not-a-backup = O arquivo não é um backup das configurações do rádio
+backup-too-new = O backup é de uma versão mais nova do applet
+backup-malformed = O arquivo de backup está danificado
+restore-settings = Restaurar de um backup
+restore-confirm = { $count ->
+    [one] O backup tem 1 favorito. Mesclá-lo aos seus favoritos atuais ou substituir todas as configurações?
+   *[other] O backup tem { $count } favoritos. Mesclá-los aos seus favoritos atuais ou substituir todas as configurações?
+}
+restore-merge = Mesclar
+restore-replace = Substituir tudo
+restore-cancel = Cancelar
+restore-merged = { $count ->
+    [one] 1 novo favorito restaurado
+   *[other] { $count } novos favoritos restaurados
+}
+restore-replaced = Configurações restauradas com { $count } favoritos
+restore-failed = Falha ao restaurar: { $error }
+
+# Hidden stations
+hidden-stations-header = Estações ocultas
+hidden-stations-empty = Estações que você ocultar ficam de fora dos resultados de busca e exploração
+hidden-station-unhide = Mostrar novamente
+
+# Genre chips
+genre-chips-header = Atalhos de gênero
+genre-chip-placeholder = Tag, ex.: ambient
+genre-chip-add = Adicionar
+
+# Subscriptions
+subscriptions-header = Listas de estações assinadas
+subscription-name-placeholder = Nome
+subscription-url-placeholder = URL de lista OPML ou JSON
+subscription-add = Assinar
+subscription-exists = Você já assina esta lista
+subscription-pending = Ainda não baixada
+subscription-stations = { $count ->
+    [one] 1 estação
+   *[other] { $count } estações
+}
+subscription-updated = { $name } mudou: { $count ->
+    [one] 1 estação adicionada, removida ou renomeada
+   *[other] { $count } estações adicionadas, removidas ou renomeadas
+}
+
+# Favorites change journal
+journal-header = Alterações recentes nos favoritos
+journal-empty = Nenhuma alteração registrada ainda
+journal-added = Adicionada
+journal-removed = Removida
+journal-renamed = Renomeada
+journal-revert = Desfazer
+history-header = Tocadas recentemente:
+history-empty = Nada tocado ainda.
+history-clear = Limpar histórico
+history-playing = Tocando agora
+history-unknown-duration = Duração desconhecida
+history-minutes = { $minutes } min
+
+# Sharing
+share-title = Compartilhar { $name }
+share-homepage = Compartilhar a página em vez do stream
+share-too-long = Este endereço é longo demais para um código QR.
+share-deep-link = Link do applet: { $link }
+
+# Deep links
+deeplink-invalid = Não foi possível entender esse link de rádio.
+deeplink-not-found = A estação do link não está mais no diretório.
+deeplink-no-favorite = Nenhum favorito se chama { $name }.
+open-uri-invalid = Só é possível tocar links de stream http(s).
+network-waiting = Aguardando a rede voltar…
+output-disconnected = Parado porque o dispositivo de áudio foi desconectado.
+duck-paused = Parado enquanto outro app toca áudio; volta quando ele terminar.
+notification-now-playing = Tocando agora
+notification-stream-failed = { $name } parou
+notification-stop = Parar
+notification-next = Próxima
+
+# Nomes dos botões só com ícone para leitores de tela
+a11y-play = Tocar { $name }
+a11y-pause = Pausar { $name }
+a11y-stop = Parar { $name }
+a11y-add-favorite = Adicionar { $name } às favoritas
+a11y-remove-favorite = Remover { $name } das favoritas
+a11y-previous-favorite = Favorita anterior
+a11y-next-favorite = Próxima favorita
+a11y-hide-station = Ocultar { $name }
+a11y-share-station = Compartilhar { $name }
+a11y-edit-station = Editar { $name }
+a11y-move-up = Mover { $name } para cima
+a11y-move-down = Mover { $name } para baixo
+a11y-run-search = Buscar { $name }
+a11y-remove = Remover { $name }
+a11y-add-station = Adicionar uma estação
+a11y-add-profile = Adicionar perfil
+a11y-list-layout = Mudar o layout da lista
+a11y-previous-page = Página anterior
+a11y-next-page = Próxima página
+a11y-close = Fechar
+a11y-now-playing = Tocando agora { $name }
@@ -1,6 +1,18 @@
+use crate::cache::{self, ResponseCache};
+use crate::error::{ApiError, StreamError, UrlError};
+use crate::geo::{self, Nearby};
+use crate::station::{StationId, PROVIDER_CUSTOM, PROVIDER_RADIO_BROWSER};
+use futures::channel::mpsc::UnboundedSender;
 use reqwest::Error;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, warn};
+use std::future::Future;
+use std::hash::{BuildHasher, RandomState};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+use url::Url;
+use uuid::Uuid;
 
 /// Maximum response body size (1MB) to prevent memory exhaustion attacks
 /// This is sufficient for 20 station records with metadata
@@ -8,6 +20,9 @@ const MAX_RESPONSE_SIZE: usize = 1024 * 1024;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub struct Station {
+    /// Internal identifier; provider-specific keys live in the fields below
+    #[serde(default)]
+    pub id: StationId,
     #[serde(default)]
     pub stationuuid: String,
     #[serde(default)]
@@ -26,6 +41,267 @@ pub struct Station {
     pub country: String,
     #[serde(default)]
     pub language: String,
+    #[serde(default)]
+    pub countrycode: String,
+    /// Stream bitrate in kbps, 0 when unknown
+    #[serde(default)]
+    pub bitrate: u32,
+    #[serde(default)]
+    pub codec: String,
+    #[serde(default)]
+    pub votes: u32,
+    #[serde(default)]
+    pub clickcount: u32,
+    /// Result of the directory's last health check, `None` when unknown
+    #[serde(default)]
+    pub lastcheckok: Option<bool>,
+
+    // User-managed attributes, only meaningful for favorites
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub folder: String,
+    /// Name the user gave the station; `name` keeps the directory's
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub alias: String,
+    /// Stream URL the user prefers over `url_resolved`. Directory refreshes
+    /// leave it alone.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub stream_override: String,
+    /// Hidden from the favorites list without being deleted
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
+}
+
+/// Namespace for identifiers of stations that don't come from radio-browser.info
+const CUSTOM_STATION_NAMESPACE: Uuid = Uuid::from_u128(0x6b1f_3c2e_9a4d_5e70_8c21_d4f0_a3b5_7e19);
+
+impl Station {
+    /// Derive a stable UUIDv5 from a stream URL, so a custom or imported
+    /// station gets the same identifier on every machine
+    pub fn custom_uuid(url: &str) -> String {
+        let url = url.trim();
+        // Let the url crate normalize scheme/host case and empty paths
+        let normalized = Url::parse(url).map_or_else(|_| url.to_string(), |u| u.to_string());
+        Uuid::new_v5(&CUSTOM_STATION_NAMESPACE, normalized.as_bytes()).to_string()
+    }
+
+    /// Build a user-added station from the add-station form. The stream URL
+    /// (and icon URL, if given) must be http(s); an empty name falls back to
+    /// the stream's host.
+    pub fn custom(name: &str, url: &str, favicon: &str) -> Result<Self, UrlError> {
+        let stream = parse_http_url(url).ok_or(UrlError::NotHttp)?;
+        let favicon = favicon.trim();
+        if !favicon.is_empty() && parse_http_url(favicon).is_none() {
+            return Err(UrlError::Icon);
+        }
+        let name = match name.trim() {
+            "" => stream.host_str().unwrap_or_default().to_string(),
+            name => name.to_string(),
+        };
+
+        let url = url.trim().to_string();
+        let mut station = Station {
+            name,
+            url_resolved: url.clone(),
+            url,
+            favicon: favicon.to_string(),
+            ..Default::default()
+        };
+        station.ensure_uuid();
+        station.ensure_id();
+        Ok(station)
+    }
+
+    /// Assign a URL-derived identifier if the station has none.
+    /// Returns true when a new identifier was generated.
+    pub fn ensure_uuid(&mut self) -> bool {
+        if !self.stationuuid.trim().is_empty() {
+            return false;
+        }
+        let url = if self.url.trim().is_empty() {
+            &self.url_resolved
+        } else {
+            &self.url
+        };
+        if url.trim().is_empty() {
+            return false;
+        }
+        self.stationuuid = Self::custom_uuid(url);
+        true
+    }
+
+    /// Assign the internal identifier if the station has none yet.
+    /// Returns true when a new identifier was generated.
+    pub fn ensure_id(&mut self) -> bool {
+        if !self.id.is_empty() || self.stationuuid.is_empty() {
+            return false;
+        }
+        let provider = if self.is_custom() {
+            PROVIDER_CUSTOM
+        } else {
+            PROVIDER_RADIO_BROWSER
+        };
+        self.id = StationId::new(provider, &self.stationuuid);
+        true
+    }
+
+    /// Whether two entries refer to the same station, either through the
+    /// internal id or the provider UUID
+    pub fn is_same(&self, other: &Station) -> bool {
+        (!self.id.is_empty() && self.id == other.id)
+            || (!self.stationuuid.is_empty() && self.stationuuid == other.stationuuid)
+    }
+
+    /// The alias if one is set, otherwise the directory name
+    pub fn display_name(&self) -> &str {
+        if self.alias.trim().is_empty() {
+            &self.name
+        } else {
+            self.alias.trim()
+        }
+    }
+
+    /// Whether the name, alias or a tag contains `filter`, ignoring case
+    pub fn matches_filter(&self, filter: &str) -> bool {
+        let filter = filter.trim().to_lowercase();
+        [&self.alias, &self.name, &self.tags]
+            .iter()
+            .any(|field| field.to_lowercase().contains(&filter))
+    }
+
+    /// Letter the station is listed under in the favorites index: the
+    /// first letter of its name, or `#` for names starting otherwise
+    pub fn index_letter(&self) -> char {
+        self.display_name()
+            .chars()
+            .find(|c| c.is_alphanumeric())
+            .filter(|c| c.is_alphabetic())
+            .and_then(|c| c.to_uppercase().next())
+            .unwrap_or('#')
+    }
+
+    /// URL to play: the user's override if set, otherwise the directory's
+    pub fn stream_url(&self) -> &str {
+        if self.stream_override.trim().is_empty() {
+            &self.url_resolved
+        } else {
+            self.stream_override.trim()
+        }
+    }
+
+    /// Set or clear (empty `url`) the stream override. The URL must be
+    /// http(s); one matching the directory's stream clears the override.
+    /// Returns whether the station changed.
+    pub fn set_stream_override(&mut self, url: &str) -> Result<bool, UrlError> {
+        let url = url.trim();
+        if !url.is_empty() && parse_http_url(url).is_none() {
+            return Err(UrlError::NotHttp);
+        }
+        let url = if url == self.url_resolved.trim() {
+            ""
+        } else {
+            url
+        };
+        if self.stream_override == url {
+            return Ok(false);
+        }
+        self.stream_override = url.to_string();
+        Ok(true)
+    }
+
+    /// Short codec/bitrate summary such as "MP3 · 128 kbps"
+    pub fn quality_label(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if !self.codec.trim().is_empty() {
+            parts.push(self.codec.trim().to_string());
+        }
+        if self.bitrate > 0 {
+            parts.push(format!("{} kbps", self.bitrate));
+        }
+        (!parts.is_empty()).then(|| parts.join(" · "))
+    }
+
+    /// Flag emoji for `countrycode`, if it is an ISO 3166-1 alpha-2 code
+    pub fn country_flag(&self) -> Option<String> {
+        let code = self.countrycode.trim();
+        if code.len() != 2 || !code.bytes().all(|b| b.is_ascii_alphabetic()) {
+            return None;
+        }
+        // Regional indicator symbols pair up into the flag
+        code.bytes()
+            .map(|b| char::from_u32(0x1F1E6 + u32::from(b.to_ascii_uppercase() - b'A')))
+            .collect()
+    }
+
+    /// Tags as a trimmed list, skipping empty entries
+    pub fn tag_list(&self) -> Vec<&str> {
+        self.tags
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .collect()
+    }
+
+    /// Add a tag unless it is already present (case-insensitive).
+    /// Returns true if the tags changed.
+    pub fn add_tag(&mut self, tag: &str) -> bool {
+        let tag = tag.trim();
+        if tag.is_empty() || self.tag_list().iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            return false;
+        }
+        let mut tags = self.tag_list();
+        tags.push(tag);
+        self.tags = tags.join(",");
+        true
+    }
+
+    /// Remove a tag (case-insensitive). Returns true if the tags changed.
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let tag = tag.trim();
+        let tags = self.tag_list();
+        let kept: Vec<&str> = tags
+            .iter()
+            .copied()
+            .filter(|t| !t.eq_ignore_ascii_case(tag))
+            .collect();
+        if kept.len() == tags.len() {
+            return false;
+        }
+        self.tags = kept.join(",");
+        true
+    }
+
+    /// Take the directory's current stream URL, favicon and tags from
+    /// `fresh`. Empty values never replace known ones, and tags added
+    /// locally are kept. Returns true if anything changed.
+    pub fn refresh_from(&mut self, fresh: &Station) -> bool {
+        let mut changed = false;
+        for (field, value) in [
+            (&mut self.url_resolved, &fresh.url_resolved),
+            (&mut self.favicon, &fresh.favicon),
+        ] {
+            if !value.trim().is_empty() && *field != *value {
+                field.clone_from(value);
+                changed = true;
+            }
+        }
+        for tag in fresh.tag_list() {
+            changed |= self.add_tag(tag);
+        }
+        changed
+    }
+
+    /// True if the identifier was generated locally rather than by radio-browser.info
+    pub fn is_custom(&self) -> bool {
+        [&self.url, &self.url_resolved]
+            .iter()
+            .any(|url| !url.trim().is_empty() && Self::custom_uuid(url) == self.stationuuid)
+    }
+
+    /// Whether the user entered the stream URL themselves, as a custom
+    /// station or an override. Those may point into the local network.
+    pub fn has_user_stream(&self) -> bool {
+        self.is_custom() || !self.stream_override.trim().is_empty()
+    }
 }
 
 /// Intermediate struct to handle null values from API JSON
@@ -49,12 +325,32 @@ struct ApiStation {
     country: Option<String>,
     #[serde(default)]
     language: Option<String>,
+    #[serde(default)]
+    countrycode: Option<String>,
+    #[serde(default)]
+    bitrate: Option<u32>,
+    #[serde(default)]
+    codec: Option<String>,
+    #[serde(default)]
+    votes: Option<u32>,
+    #[serde(default)]
+    clickcount: Option<u32>,
+    /// Sent as 0/1 by the API
+    #[serde(default)]
+    lastcheckok: Option<u8>,
 }
 
 impl From<ApiStation> for Station {
     fn from(api: ApiStation) -> Self {
+        let stationuuid = api.stationuuid.unwrap_or_default();
+        let id = if stationuuid.is_empty() {
+            StationId::default()
+        } else {
+            StationId::new(PROVIDER_RADIO_BROWSER, &stationuuid)
+        };
         Self {
-            stationuuid: api.stationuuid.unwrap_or_default(),
+            id,
+            stationuuid,
             name: api.name.unwrap_or_default(),
             url: api.url.unwrap_or_default(),
             url_resolved: api.url_resolved.unwrap_or_default(),
@@ -63,11 +359,18 @@ impl From<ApiStation> for Station {
             tags: api.tags.unwrap_or_default(),
             country: api.country.unwrap_or_default(),
             language: api.language.unwrap_or_default(),
+            countrycode: api.countrycode.unwrap_or_default(),
+            bitrate: api.bitrate.unwrap_or_default(),
+            codec: api.codec.unwrap_or_default(),
+            votes: api.votes.unwrap_or_default(),
+            clickcount: api.clickcount.unwrap_or_default(),
+            lastcheckok: api.lastcheckok.map(|ok| ok != 0),
+            ..Default::default()
         }
     }
 }
 
-/// Mirror servers for radio-browser.info API redundancy
+/// Fallback mirrors for radio-browser.info, used when discovery fails
 const API_SERVERS: &[&str] = &[
     "https://all.api.radio-browser.info",
     "https://de1.api.radio-browser.info",
@@ -78,241 +381,1339 @@ const API_SERVERS: &[&str] = &[
     "https://es1.api.radio-browser.info",
 ];
 
-/// Search for radio stations by name
-pub async fn search_stations(query: String) -> Result<Vec<Station>, Error> {
-    if query.trim().is_empty() {
-        return Ok(Vec::new());
-    }
+/// Endpoint listing the currently active radio-browser.info mirrors
+const SERVER_LIST_URL: &str = "https://all.api.radio-browser.info/json/servers";
 
-    debug!("Searching stations for '{}'", query);
+/// How long the built-in list stands in for discovery before it is retried
+const FALLBACK_MIRRORS_TTL: Duration = Duration::from_secs(300);
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()
-        .unwrap_or_else(|_| reqwest::Client::new());
+/// Mirrors in the order they are tried; the last one that answered is kept
+/// at the front
+#[derive(Default)]
+struct MirrorList {
+    servers: Vec<String>,
+    /// Set when the list is the built-in fallback, which is only kept until
+    /// discovery is worth another try
+    expires: Option<Instant>,
+}
 
-    let mut last_error: Option<Error> = None;
+impl MirrorList {
+    fn is_fresh(&self) -> bool {
+        !self.servers.is_empty() && self.expires.is_none_or(|at| Instant::now() < at)
+    }
+}
 
-    for server in API_SERVERS {
-        let url = format!("{}/json/stations/search", server);
-        let params = [("name", query.as_str()), ("limit", "20")];
+static MIRRORS: LazyLock<Mutex<MirrorList>> = LazyLock::new(|| Mutex::new(MirrorList::default()));
 
-        match client.get(&url).query(&params).send().await {
-            Ok(response) => match response.error_for_status() {
-                Ok(valid_response) => {
-                    // Check Content-Length header first if available (early rejection)
-                    if let Some(content_length) = valid_response.content_length() {
-                        if content_length as usize > MAX_RESPONSE_SIZE {
-                            warn!(
-                                "Response from {} exceeds size limit: {} bytes (max: {})",
-                                server, content_length, MAX_RESPONSE_SIZE
-                            );
-                            continue;
-                        }
-                    }
+/// User-configured directory server that replaces the public mirrors
+static CUSTOM_BASE_URL: RwLock<Option<String>> = RwLock::new(None);
 
-                    // Read response body as bytes with size validation
-                    match valid_response.bytes().await {
-                        Ok(bytes) => {
-                            if bytes.len() > MAX_RESPONSE_SIZE {
-                                warn!(
-                                    "Response body from {} exceeds size limit: {} bytes (max: {})",
-                                    server,
-                                    bytes.len(),
-                                    MAX_RESPONSE_SIZE
-                                );
-                                continue;
-                            }
+fn parse_http_url(url: &str) -> Option<Url> {
+    Url::parse(url.trim())
+        .ok()
+        .filter(|u| matches!(u.scheme(), "http" | "https"))
+        .filter(|u| u.host_str().is_some_and(|h| !h.is_empty()))
+}
 
-                            // Deserialize from validated bytes
-                            match serde_json::from_slice::<Vec<ApiStation>>(&bytes) {
-                                Ok(api_stations) => {
-                                    debug!("Found {} stations from {}", api_stations.len(), server);
-                                    return Ok(api_stations.into_iter().map(Station::from).collect());
-                                }
-                                Err(e) => {
-                                    warn!("JSON parse error from {}: {}", server, e);
-                                    // Continue to next server on parse error
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            warn!("Failed to read response body from {}: {}", server, e);
-                            last_error = Some(e);
-                        }
-                    }
-                }
-                Err(e) => {
-                    warn!("HTTP error from {}: {}", server, e);
-                    last_error = Some(e);
-                }
-            },
-            Err(e) => {
-                warn!("Connection error to {}: {}", server, e);
-                last_error = Some(e);
-            }
-        }
+/// Check a user-supplied directory base URL and normalize it (no trailing
+/// slash). Unlike stream URLs, LAN and localhost hosts are allowed here so
+/// self-hosted radio-browser instances work.
+pub fn validate_base_url(url: &str) -> Result<String, UrlError> {
+    let parsed = Url::parse(url.trim()).map_err(|_| UrlError::Malformed)?;
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        return Err(UrlError::NotHttp);
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err(UrlError::Malformed);
     }
+    Ok(parsed.as_str().trim_end_matches('/').to_string())
+}
 
-    // All servers failed - return the last error or empty result
-    match last_error {
-        Some(e) => Err(e),
-        None => Ok(Vec::new()),
+/// Use `url` for every directory request instead of the public mirrors.
+/// An empty string restores the public mirrors.
+pub fn set_base_url(url: &str) -> Result<(), UrlError> {
+    let base = if url.trim().is_empty() {
+        None
+    } else {
+        Some(validate_base_url(url)?)
+    };
+    info!(
+        "Directory endpoint: {}",
+        base.as_deref().unwrap_or("public mirrors")
+    );
+    if let Ok(mut custom) = CUSTOM_BASE_URL.write() {
+        if *custom != base {
+            // Responses from another directory don't apply
+            clear_cache();
+        }
+        *custom = base;
     }
+    Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serde_json::json;
+/// Proxy for directory requests. When unset, reqwest honors
+/// `http_proxy`/`https_proxy` from the environment.
+static PROXY_URL: RwLock<Option<String>> = RwLock::new(None);
 
-    #[test]
-    fn test_station_default() {
-        let station = Station::default();
-        assert_eq!(station.stationuuid, "");
-        assert_eq!(station.name, "");
-        assert_eq!(station.url, "");
-        assert_eq!(station.url_resolved, "");
-        assert_eq!(station.homepage, "");
-        assert_eq!(station.favicon, "");
-        assert_eq!(station.tags, "");
-        assert_eq!(station.country, "");
-        assert_eq!(station.language, "");
+/// Route directory requests through `url`. An empty string falls back to
+/// the proxy environment variables.
+pub fn set_proxy(url: &str) -> Result<(), UrlError> {
+    let proxy = if url.trim().is_empty() {
+        None
+    } else {
+        // A proxy follows the same rules as a self-hosted endpoint
+        Some(validate_base_url(url)?)
+    };
+    info!(
+        "HTTP proxy: {}",
+        proxy.as_deref().unwrap_or("from environment")
+    );
+    if let Ok(mut current) = PROXY_URL.write() {
+        *current = proxy;
     }
+    reset_client();
+    Ok(())
+}
 
-    #[test]
-    fn test_station_deserialize_complete() {
-        let json = json!({
-            "stationuuid": "abc-123",
-            "name": "Test Radio",
-            "url": "http://example.com/stream",
-            "url_resolved": "http://example.com/resolved",
-            "homepage": "http://example.com",
-            "favicon": "http://example.com/favicon.png",
-            "tags": "rock,pop",
-            "country": "USA",
-            "language": "English"
-        });
+/// Per-request timeout used unless the user picks another one
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 5;
 
-        let station: Station = serde_json::from_value(json).unwrap();
-        assert_eq!(station.stationuuid, "abc-123");
-        assert_eq!(station.name, "Test Radio");
-        assert_eq!(station.url, "http://example.com/stream");
-        assert_eq!(station.url_resolved, "http://example.com/resolved");
-        assert_eq!(station.homepage, "http://example.com");
-        assert_eq!(station.favicon, "http://example.com/favicon.png");
-        assert_eq!(station.tags, "rock,pop");
-        assert_eq!(station.country, "USA");
-        assert_eq!(station.language, "English");
-    }
+static REQUEST_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(DEFAULT_REQUEST_TIMEOUT_SECS);
 
-    #[test]
-    fn test_station_deserialize_with_nulls() {
-        let json = json!({
-            "stationuuid": null,
-            "name": "Test Radio",
-            "url": null,
-            "url_resolved": "http://example.com/resolved",
-            "homepage": null,
-            "favicon": null,
-            "tags": null,
-            "country": null,
-            "language": null
-        });
+/// Give up on a mirror after `secs` seconds (clamped to 1–60) and try the next
+pub fn set_request_timeout(secs: u64) {
+    REQUEST_TIMEOUT_SECS.store(secs.clamp(1, 60), Ordering::Relaxed);
+    reset_client();
+}
 
-        // Station struct uses ApiStation internally for null handling
-        let api_station: ApiStation = serde_json::from_value(json).unwrap();
-        let station: Station = api_station.into();
+/// radio-browser asks clients to identify themselves
+const USER_AGENT: &str = concat!("cosmic-radio-applet/", env!("CARGO_PKG_VERSION"));
 
-        assert_eq!(station.stationuuid, "");
-        assert_eq!(station.name, "Test Radio");
-        assert_eq!(station.url, "");
-        assert_eq!(station.url_resolved, "http://example.com/resolved");
-        assert_eq!(station.homepage, "");
-        assert_eq!(station.favicon, "");
-        assert_eq!(station.tags, "");
-        assert_eq!(station.country, "");
-        assert_eq!(station.language, "");
+/// Shared client so connections to a mirror are reused across requests.
+/// Built on first use and rebuilt after the proxy or timeout changes.
+static CLIENT: RwLock<Option<reqwest::Client>> = RwLock::new(None);
+
+fn http_client() -> reqwest::Client {
+    if let Some(client) = CLIENT.read().ok().and_then(|c| c.clone()) {
+        return client;
     }
+    let client = build_client();
+    if let Ok(mut cached) = CLIENT.write() {
+        *cached = Some(client.clone());
+    }
+    client
+}
 
-    #[test]
-    fn test_station_deserialize_missing_fields() {
-        let json = json!({
-            "name": "Minimal Station"
-        });
+fn reset_client() {
+    if let Ok(mut cached) = CLIENT.write() {
+        *cached = None;
+    }
+}
 
-        let station: Station = serde_json::from_value(json).unwrap();
-        assert_eq!(station.name, "Minimal Station");
-        assert_eq!(station.stationuuid, "");
-        assert_eq!(station.url, "");
-        assert_eq!(station.url_resolved, "");
+fn build_client() -> reqwest::Client {
+    let timeout = Duration::from_secs(REQUEST_TIMEOUT_SECS.load(Ordering::Relaxed));
+    let mut builder = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .gzip(true)
+        .timeout(timeout);
+    if let Some(proxy) = PROXY_URL.read().ok().and_then(|p| p.clone()) {
+        match reqwest::Proxy::all(&proxy) {
+            Ok(proxy) => builder = builder.proxy(proxy),
+            Err(e) => warn!("Ignoring proxy {}: {}", proxy, e),
+        }
     }
+    builder.build().unwrap_or_else(|_| reqwest::Client::new())
+}
 
-    #[test]
-    fn test_station_deserialize_empty_object() {
-        let json = json!({});
+static RESPONSE_CACHE: LazyLock<Mutex<ResponseCache>> =
+    LazyLock::new(|| Mutex::new(ResponseCache::default()));
 
-        let station: Station = serde_json::from_value(json).unwrap();
-        assert_eq!(station, Station::default());
-    }
+static DISK_CACHE: AtomicBool = AtomicBool::new(false);
 
-    #[test]
-    fn test_station_serialize() {
-        let station = Station {
-            stationuuid: "test-uuid".to_string(),
-            name: "Test Station".to_string(),
-            url: "http://test.com".to_string(),
-            url_resolved: "http://resolved.test.com".to_string(),
-            homepage: "http://homepage.com".to_string(),
-            favicon: "http://favicon.com".to_string(),
-            tags: "test".to_string(),
-            country: "TestLand".to_string(),
-            language: "TestLang".to_string(),
-        };
+/// Keep the response cache on disk so it survives restarts. Enabling loads
+/// what a previous session stored; disabling deletes the file.
+pub fn set_disk_cache(enabled: bool) {
+    DISK_CACHE.store(enabled, Ordering::Relaxed);
+    let Some(path) = cache::cache_file() else {
+        return;
+    };
+    if enabled {
+        let stored = ResponseCache::load(&path);
+        if let Ok(mut memory) = RESPONSE_CACHE.lock() {
+            memory.merge(stored, cache::now());
+        }
+    } else if path.exists() {
+        if let Err(e) = std::fs::remove_file(&path) {
+            warn!("Failed to remove response cache {}: {}", path.display(), e);
+        }
+    }
+}
 
-        let json = serde_json::to_value(&station).unwrap();
-        assert_eq!(json["stationuuid"], "test-uuid");
-        assert_eq!(json["name"], "Test Station");
-        assert_eq!(json["url"], "http://test.com");
+fn clear_cache() {
+    if let Ok(mut memory) = RESPONSE_CACHE.lock() {
+        *memory = ResponseCache::default();
     }
+}
 
-    #[test]
-    fn test_station_clone() {
-        let station1 = Station {
-            name: "Clone Test".to_string(),
-            ..Default::default()
-        };
-        let station2 = station1.clone();
-        assert_eq!(station1, station2);
+fn store_in_cache(key: String, stations: &[Station]) {
+    let Ok(mut memory) = RESPONSE_CACHE.lock() else {
+        return;
+    };
+    memory.insert(key, stations.to_vec(), cache::now());
+    drop(memory);
+    if DISK_CACHE.load(Ordering::Relaxed) {
+        tokio::task::spawn_blocking(save_disk_cache);
     }
+}
 
-    #[test]
-    fn test_station_equality() {
-        let station1 = Station {
-            stationuuid: "same-uuid".to_string(),
-            name: "Same Name".to_string(),
-            ..Default::default()
-        };
-        let station2 = Station {
-            stationuuid: "same-uuid".to_string(),
-            name: "Same Name".to_string(),
-            ..Default::default()
-        };
-        let station3 = Station {
-            stationuuid: "different-uuid".to_string(),
-            name: "Same Name".to_string(),
-            ..Default::default()
-        };
+/// Write the response cache to disk, off the async runtime and without
+/// holding the cache during the fsync. Writers take turns and each one
+/// saves the latest contents, so an older copy never lands last.
+fn save_disk_cache() {
+    static WRITER: Mutex<()> = Mutex::new(());
+    let Ok(_turn) = WRITER.lock() else {
+        return;
+    };
+    let Some(path) = cache::cache_file() else {
+        return;
+    };
+    let snapshot = match RESPONSE_CACHE.lock() {
+        Ok(memory) => memory.clone(),
+        Err(_) => return,
+    };
+    snapshot.save(&path);
+}
 
-        assert_eq!(station1, station2);
-        assert_ne!(station1, station3);
+/// Turn a reqwest failure into the matching `ApiError`
+fn classify(err: Error) -> ApiError {
+    if err.is_timeout() {
+        ApiError::Timeout(REQUEST_TIMEOUT_SECS.load(Ordering::Relaxed))
+    } else if let Some(status) = err.status() {
+        ApiError::ErrorResponse {
+            status: status.as_u16(),
+            message: status.canonical_reason().unwrap_or_default().to_string(),
+        }
+    } else {
+        ApiError::RequestFailed(err)
     }
+}
 
-    #[test]
-    fn test_api_station_to_station_conversion() {
-        let api_station = ApiStation {
-            stationuuid: Some("uuid".to_string()),
-            name: Some("Name".to_string()),
+fn too_large() -> ApiError {
+    ApiError::InvalidResponse(format!("response exceeds {} bytes", MAX_RESPONSE_SIZE))
+}
+
+/// Attempts per mirror when it reports a transient failure
+pub const MAX_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry; doubled for each further attempt
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(250);
+
+/// Rate limiting and overloaded gateways are worth retrying on the same
+/// mirror; anything else moves on to the next one
+fn is_transient(err: &Error) -> bool {
+    matches!(
+        err.status().map(|s| s.as_u16()),
+        Some(429 | 502 | 503 | 504)
+    )
+}
+
+fn backoff_delay(attempt: u32) -> Duration {
+    RETRY_BASE_DELAY * 2u32.saturating_pow(attempt)
+}
+
+/// What a directory request is doing while it works through the mirrors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestProgress {
+    /// Asking `host`, the mirror at `index` in the order they are tried
+    Mirror { host: String, index: usize },
+    /// Asking `host` again after a transient failure; `attempt` counts
+    /// from 1
+    Retry { host: String, attempt: u32 },
+}
+
+tokio::task_local! {
+    static PROGRESS: UnboundedSender<RequestProgress>;
+}
+
+/// Run `request`, sending the progress of the directory requests it makes
+/// to `progress`. The channel closes once `request` finishes.
+pub async fn with_progress<F: Future>(
+    progress: UnboundedSender<RequestProgress>,
+    request: F,
+) -> F::Output {
+    PROGRESS.scope(progress, request).await
+}
+
+/// Tell whoever is waiting in [`with_progress`], if anyone
+fn report(progress: RequestProgress) {
+    let _ = PROGRESS.try_with(|sender| sender.unbounded_send(progress));
+}
+
+fn host_of(url: &str) -> String {
+    Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| url.to_string())
+}
+
+/// GET `url`, retrying transient failures with exponential backoff
+async fn get_with_retry(
+    client: &reqwest::Client,
+    url: &str,
+    params: &[(&str, &str)],
+) -> Result<reqwest::Response, Error> {
+    let mut attempt = 0;
+    loop {
+        let result = client
+            .get(url)
+            .query(params)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status());
+        match result {
+            Err(e) if is_transient(&e) && attempt + 1 < MAX_ATTEMPTS => {
+                let delay = backoff_delay(attempt);
+                debug!("{} from {}, retrying in {:?}", e, url, delay);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+                report(RequestProgress::Retry {
+                    host: host_of(url),
+                    attempt: attempt + 1,
+                });
+            }
+            other => return other,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ServerEntry {
+    #[serde(default)]
+    name: String,
+}
+
+/// Turn the `/json/servers` response into a de-duplicated list of base URLs
+fn parse_server_list(bytes: &[u8]) -> Vec<String> {
+    let entries: Vec<ServerEntry> = serde_json::from_slice(bytes).unwrap_or_default();
+    let mut servers: Vec<String> = Vec::new();
+    for entry in entries {
+        let name = entry.name.trim().to_lowercase();
+        if name.is_empty() || name.contains('/') {
+            continue;
+        }
+        let url = format!("https://{name}");
+        if !servers.contains(&url) {
+            servers.push(url);
+        }
+    }
+    servers
+}
+
+/// Shuffle so load is spread across mirrors, as radio-browser.info recommends
+fn shuffle_servers(servers: &mut [String]) {
+    let state = RandomState::new();
+    servers.sort_by_cached_key(|s| state.hash_one(s));
+}
+
+/// Move a mirror that just answered to the front of the list
+fn promote_server(servers: &mut Vec<String>, server: &str) {
+    if let Some(pos) = servers.iter().position(|s| s == server) {
+        let working = servers.remove(pos);
+        servers.insert(0, working);
+    }
+}
+
+async fn discover_servers() -> Vec<String> {
+    let client = http_client();
+
+    let bytes = match get_with_retry(&client, SERVER_LIST_URL, &[]).await {
+        Ok(response) => response.bytes().await.ok(),
+        Err(e) => {
+            warn!("Mirror discovery failed: {}", e);
+            None
+        }
+    };
+
+    bytes
+        .filter(|b| b.len() <= MAX_RESPONSE_SIZE)
+        .map(|b| parse_server_list(&b))
+        .unwrap_or_default()
+}
+
+/// Servers to try, in order: the configured endpoint if any, otherwise the
+/// public mirrors. A successful discovery is kept for the session; when it
+/// fails the built-in list is used and discovery is retried after
+/// [`FALLBACK_MIRRORS_TTL`].
+async fn mirror_list() -> Vec<String> {
+    if let Some(custom) = CUSTOM_BASE_URL.read().ok().and_then(|c| c.clone()) {
+        return vec![custom];
+    }
+
+    if let Ok(cached) = MIRRORS.lock() {
+        if cached.is_fresh() {
+            return cached.servers.clone();
+        }
+    }
+
+    let mut servers = discover_servers().await;
+    let expires = if servers.is_empty() {
+        servers = API_SERVERS.iter().map(|s| (*s).to_string()).collect();
+        Some(Instant::now() + FALLBACK_MIRRORS_TTL)
+    } else {
+        info!("Discovered {} radio-browser mirrors", servers.len());
+        None
+    };
+    shuffle_servers(&mut servers);
+
+    if let Ok(mut cached) = MIRRORS.lock() {
+        *cached = MirrorList {
+            servers: servers.clone(),
+            expires,
+        };
+    }
+    servers
+}
+
+/// Remember a mirror that answered so the next request starts there
+fn mark_server_working(server: &str) {
+    if let Ok(mut cached) = MIRRORS.lock() {
+        promote_server(&mut cached.servers, server);
+    }
+}
+
+/// Field that plain words in the search box are matched against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SearchField {
+    #[default]
+    Name,
+    Tag,
+    Country,
+    Language,
+}
+
+impl SearchField {
+    pub const ALL: [SearchField; 4] = [Self::Name, Self::Tag, Self::Country, Self::Language];
+
+    fn from_key(key: &str) -> Option<Self> {
+        match key.to_ascii_lowercase().as_str() {
+            "name" => Some(Self::Name),
+            "tag" | "genre" => Some(Self::Tag),
+            "country" => Some(Self::Country),
+            "language" | "lang" => Some(Self::Language),
+            _ => None,
+        }
+    }
+}
+
+/// Result ordering supported by the `order:` query keyword
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchOrder {
+    Name,
+    Votes,
+    Clicks,
+    Bitrate,
+}
+
+impl SearchOrder {
+    fn from_value(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "name" => Some(Self::Name),
+            "votes" | "vote" => Some(Self::Votes),
+            "clicks" | "clickcount" | "popular" => Some(Self::Clicks),
+            "bitrate" | "quality" => Some(Self::Bitrate),
+            _ => None,
+        }
+    }
+
+    /// API `order` value and whether it should be descending
+    fn api_order(self) -> (&'static str, bool) {
+        match self {
+            Self::Name => ("name", false),
+            Self::Votes => ("votes", true),
+            Self::Clicks => ("clickcount", true),
+            Self::Bitrate => ("bitrate", true),
+        }
+    }
+}
+
+/// Search filters picked above the results and kept as defaults
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchFilters {
+    /// ISO 3166 code; empty for any country
+    #[serde(default)]
+    pub country: String,
+    /// Directory language name, e.g. "english"; empty for any
+    #[serde(default)]
+    pub language: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl SearchFilters {
+    pub fn is_active(&self) -> bool {
+        !self.country.is_empty() || !self.language.is_empty() || !self.tags.is_empty()
+    }
+
+    /// Add `tag` if it isn't selected yet, otherwise remove it
+    pub fn toggle_tag(&mut self, tag: &str) {
+        match self.tags.iter().position(|t| t == tag) {
+            Some(index) => {
+                self.tags.remove(index);
+            }
+            None => self.tags.push(tag.to_string()),
+        }
+    }
+}
+
+/// Directory list the filter choices come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FacetKind {
+    Country,
+    Language,
+    Tag,
+}
+
+impl FacetKind {
+    fn path(self) -> &'static str {
+        match self {
+            Self::Country => "json/countries",
+            Self::Language => "json/languages",
+            Self::Tag => "json/tags",
+        }
+    }
+}
+
+/// A country, language or tag and how many stations have it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Facet {
+    pub name: String,
+    /// ISO code of a country or language
+    #[serde(default, alias = "iso_3166_1", alias = "iso_639")]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub stationcount: u32,
+}
+
+/// Structured search, built from the search box text
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchParams {
+    pub name: Option<String>,
+    pub tag: Option<String>,
+    pub country: Option<String>,
+    pub language: Option<String>,
+    /// Minimum bitrate in kbps (`bitrate>128`)
+    pub min_bitrate: Option<u32>,
+    /// Tags a station must all have, from the filter chips
+    pub tag_list: Vec<String>,
+    pub order: Option<SearchOrder>,
+    /// Skip stations that failed the directory's last health check
+    pub hide_broken: bool,
+}
+
+impl SearchParams {
+    /// Parse `tag:jazz country:BR name:fm bitrate>128 order:votes` style
+    /// queries. Words without a known `key:` prefix are matched against
+    /// `default_field`.
+    pub fn parse(query: &str, default_field: SearchField) -> Self {
+        let mut params = Self::default();
+        let mut free_words: Vec<&str> = Vec::new();
+
+        for word in query.split_whitespace() {
+            if let Some(min) = parse_min_bitrate(word) {
+                params.min_bitrate = Some(min);
+                continue;
+            }
+            if let Some(order) = word
+                .strip_prefix("order:")
+                .and_then(SearchOrder::from_value)
+            {
+                params.order = Some(order);
+                continue;
+            }
+            match word.split_once(':') {
+                Some((key, value)) if !value.is_empty() => match SearchField::from_key(key) {
+                    Some(field) => params.append(field, value),
+                    None => free_words.push(word),
+                },
+                _ => free_words.push(word),
+            }
+        }
+        if !free_words.is_empty() {
+            params.append(default_field, &free_words.join(" "));
+        }
+        params
+    }
+
+    fn append(&mut self, field: SearchField, value: &str) {
+        let slot = match field {
+            SearchField::Name => &mut self.name,
+            SearchField::Tag => &mut self.tag,
+            SearchField::Country => &mut self.country,
+            SearchField::Language => &mut self.language,
+        };
+        match slot {
+            Some(existing) => {
+                existing.push(' ');
+                existing.push_str(value);
+            }
+            None => *slot = Some(value.to_string()),
+        }
+    }
+
+    /// Narrow the search by `filters` where the query doesn't say otherwise
+    pub fn apply_filters(&mut self, filters: &SearchFilters) {
+        if self.country.is_none() && !filters.country.is_empty() {
+            self.country = Some(filters.country.clone());
+        }
+        if self.language.is_none() && !filters.language.is_empty() {
+            self.language = Some(filters.language.clone());
+        }
+        self.tag_list = filters.tags.clone();
+    }
+
+    /// Require at least `min` kbps unless the query already asks for more
+    pub fn raise_min_bitrate(&mut self, min: u32) {
+        if min > 0 && self.min_bitrate.is_none_or(|current| current < min) {
+            self.min_bitrate = Some(min);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min_bitrate.is_none()
+            && self.order.is_none()
+            && self.tag_list.is_empty()
+            && [&self.name, &self.tag, &self.country, &self.language]
+                .iter()
+                .all(|v| v.as_deref().is_none_or(|v| v.trim().is_empty()))
+    }
+
+    /// Query string pairs for `/json/stations/search`
+    fn to_query(&self) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+        if let Some(name) = &self.name {
+            query.push(("name", name.clone()));
+        }
+        if let Some(tag) = &self.tag {
+            query.push(("tag", tag.clone()));
+        }
+        if let Some(country) = &self.country {
+            // Two letters is an ISO code, anything longer a country name
+            if country.len() == 2 && country.chars().all(|c| c.is_ascii_alphabetic()) {
+                query.push(("countrycode", country.to_ascii_uppercase()));
+            } else {
+                query.push(("country", country.clone()));
+            }
+        }
+        if let Some(language) = &self.language {
+            query.push(("language", language.clone()));
+        }
+        if !self.tag_list.is_empty() {
+            query.push(("tagList", self.tag_list.join(",")));
+        }
+        if let Some(min) = self.min_bitrate {
+            query.push(("bitrateMin", min.to_string()));
+        }
+        if let Some(order) = self.order {
+            let (order, reverse) = order.api_order();
+            query.push(("order", order.to_string()));
+            query.push(("reverse", reverse.to_string()));
+        }
+        if self.hide_broken {
+            query.push(("hidebroken", "true".to_string()));
+        }
+        query
+    }
+}
+
+/// Parse `bitrate>128`, `bitrate>=128` or `bitrate:128` into a minimum bitrate
+fn parse_min_bitrate(word: &str) -> Option<u32> {
+    let rest = word.strip_prefix("bitrate")?;
+    if let Some(value) = rest.strip_prefix(">=").or_else(|| rest.strip_prefix(':')) {
+        value.parse().ok()
+    } else {
+        let value: u32 = rest.strip_prefix('>')?.parse().ok()?;
+        value.checked_add(1)
+    }
+}
+
+/// Search for radio stations
+pub async fn search_stations(params: SearchParams) -> Result<Vec<Station>, ApiError> {
+    if params.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    debug!("Searching stations for {:?}", params);
+
+    let mut query = params.to_query();
+    query.push(("limit", "20".to_string()));
+    let query: Vec<(&str, &str)> = query.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    let stations = fetch_stations("json/stations/search", &query).await?;
+    Ok(drop_broken(stations, params.hide_broken))
+}
+
+/// Fetch the stations with the most votes on radio-browser.info
+pub async fn top_voted_stations(limit: u32, hide_broken: bool) -> Result<Vec<Station>, ApiError> {
+    debug!("Fetching top {} voted stations", limit);
+    let stations = fetch_stations(
+        &format!("json/stations/topvote/{limit}"),
+        &[("hidebroken", if hide_broken { "true" } else { "false" })],
+    )
+    .await?;
+    Ok(drop_broken(stations, hide_broken))
+}
+
+/// Fetch the most clicked (played) stations on radio-browser.info
+pub async fn top_clicked_stations(limit: u32, hide_broken: bool) -> Result<Vec<Station>, ApiError> {
+    debug!("Fetching top {} clicked stations", limit);
+    let stations = fetch_stations(
+        &format!("json/stations/topclick/{limit}"),
+        &[("hidebroken", if hide_broken { "true" } else { "false" })],
+    )
+    .await?;
+    Ok(drop_broken(stations, hide_broken))
+}
+
+/// Popular stations around `near`, most played first. A `min_bitrate` of
+/// 0 allows any.
+pub async fn nearby_stations(
+    near: Nearby,
+    limit: u32,
+    hide_broken: bool,
+    min_bitrate: u32,
+) -> Result<Vec<Station>, ApiError> {
+    debug!("Fetching stations near {:?}", near);
+    let limit = limit.to_string();
+    let mut params = vec![
+        ("order", "clickcount".to_string()),
+        ("reverse", "true".to_string()),
+        ("limit", limit),
+        ("hidebroken", hide_broken.to_string()),
+    ];
+    if min_bitrate > 0 {
+        params.push(("bitrateMin", min_bitrate.to_string()));
+    }
+    match near {
+        Nearby::Coordinates { lat, long } => {
+            params.push(("geo_lat", lat.to_string()));
+            params.push(("geo_long", long.to_string()));
+            params.push(("geo_distance", geo::NEARBY_RADIUS_M.to_string()));
+        }
+        Nearby::Country(code) => params.push(("countrycode", code)),
+    }
+    let params: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    let stations = fetch_stations("json/stations/search", &params).await?;
+    Ok(drop_broken(stations, hide_broken))
+}
+
+/// The `limit` countries, languages or tags with the most stations
+pub async fn facets(kind: FacetKind, limit: u32) -> Result<Vec<Facet>, ApiError> {
+    debug!("Fetching {:?} filter choices", kind);
+    let limit = limit.to_string();
+    let params = [
+        ("order", "stationcount"),
+        ("reverse", "true"),
+        ("hidebroken", "true"),
+        ("limit", limit.as_str()),
+    ];
+    let client = http_client();
+    let mut last_error = None;
+    for server in mirror_list().await {
+        let url = format!("{}/{}", server, kind.path());
+        let bytes = match get_with_retry(&client, &url, &params).await {
+            Ok(response) => response.bytes().await,
+            Err(e) => Err(e),
+        };
+        match bytes {
+            Ok(bytes) if bytes.len() > MAX_RESPONSE_SIZE => last_error = Some(too_large()),
+            Ok(bytes) => match serde_json::from_slice::<Vec<Facet>>(&bytes) {
+                Ok(facets) => {
+                    mark_server_working(&server);
+                    return Ok(facets
+                        .into_iter()
+                        .filter(|f| !f.name.trim().is_empty())
+                        .collect());
+                }
+                Err(e) => last_error = Some(ApiError::JsonParseFailed(e)),
+            },
+            Err(e) => {
+                warn!("Request to {} failed: {}", server, e);
+                last_error = Some(classify(e));
+            }
+        }
+    }
+    Err(last_error.unwrap_or_else(|| ApiError::InvalidResponse("no servers".to_string())))
+}
+
+/// Client-side guard in case a mirror ignores `hidebroken`
+fn drop_broken(mut stations: Vec<Station>, hide_broken: bool) -> Vec<Station> {
+    if hide_broken {
+        stations.retain(|s| s.lastcheckok != Some(false));
+    }
+    stations
+}
+
+/// Tell radio-browser.info that a station was played, so its popularity
+/// statistics stay meaningful. Failures are logged and otherwise ignored.
+pub async fn report_click(stationuuid: String) {
+    let client = http_client();
+
+    for server in mirror_list().await {
+        let url = format!("{}/json/url/{}", server, stationuuid);
+        match get_with_retry(&client, &url, &[]).await {
+            Ok(_) => {
+                debug!("Reported click for {} to {}", stationuuid, server);
+                mark_server_working(&server);
+                return;
+            }
+            Err(e) => warn!("Click report to {} failed: {}", server, e),
+        }
+    }
+}
+
+const LISTENBRAINZ_SUBMIT_URL: &str = "https://api.listenbrainz.org/1/submit-listens";
+
+/// Send a `submit-listens` request to ListenBrainz, authenticated with the
+/// user's token
+pub async fn submit_listens<T: Serialize>(token: &str, submission: &T) -> Result<(), ApiError> {
+    http_client()
+        .post(LISTENBRAINZ_SUBMIT_URL)
+        .header(reqwest::header::AUTHORIZATION, format!("Token {token}"))
+        .json(submission)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(classify)?;
+    Ok(())
+}
+
+/// How long the pre-flight check waits for a stream to answer
+const PREFLIGHT_TIMEOUT: Duration = Duration::from_secs(4);
+
+/// Open `url` and look at the response headers before handing the stream to
+/// the player, so a dead station gets a precise error instead of silence.
+/// Streams are endless, so the body is never read.
+///
+/// Responses reqwest can't parse are not reported: mpv understands more than
+/// reqwest does (SHOUTCAST answers `ICY 200 OK`, for one), so those are
+/// logged and the stream is played anyway. See [`StreamError::is_definite`]
+/// for which of the reported problems should stop playback.
+pub async fn check_stream(url: String) -> Result<(), StreamError> {
+    let response = match http_client()
+        .get(&url)
+        .timeout(PREFLIGHT_TIMEOUT)
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            return match classify_stream_error(&e) {
+                Some(err) => Err(err),
+                None => {
+                    warn!("Pre-flight check of {} inconclusive: {}", url, e);
+                    Ok(())
+                }
+            };
+        }
+    };
+    check_stream_status(response.status().as_u16())
+}
+
+fn check_stream_status(status: u16) -> Result<(), StreamError> {
+    if status >= 400 {
+        Err(StreamError::Offline(status))
+    } else {
+        Ok(())
+    }
+}
+
+/// Tell timeouts, TLS failures and unreachable hosts apart. Anything else
+/// (a response reqwest can't parse) gives `None`.
+fn classify_stream_error(err: &Error) -> Option<StreamError> {
+    if err.is_timeout() {
+        return Some(StreamError::Timeout(PREFLIGHT_TIMEOUT.as_secs()));
+    }
+    // reqwest doesn't expose these as kinds; find them in the chain
+    let mut source: Option<&dyn std::error::Error> = Some(err);
+    while let Some(e) = source {
+        let message = e.to_string();
+        if is_tls_message(&message) {
+            return Some(StreamError::Tls(message));
+        }
+        let refused = e
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|io| io.kind() == std::io::ErrorKind::ConnectionRefused);
+        if refused || is_dns_message(&message) {
+            return Some(StreamError::Unreachable(err.to_string()));
+        }
+        source = e.source();
+    }
+    None
+}
+
+fn is_tls_message(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    ["certificate", "tls", "ssl", "handshake"]
+        .iter()
+        .any(|word| message.contains(word))
+}
+
+fn is_dns_message(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    ["dns error", "failed to lookup address"]
+        .iter()
+        .any(|phrase| message.contains(phrase))
+}
+
+/// Stations kept from a single remote list
+const MAX_LIST_STATIONS: usize = 500;
+
+/// Parse a station list in OPML or JSON (radio-browser's format, or this
+/// applet's own). Entries without an http(s) stream URL are skipped and
+/// every station gets an identifier.
+pub fn parse_station_list(bytes: &[u8]) -> Result<Vec<Station>, ApiError> {
+    let text = std::str::from_utf8(bytes).map_err(|e| ApiError::InvalidResponse(e.to_string()))?;
+    let stations = if text.trim_start().starts_with('<') {
+        crate::opml::parse(text).map_err(|e| ApiError::InvalidResponse(e.to_string()))?
+    } else {
+        match serde_json::from_str::<Vec<ApiStation>>(text) {
+            Ok(api) => api.into_iter().map(Station::from).collect(),
+            Err(_) => serde_json::from_str::<Vec<Station>>(text)?,
+        }
+    };
+
+    Ok(stations
+        .into_iter()
+        .filter(|s| {
+            Url::parse(s.url.trim()).is_ok_and(|u| u.scheme() == "http" || u.scheme() == "https")
+        })
+        .take(MAX_LIST_STATIONS)
+        .map(|mut s| {
+            if s.url_resolved.is_empty() {
+                s.url_resolved.clone_from(&s.url);
+            }
+            s.ensure_uuid();
+            s.ensure_id();
+            s
+        })
+        .collect())
+}
+
+/// Download a station list published at `url`, e.g. a curator's OPML file
+pub async fn fetch_station_list(url: String) -> Result<Vec<Station>, ApiError> {
+    let url = validate_base_url(&url).map_err(|e| ApiError::InvalidUrl(e.to_string()))?;
+    let response = get_with_retry(&http_client(), &url, &[])
+        .await
+        .map_err(classify)?;
+    if response
+        .content_length()
+        .is_some_and(|len| len as usize > MAX_RESPONSE_SIZE)
+    {
+        return Err(too_large());
+    }
+    let bytes = response.bytes().await.map_err(classify)?;
+    if bytes.len() > MAX_RESPONSE_SIZE {
+        return Err(too_large());
+    }
+    parse_station_list(&bytes)
+}
+
+/// Favicons larger than this aren't shown
+const MAX_FAVICON_SIZE: usize = 512 * 1024;
+
+/// Download a station's favicon for display
+pub async fn fetch_favicon(url: String) -> Result<Vec<u8>, ApiError> {
+    let url = parse_http_url(&url).ok_or_else(|| ApiError::InvalidUrl(url.clone()))?;
+    let response = http_client()
+        .get(url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(classify)?;
+    if response
+        .content_length()
+        .is_some_and(|len| len as usize > MAX_FAVICON_SIZE)
+    {
+        return Err(favicon_too_large());
+    }
+    let bytes = response.bytes().await.map_err(classify)?;
+    if bytes.len() > MAX_FAVICON_SIZE {
+        return Err(favicon_too_large());
+    }
+    Ok(bytes.to_vec())
+}
+
+fn favicon_too_large() -> ApiError {
+    ApiError::InvalidResponse(format!("favicon exceeds {} bytes", MAX_FAVICON_SIZE))
+}
+
+/// Maximum number of UUIDs sent in a single `byuuid` request
+const UUID_BATCH_SIZE: usize = 50;
+
+/// Fetch stations by their radio-browser UUIDs; unknown UUIDs are simply absent
+pub async fn stations_by_uuid(uuids: &[String]) -> Result<Vec<Station>, ApiError> {
+    let mut stations = Vec::with_capacity(uuids.len());
+    for batch in uuids.chunks(UUID_BATCH_SIZE) {
+        let joined = batch.join(",");
+        stations
+            .extend(fetch_stations("json/stations/byuuid", &[("uuids", joined.as_str())]).await?);
+    }
+    Ok(stations)
+}
+
+/// Current directory entries of the favorites that came from radio-browser,
+/// to refresh their snapshots with (see [`Station::refresh_from`])
+pub async fn refresh_favorites(favorites: Vec<Station>) -> Result<Vec<Station>, ApiError> {
+    let uuids = directory_uuids(&favorites);
+    if uuids.is_empty() {
+        return Ok(Vec::new());
+    }
+    stations_by_uuid(&uuids).await
+}
+
+/// UUIDs of the stations radio-browser knows, i.e. not custom ones
+fn directory_uuids(stations: &[Station]) -> Vec<String> {
+    stations
+        .iter()
+        .filter(|s| !s.stationuuid.is_empty() && !s.is_custom())
+        .map(|s| s.stationuuid.clone())
+        .collect()
+}
+
+/// The directory favorites missing from a [`refresh_favorites`] result:
+/// radio-browser no longer knows their UUID
+pub fn stale_favorites(favorites: &[Station], fresh: &[Station]) -> Vec<Station> {
+    let known: std::collections::HashSet<&str> =
+        fresh.iter().map(|s| s.stationuuid.as_str()).collect();
+    favorites
+        .iter()
+        .filter(|s| !s.stationuuid.is_empty() && !s.is_custom())
+        .filter(|s| !known.contains(s.stationuuid.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// Re-resolve favorites whose UUID radio-browser no longer knows about
+/// (see [`stale_favorites`]).
+///
+/// Returns `(old_uuid, replacement)` pairs for every favorite that could be
+/// found again by stream URL or exact name.
+pub async fn reconcile_favorites(stale: Vec<Station>) -> Result<Vec<(String, Station)>, ApiError> {
+    let mut replacements = Vec::new();
+    for station in stale.iter().filter(|s| !s.is_custom()) {
+        debug!(
+            "Favorite '{}' has a stale UUID, searching again",
+            station.name
+        );
+        let mut candidates = Vec::new();
+        for url in [&station.url, &station.url_resolved] {
+            if !url.is_empty() {
+                candidates
+                    .extend(fetch_stations("json/stations/byurl", &[("url", url.as_str())]).await?);
+            }
+        }
+        if candidates.is_empty() {
+            let params = [("name", station.name.as_str()), ("nameExact", "true")];
+            candidates = fetch_stations("json/stations/search", &params).await?;
+        }
+        if let Some(found) = pick_replacement(station, &candidates) {
+            replacements.push((station.stationuuid.clone(), found));
+        } else {
+            warn!(
+                "Could not find a replacement for favorite '{}'",
+                station.name
+            );
+        }
+    }
+    Ok(replacements)
+}
+
+/// Choose the candidate that most plausibly is the same station under a new UUID
+fn pick_replacement(stale: &Station, candidates: &[Station]) -> Option<Station> {
+    let same_url = |c: &Station| {
+        (!stale.url.is_empty() && c.url == stale.url)
+            || (!stale.url_resolved.is_empty() && c.url_resolved == stale.url_resolved)
+    };
+    let same_name = |c: &Station| c.name.trim().eq_ignore_ascii_case(stale.name.trim());
+
+    let eligible: Vec<&Station> = candidates
+        .iter()
+        .filter(|c| !c.stationuuid.is_empty() && c.stationuuid != stale.stationuuid)
+        .collect();
+
+    eligible
+        .iter()
+        .find(|c| same_url(c) && same_name(c))
+        .or_else(|| eligible.iter().find(|c| same_url(c)))
+        .or_else(|| eligible.iter().find(|c| same_name(c)))
+        .map(|c| (*c).clone())
+}
+
+/// Query a station list endpoint, falling back through the mirror servers
+async fn fetch_stations(path: &str, params: &[(&str, &str)]) -> Result<Vec<Station>, ApiError> {
+    let key = ResponseCache::key(path, params);
+    let cached = RESPONSE_CACHE
+        .lock()
+        .ok()
+        .and_then(|memory| memory.get_fresh(&key, cache::now()));
+    if let Some(stations) = cached {
+        debug!("Serving {} from cache", key);
+        return Ok(stations);
+    }
+
+    let client = http_client();
+
+    let mut last_error: Option<ApiError> = None;
+
+    for (index, server) in mirror_list().await.into_iter().enumerate() {
+        let url = format!("{}/{}", server, path);
+        report(RequestProgress::Mirror {
+            host: host_of(&server),
+            index,
+        });
+
+        match get_with_retry(&client, &url, params).await {
+            Ok(valid_response) => {
+                // Check Content-Length header first if available (early rejection)
+                if let Some(content_length) = valid_response.content_length() {
+                    if content_length as usize > MAX_RESPONSE_SIZE {
+                        warn!(
+                            "Response from {} exceeds size limit: {} bytes (max: {})",
+                            server, content_length, MAX_RESPONSE_SIZE
+                        );
+                        last_error = Some(too_large());
+                        continue;
+                    }
+                }
+
+                // Read response body as bytes with size validation
+                match valid_response.bytes().await {
+                    Ok(bytes) => {
+                        if bytes.len() > MAX_RESPONSE_SIZE {
+                            warn!(
+                                "Response body from {} exceeds size limit: {} bytes (max: {})",
+                                server,
+                                bytes.len(),
+                                MAX_RESPONSE_SIZE
+                            );
+                            last_error = Some(too_large());
+                            continue;
+                        }
+
+                        // Deserialize from validated bytes
+                        match serde_json::from_slice::<Vec<ApiStation>>(&bytes) {
+                            Ok(api_stations) => {
+                                debug!("Found {} stations from {}", api_stations.len(), server);
+                                mark_server_working(&server);
+                                let mut stations: Vec<Station> =
+                                    api_stations.into_iter().map(Station::from).collect();
+                                // Entries without a UUID get one from their URL,
+                                // so every result can be referenced by id
+                                for station in &mut stations {
+                                    station.ensure_uuid();
+                                    station.ensure_id();
+                                }
+                                store_in_cache(key, &stations);
+                                return Ok(stations);
+                            }
+                            Err(e) => {
+                                warn!("JSON parse error from {}: {}", server, e);
+                                // Continue to next server on parse error
+                                last_error = Some(ApiError::JsonParseFailed(e));
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        warn!("Failed to read response body from {}: {}", server, e);
+                        last_error = Some(classify(e));
+                    }
+                }
+            }
+            Err(e) => {
+                warn!("Request to {} failed: {}", server, e);
+                last_error = Some(classify(e));
+            }
+        }
+    }
+
+    // Offline: an older answer beats none
+    let stale = RESPONSE_CACHE
+        .lock()
+        .ok()
+        .and_then(|memory| memory.get_stale(&key, cache::now()));
+    if let Some(stations) = stale {
+        warn!("All servers failed, using cached results for {}", key);
+        return Ok(stations);
+    }
+
+    // All servers failed - return the last error or empty result
+    match last_error {
+        Some(e) => Err(e),
+        None => Ok(Vec::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_station_default() {
+        let station = Station::default();
+        assert!(station.id.is_empty());
+        assert_eq!(station.stationuuid, "");
+        assert_eq!(station.name, "");
+        assert_eq!(station.url, "");
+        assert_eq!(station.url_resolved, "");
+        assert_eq!(station.homepage, "");
+        assert_eq!(station.favicon, "");
+        assert_eq!(station.tags, "");
+        assert_eq!(station.country, "");
+        assert_eq!(station.language, "");
+        assert_eq!(station.countrycode, "");
+        assert_eq!(station.bitrate, 0);
+        assert_eq!(station.codec, "");
+        assert_eq!(station.votes, 0);
+        assert_eq!(station.clickcount, 0);
+        assert_eq!(station.lastcheckok, None);
+    }
+
+    #[test]
+    fn test_station_deserialize_complete() {
+        let json = json!({
+            "stationuuid": "abc-123",
+            "name": "Test Radio",
+            "url": "http://example.com/stream",
+            "url_resolved": "http://example.com/resolved",
+            "homepage": "http://example.com",
+            "favicon": "http://example.com/favicon.png",
+            "tags": "rock,pop",
+            "country": "USA",
+            "language": "English"
+        });
+
+        let station: Station = serde_json::from_value(json).unwrap();
+        assert_eq!(station.stationuuid, "abc-123");
+        assert_eq!(station.name, "Test Radio");
+        assert_eq!(station.url, "http://example.com/stream");
+        assert_eq!(station.url_resolved, "http://example.com/resolved");
+        assert_eq!(station.homepage, "http://example.com");
+        assert_eq!(station.favicon, "http://example.com/favicon.png");
+        assert_eq!(station.tags, "rock,pop");
+        assert_eq!(station.country, "USA");
+        assert_eq!(station.language, "English");
+    }
+
+    #[test]
+    fn test_station_deserialize_with_nulls() {
+        let json = json!({
+            "stationuuid": null,
+            "name": "Test Radio",
+            "url": null,
+            "url_resolved": "http://example.com/resolved",
+            "homepage": null,
+            "favicon": null,
+            "tags": null,
+            "country": null,
+            "language": null
+        });
+
+        // Station struct uses ApiStation internally for null handling
+        let api_station: ApiStation = serde_json::from_value(json).unwrap();
+        let station: Station = api_station.into();
+
+        assert_eq!(station.stationuuid, "");
+        assert_eq!(station.name, "Test Radio");
+        assert_eq!(station.url, "");
+        assert_eq!(station.url_resolved, "http://example.com/resolved");
+        assert_eq!(station.homepage, "");
+        assert_eq!(station.favicon, "");
+        assert_eq!(station.tags, "");
+        assert_eq!(station.country, "");
+        assert_eq!(station.language, "");
+    }
+
+    #[test]
+    fn test_station_deserialize_missing_fields() {
+        let json = json!({
+            "name": "Minimal Station"
+        });
+
+        let station: Station = serde_json::from_value(json).unwrap();
+        assert_eq!(station.name, "Minimal Station");
+        assert_eq!(station.stationuuid, "");
+        assert_eq!(station.url, "");
+        assert_eq!(station.url_resolved, "");
+    }
+
+    #[test]
+    fn test_station_deserialize_empty_object() {
+        let json = json!({});
+
+        let station: Station = serde_json::from_value(json).unwrap();
+        assert_eq!(station, Station::default());
+    }
+
+    #[test]
+    fn test_station_serialize() {
+        let station = Station {
+            id: StationId::new(PROVIDER_RADIO_BROWSER, "test-uuid"),
+            stationuuid: "test-uuid".to_string(),
+            name: "Test Station".to_string(),
+            url: "http://test.com".to_string(),
+            url_resolved: "http://resolved.test.com".to_string(),
+            homepage: "http://homepage.com".to_string(),
+            favicon: "http://favicon.com".to_string(),
+            tags: "test".to_string(),
+            country: "TestLand".to_string(),
+            language: "TestLang".to_string(),
+            ..Default::default()
+        };
+
+        let json = serde_json::to_value(&station).unwrap();
+        assert_eq!(json["id"], "radio-browser:test-uuid");
+        assert_eq!(json["stationuuid"], "test-uuid");
+        assert_eq!(json["name"], "Test Station");
+        assert_eq!(json["url"], "http://test.com");
+    }
+
+    #[test]
+    fn test_station_clone() {
+        let station1 = Station {
+            name: "Clone Test".to_string(),
+            ..Default::default()
+        };
+        let station2 = station1.clone();
+        assert_eq!(station1, station2);
+    }
+
+    #[test]
+    fn test_station_equality() {
+        let station1 = Station {
+            stationuuid: "same-uuid".to_string(),
+            name: "Same Name".to_string(),
+            ..Default::default()
+        };
+        let station2 = Station {
+            stationuuid: "same-uuid".to_string(),
+            name: "Same Name".to_string(),
+            ..Default::default()
+        };
+        let station3 = Station {
+            stationuuid: "different-uuid".to_string(),
+            name: "Same Name".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(station1, station2);
+        assert_ne!(station1, station3);
+    }
+
+    #[test]
+    fn test_api_station_to_station_conversion() {
+        let api_station = ApiStation {
+            stationuuid: Some("uuid".to_string()),
+            name: Some("Name".to_string()),
             url: None,
             url_resolved: Some("resolved".to_string()),
             homepage: None,
@@ -320,25 +1721,831 @@ mod tests {
             tags: None,
             country: None,
             language: None,
+            countrycode: Some("US".to_string()),
+            bitrate: Some(128),
+            codec: Some("AAC".to_string()),
+            votes: Some(42),
+            clickcount: None,
+            lastcheckok: Some(0),
+        };
+
+        let station: Station = api_station.into();
+        assert_eq!(station.id, StationId::new(PROVIDER_RADIO_BROWSER, "uuid"));
+        assert_eq!(station.stationuuid, "uuid");
+        assert_eq!(station.name, "Name");
+        assert_eq!(station.url, "");
+        assert_eq!(station.url_resolved, "resolved");
+        assert_eq!(station.countrycode, "US");
+        assert_eq!(station.bitrate, 128);
+        assert_eq!(station.codec, "AAC");
+        assert_eq!(station.votes, 42);
+        assert_eq!(station.clickcount, 0);
+        assert_eq!(station.lastcheckok, Some(false));
+    }
+
+    #[test]
+    fn test_api_station_extended_fields_from_json() {
+        let json = json!({
+            "stationuuid": "abc",
+            "name": "Radio",
+            "bitrate": 320,
+            "codec": "MP3",
+            "votes": 10,
+            "clickcount": 99,
+            "lastcheckok": 1,
+            "countrycode": null
+        });
+        let station: Station = serde_json::from_value::<ApiStation>(json).unwrap().into();
+        assert_eq!(station.bitrate, 320);
+        assert_eq!(station.clickcount, 99);
+        assert_eq!(station.lastcheckok, Some(true));
+        assert_eq!(station.countrycode, "");
+    }
+
+    #[test]
+    fn test_old_favorite_without_extended_fields() {
+        // Favorites saved before the extended fields existed must still load
+        let json = json!({
+            "stationuuid": "abc",
+            "name": "Old Favorite",
+            "url_resolved": "http://example.com/stream"
+        });
+        let station: Station = serde_json::from_value(json).unwrap();
+        assert_eq!(station.name, "Old Favorite");
+        assert_eq!(station.bitrate, 0);
+        assert_eq!(station.lastcheckok, None);
+    }
+
+    #[test]
+    fn test_quality_label() {
+        let mut station = Station::default();
+        assert_eq!(station.quality_label(), None);
+        station.bitrate = 128;
+        assert_eq!(station.quality_label().as_deref(), Some("128 kbps"));
+        station.codec = "MP3".to_string();
+        assert_eq!(station.quality_label().as_deref(), Some("MP3 · 128 kbps"));
+    }
+
+    #[test]
+    fn test_country_flag() {
+        let mut station = Station::default();
+        assert_eq!(station.country_flag(), None);
+        station.countrycode = "de".to_string();
+        assert_eq!(
+            station.country_flag().as_deref(),
+            Some("\u{1F1E9}\u{1F1EA}")
+        );
+        station.countrycode = "GB".to_string();
+        assert_eq!(
+            station.country_flag().as_deref(),
+            Some("\u{1F1EC}\u{1F1E7}")
+        );
+        station.countrycode = "D1".to_string();
+        assert_eq!(station.country_flag(), None);
+    }
+
+    #[test]
+    fn test_custom_uuid_is_stable() {
+        let a = Station::custom_uuid("https://radio.example.com/live");
+        let b = Station::custom_uuid("https://radio.example.com/live");
+        assert_eq!(a, b);
+        assert!(Uuid::parse_str(&a).is_ok());
+    }
+
+    #[test]
+    fn test_custom_uuid_normalizes_url() {
+        assert_eq!(
+            Station::custom_uuid("HTTPS://Radio.Example.com"),
+            Station::custom_uuid(" https://radio.example.com/ ")
+        );
+        assert_ne!(
+            Station::custom_uuid("https://radio.example.com/a"),
+            Station::custom_uuid("https://radio.example.com/b")
+        );
+    }
+
+    #[test]
+    fn test_ensure_uuid_fills_missing() {
+        let mut station = Station {
+            name: "Custom".to_string(),
+            url_resolved: "https://icecast.example.com/stream".to_string(),
+            ..Default::default()
         };
+        assert!(station.ensure_uuid());
+        assert_eq!(
+            station.stationuuid,
+            Station::custom_uuid("https://icecast.example.com/stream")
+        );
+        assert!(!station.ensure_uuid());
+    }
 
-        let station: Station = api_station.into();
-        assert_eq!(station.stationuuid, "uuid");
-        assert_eq!(station.name, "Name");
-        assert_eq!(station.url, "");
-        assert_eq!(station.url_resolved, "resolved");
+    #[test]
+    fn test_ensure_uuid_keeps_existing_and_skips_urlless() {
+        let mut station = Station {
+            stationuuid: "abc-123".to_string(),
+            url: "https://example.com".to_string(),
+            ..Default::default()
+        };
+        assert!(!station.ensure_uuid());
+        assert_eq!(station.stationuuid, "abc-123");
+
+        let mut empty = Station::default();
+        assert!(!empty.ensure_uuid());
+        assert!(empty.stationuuid.is_empty());
+    }
+
+    #[test]
+    fn test_is_custom() {
+        let mut custom = Station {
+            url: "https://icecast.example.com/live".to_string(),
+            ..Default::default()
+        };
+        custom.ensure_uuid();
+        assert!(custom.is_custom());
+
+        let directory = Station {
+            stationuuid: "96202c39-0601-11e8-ae97-52543be04c81".to_string(),
+            url: "https://icecast.example.com/live".to_string(),
+            ..Default::default()
+        };
+        assert!(!directory.is_custom());
+    }
+
+    #[test]
+    fn test_check_stream_status() {
+        assert_eq!(check_stream_status(200), Ok(()));
+        assert_eq!(check_stream_status(302), Ok(()));
+        assert_eq!(check_stream_status(404), Err(StreamError::Offline(404)));
+        assert_eq!(check_stream_status(503), Err(StreamError::Offline(503)));
+    }
+
+    #[test]
+    fn test_is_dns_message() {
+        assert!(is_dns_message(
+            "dns error: failed to lookup address information: Name or service not known"
+        ));
+        assert!(!is_dns_message("invalid peer certificate: Expired"));
+        assert!(!is_dns_message("Connection refused (os error 111)"));
+    }
+
+    #[test]
+    fn test_is_tls_message() {
+        assert!(is_tls_message("invalid peer certificate: Expired"));
+        assert!(is_tls_message("received fatal alert: HandshakeFailure"));
+        assert!(is_tls_message("SSL routines::wrong version number"));
+        assert!(!is_tls_message("Connection refused (os error 111)"));
+    }
+
+    #[tokio::test]
+    async fn test_check_stream_refused() {
+        // Nothing can listen on port 0, so the connection is always refused
+        let res = check_stream("http://127.0.0.1:0/live".to_string()).await;
+        assert!(matches!(res, Err(StreamError::Unreachable(_))));
+    }
+
+    #[tokio::test]
+    async fn test_check_stream_timeout() {
+        // The backlog accepts the connection; nobody ever answers
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let res = check_stream(format!("http://127.0.0.1:{port}/live")).await;
+        assert_eq!(res, Err(StreamError::Timeout(PREFLIGHT_TIMEOUT.as_secs())));
+        drop(listener);
+    }
+
+    #[tokio::test]
+    async fn test_check_stream_plays_unparsed_response() {
+        use std::io::{Read, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = socket.read(&mut request);
+            let _ = socket.write_all(b"ICY 200 OK\r\nicy-name: Test\r\n\r\n");
+        });
+        let res = check_stream(format!("http://127.0.0.1:{port}/live")).await;
+        assert_eq!(res, Ok(()));
+    }
+
+    #[test]
+    fn test_custom_station() {
+        let station =
+            Station::custom(" My Icecast ", " https://icecast.example.com/live ", "").unwrap();
+        assert_eq!(station.name, "My Icecast");
+        assert_eq!(station.url, "https://icecast.example.com/live");
+        assert_eq!(station.url_resolved, station.url);
+        assert!(station.is_custom());
+        assert!(station.has_user_stream());
+        assert_eq!(
+            station.id,
+            StationId::new(PROVIDER_CUSTOM, &station.stationuuid)
+        );
+
+        let unnamed = Station::custom("", "http://radio.example.org:8000/stream", "").unwrap();
+        assert_eq!(unnamed.name, "radio.example.org");
+    }
+
+    #[test]
+    fn test_custom_station_rejects_bad_urls() {
+        assert!(Station::custom("A", "", "").is_err());
+        assert!(Station::custom("A", "ftp://example.com/stream", "").is_err());
+        assert!(Station::custom("A", "not a url", "").is_err());
+        assert_eq!(
+            Station::custom("A", "https://example.com/s", "file:///icon.png"),
+            Err(UrlError::Icon)
+        );
+        assert!(Station::custom("A", "https://example.com/s", "https://example.com/i.png").is_ok());
+    }
+
+    #[test]
+    fn test_ensure_id() {
+        let mut directory = Station {
+            stationuuid: "abc".to_string(),
+            ..Default::default()
+        };
+        assert!(directory.ensure_id());
+        assert_eq!(directory.id.to_string(), "radio-browser:abc");
+        assert!(!directory.ensure_id());
+
+        let mut custom = Station {
+            url: "https://icecast.example.com/live".to_string(),
+            ..Default::default()
+        };
+        custom.ensure_uuid();
+        custom.ensure_id();
+        assert!(custom.id.to_string().starts_with("custom:"));
+    }
+
+    #[test]
+    fn test_id_survives_uuid_change() {
+        let mut favorite = Station {
+            stationuuid: "old".to_string(),
+            ..Default::default()
+        };
+        favorite.ensure_id();
+        favorite.stationuuid = "new".to_string();
+        assert!(!favorite.ensure_id());
+        assert_eq!(favorite.id.to_string(), "radio-browser:old");
+    }
+
+    #[test]
+    fn test_folder_not_serialized_when_empty() {
+        let station = Station::default();
+        let json = serde_json::to_value(&station).unwrap();
+        assert!(json.get("folder").is_none());
+        assert!(json.get("archived").is_none());
+
+        let station = Station {
+            folder: "Jazz".to_string(),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&station).unwrap();
+        assert_eq!(json["folder"], "Jazz");
+    }
+
+    #[test]
+    fn test_display_name() {
+        let mut station = Station {
+            name: "RÁDIO GLOBO FM 98,1 MHz - AO VIVO!!!".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(station.display_name(), station.name);
+        assert!(serde_json::to_value(&station)
+            .unwrap()
+            .get("alias")
+            .is_none());
+
+        station.alias = " Globo ".to_string();
+        assert_eq!(station.display_name(), "Globo");
+        assert_eq!(serde_json::to_value(&station).unwrap()["alias"], " Globo ");
+    }
+
+    #[test]
+    fn test_matches_filter_and_index_letter() {
+        let station = Station {
+            name: "  Rádio Globo".to_string(),
+            tags: "news,talk".to_string(),
+            ..Default::default()
+        };
+        assert!(station.matches_filter("globo"));
+        assert!(station.matches_filter(" TALK "));
+        assert!(!station.matches_filter("jazz"));
+        assert_eq!(station.index_letter(), 'R');
+
+        let numbered = Station {
+            name: "1.FM Jazz".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(numbered.index_letter(), '#');
+    }
+
+    #[test]
+    fn test_stream_override() {
+        let mut station = Station {
+            stationuuid: "a".to_string(),
+            url_resolved: "http://example.com/64.aac".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(station.stream_url(), "http://example.com/64.aac");
+
+        assert!(station
+            .set_stream_override("ftp://example.com/320.mp3")
+            .is_err());
+        assert_eq!(
+            station.set_stream_override(" http://example.com/320.mp3 "),
+            Ok(true)
+        );
+        assert_eq!(
+            station.set_stream_override("http://example.com/320.mp3"),
+            Ok(false)
+        );
+        assert_eq!(station.stream_url(), "http://example.com/320.mp3");
+
+        // A directory refresh updates url_resolved but not the override
+        let fresh = Station {
+            stationuuid: "a".to_string(),
+            url_resolved: "http://example.com/96.aac".to_string(),
+            ..Default::default()
+        };
+        assert!(station.refresh_from(&fresh));
+        assert_eq!(station.stream_url(), "http://example.com/320.mp3");
+
+        // Pointing it back at the directory stream clears it
+        assert_eq!(
+            station.set_stream_override("http://example.com/96.aac"),
+            Ok(true)
+        );
+        assert!(station.stream_override.is_empty());
+        assert_eq!(station.set_stream_override(""), Ok(false));
+    }
+
+    #[test]
+    fn test_add_and_remove_tag() {
+        let mut station = Station {
+            tags: "jazz, smooth ,".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(station.tag_list(), vec!["jazz", "smooth"]);
+
+        assert!(station.add_tag("lofi"));
+        assert!(!station.add_tag("JAZZ"));
+        assert_eq!(station.tags, "jazz,smooth,lofi");
+
+        assert!(station.remove_tag("Smooth"));
+        assert!(!station.remove_tag("rock"));
+        assert_eq!(station.tags, "jazz,lofi");
+    }
+
+    #[test]
+    fn test_is_same() {
+        let a = Station {
+            id: StationId::new(PROVIDER_RADIO_BROWSER, "old"),
+            stationuuid: "new".to_string(),
+            ..Default::default()
+        };
+        let b = Station {
+            id: StationId::new(PROVIDER_RADIO_BROWSER, "new"),
+            stationuuid: "new".to_string(),
+            ..Default::default()
+        };
+        assert!(a.is_same(&b));
+        assert!(!Station::default().is_same(&Station::default()));
+    }
+
+    #[test]
+    fn test_pick_replacement_prefers_url_and_name() {
+        let stale = Station {
+            stationuuid: "old".to_string(),
+            name: "Jazz FM".to_string(),
+            url: "http://jazz.example.com/stream".to_string(),
+            ..Default::default()
+        };
+        let candidates = vec![
+            Station {
+                stationuuid: "other".to_string(),
+                name: "Jazz FM".to_string(),
+                ..Default::default()
+            },
+            Station {
+                stationuuid: "new".to_string(),
+                name: "jazz fm".to_string(),
+                url: "http://jazz.example.com/stream".to_string(),
+                ..Default::default()
+            },
+        ];
+        let found = pick_replacement(&stale, &candidates).unwrap();
+        assert_eq!(found.stationuuid, "new");
+    }
+
+    #[test]
+    fn test_pick_replacement_falls_back_to_name() {
+        let stale = Station {
+            stationuuid: "old".to_string(),
+            name: "News Radio".to_string(),
+            url: "http://gone.example.com".to_string(),
+            ..Default::default()
+        };
+        let candidates = vec![Station {
+            stationuuid: "new".to_string(),
+            name: "News Radio".to_string(),
+            url: "http://moved.example.com".to_string(),
+            ..Default::default()
+        }];
+        assert_eq!(
+            pick_replacement(&stale, &candidates).unwrap().stationuuid,
+            "new"
+        );
+        assert!(pick_replacement(&stale, &[]).is_none());
+    }
+
+    #[test]
+    fn test_stale_favorites() {
+        let directory = |uuid: &str| Station {
+            stationuuid: uuid.to_string(),
+            ..Default::default()
+        };
+        let custom = Station::custom("Mine", "http://icecast.example.com/live", "").unwrap();
+        let favorites = vec![directory("kept"), directory("gone"), custom];
+        let stale = stale_favorites(&favorites, &[directory("kept")]);
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].stationuuid, "gone");
+    }
+
+    #[test]
+    fn test_refresh_from_keeps_local_values() {
+        let mut favorite = Station {
+            url_resolved: "http://old.example.com/live".to_string(),
+            favicon: "http://old.example.com/icon.png".to_string(),
+            tags: "jazz,my pick".to_string(),
+            ..Default::default()
+        };
+        let fresh = Station {
+            url_resolved: "https://new.example.com/live".to_string(),
+            tags: "Jazz,blues".to_string(),
+            ..Default::default()
+        };
+
+        assert!(favorite.refresh_from(&fresh));
+        assert_eq!(favorite.url_resolved, "https://new.example.com/live");
+        assert_eq!(favorite.favicon, "http://old.example.com/icon.png");
+        assert_eq!(favorite.tags, "jazz,my pick,blues");
+        assert!(!favorite.refresh_from(&fresh));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_favorites_skips_custom() {
+        let mut custom = Station {
+            url: "https://icecast.example.com/live".to_string(),
+            ..Default::default()
+        };
+        custom.ensure_uuid();
+        assert!(refresh_favorites(vec![custom]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_favorites_skips_custom() {
+        let mut custom = Station {
+            url: "https://icecast.example.com/live".to_string(),
+            ..Default::default()
+        };
+        custom.ensure_uuid();
+        let result = reconcile_favorites(vec![custom]).await.unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn test_search_params_plain_words_use_default_field() {
+        let params = SearchParams::parse("smooth jazz", SearchField::Tag);
+        assert_eq!(params.tag.as_deref(), Some("smooth jazz"));
+        assert!(params.name.is_none());
+
+        let params = SearchParams::parse("Groove Salad", SearchField::Name);
+        assert_eq!(params.name.as_deref(), Some("Groove Salad"));
+    }
+
+    #[test]
+    fn test_search_params_query_syntax() {
+        let params = SearchParams::parse(
+            "tag:jazz country:BR name:fm lang:portuguese",
+            SearchField::Name,
+        );
+        assert_eq!(params.tag.as_deref(), Some("jazz"));
+        assert_eq!(params.country.as_deref(), Some("BR"));
+        assert_eq!(params.name.as_deref(), Some("fm"));
+        assert_eq!(params.language.as_deref(), Some("portuguese"));
+    }
+
+    #[test]
+    fn test_search_params_unknown_keys_are_free_text() {
+        let params = SearchParams::parse("http://x foo: tag:rock", SearchField::Name);
+        assert_eq!(params.name.as_deref(), Some("http://x foo:"));
+        assert_eq!(params.tag.as_deref(), Some("rock"));
+    }
+
+    #[test]
+    fn test_search_params_empty() {
+        assert!(SearchParams::parse("  ", SearchField::Name).is_empty());
+        assert!(!SearchParams::parse("tag:x", SearchField::Name).is_empty());
+    }
+
+    #[test]
+    fn test_search_params_country_code_vs_name() {
+        let code = SearchParams::parse("country:br", SearchField::Name).to_query();
+        assert_eq!(code, vec![("countrycode", "BR".to_string())]);
+
+        let name = SearchParams::parse("brazil", SearchField::Country).to_query();
+        assert_eq!(name, vec![("country", "brazil".to_string())]);
+    }
+
+    #[test]
+    fn test_search_params_bitrate_and_order() {
+        let params = SearchParams::parse("tag:lofi bitrate>128 order:votes", SearchField::Name);
+        assert_eq!(params.tag.as_deref(), Some("lofi"));
+        assert_eq!(params.min_bitrate, Some(129));
+        assert_eq!(params.order, Some(SearchOrder::Votes));
+        assert!(params.name.is_none());
+
+        let query = params.to_query();
+        assert!(query.contains(&("bitrateMin", "129".to_string())));
+        assert!(query.contains(&("order", "votes".to_string())));
+        assert!(query.contains(&("reverse", "true".to_string())));
+    }
+
+    #[test]
+    fn test_search_params_apply_filters() {
+        let mut filters = SearchFilters {
+            country: "BR".to_string(),
+            language: "portuguese".to_string(),
+            tags: Vec::new(),
+        };
+        filters.toggle_tag("jazz");
+        filters.toggle_tag("lofi");
+        filters.toggle_tag("jazz");
+        assert_eq!(filters.tags, ["lofi"]);
+
+        let mut params = SearchParams::parse("country:DE news", SearchField::Name);
+        params.apply_filters(&filters);
+        let query = params.to_query();
+        assert!(query.contains(&("countrycode", "DE".to_string())));
+        assert!(query.contains(&("language", "portuguese".to_string())));
+        assert!(query.contains(&("tagList", "lofi".to_string())));
+
+        let mut params = SearchParams::parse("", SearchField::Name);
+        assert!(params.is_empty());
+        params.apply_filters(&filters);
+        assert!(!params.is_empty());
+    }
+
+    #[test]
+    fn test_facet_codes() {
+        let facets: Vec<Facet> = serde_json::from_value(json!([
+            {"name": "Brazil", "iso_3166_1": "BR", "stationcount": 2000},
+            {"name": "english", "iso_639": null, "stationcount": 5000},
+            {"name": "jazz", "stationcount": 900}
+        ]))
+        .unwrap();
+        assert_eq!(facets[0].code.as_deref(), Some("BR"));
+        assert_eq!(facets[1].code, None);
+        assert_eq!(facets[2].stationcount, 900);
+    }
+
+    #[test]
+    fn test_raise_min_bitrate() {
+        let mut params = SearchParams::parse("jazz", SearchField::Name);
+        params.raise_min_bitrate(0);
+        assert_eq!(params.min_bitrate, None);
+        params.raise_min_bitrate(128);
+        assert_eq!(params.min_bitrate, Some(128));
+
+        let mut params = SearchParams::parse("jazz bitrate>=256", SearchField::Name);
+        params.raise_min_bitrate(128);
+        assert_eq!(params.min_bitrate, Some(256));
+    }
+
+    #[test]
+    fn test_parse_min_bitrate_forms() {
+        assert_eq!(parse_min_bitrate("bitrate>=128"), Some(128));
+        assert_eq!(parse_min_bitrate("bitrate:96"), Some(96));
+        assert_eq!(parse_min_bitrate("bitrate>x"), None);
+        assert_eq!(parse_min_bitrate("bitrates"), None);
+        assert_eq!(
+            SearchParams::parse("order:nonsense", SearchField::Name)
+                .name
+                .as_deref(),
+            Some("order:nonsense")
+        );
+    }
+
+    #[test]
+    fn test_search_params_hide_broken() {
+        let mut params = SearchParams::parse("jazz", SearchField::Name);
+        assert!(!params.to_query().iter().any(|(k, _)| *k == "hidebroken"));
+        params.hide_broken = true;
+        assert!(params
+            .to_query()
+            .contains(&("hidebroken", "true".to_string())));
+    }
+
+    #[test]
+    fn test_drop_broken() {
+        let stations = vec![
+            Station {
+                name: "ok".to_string(),
+                lastcheckok: Some(true),
+                ..Default::default()
+            },
+            Station {
+                name: "dead".to_string(),
+                lastcheckok: Some(false),
+                ..Default::default()
+            },
+            Station {
+                name: "unknown".to_string(),
+                ..Default::default()
+            },
+        ];
+        let kept = drop_broken(stations.clone(), true);
+        assert_eq!(
+            kept.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(),
+            vec!["ok", "unknown"]
+        );
+        assert_eq!(drop_broken(stations, false).len(), 3);
+    }
+
+    #[test]
+    fn test_parse_server_list() {
+        let json = br#"[
+            {"ip": "1.2.3.4", "name": "de1.api.radio-browser.info"},
+            {"ip": "::1", "name": "DE1.api.radio-browser.info"},
+            {"ip": "5.6.7.8", "name": "nl1.api.radio-browser.info"},
+            {"ip": "9.9.9.9", "name": ""}
+        ]"#;
+        assert_eq!(
+            parse_server_list(json),
+            vec![
+                "https://de1.api.radio-browser.info".to_string(),
+                "https://nl1.api.radio-browser.info".to_string(),
+            ]
+        );
+        assert!(parse_server_list(b"not json").is_empty());
+    }
+
+    #[test]
+    fn test_shuffle_keeps_all_servers() {
+        let mut servers: Vec<String> = API_SERVERS.iter().map(|s| s.to_string()).collect();
+        shuffle_servers(&mut servers);
+        assert_eq!(servers.len(), API_SERVERS.len());
+        for server in API_SERVERS {
+            assert!(servers.iter().any(|s| s == server));
+        }
+    }
+
+    #[test]
+    fn test_validate_base_url_allows_lan() {
+        assert_eq!(
+            validate_base_url("http://192.168.1.10:8080/").unwrap(),
+            "http://192.168.1.10:8080"
+        );
+        assert_eq!(
+            validate_base_url(" https://radio.home.lan/api ").unwrap(),
+            "https://radio.home.lan/api"
+        );
+        assert!(validate_base_url("http://localhost:8080").is_ok());
+    }
+
+    #[test]
+    fn test_validate_base_url_rejects_bad_input() {
+        assert_eq!(
+            validate_base_url("ftp://example.com"),
+            Err(UrlError::NotHttp)
+        );
+        assert_eq!(validate_base_url("not a url"), Err(UrlError::Malformed));
+    }
+
+    #[test]
+    fn test_parse_station_list_json() {
+        let json = br#"[
+            {"name": "Mine", "url": "http://radio.example.com/live"},
+            {"stationuuid": "abc", "name": "Directory", "url": "https://x.example.com/s", "lastcheckok": 1},
+            {"name": "Bad scheme", "url": "rtsp://example.com/s"}
+        ]"#;
+        let stations = parse_station_list(json).unwrap();
+        assert_eq!(stations.len(), 2);
+        assert!(stations[0].is_custom());
+        assert_eq!(stations[0].url_resolved, "http://radio.example.com/live");
+        assert_eq!(stations[1].id.to_string(), "radio-browser:abc");
+        assert!(stations.iter().all(|s| !s.id.is_empty()));
+    }
+
+    #[test]
+    fn test_parse_station_list_own_format() {
+        let json = br#"[{"name": "Saved", "url": "http://a.example.com/", "lastcheckok": true}]"#;
+        let stations = parse_station_list(json).unwrap();
+        assert_eq!(stations.len(), 1);
+        assert_eq!(stations[0].lastcheckok, Some(true));
+    }
+
+    #[test]
+    fn test_parse_station_list_opml() {
+        let opml = br#"<opml version="2.0"><body><outline text="A" URL="http://a.example.com/live"/></body></opml>"#;
+        let stations = parse_station_list(opml).unwrap();
+        assert_eq!(stations.len(), 1);
+        assert_eq!(stations[0].name, "A");
+        assert!(parse_station_list(b"not a list").is_err());
+    }
+
+    #[test]
+    fn test_parse_station_list_error_kinds() {
+        assert!(matches!(
+            parse_station_list(b"{\"not\": \"a list\"}"),
+            Err(ApiError::JsonParseFailed(_))
+        ));
+        assert!(matches!(
+            parse_station_list(b"<opml><body>"),
+            Err(ApiError::InvalidResponse(_))
+        ));
+        assert!(matches!(
+            parse_station_list(&[0xff, 0xfe]),
+            Err(ApiError::InvalidResponse(_))
+        ));
+    }
+
+    #[test]
+    fn test_backoff_doubles() {
+        assert_eq!(backoff_delay(0), Duration::from_millis(250));
+        assert_eq!(backoff_delay(1), Duration::from_millis(500));
+        assert_eq!(backoff_delay(2), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_user_agent_names_applet() {
+        assert!(USER_AGENT.starts_with("cosmic-radio-applet/"));
+        assert!(USER_AGENT.len() > "cosmic-radio-applet/".len());
+    }
+
+    #[test]
+    fn test_set_proxy_rejects_non_http() {
+        assert_eq!(set_proxy("socks5://127.0.0.1:1080"), Err(UrlError::NotHttp));
+    }
+
+    #[test]
+    fn test_promote_server() {
+        let mut servers = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        promote_server(&mut servers, "c");
+        assert_eq!(servers, vec!["c", "a", "b"]);
+        promote_server(&mut servers, "missing");
+        assert_eq!(servers, vec!["c", "a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_progress_reaches_the_waiting_caller() {
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        let host = host_of("https://de1.api.radio-browser.info");
+        with_progress(sender, async {
+            report(RequestProgress::Mirror {
+                host: host.clone(),
+                index: 0,
+            });
+        })
+        .await;
+        // Nobody listening out here
+        report(RequestProgress::Retry {
+            host: host.clone(),
+            attempt: 2,
+        });
+
+        assert_eq!(host, "de1.api.radio-browser.info");
+        let received: Vec<_> = futures::StreamExt::collect(receiver).await;
+        assert_eq!(received, [RequestProgress::Mirror { host, index: 0 }]);
     }
 
     #[tokio::test]
     async fn test_search_stations_empty_query() {
-        let result = search_stations("".to_string()).await;
+        let result = search_stations(SearchParams::default()).await;
         assert!(result.is_ok());
         assert_eq!(result.unwrap().len(), 0);
     }
 
+    #[test]
+    fn test_fallback_mirrors_expire() {
+        let servers = vec!["a".to_string()];
+        let discovered = MirrorList {
+            servers: servers.clone(),
+            expires: None,
+        };
+        assert!(discovered.is_fresh());
+        let fallback = MirrorList {
+            servers,
+            expires: Some(Instant::now() - Duration::from_secs(1)),
+        };
+        assert!(!fallback.is_fresh());
+        assert!(!MirrorList::default().is_fresh());
+    }
+
     #[tokio::test]
     async fn test_search_stations_whitespace_query() {
-        let result = search_stations("   ".to_string()).await;
+        let result = search_stations(SearchParams::parse("   ", SearchField::Name)).await;
         assert!(result.is_ok());
         assert_eq!(result.unwrap().len(), 0);
     }
@@ -1,64 +1,741 @@
-use crate::api::{self, Station};
-use crate::audio::AudioManager;
-use crate::config::Config;
+use crate::api::{self, Facet, FacetKind, RequestProgress, SearchField, SearchParams, Station};
+use crate::audio::{self, AudioBackend, AudioManager, BufferState};
+use crate::backup::{self, RestoreMode};
+use crate::config::{
+    self, BulkEdit, Config, ListLayout, MoveDirection, PopupSize, PopupTab, SavedSearch,
+};
+use crate::dedupe::{self, StationGroup};
+use crate::deeplink::{self, DeepLink};
+use crate::duck::{self, DuckEvent, DuckMode};
+use crate::error::{ApiError, AudioError, BackupError, FileError, StreamError, UrlError};
+use crate::events::{
+    AppEvent, EventBus, Navigation, NetworkEvent, PlaybackEvent, StationCollection,
+};
+use crate::favicon;
 use crate::fl;
-use crate::mpris::{self, MprisStateUpdate};
-use cosmic::cosmic_config::CosmicConfigEntry;
+use crate::geo::{self, Nearby};
+use crate::history;
+use crate::inhibit;
+use crate::journal::{self, FavoriteChange};
+use crate::migration;
+use crate::mock_audio;
+use crate::mpris::{self, PlaylistSource};
+use crate::network;
+use crate::notify::{self, Notification, NotificationAction, NotifyEvent};
+use crate::opml;
+use crate::output::{self, OutputEvent};
+use crate::panel::{self, ClickAction, PanelText, ScrollAccumulator};
+use crate::playlist::PlaylistFormat;
+use crate::profile::{self, LiteMode};
+use crate::provider::{RadioBrowser, StationList, StationProvider};
+use crate::qr;
+use crate::quality::{PreferredCodec, MIN_BITRATE_CHOICES};
+use crate::safe_write;
+use crate::scrobble::{self, ScrobbleEvent};
+use crate::shortcuts::{self, ShortcutAction, ShortcutKey};
+use crate::sleep::{self, SleepEvent};
+use crate::station::{StationId, StationIndex};
+use crate::store::StationStore;
+use crate::sync::{self, SyncFormat, SyncRead};
+use cosmic::applet::cosmic_panel_config::PanelAnchor;
+use cosmic::cosmic_config::{ConfigSet, CosmicConfigEntry};
+use cosmic::dialog::file_chooser::{self, FileFilter};
 use cosmic::iced::event::{self, Event};
-use cosmic::iced::keyboard::{key::Named, Key};
-use cosmic::iced::widget::text_input;
-use cosmic::iced::{window::Id, Alignment, Length, Subscription, Task};
+use cosmic::iced::keyboard::{self, key::Named, Key};
+use cosmic::iced::mouse::ScrollDelta;
+use cosmic::iced::widget::{scrollable, text_input};
+use cosmic::iced::{task, window::Id, Alignment, Length, Limits, Subscription, Task};
 use cosmic::iced_winit::commands::popup::{destroy_popup, get_popup};
 use cosmic::prelude::*;
-use cosmic::widget::{self, icon, slider};
-use tokio::sync::mpsc;
+use cosmic::widget::{self, icon, segmented_button, slider};
+use futures::channel::mpsc;
+use futures::{stream, StreamExt};
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 use tracing::{debug, error, info, warn};
 
+/// How long a held preview keeps playing before it is stopped automatically
+const PREVIEW_DURATION: Duration = Duration::from_secs(10);
+
+/// Preview loudness as a percentage of the configured volume
+const PREVIEW_VOLUME_PERCENT: u16 = 60;
+
+/// Holding a row this long shows its buttons on touchscreens
+const LONG_PRESS: Duration = Duration::from_millis(500);
+
+/// Volume changes settle this long before they are written, so dragging
+/// the slider saves once
+const VOLUME_SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// Typing pause after which the search runs on its own
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(400);
+
+/// Shorter queries only run when submitted explicitly
+const MIN_AUTO_SEARCH_LEN: usize = 2;
+
+/// Number of stations fetched for each popular list
+const POPULAR_LIMIT: u32 = 15;
+
+/// Number of recent favorite changes listed in the settings tab
+const JOURNAL_VIEW_LIMIT: usize = 10;
+
+/// How often subscribed station lists are downloaded again
+const SUBSCRIPTION_REFRESH: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// How often favorites are refreshed from their directory entries
+const FAVORITES_REFRESH: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Request timeouts offered in the settings tab, in seconds
+const TIMEOUT_CHOICES: [u64; 4] = [5, 10, 20, 30];
+
+/// Volume ceilings offered in the settings tab, in percent
+const MAX_VOLUME_CHOICES: [u8; 6] = [50, 60, 70, 80, 90, 100];
+
+/// Wait after startup or resume before playing, and between attempts while
+/// the network is still coming up
+const AUTOPLAY_DELAY: Duration = Duration::from_secs(3);
+
+/// Autoplay and reconnecting give up after this many unreachable attempts
+const AUTOPLAY_ATTEMPTS: u32 = 10;
+
+/// Favicon downloads started per tick while some are queued
+const FAVICON_BATCH: usize = 6;
+
+/// Pause between batches of favicon downloads
+const FAVICON_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Decoded favicons kept in memory; the oldest are dropped beyond this and
+/// come back from the disk cache when listed again
+const MAX_LOADED_FAVICONS: usize = 256;
+
+/// Favorites listed per page, so huge imported lists stay responsive
+const FAVORITES_PAGE_SIZE: usize = 50;
+
+/// Countries and languages offered as search filters, most stations first
+const FILTER_CHOICES_LIMIT: u32 = 80;
+
+/// Tags offered as search filter chips
+const FILTER_TAGS_LIMIT: u32 = 12;
+
+/// Genres offered as shortcuts while there are no favorites yet
+const SUGGESTED_GENRES: [&str; 6] = ["pop", "rock", "jazz", "news", "classical", "electronic"];
+
+/// Stations suggested while there are no favorites yet
+const SUGGESTED_STATIONS: usize = 5;
+
+/// Placeholder rows shown while a search runs
+const SKELETON_ROWS: usize = 4;
+
+/// Favorites needed before the filter box and letter index are shown
+const FAVORITES_FILTER_MIN: usize = 10;
+
+/// Pixels per QR module in the share dialog
+const QR_SCALE: usize = 5;
+
+/// How often shown toasts are checked for expiry
+const TOAST_TICK: Duration = Duration::from_secs(1);
+
+/// How often the player's cache is checked while a stream fills it
+const BUFFER_POLL: Duration = Duration::from_millis(500);
+
+/// How often a stream playing smoothly is checked for stalls
+const BUFFER_POLL_STABLE: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    fn icon(self) -> &'static str {
+        match self {
+            Self::Info => "dialog-information-symbolic",
+            Self::Warning => "dialog-warning-symbolic",
+            Self::Error => "dialog-error-symbolic",
+        }
+    }
+
+    /// Text color; information uses the theme's own
+    fn color(self) -> Option<cosmic::iced::Color> {
+        match self {
+            Self::Info => None,
+            Self::Warning => Some(cosmic::iced::Color::from_rgb(0.85, 0.55, 0.0)),
+            Self::Error => Some(cosmic::iced::Color::from_rgb(0.85, 0.2, 0.2)),
+        }
+    }
+
+    /// How long a toast stays up; errors linger so the retry is reachable
+    fn duration(self) -> Duration {
+        match self {
+            Self::Info => Duration::from_secs(5),
+            Self::Warning => Duration::from_secs(8),
+            Self::Error => Duration::from_secs(15),
+        }
+    }
+}
+
+/// What a toast's Retry button repeats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryAction {
+    /// The current search
+    Search,
+    /// Loading the Browse tab
+    Browse,
+    /// Playing the current station
+    Playback,
+}
+
+/// Message shown at the top of the popup
+struct Toast {
+    severity: Severity,
+    text: String,
+    retry: Option<RetryAction>,
+    /// Counted from when the popup shows the toast, so one raised while
+    /// it is closed is still seen
+    expires: Option<Instant>,
+}
+
+impl Toast {
+    fn start_expiry(&mut self) {
+        if self.expires.is_none() {
+            self.expires = Some(Instant::now() + self.severity.duration());
+        }
+    }
+}
+
+/// Offered after starring a station while favorite folders exist
+struct FolderPicker {
+    station: StationId,
+    station_name: String,
+    /// "No folder" followed by the folder names
+    options: Vec<String>,
+}
+
+/// Favorite being edited in the manage view
+struct FavoriteEdit {
+    station: StationId,
+    alias: String,
+    /// Empty plays the directory's stream
+    stream_url: String,
+    error: Option<String>,
+}
+
+/// Station being shared and the QR code for the chosen URL
+struct ShareDialog {
+    station: Station,
+    /// Encode the homepage instead of the stream
+    homepage: bool,
+    url: String,
+    /// `None` if the URL is too long to encode
+    qr: Option<widget::image::Handle>,
+}
+
+impl ShareDialog {
+    fn new(station: Station, homepage: bool) -> Self {
+        let homepage = homepage && !station.homepage.trim().is_empty();
+        let url = if homepage {
+            station.homepage.trim().to_string()
+        } else {
+            station.url.clone()
+        };
+        let qr = match qr::render(&url, QR_SCALE) {
+            Ok(image) => Some(widget::image::Handle::from_rgba(
+                image.size,
+                image.size,
+                image.pixels,
+            )),
+            Err(e) => {
+                warn!("Cannot encode {} as a QR code: {}", url, e);
+                None
+            }
+        };
+        Self {
+            station,
+            homepage,
+            url,
+            qr,
+        }
+    }
+}
+
 pub struct AppModel {
     core: cosmic::Core,
     popup: Option<Id>,
+    /// Keep the popup open when focus moves to another window
+    popup_pinned: bool,
     config: Config,
     config_handler: cosmic::cosmic_config::Config,
-    audio: AudioManager,
+    /// Shared with the blocking tasks that query the player's state
+    audio: Arc<AudioManager>,
+    preview_audio: Arc<AudioManager>,
 
     // UI State
     search_query: String,
+    search_field_labels: Vec<String>,
+    /// radio-browser.info followed by each subscribed list
+    providers: Vec<Arc<dyn StationProvider>>,
+    provider_labels: Vec<String>,
+    /// Index into `providers` the next search runs against
+    search_provider: usize,
+    filter_countries: Vec<Facet>,
+    filter_languages: Vec<Facet>,
+    filter_tags: Vec<Facet>,
+    /// "Any" followed by `filter_countries`
+    country_labels: Vec<String>,
+    /// "Any" followed by `filter_languages`
+    language_labels: Vec<String>,
+    /// The filter choices were fetched (or are being fetched) this session
+    filter_choices_requested: bool,
     search_results: Vec<Station>,
+    /// `search_results` with duplicates collapsed behind their first entry
+    search_groups: Vec<StationGroup>,
+    /// Groups (by primary station) whose variants are shown
+    expanded_groups: HashSet<StationId>,
     is_searching: bool,
+    /// Bumped per search so results of superseded queries are dropped
+    search_generation: u64,
+    /// Aborts the search in flight
+    search_handle: Option<task::Handle>,
+    /// Which mirror the search in flight is asking
+    search_progress: Option<RequestProgress>,
+    /// Bumped per keystroke so only the last pause in typing searches
+    typing_generation: u64,
     current_station: Option<Station>,
     is_playing: bool,
-    error_message: Option<String>,
+    /// The current station's stream is being checked before playback
+    is_connecting: bool,
+    /// Bumped per pre-flight check so results for an abandoned station are dropped
+    stream_check_generation: u64,
+    /// Bumped per volume change so only the last one is written
+    volume_save_generation: u32,
+    /// A volume change is waiting to be written
+    volume_save_pending: bool,
+    /// Track announced by the current stream, if any
+    track_title: Option<String>,
+    /// How far the stream is from playing smoothly, while it plays
+    buffer_state: Option<BufferState>,
+    /// Loaded station favicons by URL
+    favicons: HashMap<String, widget::image::Handle>,
+    /// URLs in `favicons`, oldest first
+    favicons_loaded: VecDeque<String>,
+    /// Favicon URLs waiting to be downloaded
+    favicon_queue: VecDeque<String>,
+    /// Favicon URLs queued, loading, loaded or failed; none is tried twice
+    favicons_requested: HashSet<String>,
+
+    // Preview
+    preview_station: Option<StationId>,
+    preview_generation: u64,
+
+    // Row buttons shown on hover or long press
+    hovered_row: Option<StationId>,
+    pressed_row: Option<StationId>,
+    revealed_row: Option<StationId>,
+    /// Set by Tab so hover-only row buttons show while moving focus with the keyboard
+    keyboard_focus: bool,
+    long_press_generation: u64,
 
-    // MPRIS
-    mpris_tx: Option<mpsc::UnboundedSender<MprisStateUpdate>>,
+    // Tabs
+    tabs: segmented_button::SingleSelectModel,
+    popular_voted: Vec<Station>,
+    popular_clicked: Vec<Station>,
+    popular_nearby: Vec<Station>,
+    is_loading_popular: bool,
+    is_refreshing_favorites: bool,
+
+    /// Every listed station by id; messages carry ids resolved here
+    stations: StationStore,
+
+    // Favorites management
+    /// Lookup of `config.favorites`, rebuilt when a save changed them
+    favorites_index: StationIndex,
+    /// The favorites and subscribed lists the station lookups were last
+    /// built from
+    indexed_favorites: Vec<Station>,
+    indexed_subscriptions: Vec<config::Subscription>,
+    /// Zero-based page of the favorites list
+    favorites_page: usize,
+    /// Where each tab's list was scrolled to, restored when the popup or
+    /// the tab is opened again
+    scroll_offsets: HashMap<PopupTab, scrollable::RelativeOffset>,
+    /// Last reported size of the active tab's scrolled content
+    popup_viewport: Option<scrollable::Viewport>,
+    /// Narrows the favorites list by name or tag
+    favorites_filter: String,
+    managing_favorites: bool,
+    /// Folder sections folded away in the favorites list
+    collapsed_folders: HashSet<String>,
+    folder_picker: Option<FolderPicker>,
+    selected_favorites: HashSet<StationId>,
+    bulk_tag_input: String,
+    bulk_folder_input: String,
+    /// Asking whether to delete the selected favorites
+    confirm_bulk_remove: bool,
+    editing_favorite: Option<FavoriteEdit>,
+    save_search_name: String,
+
+    // Add-station form
+    adding_station: bool,
+    custom_name_input: String,
+    custom_url_input: String,
+    custom_icon_input: String,
+    custom_station_error: Option<String>,
+
+    // Settings
+    api_base_url_input: String,
+    api_base_url_error: Option<String>,
+    proxy_input: String,
+    proxy_error: Option<String>,
+    listenbrainz_token_input: String,
+    location_input: String,
+    location_error: Option<String>,
+    popup_size_labels: Vec<String>,
+    popup_width_input: String,
+    popup_height_input: String,
+    popup_size_error: Option<String>,
+    timeout_labels: Vec<String>,
+    max_volume_labels: Vec<String>,
+    min_bitrate_labels: Vec<String>,
+    codec_labels: Vec<String>,
+    backend_labels: Vec<String>,
+    lite_labels: Vec<String>,
+    duck_labels: Vec<String>,
+    duck_volume_labels: Vec<String>,
+    duck_delay_labels: Vec<String>,
+    history_limit_labels: Vec<String>,
+    left_click_labels: Vec<String>,
+    middle_click_labels: Vec<String>,
+    /// Scroll over the panel icon not yet turned into volume steps
+    panel_scroll: ScrollAccumulator,
+    /// Sideways scroll not yet turned into favorite switches
+    panel_swipe: ScrollAccumulator,
+    /// Show the volume next to the panel icon after scrolling it
+    volume_hint: bool,
+    volume_hint_generation: u32,
+    scroll_step_labels: Vec<String>,
+    panel_text_labels: Vec<String>,
+    panel_width_labels: Vec<String>,
+    /// Characters the panel text has scrolled by
+    marquee_offset: usize,
+    /// `Config::profile_names`, with the default profile's localized name
+    profile_labels: Vec<String>,
+    profile_name_input: String,
+    /// "Last station" followed by the active favorites
+    autoplay_labels: Vec<String>,
+    sync_file_input: String,
+    sync_file_error: Option<String>,
+
+    // Lite profile
+    low_memory: bool,
+    lite: bool,
+
+    /// Backup picked for restoring, waiting for merge or overwrite
+    pending_restore: Option<Box<Config>>,
+
+    // Subscriptions
+    subscription_name_input: String,
+    subscription_url_input: String,
+    subscription_error: Option<String>,
+    genre_chip_input: String,
+    /// Message shown at the top of the popup until dismissed or expired
+    toast: Option<Toast>,
+    share: Option<ShareDialog>,
+    /// Whether the keyboard shortcut overlay is open
+    show_shortcuts: bool,
+
+    /// Favorites as of the last save, diffed into the change journal
+    journaled_favorites: Vec<Station>,
+
+    /// Delivers playback, config and network events to MPRIS and friends
+    events: EventBus,
+    /// Whether the last directory request reached a server, or
+    /// NetworkManager last reported a connection
+    online: bool,
+    /// NetworkManager reports connectivity, so failed requests no longer
+    /// decide what the rest of the app is told
+    network_monitored: bool,
+    /// Station whose play is the open entry at the top of the history
+    history_playing: Option<StationId>,
+    /// The sync file has been read since it was configured, so writing it
+    /// can't clobber changes made elsewhere
+    sync_ready: bool,
+    /// Modification time of the sync file when last read or written
+    sync_seen: Option<SystemTime>,
+    /// Contents last read from or written to the sync file
+    sync_written: String,
+    /// Bumped when the sync file is switched or written, so a read that
+    /// started before is dropped
+    sync_generation: u32,
+    /// Session bus connection desktop notifications are sent on
+    notify_connection: Option<zbus::Connection>,
+    /// Id of the last notification shown, which the next one replaces
+    notification_id: u32,
+    /// A screen reader is running, so playback changes are announced
+    screen_reader: bool,
+    /// Playback was stopped for a suspend, to be picked up again on resume
+    resume_after_sleep: bool,
+    /// An MPRIS client quit the server; it comes back with the next station
+    /// played
+    mpris_quit: bool,
+    /// Bumped to start the MPRIS server again after a quit
+    mpris_session: u32,
+    /// NetworkManager reports no connection, so streams aren't tried
+    waiting_for_network: bool,
+    /// Sent once NetworkManager reports the network back, e.g. to restart
+    /// the station the outage stopped
+    when_online: Option<Message>,
+    /// Playback was stopped because the audio device went away
+    paused_for_output: bool,
+    /// How the radio is making way for another app's audio, if it is
+    ducked: Option<DuckMode>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     TogglePopup,
     PopupClosed(Id),
+    TogglePinPopup,
+    /// The config changed on disk, e.g. edited by another instance
+    ConfigReloaded(Box<Config>),
+    PanelAction(ClickAction),
+    PanelScrolled(ScrollDelta),
+    HideVolumeHint(u32),
 
     // Search
     SearchInputChanged(String),
+    SearchDebounced(u64),
+    SearchFieldSelected(usize),
+    SearchProviderSelected(usize),
+    FilterChoicesLoaded(FacetKind, Result<Vec<Facet>, Arc<ApiError>>),
+    FilterCountrySelected(usize),
+    FilterLanguageSelected(usize),
+    ToggleFilterTag(String),
+    ClearFilters,
+    /// Search stations with this tag on the Search tab
+    SearchGenre(String),
+    GenreChipInputChanged(String),
+    AddGenreChip,
+    RemoveGenreChip(usize),
     PerformSearch,
-    SearchCompleted(Result<Vec<Station>, String>),
+    SaveSearchNameChanged(String),
+    SaveCurrentSearch,
+    RunSavedSearch(usize),
+    DeleteSavedSearch(usize),
+    SearchCompleted(u64, Result<Vec<Station>, Arc<ApiError>>),
+    SearchProgress(u64, RequestProgress),
+    CancelSearch,
 
     // Stations
-    PlayStation(Station),
-    ToggleFavorite(Station),
+    PlayStation(StationId),
+    StreamChecked(u64, Result<(), StreamError>),
+    /// The stream stopped by itself, for the given reason
+    StreamEnded(String),
+    /// Start downloading the next queued favicons
+    FaviconTick,
+    FaviconLoaded(String, Result<Vec<u8>, Arc<ApiError>>),
+    ToggleFavorite(StationId),
     ClearSearch,
+    ToggleVariants(StationId),
+
+    // Preview
+    StartPreview(StationId),
+    StopPreview,
+    PreviewTimeout(u64),
+    RowHovered(StationId),
+    RowUnhovered(StationId),
+    RowPressed(StationId),
+    RowReleased,
+    RowLongPressed(u64),
+    SetRowActionsOnHover(bool),
+
+    // Favorites maintenance
+    FavoritesReconciled(Result<Vec<(String, Station)>, Arc<ApiError>>),
+    /// Re-fetch favorites from the directory to update URLs, favicons and tags
+    RefreshFavorites,
+    FavoritesRefreshed(Result<Vec<Station>, Arc<ApiError>>),
+    ToggleManageFavorites,
+    FavoriteSelected(StationId, bool),
+    SelectAllFavorites(bool),
+    BulkTagInputChanged(String),
+    BulkFolderInputChanged(String),
+    ApplyBulkEdit(BulkEdit),
+    /// Show or dismiss the confirmation before deleting the selection
+    AskRemoveSelected(bool),
+    RemoveSelectedFavorites,
+    ExportSelectedFavorites,
+    RestoreFavorite(StationId),
+    MoveFavorite(StationId, MoveDirection),
+    EditFavorite(StationId),
+    EditAliasChanged(String),
+    EditStreamUrlChanged(String),
+    ApplyFavoriteEdit,
+    CancelFavoriteEdit,
+    ImportFavorites,
+    /// Stations read from the chosen file; `None` if the dialog was cancelled
+    FavoritesImported(Result<Option<Vec<Station>>, Arc<FileError>>),
+    ExportFavorites,
+    ExportPlaylist(PlaylistFormat),
+    /// Where the favorites were written; `None` if the dialog was cancelled
+    FavoritesExported(Result<Option<PathBuf>, Arc<FileError>>),
+    BackupSettings,
+    /// Where the backup was written; `None` if the dialog was cancelled
+    SettingsBackedUp(Result<Option<PathBuf>, Arc<FileError>>),
+    RestoreSettings,
+    /// The chosen backup; `None` if the dialog was cancelled
+    BackupOpened(Result<Option<Box<Config>>, Arc<FileError>>),
+    ApplyRestore(RestoreMode),
+    CancelRestore,
+    ToggleFolder(String),
+    /// Index into the folder picker's options
+    PickFolder(usize),
+    CloseFolderPicker,
+    FavoritesPage(usize),
+    FavoritesFilterChanged(String),
+    CycleListLayout,
+    /// Scroll to the first favorite listed under this letter
+    JumpToLetter(char),
+    ToggleAddStation,
+    CustomNameChanged(String),
+    CustomUrlChanged(String),
+    CustomIconChanged(String),
+    SaveCustomStation,
+
+    // Settings
+    SetShowBrokenStations(bool),
+    SetShowStationStats(bool),
+    SetReportClicks(bool),
+    SetResumeOnLogin(bool),
+    SetAutoplay(bool),
+    SetShuffleFavorites(bool),
+    SetLoopFavorites(bool),
+    SetNotifyStation(bool),
+    SetNotifySong(bool),
+    SetNotifyErrors(bool),
+    SetInhibitIdle(bool),
+    SetInhibitSleep(bool),
+    SetPauseOnOutputLoss(bool),
+    SetResumeOnOutputReturn(bool),
+    DuckModeSelected(usize),
+    DuckVolumeSelected(usize),
+    DuckDelaySelected(usize),
+    AutoplayStationSelected(usize),
+    /// Start the autoplay station; the attempt counts unreachable retries
+    Autoplay(u32),
+    AutoplayChecked(u32, StationId, Result<(), StreamError>),
+    /// Start the current station again once its stream answers, retrying
+    /// while the network comes back; carries the attempt
+    Reconnect(u32),
+    ReconnectChecked(u32, Result<(), StreamError>),
+    MaxVolumeSelected(usize),
+    SetCacheOnDisk(bool),
+    ApiBaseUrlInputChanged(String),
+    ApplyApiBaseUrl,
+    ProxyInputChanged(String),
+    ApplyProxy,
+    ListenBrainzTokenChanged(String),
+    ApplyListenBrainzToken,
+    LocationInputChanged(String),
+    ApplyLocation,
+    PopupSizeSelected(usize),
+    PopupWidthChanged(String),
+    PopupHeightChanged(String),
+    ApplyPopupSize,
+    RequestTimeoutSelected(usize),
+    MinBitrateSelected(usize),
+    PreferredCodecSelected(usize),
+    AudioBackendSelected(usize),
+    LiteModeSelected(usize),
+    HistoryLimitSelected(usize),
+    LeftClickSelected(usize),
+    MiddleClickSelected(usize),
+    SetScrollVolume(bool),
+    ScrollStepSelected(usize),
+    PanelTextSelected(usize),
+    PanelTextWidthSelected(usize),
+    SetPanelTextScroll(bool),
+    MarqueeTick,
+    ProfileSelected(usize),
+    ProfileNameChanged(String),
+    SyncFileInputChanged(String),
+    ApplySyncFile,
+    /// Check the sync file for changes made elsewhere
+    SyncFileTick,
+    SyncFileRead(u32, Result<SyncRead, Arc<io::Error>>),
+    AddProfile,
+    RemoveProfile(String),
+    RevertJournalEntry(usize),
+    ClearHistory,
+
+    // Subscriptions
+    SubscriptionNameChanged(String),
+    SubscriptionUrlChanged(String),
+    AddSubscription,
+    RemoveSubscription(usize),
+    RefreshSubscriptions,
+    SubscriptionFetched(String, Result<Vec<Station>, Arc<ApiError>>),
+    DismissToast,
+    ToastTick,
+    Retry(RetryAction),
+
+    // Sharing
+    ShareStation(StationId),
+    SetShareHomepage(bool),
+    CloseShare,
+
+    // Blocklist
+    HideStation(StationId),
+    /// Show the station with this UUID in results again
+    UnhideStation(String),
+
+    // Tabs
+    TabActivated(segmented_button::Entity),
+    PopupScrolled(scrollable::Viewport),
+    PopularLoaded(Result<(Vec<Station>, Vec<Station>), Arc<ApiError>>),
+    NearbyLoaded(Result<Vec<Station>, Arc<ApiError>>),
 
     // Volume control
     VolumeChanged(f32),
     VolumeUp,
     VolumeDown,
+    SaveVolume(u32),
 
     // Keyboard shortcuts
     TogglePlayPause,
+    /// Play the next favorite in list order, or the previous one if false
+    SkipFavorite(bool),
     KeyboardEvent(Event),
+    ToggleShortcutHelp,
 
     // MPRIS D-Bus
     MprisEvent(mpris::MprisEvent),
+    /// Directory lookup for a deep-linked station that isn't known locally
+    DeepLinkResolved(String, Result<Vec<Station>, Arc<ApiError>>),
+
+    // Desktop notifications
+    NotifyEvent(NotifyEvent),
+    NotificationShown(Result<u32, Arc<zbus::Error>>),
+
+    Sleep(SleepEvent),
+    Scrobble(ScrobbleEvent),
+    Network(NetworkEvent),
+    Output(OutputEvent),
+    Duck(DuckEvent),
+
+    /// Simulated playback moves on to its next track
+    MockTrackTick,
+    /// Check how full the player's cache is and what song the stream
+    /// announces
+    BufferTick,
+    /// How full the player's cache was when asked, and the song the
+    /// stream announced, for the stream started by that check generation
+    BufferPolled(u64, Option<BufferState>, Option<String>),
 }
 
 impl cosmic::Application for AppModel {
@@ -78,16 +755,32 @@ impl cosmic::Application for AppModel {
         core: cosmic::Core,
         _flags: Self::Flags,
     ) -> (Self, Task<cosmic::Action<Self::Message>>) {
+        let config_root = migration::config_root(Self::APP_ID);
+        if let Some(root) = &config_root {
+            match migration::migrate(root, Config::VERSION, journal::now()) {
+                Ok(Some(from)) => info!("Migrated config from v{} to v{}", from, Config::VERSION),
+                Ok(None) => {}
+                Err(e) => warn!("Config migration failed: {}", e),
+            }
+        }
         let config_handler = cosmic::cosmic_config::Config::new(Self::APP_ID, Config::VERSION)
             .expect("Failed to create config handler");
 
-        let config = match Config::get_entry(&config_handler) {
+        let mut config = match Config::get_entry(&config_handler) {
             Ok(c) => {
                 info!("Config loaded successfully");
                 c
             }
             Err((errs, c)) => {
                 warn!("Errors loading config: {:?}. Using defaults.", errs);
+                // Keep the unreadable values around before defaults replace them
+                if let Some(root) = &config_root {
+                    match migration::backup(root, Config::VERSION, journal::now()) {
+                        Ok(Some(dir)) => info!("Backed up config to {}", dir.display()),
+                        Ok(None) => {}
+                        Err(e) => warn!("Config backup failed: {}", e),
+                    }
+                }
                 if let Err(e) = c.write_entry(&config_handler) {
                     error!("Failed to write initial config: {:?}", e);
                 }
@@ -95,30 +788,327 @@ impl cosmic::Application for AppModel {
             }
         };
 
+        // Hand-written or imported favorites may lack an identifier
+        let mut assigned_ids = false;
+        for station in &mut config.favorites {
+            assigned_ids |= station.ensure_uuid();
+            assigned_ids |= station.ensure_id();
+        }
+        if assigned_ids {
+            info!("Assigned identifiers to custom favorites");
+            if let Err(e) = config.write_entry(&config_handler) {
+                error!("Failed to save config: {:?}", e);
+            }
+        }
+
         debug!(
             "Running Config v{}. Favorites: {}",
             Config::VERSION,
             config.favorites.len()
         );
 
-        let audio = AudioManager::new();
+        if let Err(e) = api::set_base_url(&config.api_base_url) {
+            warn!(
+                "Ignoring invalid API endpoint '{}': {}",
+                config.api_base_url, e
+            );
+        }
+
+        if let Err(e) = api::set_proxy(&config.proxy_url) {
+            warn!("Ignoring invalid proxy '{}': {}", config.proxy_url, e);
+        }
+        let stream_proxy = api::validate_base_url(&config.proxy_url).ok();
+        api::set_request_timeout(config.request_timeout_secs);
+        api::set_disk_cache(config.cache_on_disk);
+
+        let low_memory = profile::is_low_memory();
+        let lite = config.lite_mode.is_active(low_memory);
+        if lite {
+            info!("Using the lite profile");
+        }
+
+        let audio = Arc::new(AudioManager::new());
+        let preview_audio = Arc::new(AudioManager::preview());
+        for player in [&audio, &preview_audio] {
+            player.set_http_proxy(stream_proxy.clone());
+            player.set_backend(playback_backend(config.audio_backend, lite));
+            player.set_lite(lite);
+            player.set_buffer_ms(config.gst_buffer_ms);
+            player.set_max_volume(config.max_volume);
+        }
         audio.set_volume(config.volume as f32);
+        let api_base_url_input = config.api_base_url.clone();
+        let proxy_input = config.proxy_url.clone();
+        let listenbrainz_token_input = config.listenbrainz_token.clone();
+        let sync_file_input = config.sync_file.clone();
+        let autoplay_labels = autoplay_labels(&config);
+        let journaled_favorites = config.favorites.clone();
+        let favorites_index = StationIndex::build(&config.favorites);
+        let indexed_favorites = config.favorites.clone();
+        let indexed_subscriptions = config.subscriptions.clone();
+        let location_input = config.location.clone();
+        let profile_choices = profile_labels(&config);
+        let (popup_width, popup_height) = config.popup_size.dimensions();
+        let popup_width_input = popup_width.to_string();
+        let popup_height_input = popup_height.to_string();
+        // Shown as now playing before anything is started
+        let current_station = config.last_station.clone();
+
+        let mut tabs: segmented_button::SingleSelectModel =
+            segmented_button::Model::builder().build();
+        for tab in PopupTab::ALL {
+            tabs.insert().text(tab_label(tab)).data(tab);
+        }
+        select_tab(&mut tabs, config.last_tab);
 
-        let app = AppModel {
+        let mut app = AppModel {
             core,
             popup: None,
+            popup_pinned: false,
             config,
             config_handler,
             audio,
+            preview_audio,
             search_query: String::new(),
+            providers: Vec::new(),
+            provider_labels: Vec::new(),
+            search_provider: 0,
+            filter_countries: Vec::new(),
+            filter_languages: Vec::new(),
+            filter_tags: Vec::new(),
+            country_labels: Vec::new(),
+            language_labels: Vec::new(),
+            filter_choices_requested: false,
+            search_field_labels: SearchField::ALL
+                .iter()
+                .map(|field| match field {
+                    SearchField::Name => fl!("search-field-name"),
+                    SearchField::Tag => fl!("search-field-tag"),
+                    SearchField::Country => fl!("search-field-country"),
+                    SearchField::Language => fl!("search-field-language"),
+                })
+                .collect(),
             search_results: Vec::new(),
+            search_groups: Vec::new(),
+            expanded_groups: HashSet::new(),
             is_searching: false,
-            current_station: None,
+            search_generation: 0,
+            search_handle: None,
+            search_progress: None,
+            typing_generation: 0,
+            current_station,
             is_playing: false,
-            error_message: None,
-            mpris_tx: None,
+            is_connecting: false,
+            track_title: None,
+            buffer_state: None,
+            favicons: HashMap::new(),
+            favicons_loaded: VecDeque::new(),
+            favicon_queue: VecDeque::new(),
+            favicons_requested: HashSet::new(),
+            stream_check_generation: 0,
+            volume_save_generation: 0,
+            volume_save_pending: false,
+            preview_station: None,
+            preview_generation: 0,
+            hovered_row: None,
+            pressed_row: None,
+            revealed_row: None,
+            keyboard_focus: false,
+            long_press_generation: 0,
+            tabs,
+            popular_voted: Vec::new(),
+            popular_clicked: Vec::new(),
+            popular_nearby: Vec::new(),
+            is_loading_popular: false,
+            is_refreshing_favorites: false,
+            stations: StationStore::default(),
+            favorites_index,
+            indexed_favorites,
+            indexed_subscriptions,
+            favorites_page: 0,
+            scroll_offsets: HashMap::new(),
+            popup_viewport: None,
+            favorites_filter: String::new(),
+            managing_favorites: false,
+            collapsed_folders: HashSet::new(),
+            folder_picker: None,
+            selected_favorites: HashSet::new(),
+            bulk_tag_input: String::new(),
+            bulk_folder_input: String::new(),
+            confirm_bulk_remove: false,
+            editing_favorite: None,
+            save_search_name: String::new(),
+            adding_station: false,
+            custom_name_input: String::new(),
+            custom_url_input: String::new(),
+            custom_icon_input: String::new(),
+            custom_station_error: None,
+            api_base_url_input,
+            api_base_url_error: None,
+            proxy_input,
+            proxy_error: None,
+            listenbrainz_token_input,
+            location_input,
+            location_error: None,
+            popup_size_labels: vec![
+                fl!("popup-size-small"),
+                fl!("popup-size-medium"),
+                fl!("popup-size-large"),
+                fl!("popup-size-custom"),
+            ],
+            popup_width_input,
+            popup_height_input,
+            popup_size_error: None,
+            timeout_labels: TIMEOUT_CHOICES
+                .iter()
+                .map(|&seconds| fl!("settings-timeout-seconds", seconds = seconds))
+                .collect(),
+            max_volume_labels: MAX_VOLUME_CHOICES
+                .iter()
+                .map(|&percent| fl!("settings-max-volume-percent", percent = percent))
+                .collect(),
+            min_bitrate_labels: MIN_BITRATE_CHOICES
+                .iter()
+                .map(|&kbps| match kbps {
+                    0 => fl!("quality-any"),
+                    kbps => fl!("quality-min-bitrate", kbps = kbps),
+                })
+                .collect(),
+            codec_labels: PreferredCodec::ALL
+                .iter()
+                .map(|codec| {
+                    codec
+                        .name()
+                        .map_or_else(|| fl!("quality-any"), str::to_string)
+                })
+                .collect(),
+            backend_labels: AudioBackend::ALL
+                .iter()
+                .map(|backend| match backend {
+                    AudioBackend::Mpv => fl!("backend-mpv"),
+                    AudioBackend::GStreamer => fl!("backend-gstreamer"),
+                })
+                .collect(),
+            lite_labels: LiteMode::ALL
+                .iter()
+                .map(|mode| match mode {
+                    LiteMode::Auto => fl!("lite-auto"),
+                    LiteMode::On => fl!("lite-on"),
+                    LiteMode::Off => fl!("lite-off"),
+                })
+                .collect(),
+            duck_labels: DuckMode::ALL
+                .iter()
+                .map(|mode| match mode {
+                    DuckMode::Off => fl!("duck-off"),
+                    DuckMode::Lower => fl!("duck-lower"),
+                    DuckMode::Pause => fl!("duck-pause"),
+                })
+                .collect(),
+            duck_volume_labels: duck::VOLUME_CHOICES
+                .iter()
+                .map(|&percent| fl!("settings-max-volume-percent", percent = percent))
+                .collect(),
+            duck_delay_labels: duck::DELAY_CHOICES
+                .iter()
+                .map(|&seconds| fl!("settings-timeout-seconds", seconds = seconds))
+                .collect(),
+            history_limit_labels: history::MAX_ENTRIES_CHOICES
+                .iter()
+                .map(|&entries| fl!("settings-history-entries", entries = entries))
+                .collect(),
+            left_click_labels: ClickAction::LEFT
+                .iter()
+                .map(|&a| click_action_label(a))
+                .collect(),
+            middle_click_labels: ClickAction::MIDDLE
+                .iter()
+                .map(|&a| click_action_label(a))
+                .collect(),
+            panel_scroll: ScrollAccumulator::default(),
+            panel_swipe: ScrollAccumulator::default(),
+            volume_hint: false,
+            volume_hint_generation: 0,
+            scroll_step_labels: panel::SCROLL_STEP_CHOICES
+                .iter()
+                .map(|step| fl!("settings-scroll-step-percent", percent = step))
+                .collect(),
+            panel_text_labels: PanelText::ALL
+                .iter()
+                .map(|&t| panel_text_label(t))
+                .collect(),
+            panel_width_labels: panel::TEXT_WIDTH_CHOICES
+                .iter()
+                .map(|&chars| fl!("settings-panel-text-chars", chars = chars))
+                .collect(),
+            marquee_offset: 0,
+            profile_labels: profile_choices,
+            profile_name_input: String::new(),
+            autoplay_labels,
+            sync_file_input,
+            sync_file_error: None,
+            low_memory,
+            lite,
+            pending_restore: None,
+            subscription_name_input: String::new(),
+            subscription_url_input: String::new(),
+            subscription_error: None,
+            genre_chip_input: String::new(),
+            toast: None,
+            share: None,
+            show_shortcuts: false,
+            journaled_favorites,
+            events: EventBus::default(),
+            online: true,
+            network_monitored: false,
+            history_playing: None,
+            sync_ready: false,
+            sync_seen: None,
+            sync_generation: 0,
+            sync_written: String::new(),
+            notify_connection: None,
+            screen_reader: false,
+            notification_id: 0,
+            resume_after_sleep: false,
+            mpris_quit: false,
+            mpris_session: 0,
+            waiting_for_network: false,
+            when_online: None,
+            paused_for_output: false,
+            ducked: None,
+        };
+
+        app.sync_stations();
+        app.rebuild_providers();
+        app.rebuild_filter_labels();
+        app.refresh_favorites_tab();
+        let refresh = app.refresh_subscriptions();
+        // Restarts within a day leave the directory alone
+        let refreshed_ago = journal::now().saturating_sub(app.config.favorites_refreshed_at);
+        let refresh_favorites = if refreshed_ago >= FAVORITES_REFRESH.as_secs() {
+            app.update(Message::RefreshFavorites)
+        } else {
+            Task::none()
+        };
+        let resume = match &app.config.last_station {
+            _ if app.config.autoplay => {
+                Task::perform(tokio::time::sleep(AUTOPLAY_DELAY), |_| Message::Autoplay(0))
+                    .map(Into::into)
+            }
+            Some(station) if app.config.resume_on_login && app.config.was_playing => {
+                info!("Resuming {}", station.name);
+                app.update(Message::PlayStation(station.id.clone()))
+            }
+            _ => Task::none(),
         };
-        (app, Task::none())
+
+        let sync_file = app.update(Message::SyncFileTick);
+        let artwork = app.load_artwork();
+
+        (
+            app,
+            Task::batch([refresh, refresh_favorites, sync_file, artwork, resume]),
+        )
     }
 
     fn on_close_requested(&self, id: Id) -> Option<Message> {
@@ -131,51 +1121,300 @@ impl cosmic::Application for AppModel {
         } else {
             Subscription::none()
         };
-        let mpris_sub = mpris::mpris_subscription().map(Message::MprisEvent);
-        Subscription::batch([keyboard_sub, mpris_sub])
+        let mpris_sub = mpris::mpris_subscription(self.mpris_session).map(Message::MprisEvent);
+        // Always on, so an error right after starting isn't missed
+        let ends_sub = audio::end_subscription(self.audio.stream_ends()).map(Message::StreamEnded);
+        let network_sub = network::subscription().map(Message::Network);
+        let scrobble_sub = if self.config.listenbrainz_token.is_empty() {
+            Subscription::none()
+        } else {
+            scrobble::subscription(self.config.listenbrainz_token.clone()).map(Message::Scrobble)
+        };
+        // Always on: screen reader announcements go out as notifications too
+        let notify_sub = notify::subscription().map(Message::NotifyEvent);
+        let refresh_sub = if self.config.subscriptions.is_empty() {
+            Subscription::none()
+        } else {
+            cosmic::iced::time::every(SUBSCRIPTION_REFRESH).map(|_| Message::RefreshSubscriptions)
+        };
+        let buffer_sub = match self.buffer_state {
+            _ if !self.is_playing => Subscription::none(),
+            Some(BufferState::Stable) => {
+                cosmic::iced::time::every(BUFFER_POLL_STABLE).map(|_| Message::BufferTick)
+            }
+            _ => cosmic::iced::time::every(BUFFER_POLL).map(|_| Message::BufferTick),
+        };
+        let inhibit_sub =
+            match inhibit::inhibited(self.config.inhibit_idle, self.config.inhibit_sleep) {
+                Some(what) if self.is_playing => inhibit::subscription(what),
+                _ => Subscription::none(),
+            };
+        // Also kept while suspended, to hear about the resume
+        let sleep_sub = if self.is_playing || self.is_connecting || self.resume_after_sleep {
+            sleep::subscription().map(Message::Sleep)
+        } else {
+            Subscription::none()
+        };
+        // Kept after a disconnect only to hear about the device coming back
+        let output_sub = if self.config.pause_on_output_loss
+            && (self.is_playing
+                || self.is_connecting
+                || (self.paused_for_output && self.config.resume_on_output_return))
+        {
+            output::subscription().map(Message::Output)
+        } else {
+            Subscription::none()
+        };
+        // Kept after stopping for other audio only to hear about it ending
+        let duck_sub = if self.config.duck_mode != DuckMode::Off
+            && (self.is_playing || self.is_connecting || self.ducked == Some(DuckMode::Pause))
+        {
+            duck::subscription(Duration::from_secs(self.config.duck_delay_secs)).map(Message::Duck)
+        } else {
+            Subscription::none()
+        };
+        let mock_track_sub = if self.audio.is_mock() && self.is_playing {
+            cosmic::iced::time::every(mock_audio::TRACK_INTERVAL).map(|_| Message::MockTrackTick)
+        } else {
+            Subscription::none()
+        };
+        let favorites_refresh_sub = if self.config.favorites.is_empty() {
+            Subscription::none()
+        } else {
+            cosmic::iced::time::every(FAVORITES_REFRESH).map(|_| Message::RefreshFavorites)
+        };
+        let favicon_sub = if self.favicon_queue.is_empty() {
+            Subscription::none()
+        } else {
+            cosmic::iced::time::every(FAVICON_INTERVAL).map(|_| Message::FaviconTick)
+        };
+        let toast_sub = if self.toast.as_ref().is_some_and(|t| t.expires.is_some()) {
+            cosmic::iced::time::every(TOAST_TICK).map(|_| Message::ToastTick)
+        } else {
+            Subscription::none()
+        };
+        let marquee_sub = match self.panel_text() {
+            Some(text)
+                if self.config.panel_text_scroll
+                    && text.chars().count() > self.config.panel_text_width =>
+            {
+                cosmic::iced::time::every(panel::MARQUEE_INTERVAL).map(|_| Message::MarqueeTick)
+            }
+            _ => Subscription::none(),
+        };
+        let sync_file_sub = if self.syncing() {
+            cosmic::iced::time::every(sync::POLL_INTERVAL).map(|_| Message::SyncFileTick)
+        } else {
+            Subscription::none()
+        };
+        let config_sub = self
+            .core
+            .watch_config::<Config>(Self::APP_ID)
+            .map(|update| {
+                for e in &update.errors {
+                    warn!("Config reload: {:?}", e);
+                }
+                Message::ConfigReloaded(Box::new(update.config))
+            });
+        Subscription::batch([
+            config_sub,
+            keyboard_sub,
+            mpris_sub,
+            ends_sub,
+            network_sub,
+            scrobble_sub,
+            notify_sub,
+            refresh_sub,
+            mock_track_sub,
+            buffer_sub,
+            inhibit_sub,
+            sleep_sub,
+            output_sub,
+            duck_sub,
+            favorites_refresh_sub,
+            sync_file_sub,
+            favicon_sub,
+            toast_sub,
+            marquee_sub,
+        ])
     }
 
     fn view(&self) -> Element<'_, Self::Message> {
-        widget::container(
-            cosmic::widget::button::custom(icon::from_name("multimedia-player-symbolic").size(16))
-                .on_press(Message::TogglePopup)
-                .class(cosmic::theme::Button::Icon),
+        let (panel_icon, label) = if self.volume_hint {
+            (
+                volume_icon(self.config.volume),
+                Some(fl!("volume-hint", percent = self.config.volume)),
+            )
+        } else {
+            let width = self.config.panel_text_width;
+            let label = self.panel_text().map(|text| {
+                if self.config.panel_text_scroll {
+                    panel::marquee(&text, width, self.marquee_offset)
+                } else {
+                    panel::truncate(&text, width)
+                }
+            });
+            ("multimedia-player-symbolic", label)
+        };
+        // Updates with every track change, since the view is rebuilt then.
+        // Also the button's name, so screen readers hear what is playing.
+        let station = self
+            .current_station
+            .as_ref()
+            .filter(|_| self.is_playing || self.is_connecting)
+            .map(Station::display_name);
+        let track = self.track_title.as_deref().filter(|_| self.is_playing);
+        let tooltip = panel::tooltip_text(&fl!("app-title"), station, track);
+        let (icon_size, _) = self.core.applet.suggested_size(true);
+        let panel_icon = icon::from_name(panel_icon).size(icon_size);
+        // Side panels are narrow, so text goes below the icon there
+        let button = match label {
+            Some(text) if self.core.applet.is_horizontal() => cosmic::widget::button::custom(
+                widget::row()
+                    .spacing(6)
+                    .align_y(Alignment::Center)
+                    .push(panel_icon)
+                    .push(widget::text(text).size(13)),
+            ),
+            Some(text) => cosmic::widget::button::custom(
+                widget::column()
+                    .spacing(2)
+                    .align_x(Alignment::Center)
+                    .push(panel_icon)
+                    .push(widget::text(text).size(10)),
+            ),
+            None => cosmic::widget::button::custom(panel_icon),
+        }
+        .on_press(Message::PanelAction(self.config.left_click))
+        .class(cosmic::theme::Button::Icon)
+        .name(tooltip.clone());
+        let mut area = widget::mouse_area(button);
+        if self.config.middle_click != ClickAction::Nothing {
+            area = area.on_middle_press(Message::PanelAction(self.config.middle_click));
+        }
+        // Right click keeps the popup reachable when left click plays
+        if self.config.left_click != ClickAction::TogglePopup {
+            area = area.on_right_press(Message::PanelAction(ClickAction::TogglePopup));
+        }
+        if self.config.scroll_volume {
+            area = area.on_scroll(Message::PanelScrolled);
+        }
+        widget::tooltip(
+            widget::container(area)
+                .height(Length::Fill)
+                .center_y(Length::Fill)
+                .center_x(Length::Fill),
+            widget::text(tooltip),
+            tooltip_position(self.core.applet.anchor),
         )
-        .height(Length::Fill)
-        .center_y(Length::Fill)
-        .center_x(Length::Fill)
         .into()
     }
 
     fn view_window(&self, _id: Id) -> Element<'_, Self::Message> {
         let title = widget::text(fl!("window-title")).size(24);
 
+        if let Some(share) = &self.share {
+            let content = widget::column()
+                .padding(20)
+                .spacing(12)
+                .push(title)
+                .push(self.view_share(share));
+            return self.core.applet.popup_container(content).into();
+        }
+        if self.show_shortcuts {
+            let content = widget::column()
+                .padding(20)
+                .spacing(12)
+                .push(title)
+                .push(self.view_shortcuts());
+            return self.core.applet.popup_container(content).into();
+        }
+
         // Now Playing section (if playing)
         let now_playing: Element<'_, Message> = if let Some(station) = &self.current_station {
-            let status_icon = if self.is_playing {
-                "media-playback-pause-symbolic"
-            } else {
+            // A station remembered from the last run is pre-selected but stopped
+            let toggle_icon = if self.is_playing || self.is_connecting {
                 "media-playback-stop-symbolic"
+            } else {
+                "media-playback-start-symbolic"
+            };
+            let fav_icon = if self.favorites_index.contains(station) {
+                "starred-symbolic"
+            } else {
+                "non-starred-symbolic"
             };
+            let artwork = self.view_favicon(station, 48);
 
-            widget::column()
-                .spacing(8)
-                .push(
-                    widget::row()
-                        .spacing(10)
-                        .align_y(Alignment::Center)
-                        .push(icon::from_name(status_icon).size(20))
-                        .push(
-                            widget::text(&station.name)
-                                .size(16)
-                                .width(Length::Fill),
-                        )
+            let label = self.station_label(station);
+            // An aliased station still shows what the directory calls it
+            let details: Vec<String> = (label != station.name)
+                .then(|| station.name.clone())
+                .into_iter()
+                .chain(station.quality_label())
+                .chain((!station.country.is_empty()).then(|| station.country.clone()))
+                .collect();
+            let mut info = widget::column()
+                .spacing(2)
+                .width(Length::Fill)
+                .push(widget::text(label).size(16));
+            if self.is_connecting {
+                info = info.push(widget::text(fl!("connecting")).size(12));
+            } else if let Some(track) = &self.track_title {
+                info = info.push(widget::text(track).size(12));
+            }
+            if !details.is_empty() {
+                info = info.push(widget::text(details.join(" · ")).size(11));
+            }
+            match self.buffer_state.filter(|_| self.is_playing) {
+                Some(BufferState::Buffering(fill)) => {
+                    info = info
+                        .push(widget::text(fl!("buffering", percent = fill)).size(11))
                         .push(
-                            cosmic::iced::widget::button(icon::from_name(
-                                "media-playback-stop-symbolic",
-                            ))
-                            .on_press(Message::TogglePlayPause),
-                        ),
+                            widget::progress_bar(0.0..=100.0, f32::from(fill))
+                                .height(Length::Fixed(4.0)),
+                        );
+                }
+                Some(BufferState::Stable) => {
+                    info = info.push(widget::text(fl!("live")).size(11).class(
+                        cosmic::theme::Text::Color(cosmic::iced::Color::from_rgb(0.5, 0.5, 0.5)),
+                    ));
+                }
+                None => {}
+            }
+
+            widget::row()
+                .spacing(10)
+                .align_y(Alignment::Center)
+                .push(artwork)
+                .push(info)
+                .push(
+                    icon_button(
+                        fav_icon,
+                        favorite_toggle_label(self.favorites_index.contains(station), label),
+                    )
+                    .on_press(Message::ToggleFavorite(station.id.clone())),
+                )
+                .push(
+                    icon_button(
+                        "media-skip-backward-symbolic",
+                        fl!("a11y-previous-favorite"),
+                    )
+                    .on_press_maybe(self.can_skip(false).then_some(Message::SkipFavorite(false))),
+                )
+                .push(
+                    icon_button(
+                        toggle_icon,
+                        if self.is_playing || self.is_connecting {
+                            fl!("a11y-stop", name = label)
+                        } else {
+                            fl!("a11y-play", name = label)
+                        },
+                    )
+                    .on_press(Message::TogglePlayPause),
+                )
+                .push(
+                    icon_button("media-skip-forward-symbolic", fl!("a11y-next-favorite"))
+                        .on_press_maybe(self.can_skip(true).then_some(Message::SkipFavorite(true))),
                 )
                 .into()
         } else {
@@ -185,90 +1424,126 @@ impl cosmic::Application for AppModel {
         // Volume control section
         let volume_section = {
             let volume_label = format!("{} {}%", fl!("volume"), self.config.volume);
-            let volume_icon = if self.config.volume == 0 {
-                "audio-volume-muted-symbolic"
-            } else if self.config.volume < 33 {
-                "audio-volume-low-symbolic"
-            } else if self.config.volume < 66 {
-                "audio-volume-medium-symbolic"
-            } else {
-                "audio-volume-high-symbolic"
-            };
 
             widget::row()
                 .spacing(10)
                 .align_y(Alignment::Center)
-                .push(icon::from_name(volume_icon).size(20))
+                .push(icon::from_name(volume_icon(self.config.volume)).size(20))
                 .push(
-                    slider(0.0..=100.0, self.config.volume as f32, Message::VolumeChanged)
+                    slider(
+                        0.0..=f32::from(self.config.max_volume),
+                        self.config.volume as f32,
+                        Message::VolumeChanged,
+                    )
                         .width(Length::Fill),
                 )
                 .push(widget::text(volume_label).size(14).width(Length::Shrink))
         };
 
-        // Search Bar
-        let search_input = text_input(&fl!("search-placeholder"), &self.search_query)
-            .on_input(Message::SearchInputChanged)
-            .on_submit(Message::PerformSearch)
-            .padding(10);
-
-        let search_btn = cosmic::iced::widget::button(widget::text(fl!("search-button")))
-            .on_press(Message::PerformSearch);
-
-        let search_row = widget::row()
-            .spacing(10)
-            .push(search_input)
-            .push(search_btn);
-
-        // Results List
-        let mut stations_list = widget::column().spacing(5);
+        // Keyboard shortcuts hint
+        let shortcuts_hint =
+            widget::text(fl!("shortcuts-hint"))
+                .size(11)
+                .class(cosmic::theme::Text::Color(cosmic::iced::Color::from_rgb(
+                    0.5, 0.5, 0.5,
+                )));
 
-        if self.is_searching {
-            stations_list = stations_list.push(widget::text(fl!("searching-status")));
-        } else if let Some(err) = &self.error_message {
-            stations_list =
-                stations_list.push(widget::text(format!("{} {}", fl!("error-message"), err)));
-        } else if self.search_query.is_empty() && self.search_results.is_empty() {
-            stations_list = stations_list.push(widget::text(fl!("favorites-header")).size(18));
-            if self.config.favorites.is_empty() {
-                stations_list = stations_list.push(widget::text(fl!("no-favorites")));
-            }
-            for station in &self.config.favorites {
-                stations_list = stations_list.push(self.view_station_row(station, true));
-            }
+        let pin_label = if self.popup_pinned {
+            fl!("unpin-popup")
         } else {
-            let back_btn = cosmic::iced::widget::button(widget::text(fl!("back-to-favorites")))
-                .on_press(Message::ClearSearch);
-
-            stations_list = stations_list.push(back_btn);
-            stations_list = stations_list.push(widget::text(fl!("search-results-header")).size(18));
-            for station in &self.search_results {
-                let is_fav = self
-                    .config
-                    .favorites
+            fl!("pin-popup")
+        };
+        let mut header = widget::row()
+            .spacing(10)
+            .align_y(Alignment::Center)
+            .push(title.width(Length::Fill))
+            .push(
+                icon_button(
+                    match self.config.list_layout {
+                        ListLayout::Compact => "view-list-symbolic",
+                        ListLayout::Comfortable => "view-continuous-symbolic",
+                        ListLayout::Grid => "view-grid-symbolic",
+                    },
+                    fl!("a11y-list-layout"),
+                )
+                .on_press(Message::CycleListLayout),
+            )
+            .push(widget::tooltip(
+                icon_button("view-pin-symbolic", pin_label.clone())
+                    .class(if self.popup_pinned {
+                        cosmic::theme::Button::Suggested
+                    } else {
+                        cosmic::theme::Button::Standard
+                    })
+                    .on_press(Message::TogglePinPopup),
+                widget::text(pin_label),
+                widget::tooltip::Position::Bottom,
+            ));
+        if !self.config.profiles.is_empty() {
+            header = header.push(widget::dropdown(
+                &self.profile_labels,
+                self.config
+                    .profile_names()
                     .iter()
-                    .any(|s| s.stationuuid == station.stationuuid);
-                stations_list = stations_list.push(self.view_station_row(station, is_fav));
-            }
+                    .position(|&name| name == self.config.active_profile),
+                Message::ProfileSelected,
+            ));
         }
-
-        // Keyboard shortcuts hint
-        let shortcuts_hint = widget::text(fl!("shortcuts-hint"))
-            .size(11)
-            .class(cosmic::theme::Text::Color(cosmic::iced::Color::from_rgb(
-                0.5, 0.5, 0.5,
-            )));
-
-        let content = widget::column()
-            .padding(20)
-            .spacing(12)
-            .push(title)
+        let mut content = widget::column().padding(20).spacing(12).push(header);
+        if let Some(toast) = &self.toast {
+            content = content.push(view_toast(toast));
+        }
+        if let Some(picker) = &self.folder_picker {
+            content = content.push(
+                widget::row()
+                    .spacing(10)
+                    .align_y(Alignment::Center)
+                    .push(
+                        widget::text(fl!("folder-picker", name = picker.station_name.clone()))
+                            .width(Length::Fill),
+                    )
+                    .push(widget::dropdown(
+                        &picker.options,
+                        Some(0),
+                        Message::PickFolder,
+                    ))
+                    .push(
+                        icon_button("window-close-symbolic", fl!("a11y-close"))
+                            .on_press(Message::CloseFolderPicker),
+                    ),
+            );
+        }
+        if !self.config.recent_stations.is_empty() {
+            content = content.push(self.view_recent_stations());
+        }
+        let mut content = content
             .push(widget::divider::horizontal::light())
             .push(now_playing)
             .push(volume_section)
             .push(widget::divider::horizontal::light())
-            .push(search_row)
-            .push(widget::scrollable(stations_list).height(250))
+            .push(widget::tab_bar::horizontal(&self.tabs).on_activate(Message::TabActivated));
+
+        let tab = self.active_tab();
+        if tab == PopupTab::Search {
+            content = content
+                .push(self.view_search_bar())
+                .push(self.view_genre_chips())
+                .push(self.view_search_filters());
+        }
+        let tab_content = match tab {
+            PopupTab::Favorites => self.view_favorites(),
+            PopupTab::Search => self.view_search(),
+            PopupTab::Browse => self.view_browse(),
+            PopupTab::History => self.view_history(),
+            PopupTab::Settings => self.view_settings(),
+        };
+        let content = content
+            .push(
+                widget::scrollable(tab_content)
+                    .id(popup_scroll_id())
+                    .on_scroll(Message::PopupScrolled)
+                    .height(self.config.popup_size.dimensions().1 as f32),
+            )
             .push(shortcuts_hint);
 
         self.core.applet.popup_container(content).into()
@@ -276,270 +1551,5360 @@ impl cosmic::Application for AppModel {
 
     fn update(&mut self, message: Self::Message) -> Task<cosmic::Action<Self::Message>> {
         match message {
+            Message::ConfigReloaded(config) => {
+                return self.reload_config(*config);
+            }
+            Message::PanelAction(action) => match action {
+                ClickAction::TogglePopup => return self.update(Message::TogglePopup),
+                // Nothing to play yet: show the popup to pick a station
+                ClickAction::PlayPause if self.current_station.is_none() => {
+                    return self.update(Message::TogglePopup)
+                }
+                ClickAction::PlayPause => return self.update(Message::TogglePlayPause),
+                ClickAction::Nothing => {}
+            },
+            Message::PanelScrolled(delta) => {
+                // Sideways scrolling switches favorites. Ctrl+scroll can't be
+                // used: Wayland only sends modifiers to the focused surface
+                let (x, y, lines) = match delta {
+                    ScrollDelta::Lines { x, y } => (x, y, true),
+                    ScrollDelta::Pixels { x, y } => (x, y, false),
+                };
+                if x.abs() > y.abs() {
+                    let swipes = if lines {
+                        self.panel_swipe.add_lines(x)
+                    } else {
+                        self.panel_swipe.add_pixels(x)
+                    };
+                    // Positive is to the left, i.e. back
+                    if swipes != 0 {
+                        return self.update(Message::SkipFavorite(swipes < 0));
+                    }
+                    return Task::none();
+                }
+                let notches = if lines {
+                    self.panel_scroll.add_lines(y)
+                } else {
+                    self.panel_scroll.add_pixels(y)
+                };
+                if notches == 0 {
+                    return Task::none();
+                }
+                let step = self.config.scroll_volume_step;
+                let volume = panel::scrolled_volume(self.config.volume, notches, step);
+                self.volume_hint = true;
+                self.volume_hint_generation = self.volume_hint_generation.wrapping_add(1);
+                let generation = self.volume_hint_generation;
+                return Task::batch([
+                    self.update(Message::VolumeChanged(volume as f32)),
+                    Task::perform(tokio::time::sleep(panel::VOLUME_HINT_DURATION), move |_| {
+                        Message::HideVolumeHint(generation)
+                    })
+                    .map(Into::into),
+                ]);
+            }
+            Message::HideVolumeHint(generation) => {
+                if generation == self.volume_hint_generation {
+                    self.volume_hint = false;
+                }
+            }
             Message::TogglePopup => {
                 return if let Some(p) = self.popup.take() {
                     destroy_popup(p)
                 } else {
-                    let new_id = Id::unique();
-                    self.popup.replace(new_id);
-                    if let Some(main_id) = self.core.main_window_id() {
-                        let popup_settings =
-                            self.core
-                                .applet
-                                .get_popup_settings(main_id, new_id, None, None, None);
-                        get_popup(popup_settings)
-                    } else {
-                        warn!("No main window ID available");
-                        Task::none()
-                    }
+                    self.show_popup()
                 };
             }
+            Message::TogglePinPopup => {
+                self.popup_pinned = !self.popup_pinned;
+                // Whether the popup grabs focus is fixed when it's created
+                if let Some(p) = self.popup.take() {
+                    let reopen = self.open_popup().unwrap_or_else(Task::none);
+                    return destroy_popup(p).chain(reopen);
+                }
+            }
             Message::PopupClosed(id) => {
                 if self.popup == Some(id) {
                     self.popup = None;
+                    self.clear_row_actions();
+                    self.show_shortcuts = false;
                 }
             }
             Message::SearchInputChanged(val) => {
                 self.search_query = val;
-            }
-            Message::PerformSearch => {
-                self.is_searching = true;
-                self.error_message = None;
-                self.search_results.clear();
-                let query = self.search_query.clone();
-                return Task::perform(
-                    async move {
-                        api::search_stations(query)
-                            .await
-                            .map_err(|e: reqwest::Error| e.to_string())
-                    },
-                    Message::SearchCompleted,
-                )
+                self.typing_generation = self.typing_generation.wrapping_add(1);
+                let generation = self.typing_generation;
+                return Task::perform(tokio::time::sleep(SEARCH_DEBOUNCE), move |_| {
+                    Message::SearchDebounced(generation)
+                })
                 .map(Into::into);
             }
-            Message::SearchCompleted(res) => {
-                self.is_searching = false;
-                match res {
-                    Ok(stations) => {
+            Message::SearchDebounced(generation) => {
+                if generation != self.typing_generation {
+                    return Task::none();
+                }
+                let query = self.search_query.trim();
+                if query.is_empty() {
+                    return self.update(Message::ClearSearch);
+                }
+                if query.chars().count() >= MIN_AUTO_SEARCH_LEN {
+                    return self.update(Message::PerformSearch);
+                }
+            }
+            Message::SaveSearchNameChanged(val) => {
+                self.save_search_name = val;
+            }
+            Message::SaveCurrentSearch => {
+                let query = self.search_query.trim().to_string();
+                if !query.is_empty() {
+                    let name = match self.save_search_name.trim() {
+                        "" => query.clone(),
+                        name => name.to_string(),
+                    };
+                    debug!("Saved search '{}': {}", name, query);
+                    self.config.saved_searches.retain(|s| s.name != name);
+                    self.config.saved_searches.push(SavedSearch { name, query });
+                    self.save_search_name.clear();
+                    self.save_config();
+                }
+            }
+            Message::RunSavedSearch(index) => {
+                if let Some(saved) = self.config.saved_searches.get(index) {
+                    self.search_query = saved.query.clone();
+                    return self.update(Message::PerformSearch);
+                }
+            }
+            Message::DeleteSavedSearch(index) => {
+                if index < self.config.saved_searches.len() {
+                    let removed = self.config.saved_searches.remove(index);
+                    debug!("Deleted saved search '{}'", removed.name);
+                    self.save_config();
+                }
+            }
+            Message::SearchFieldSelected(index) => {
+                if let Some(field) = SearchField::ALL.get(index) {
+                    self.config.search_field = *field;
+                    self.save_config();
+                }
+            }
+            Message::FilterChoicesLoaded(kind, res) => match res {
+                Ok(facets) => {
+                    match kind {
+                        FacetKind::Country => self.filter_countries = facets,
+                        FacetKind::Language => self.filter_languages = facets,
+                        FacetKind::Tag => self.filter_tags = facets,
+                    }
+                    self.rebuild_filter_labels();
+                }
+                Err(e) => {
+                    warn!("Loading {:?} filter choices failed: {}", kind, e);
+                    // Try again the next time the popup opens
+                    self.filter_choices_requested = false;
+                }
+            },
+            Message::FilterCountrySelected(index) => {
+                let code = match index.checked_sub(1) {
+                    Some(i) => match self.filter_countries.get(i).and_then(|f| f.code.clone()) {
+                        Some(code) => code,
+                        None => return Task::none(),
+                    },
+                    None => String::new(),
+                };
+                self.config.search_filters.country = code;
+                return self.filters_changed();
+            }
+            Message::FilterLanguageSelected(index) => {
+                let name = match index.checked_sub(1) {
+                    Some(i) => match self.filter_languages.get(i) {
+                        Some(language) => language.name.clone(),
+                        None => return Task::none(),
+                    },
+                    None => String::new(),
+                };
+                self.config.search_filters.language = name;
+                return self.filters_changed();
+            }
+            Message::ToggleFilterTag(tag) => {
+                self.config.search_filters.toggle_tag(&tag);
+                return self.filters_changed();
+            }
+            Message::ClearFilters => {
+                self.config.search_filters = Default::default();
+                return self.filters_changed();
+            }
+            Message::SearchGenre(tag) => {
+                // One `tag:` per word, which the query parser joins back up
+                self.search_query = tag
+                    .split_whitespace()
+                    .map(|word| format!("tag:{word}"))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                select_tab(&mut self.tabs, PopupTab::Search);
+                self.config.last_tab = PopupTab::Search;
+                self.write_config();
+                return self.update(Message::PerformSearch);
+            }
+            Message::GenreChipInputChanged(val) => {
+                self.genre_chip_input = val;
+            }
+            Message::AddGenreChip => {
+                if self.config.add_genre_chip(&self.genre_chip_input) {
+                    self.genre_chip_input.clear();
+                    self.save_config();
+                }
+            }
+            Message::RemoveGenreChip(index) => {
+                if index < self.config.genre_chips.len() {
+                    self.config.genre_chips.remove(index);
+                    self.save_config();
+                }
+            }
+            Message::SearchProviderSelected(index) => {
+                if index < self.providers.len() {
+                    self.search_provider = index;
+                }
+            }
+            Message::PerformSearch => {
+                // An explicit search makes any pending debounced one redundant
+                self.typing_generation = self.typing_generation.wrapping_add(1);
+                self.abort_search();
+                let generation = self.search_generation;
+                self.is_searching = true;
+                self.dismiss_toast(RetryAction::Search);
+                self.search_results.clear();
+                self.search_groups.clear();
+                let mut params = SearchParams::parse(&self.search_query, self.config.search_field);
+                params.apply_filters(&self.config.search_filters);
+                params.hide_broken = !self.config.show_broken_stations;
+                params.raise_min_bitrate(self.config.quality.min_bitrate);
+                let search = match self.providers.get(self.search_provider) {
+                    Some(provider) => provider.search(params),
+                    None => RadioBrowser.search(params),
+                };
+                let (progress, updates) = mpsc::unbounded();
+                let search =
+                    api::with_progress(progress, async move { search.await.map_err(Arc::new) });
+                let updates = updates.map(move |p| Message::SearchProgress(generation, p));
+                let done =
+                    stream::once(search).map(move |res| Message::SearchCompleted(generation, res));
+                let (task, handle) = Task::stream(stream::select(updates, done)).abortable();
+                self.search_handle = Some(handle);
+                return task.map(Into::into);
+            }
+            Message::SearchProgress(generation, progress) => {
+                if generation == self.search_generation && self.is_searching {
+                    self.search_progress = Some(progress);
+                }
+            }
+            Message::CancelSearch => self.abort_search(),
+            Message::SearchCompleted(generation, res) => {
+                if generation != self.search_generation {
+                    debug!("Dropping results of superseded search #{}", generation);
+                    return Task::none();
+                }
+                self.is_searching = false;
+                self.search_handle = None;
+                self.search_progress = None;
+                self.track_network(&res);
+                match res {
+                    Ok(stations) => {
                         debug!("Search completed: {} stations found", stations.len());
+                        let stations = self.visible(stations);
+                        self.search_groups = dedupe::group(&stations);
+                        self.expanded_groups.clear();
                         self.search_results = stations;
+                        self.sync_stations();
+                        // New results start at the top
+                        self.scroll_offsets.remove(&PopupTab::Search);
+                        if self.active_tab() == PopupTab::Search {
+                            return self.restore_scroll();
+                        }
                     }
                     Err(e) => {
                         error!("Search failed: {}", e);
-                        self.error_message = Some(e);
+                        self.show_toast(
+                            Severity::Error,
+                            api_error_text(&e),
+                            Some(RetryAction::Search),
+                        );
                     }
                 }
             }
-            Message::PlayStation(station) => {
+            Message::PlayStation(id) => {
+                let Some(station) = self.stations.get(&id).cloned() else {
+                    warn!("Ignoring play request for unknown station {}", id);
+                    return Task::none();
+                };
+                self.stop_preview();
                 let is_same = self
                     .current_station
                     .as_ref()
-                    .map(|s| s.stationuuid == station.stationuuid)
+                    .map(|s| s.is_same(&station))
                     .unwrap_or(false);
 
-                if self.is_playing && is_same {
-                    self.audio.stop();
-                    self.is_playing = false;
-                    debug!("Stopped playback");
+                if (self.is_playing || self.is_connecting) && is_same {
+                    self.stop_playback();
                 } else {
+                    // Check the stream answers before handing it to the player
+                    if self.is_playing {
+                        self.audio.stop();
+                        self.is_playing = false;
+                        self.publish_playback_state();
+                    }
+                    self.stream_check_generation = self.stream_check_generation.wrapping_add(1);
+                    let generation = self.stream_check_generation;
+                    self.is_connecting = true;
                     self.current_station = Some(station.clone());
-                    self.is_playing = true;
-                    self.audio
-                        .play(station.url_resolved.clone(), self.config.volume);
-                    debug!("Playing: {}", station.name);
+                    let artwork = self.load_artwork();
+                    if self.audio.is_mock() {
+                        // Simulated playback works without a network
+                        let play = self.update(Message::StreamChecked(generation, Ok(())));
+                        return Task::batch([artwork, play]);
+                    }
+                    let url = station.stream_url().to_string();
+                    let check = Task::perform(api::check_stream(url), move |res| {
+                        Message::StreamChecked(generation, res)
+                    })
+                    .map(Into::into);
+                    return Task::batch([artwork, check]);
+                }
+            }
+            Message::FaviconTick => {
+                if self.lite {
+                    self.favicon_queue.clear();
+                    return Task::none();
+                }
+                let batch = self.favicon_queue.len().min(FAVICON_BATCH);
+                let fetches: Vec<_> = self
+                    .favicon_queue
+                    .drain(..batch)
+                    .map(fetch_favicon)
+                    .collect();
+                return Task::batch(fetches);
+            }
+            Message::FaviconLoaded(url, res) => match res {
+                // Fetched before lite mode was switched on
+                Ok(_) if self.lite => {
+                    self.favicons_requested.remove(&url);
+                }
+                Ok(bytes) => {
+                    let handle = widget::image::Handle::from_bytes(bytes);
+                    if self.favicons.insert(url.clone(), handle).is_none() {
+                        self.favicons_loaded.push_back(url);
+                    }
+                    while self.favicons_loaded.len() > MAX_LOADED_FAVICONS {
+                        if let Some(oldest) = self.favicons_loaded.pop_front() {
+                            self.favicons.remove(&oldest);
+                            self.favicons_requested.remove(&oldest);
+                        }
+                    }
+                }
+                Err(e) => debug!("No favicon from {}: {}", url, e),
+            },
+            Message::StreamChecked(generation, res) => {
+                if generation != self.stream_check_generation {
+                    return Task::none();
+                }
+                self.is_connecting = false;
+                let Some(station) = self.current_station.clone() else {
+                    return Task::none();
+                };
+                match res {
+                    Err(e) if e.is_definite() => {
+                        warn!("Not playing {}: {}", station.name, e);
+                        self.show_toast(
+                            Severity::Error,
+                            stream_error_text(&e),
+                            Some(RetryAction::Playback),
+                        );
+                        return self.notify_stream_error(&station, stream_error_text(&e));
+                    }
+                    Err(e) => {
+                        // mpv may still manage; say why it might not
+                        warn!("Playing {} despite: {}", station.name, e);
+                        self.show_toast(Severity::Warning, stream_error_text(&e), None);
+                    }
+                    Ok(()) => {}
+                }
+
+                if !self.start_stream(&station) {
+                    return Task::none();
+                }
+                self.is_playing = true;
+                self.paused_for_output = false;
+                self.track_title = None;
+                self.buffer_state = Some(BufferState::Buffering(0));
+                debug!("Playing: {}", station.name);
+                self.publish_playback_state();
+                let mut tasks = Vec::new();
+                if self.config.notify_station {
+                    tasks.push(self.notify(
+                        &station,
+                        fl!("notification-now-playing"),
+                        station.display_name().to_string(),
+                    ));
+                } else {
+                    let name = station.display_name();
+                    tasks.push(self.announce(fl!("a11y-now-playing", name = name)));
+                }
+                if self.audio.is_mock() {
+                    // Announce a track right away rather than after the first interval
+                    tasks.push(self.announce_mock_track());
+                }
+
+                if self.config.report_clicks
+                    && !station.stationuuid.is_empty()
+                    && !station.is_custom()
+                {
+                    tasks.push(Task::future(api::report_click(station.stationuuid)).discard());
+                }
+                return Task::batch(tasks);
+            }
+            Message::StreamEnded(reason) => {
+                // Already stopped by hand
+                if !self.is_playing {
+                    return Task::none();
+                }
+                warn!("Stream stopped: {}", reason);
+                self.stop_playback();
+                self.show_toast(
+                    Severity::Error,
+                    fl!("stream-ended"),
+                    Some(RetryAction::Playback),
+                );
+                if let Some(station) = &self.current_station {
+                    return self.notify_stream_error(station, fl!("stream-ended"));
+                }
+            }
+            Message::Retry(action) => {
+                self.toast = None;
+                match action {
+                    RetryAction::Search => return self.update(Message::PerformSearch),
+                    RetryAction::Browse => return self.reload_popular(),
+                    RetryAction::Playback => {
+                        if let Some(station) = &self.current_station {
+                            return self.update(Message::PlayStation(station.id.clone()));
+                        }
+                    }
                 }
-                self.push_mpris_state();
             }
             Message::ClearSearch => {
+                // Also cancels a search still in flight
+                self.abort_search();
                 self.search_query.clear();
                 self.search_results.clear();
-                self.error_message = None;
+                self.search_groups.clear();
+                self.dismiss_toast(RetryAction::Search);
             }
-            Message::ToggleFavorite(station) => {
-                if let Some(pos) = self
-                    .config
-                    .favorites
-                    .iter()
-                    .position(|s| s.stationuuid == station.stationuuid)
+            Message::ToggleVariants(id) => {
+                if !self.expanded_groups.remove(&id) {
+                    self.expanded_groups.insert(id);
+                }
+            }
+            Message::StartPreview(id) => {
+                let Some(station) = self.stations.get(&id).cloned() else {
+                    return Task::none();
+                };
+                self.stop_preview();
+                self.preview_generation = self.preview_generation.wrapping_add(1);
+                let volume = (u16::from(self.config.volume) * PREVIEW_VOLUME_PERCENT / 100) as u8;
+                let url = station.stream_url().to_string();
+                if let Err(e) = self
+                    .preview_audio
+                    .play(url, volume, station.has_user_stream())
                 {
-                    self.config.favorites.remove(pos);
-                    debug!("Removed from favorites: {}", station.name);
-                } else {
-                    self.config.favorites.push(station.clone());
-                    debug!("Added to favorites: {}", station.name);
+                    warn!("Not previewing {}: {}", station.name, e);
+                    self.show_toast(Severity::Error, audio_error_text(&e), None);
+                    return Task::none();
                 }
-                self.save_config();
+                self.preview_station = Some(station.id.clone());
+                if self.is_playing {
+                    // Mute the main stream instead of stopping it so it resumes instantly
+                    self.audio.set_volume(self.playback_volume() as f32);
+                }
+                debug!("Previewing: {}", station.name);
+
+                let generation = self.preview_generation;
+                return Task::perform(tokio::time::sleep(PREVIEW_DURATION), move |_| {
+                    Message::PreviewTimeout(generation)
+                })
+                .map(Into::into);
             }
-            Message::VolumeChanged(vol) => {
-                let volume = vol.round() as u8;
-                self.config.volume = volume;
-                self.audio.set_volume(volume as f32);
-                debug!("Volume changed to {}%", volume);
-                self.save_config();
-                self.push_mpris_state();
+            Message::StopPreview => {
+                self.stop_preview();
             }
-            Message::VolumeUp => {
-                let new_vol = (self.config.volume as i16 + 5).min(100) as u8;
-                self.config.volume = new_vol;
-                self.audio.set_volume(new_vol as f32);
-                debug!("Volume up to {}%", new_vol);
-                self.save_config();
-                self.push_mpris_state();
+            Message::PreviewTimeout(generation) => {
+                if generation == self.preview_generation {
+                    self.stop_preview();
+                }
             }
-            Message::VolumeDown => {
-                let new_vol = (self.config.volume as i16 - 5).max(0) as u8;
-                self.config.volume = new_vol;
-                self.audio.set_volume(new_vol as f32);
-                debug!("Volume down to {}%", new_vol);
+            Message::RowHovered(id) => {
+                // The mouse is back in use, so rows go back to revealing on hover
+                self.keyboard_focus = false;
+                if self.revealed_row.as_ref() != Some(&id) {
+                    self.revealed_row = None;
+                }
+                self.hovered_row = Some(id);
+            }
+            Message::RowUnhovered(id) => {
+                if self.hovered_row.as_ref() == Some(&id) {
+                    self.hovered_row = None;
+                }
+            }
+            Message::RowPressed(id) => {
+                // A tap on another row hides the buttons revealed by the last long press
+                if self.revealed_row.as_ref() != Some(&id) {
+                    self.revealed_row = None;
+                }
+                self.pressed_row = Some(id);
+                self.long_press_generation = self.long_press_generation.wrapping_add(1);
+                let generation = self.long_press_generation;
+                return Task::perform(tokio::time::sleep(LONG_PRESS), move |_| {
+                    Message::RowLongPressed(generation)
+                })
+                .map(Into::into);
+            }
+            Message::RowReleased => {
+                self.pressed_row = None;
+                self.long_press_generation = self.long_press_generation.wrapping_add(1);
+            }
+            Message::RowLongPressed(generation) => {
+                if generation == self.long_press_generation {
+                    self.revealed_row = self.pressed_row.take();
+                }
+            }
+            Message::SetRowActionsOnHover(enabled) => {
+                self.config.row_actions_on_hover = enabled;
+                self.clear_row_actions();
                 self.save_config();
-                self.push_mpris_state();
             }
-            Message::TogglePlayPause => {
-                if self.is_playing {
-                    self.audio.stop();
-                    self.is_playing = false;
-                    debug!("Paused playback via shortcut");
-                } else if let Some(station) = &self.current_station {
-                    self.audio
-                        .play(station.url_resolved.clone(), self.config.volume);
-                    self.is_playing = true;
-                    debug!("Resumed playback via shortcut: {}", station.name);
+            Message::RefreshFavorites => {
+                if self.is_refreshing_favorites || self.config.favorites.is_empty() {
+                    return Task::none();
                 }
-                self.push_mpris_state();
+                self.is_refreshing_favorites = true;
+                let favorites = self.config.favorites.clone();
+                return Task::perform(
+                    async move { api::refresh_favorites(favorites).await.map_err(Arc::new) },
+                    Message::FavoritesRefreshed,
+                )
+                .map(Into::into);
             }
-            Message::MprisEvent(event) => match event {
-                mpris::MprisEvent::Ready(tx) => {
-                    info!("MPRIS server ready");
-                    self.mpris_tx = Some(tx);
-                    self.push_mpris_state();
+            Message::FavoritesRefreshed(res) => {
+                self.is_refreshing_favorites = false;
+                self.track_network(&res);
+                match res {
+                    Ok(fresh) => {
+                        let changed = self.config.refresh_favorites(&fresh);
+                        debug!("Refreshed favorites: {} changed", changed);
+                        self.config.favorites_refreshed_at = journal::now();
+                        if changed > 0 {
+                            self.save_config();
+                        } else if let Err(e) = self
+                            .config_handler
+                            .set("favorites_refreshed_at", self.config.favorites_refreshed_at)
+                        {
+                            error!("Failed to save favorites refresh time: {:?}", e);
+                        }
+                        // Radio-browser occasionally re-issues UUIDs; find
+                        // the favorites it no longer knows again
+                        let stale = api::stale_favorites(&self.config.favorites, &fresh);
+                        if !stale.is_empty() {
+                            return Task::perform(
+                                async move {
+                                    api::reconcile_favorites(stale).await.map_err(Arc::new)
+                                },
+                                Message::FavoritesReconciled,
+                            )
+                            .map(Into::into);
+                        }
+                    }
+                    Err(e) => warn!("Refreshing favorites failed: {}", e),
                 }
-                mpris::MprisEvent::Command(cmd) => match cmd {
-                    mpris::MprisCommand::Play => {
-                        if !self.is_playing {
-                            if let Some(station) = &self.current_station {
-                                self.audio
-                                    .play(station.url_resolved.clone(), self.config.volume);
-                                self.is_playing = true;
-                                debug!("MPRIS: Play");
-                                self.push_mpris_state();
+            }
+            Message::FavoritesReconciled(res) => match res {
+                Ok(replacements) if !replacements.is_empty() => {
+                    for (old_uuid, found) in replacements {
+                        if let Some(fav) = self
+                            .config
+                            .favorites
+                            .iter_mut()
+                            .find(|s| s.stationuuid == old_uuid)
+                        {
+                            info!(
+                                "Favorite '{}' moved from {} to {}",
+                                fav.name, old_uuid, found.stationuuid
+                            );
+                            fav.stationuuid = found.stationuuid.clone();
+                            if fav.url_resolved.is_empty() {
+                                fav.url_resolved = found.url_resolved.clone();
                             }
                         }
-                    }
-                    mpris::MprisCommand::Pause | mpris::MprisCommand::Stop => {
-                        if self.is_playing {
-                            self.audio.stop();
-                            self.is_playing = false;
-                            debug!("MPRIS: Stop");
-                            self.push_mpris_state();
+                        if let Some(current) = self
+                            .current_station
+                            .as_mut()
+                            .filter(|s| s.stationuuid == old_uuid)
+                        {
+                            current.stationuuid = found.stationuuid;
                         }
                     }
-                    mpris::MprisCommand::PlayPause => {
-                        return self.update(Message::TogglePlayPause);
+                    self.save_config();
+                }
+                Ok(_) => debug!("All favorites are up to date"),
+                Err(e) => warn!("Favorite reconciliation failed: {}", e),
+            },
+            Message::ToggleAddStation => {
+                self.adding_station = !self.adding_station;
+                self.custom_name_input.clear();
+                self.custom_url_input.clear();
+                self.custom_icon_input.clear();
+                self.custom_station_error = None;
+            }
+            Message::CustomNameChanged(val) => {
+                self.custom_name_input = val;
+            }
+            Message::CustomUrlChanged(val) => {
+                self.custom_url_input = val;
+                self.custom_station_error = None;
+            }
+            Message::CustomIconChanged(val) => {
+                self.custom_icon_input = val;
+                self.custom_station_error = None;
+            }
+            Message::SaveCustomStation => {
+                let station = match Station::custom(
+                    &self.custom_name_input,
+                    &self.custom_url_input,
+                    &self.custom_icon_input,
+                ) {
+                    Ok(station) => station,
+                    Err(e) => {
+                        self.custom_station_error = Some(url_error_text(e));
+                        return Task::none();
                     }
-                    mpris::MprisCommand::SetVolume(vol) => {
-                        let volume = mpris::volume_from_mpris(vol);
-                        self.config.volume = volume;
-                        self.audio.set_volume(volume as f32);
-                        debug!("MPRIS: Volume set to {}%", volume);
-                        self.save_config();
-                        self.push_mpris_state();
+                };
+                if self.is_favorite(&station) {
+                    self.custom_station_error = Some(fl!("custom-station-exists"));
+                    return Task::none();
+                }
+                info!("Added custom station '{}' ({})", station.name, station.url);
+                self.config.favorites.push(station);
+                self.save_config();
+                return self.update(Message::ToggleAddStation);
+            }
+            Message::ToggleManageFavorites => {
+                self.managing_favorites = !self.managing_favorites;
+                self.selected_favorites.clear();
+                self.editing_favorite = None;
+                self.confirm_bulk_remove = false;
+            }
+            Message::AskRemoveSelected(ask) => self.confirm_bulk_remove = ask,
+            Message::RemoveSelectedFavorites => {
+                self.confirm_bulk_remove = false;
+                let removed = self.config.remove_favorites(&self.selected_favorites);
+                debug!("Removed {} favorites", removed);
+                self.selected_favorites.clear();
+                if removed > 0 {
+                    self.save_config();
+                }
+            }
+            Message::ExportSelectedFavorites => {
+                let selected = self.config.selected_favorites(&self.selected_favorites);
+                let document = opml::export(&fl!("window-title"), &selected);
+                return Task::perform(
+                    save_document(
+                        fl!("export-favorites"),
+                        "radio-favorites.opml".to_string(),
+                        document,
+                    ),
+                    Message::FavoritesExported,
+                )
+                .map(Into::into);
+            }
+            Message::FavoriteSelected(id, selected) => {
+                if selected {
+                    self.selected_favorites.insert(id);
+                } else {
+                    self.selected_favorites.remove(&id);
+                }
+            }
+            Message::SelectAllFavorites(selected) => {
+                self.selected_favorites = if selected {
+                    self.config
+                        .active_favorites()
+                        .map(|s| s.id.clone())
+                        .collect()
+                } else {
+                    HashSet::new()
+                };
+            }
+            Message::BulkTagInputChanged(val) => {
+                self.bulk_tag_input = val;
+            }
+            Message::BulkFolderInputChanged(val) => {
+                self.bulk_folder_input = val;
+            }
+            Message::ApplyBulkEdit(edit) => {
+                let changed = self.config.apply_bulk_edit(&self.selected_favorites, &edit);
+                debug!("Bulk edit {:?} changed {} favorites", edit, changed);
+                if changed > 0 {
+                    // One write for the whole batch
+                    self.save_config();
+                }
+                if matches!(edit, BulkEdit::SetArchived(true)) {
+                    self.selected_favorites.clear();
+                }
+            }
+            Message::RestoreFavorite(id) => {
+                let ids = HashSet::from([id]);
+                if self
+                    .config
+                    .apply_bulk_edit(&ids, &BulkEdit::SetArchived(false))
+                    > 0
+                {
+                    self.save_config();
+                }
+            }
+            Message::ToggleFolder(folder) => {
+                if !self.collapsed_folders.remove(&folder) {
+                    self.collapsed_folders.insert(folder);
+                }
+            }
+            Message::PickFolder(index) => {
+                let Some(picker) = self.folder_picker.take() else {
+                    return Task::none();
+                };
+                // Option 0 keeps the station outside any folder
+                if let Some(folder) = picker.options.get(index).filter(|_| index != 0) {
+                    let ids = HashSet::from([picker.station]);
+                    self.config
+                        .apply_bulk_edit(&ids, &BulkEdit::MoveToFolder(folder.clone()));
+                    self.save_config();
+                }
+            }
+            Message::CloseFolderPicker => {
+                self.folder_picker = None;
+            }
+            Message::ImportFavorites => {
+                return Task::perform(import_station_file(), Message::FavoritesImported)
+                    .map(Into::into);
+            }
+            Message::FavoritesImported(result) => match result {
+                Ok(Some(stations)) => {
+                    let total = stations.len();
+                    let mut added = 0;
+                    for station in stations {
+                        // Also skips repeats within the file itself
+                        if !self.config.favorites.iter().any(|s| s.is_same(&station)) {
+                            self.config.favorites.push(station);
+                            added += 1;
+                        }
                     }
-                    mpris::MprisCommand::Raise => {
-                        return self.update(Message::TogglePopup);
+                    info!("Imported {} of {} stations", added, total);
+                    self.show_toast(Severity::Info, fl!("import-done", count = added), None);
+                    if added > 0 {
+                        self.save_config();
                     }
-                    mpris::MprisCommand::Quit => {
-                        if self.is_playing {
-                            self.audio.stop();
-                            self.is_playing = false;
-                            self.push_mpris_state();
-                        }
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    warn!("Importing favorites failed: {}", e);
+                    let error = file_error_text(&e);
+                    self.show_toast(Severity::Error, fl!("import-failed", error = error), None);
+                }
+            },
+            Message::ExportFavorites => {
+                let document = opml::export(&fl!("window-title"), &self.config.favorites);
+                return Task::perform(
+                    save_document(
+                        fl!("export-favorites"),
+                        "radio-favorites.opml".to_string(),
+                        document,
+                    ),
+                    Message::FavoritesExported,
+                )
+                .map(Into::into);
+            }
+            Message::ExportPlaylist(format) => {
+                let stations: Vec<&Station> = self.config.active_favorites().collect();
+                let file_name = format!("radio-favorites.{}", format.extension());
+                return Task::perform(
+                    save_document(fl!("export-favorites"), file_name, format.render(&stations)),
+                    Message::FavoritesExported,
+                )
+                .map(Into::into);
+            }
+            Message::FavoritesExported(result) => match result {
+                Ok(Some(path)) => {
+                    info!("Exported favorites to {}", path.display());
+                    self.show_toast(
+                        Severity::Info,
+                        fl!("export-done", path = path.display().to_string()),
+                        None,
+                    );
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    warn!("Exporting favorites failed: {}", e);
+                    let error = file_error_text(&e);
+                    self.show_toast(Severity::Error, fl!("export-failed", error = error), None);
+                }
+            },
+            Message::BackupSettings => {
+                let document = match backup::to_json(&self.config, journal::now()) {
+                    Ok(document) => document,
+                    Err(e) => {
+                        self.show_toast(
+                            Severity::Error,
+                            fl!("backup-failed", error = backup_error_text(&e)),
+                            None,
+                        );
+                        return Task::none();
                     }
-                },
+                };
+                return Task::perform(
+                    save_document(
+                        fl!("backup-settings"),
+                        "radio-settings-backup.json".to_string(),
+                        document,
+                    ),
+                    Message::SettingsBackedUp,
+                )
+                .map(Into::into);
+            }
+            Message::SettingsBackedUp(result) => match result {
+                Ok(Some(path)) => {
+                    info!("Backed up settings to {}", path.display());
+                    self.show_toast(
+                        Severity::Info,
+                        fl!("backup-done", path = path.display().to_string()),
+                        None,
+                    );
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    warn!("Backing up settings failed: {}", e);
+                    let error = file_error_text(&e);
+                    self.show_toast(Severity::Error, fl!("backup-failed", error = error), None);
+                }
             },
-            Message::KeyboardEvent(event) => {
-                if let Event::Keyboard(cosmic::iced::keyboard::Event::KeyPressed { key, .. }) = event {
-                    match key {
-                        Key::Named(Named::Space) => {
-                            return self.update(Message::TogglePlayPause);
-                        }
-                        Key::Named(Named::ArrowUp) => {
-                            return self.update(Message::VolumeUp);
-                        }
-                        Key::Named(Named::ArrowDown) => {
-                            return self.update(Message::VolumeDown);
+            Message::RestoreSettings => {
+                return Task::perform(open_backup_file(), Message::BackupOpened).map(Into::into);
+            }
+            Message::BackupOpened(result) => match result {
+                Ok(Some(config)) => self.pending_restore = Some(config),
+                Ok(None) => {}
+                Err(e) => {
+                    warn!("Reading backup failed: {}", e);
+                    let error = file_error_text(&e);
+                    self.show_toast(Severity::Error, fl!("restore-failed", error = error), None);
+                }
+            },
+            Message::ApplyRestore(mode) => {
+                let Some(restored) = self.pending_restore.take() else {
+                    return Task::none();
+                };
+                let count = backup::restore(&mut self.config, *restored, mode);
+                info!("Restored settings ({:?}), {} favorites", mode, count);
+                self.show_toast(
+                    Severity::Info,
+                    match mode {
+                        RestoreMode::Merge => fl!("restore-merged", count = count),
+                        RestoreMode::Overwrite => fl!("restore-replaced", count = count),
+                    },
+                    None,
+                );
+                self.apply_replaced_config();
+                self.apply_playback_settings();
+                self.save_config();
+                self.publish_playback_state();
+                return self.reload_quality_filtered();
+            }
+            Message::CancelRestore => {
+                self.pending_restore = None;
+            }
+            Message::MoveFavorite(id, direction) => {
+                if self.config.move_favorite(&id, direction) {
+                    self.save_config();
+                }
+            }
+            Message::EditFavorite(id) => {
+                self.editing_favorite =
+                    self.config
+                        .favorites
+                        .iter()
+                        .find(|s| s.id == id)
+                        .map(|s| FavoriteEdit {
+                            station: id.clone(),
+                            alias: s.display_name().to_string(),
+                            stream_url: s.stream_override.clone(),
+                            error: None,
+                        });
+            }
+            Message::EditAliasChanged(val) => {
+                if let Some(edit) = &mut self.editing_favorite {
+                    edit.alias = val;
+                }
+            }
+            Message::EditStreamUrlChanged(val) => {
+                if let Some(edit) = &mut self.editing_favorite {
+                    edit.stream_url = val;
+                    edit.error = None;
+                }
+            }
+            Message::ApplyFavoriteEdit => {
+                let Some(edit) = self.editing_favorite.take() else {
+                    return Task::none();
+                };
+                let Some(favorite) = self
+                    .config
+                    .favorites
+                    .iter_mut()
+                    .find(|s| s.id == edit.station)
+                else {
+                    return Task::none();
+                };
+                let stream_changed = match favorite.set_stream_override(&edit.stream_url) {
+                    Ok(changed) => changed,
+                    Err(e) => {
+                        self.editing_favorite = Some(FavoriteEdit {
+                            error: Some(url_error_text(e)),
+                            ..edit
+                        });
+                        return Task::none();
+                    }
+                };
+                let renamed = self.config.set_alias(&edit.station, &edit.alias);
+                if !stream_changed && !renamed {
+                    return Task::none();
+                }
+
+                let edited = self
+                    .config
+                    .favorites
+                    .iter()
+                    .find(|s| s.id == edit.station)
+                    .cloned();
+                let mut switch_to = None;
+                if let (Some(current), Some(edited)) = (&mut self.current_station, edited) {
+                    if edited.is_same(current) {
+                        current.alias = edited.alias;
+                        current.stream_override = edited.stream_override;
+                        if stream_changed && self.is_playing {
+                            info!("Switching {} to {}", current.name, current.stream_url());
+                            switch_to = Some(current.clone());
                         }
-                        Key::Named(Named::Escape) => {
-                            if let Some(p) = self.popup.take() {
-                                return destroy_popup(p);
-                            }
+                        if renamed {
+                            // MPRIS shows the new name right away
+                            self.publish_playback_state();
                         }
-                        _ => {}
                     }
                 }
+                if let Some(station) = switch_to {
+                    self.start_stream(&station);
+                }
+                self.save_config();
             }
-        }
-        Task::none()
-    }
-}
+            Message::CancelFavoriteEdit => {
+                self.editing_favorite = None;
+            }
+            Message::FavoritesPage(page) => {
+                self.favorites_page = page;
+            }
+            Message::CycleListLayout => {
+                self.config.list_layout = self.config.list_layout.next();
+                self.write_config();
+            }
+            Message::FavoritesFilterChanged(filter) => {
+                self.favorites_filter = filter;
+                self.favorites_page = 0;
+            }
+            Message::JumpToLetter(letter) => {
+                let stations: Vec<&Station> = self.config.active_favorites().collect();
+                let pages = stations.len().div_ceil(FAVORITES_PAGE_SIZE);
+                for (page, on_page) in stations.chunks(FAVORITES_PAGE_SIZE).enumerate() {
+                    let listed = config::in_list_order(on_page);
+                    let Some(target) = listed.iter().find(|s| s.index_letter() == letter) else {
+                        continue;
+                    };
+                    self.favorites_page = page;
+                    // A collapsed folder has no row to scroll to
+                    self.collapsed_folders.remove(&target.folder);
 
-impl AppModel {
-    fn view_station_row<'a>(&self, station: &'a Station, is_fav: bool) -> Element<'a, Message> {
-        let play_icon = if self.is_playing
-            && self
-                .current_station
+                    let per_line = match (self.config.list_layout, self.popup_viewport) {
+                        // Tiles are 96 wide with 5 between them
+                        (ListLayout::Grid, Some(viewport)) => {
+                            ((viewport.bounds().width + 5.0) / 101.0) as usize
+                        }
+                        _ => 1,
+                    };
+                    let collapsed = |folder: &str| self.collapsed_folders.contains(folder);
+                    let Some((line, lines)) =
+                        config::letter_line(on_page, letter, collapsed, per_line)
+                    else {
+                        break;
+                    };
+                    // Lines around the favorites: the header, filter and
+                    // letter index above, the pager and subscribed lists below
+                    let above = 3;
+                    let below = usize::from(pages > 1)
+                        + self
+                            .config
+                            .subscriptions
+                            .iter()
+                            .filter(|s| !s.stations.is_empty())
+                            .map(|s| 1 + s.stations.len())
+                            .sum::<usize>();
+                    // Lines are about the same height, so the row's share of
+                    // them is close to its share of the content's height
+                    let share = (above + line) as f32 / (above + lines + below) as f32;
+                    return match self.popup_viewport {
+                        Some(viewport) => scrollable::scroll_to(
+                            popup_scroll_id(),
+                            scrollable::AbsoluteOffset {
+                                x: 0.0,
+                                y: share * viewport.content_bounds().height,
+                            },
+                        ),
+                        None => scrollable::snap_to(
+                            popup_scroll_id(),
+                            scrollable::RelativeOffset { x: 0.0, y: share },
+                        ),
+                    };
+                }
+            }
+            Message::SetShowBrokenStations(show) => {
+                self.config.show_broken_stations = show;
+                self.save_config();
+                // Popular lists were fetched with the old filter
+                return self.reload_popular();
+            }
+            Message::SetShowStationStats(show) => {
+                self.config.show_station_stats = show;
+                self.save_config();
+            }
+            Message::SetReportClicks(report) => {
+                self.config.report_clicks = report;
+                self.save_config();
+            }
+            Message::SetResumeOnLogin(resume) => {
+                self.config.resume_on_login = resume;
+                self.save_config();
+            }
+            Message::SetAutoplay(autoplay) => {
+                self.config.autoplay = autoplay;
+                self.save_config();
+            }
+            Message::SetShuffleFavorites(shuffle) => {
+                self.config.shuffle_favorites = shuffle;
+                self.save_config();
+            }
+            Message::SetLoopFavorites(looping) => {
+                self.config.loop_favorites = looping;
+                self.save_config();
+            }
+            Message::SetInhibitIdle(inhibit) => {
+                self.config.inhibit_idle = inhibit;
+                self.save_config();
+            }
+            Message::SetInhibitSleep(inhibit) => {
+                self.config.inhibit_sleep = inhibit;
+                self.save_config();
+            }
+            Message::SetPauseOnOutputLoss(pause) => {
+                self.config.pause_on_output_loss = pause;
+                self.save_config();
+            }
+            Message::SetResumeOnOutputReturn(resume) => {
+                self.config.resume_on_output_return = resume;
+                self.save_config();
+            }
+            Message::DuckModeSelected(index) => {
+                let Some(&mode) = DuckMode::ALL.get(index) else {
+                    return Task::none();
+                };
+                self.config.duck_mode = mode;
+                // A stream turned down comes back up; a stopped one stays so
+                if self.ducked.take() == Some(DuckMode::Lower) && self.is_playing {
+                    self.audio.set_volume(self.playback_volume() as f32);
+                }
+                self.save_config();
+            }
+            Message::DuckVolumeSelected(index) => {
+                let Some(&percent) = duck::VOLUME_CHOICES.get(index) else {
+                    return Task::none();
+                };
+                self.config.duck_volume = percent;
+                if self.ducked == Some(DuckMode::Lower) && self.is_playing {
+                    self.audio.set_volume(self.playback_volume() as f32);
+                }
+                self.save_config();
+            }
+            Message::DuckDelaySelected(index) => {
+                let Some(&seconds) = duck::DELAY_CHOICES.get(index) else {
+                    return Task::none();
+                };
+                self.config.duck_delay_secs = seconds;
+                self.save_config();
+            }
+            Message::SetNotifyStation(notify) => {
+                self.config.notify_station = notify;
+                self.save_config();
+            }
+            Message::SetNotifySong(notify) => {
+                self.config.notify_song = notify;
+                self.save_config();
+            }
+            Message::SetNotifyErrors(notify) => {
+                self.config.notify_errors = notify;
+                self.save_config();
+            }
+            Message::AutoplayStationSelected(index) => {
+                self.config.autoplay_station = index
+                    .checked_sub(1)
+                    .and_then(|index| self.config.active_favorites().nth(index))
+                    .map(|station| station.id.clone());
+                self.save_config();
+            }
+            Message::Autoplay(attempt) => {
+                // Something was started by hand in the meantime
+                if self.is_playing || self.is_connecting {
+                    return Task::none();
+                }
+                if self.waiting_for_network {
+                    self.when_online = Some(Message::Autoplay(0));
+                    return Task::none();
+                }
+                let Some(station) = self.config.autoplay_target().cloned() else {
+                    return Task::none();
+                };
+                if self.audio.is_mock() {
+                    return self.update(Message::PlayStation(station.id));
+                }
+                // A reachable stream doubles as the network check
+                let url = station.stream_url().to_string();
+                return Task::perform(api::check_stream(url), move |res| {
+                    Message::AutoplayChecked(attempt, station.id.clone(), res)
+                })
+                .map(Into::into);
+            }
+            Message::AutoplayChecked(attempt, id, res) => {
+                if self.is_playing || self.is_connecting {
+                    return Task::none();
+                }
+                match res {
+                    Ok(()) => {
+                        info!("Autoplaying {}", id);
+                        return self.update(Message::PlayStation(id));
+                    }
+                    Err(StreamError::Timeout(_) | StreamError::Unreachable(_))
+                        if attempt + 1 < AUTOPLAY_ATTEMPTS =>
+                    {
+                        debug!("Network not ready for autoplay, retrying");
+                        return Task::perform(tokio::time::sleep(AUTOPLAY_DELAY), move |_| {
+                            Message::Autoplay(attempt + 1)
+                        })
+                        .map(Into::into);
+                    }
+                    Err(e) if !e.is_definite() => {
+                        info!("Autoplaying {} despite: {}", id, e);
+                        return self.update(Message::PlayStation(id));
+                    }
+                    Err(e) => {
+                        warn!("Autoplay gave up: {}", e);
+                        self.show_toast(Severity::Error, stream_error_text(&e), None);
+                        if let Some(station) = self.config.autoplay_target() {
+                            return self.notify_stream_error(station, stream_error_text(&e));
+                        }
+                    }
+                }
+            }
+            Message::Reconnect(attempt) => {
+                // Something was started by hand in the meantime
+                if self.is_playing || self.is_connecting {
+                    return Task::none();
+                }
+                if self.waiting_for_network {
+                    self.when_online = Some(Message::Reconnect(0));
+                    return Task::none();
+                }
+                let Some(station) = &self.current_station else {
+                    return Task::none();
+                };
+                if self.audio.is_mock() {
+                    self.resume_playback();
+                    return Task::none();
+                }
+                let url = station.stream_url().to_string();
+                return Task::perform(api::check_stream(url), move |res| {
+                    Message::ReconnectChecked(attempt, res)
+                })
+                .map(Into::into);
+            }
+            Message::ReconnectChecked(attempt, res) => {
+                if self.is_playing || self.is_connecting {
+                    return Task::none();
+                }
+                let Some(station) = self.current_station.clone() else {
+                    return Task::none();
+                };
+                match res {
+                    Ok(()) => {
+                        info!("Reconnected to {}", station.name);
+                        self.resume_playback();
+                    }
+                    Err(StreamError::Timeout(_) | StreamError::Unreachable(_))
+                        if attempt + 1 < AUTOPLAY_ATTEMPTS =>
+                    {
+                        debug!("Network not back yet, retrying");
+                        return Task::perform(tokio::time::sleep(AUTOPLAY_DELAY), move |_| {
+                            Message::Reconnect(attempt + 1)
+                        })
+                        .map(Into::into);
+                    }
+                    Err(e) if !e.is_definite() => {
+                        info!("Reconnecting to {} despite: {}", station.name, e);
+                        self.resume_playback();
+                    }
+                    Err(e) => {
+                        warn!("Not reconnecting to {}: {}", station.name, e);
+                        self.show_toast(
+                            Severity::Error,
+                            stream_error_text(&e),
+                            Some(RetryAction::Playback),
+                        );
+                        return self.notify_stream_error(&station, stream_error_text(&e));
+                    }
+                }
+            }
+            Message::MaxVolumeSelected(index) => {
+                let Some(&max_volume) = MAX_VOLUME_CHOICES.get(index) else {
+                    return Task::none();
+                };
+                self.config.max_volume = max_volume;
+                for player in [&self.audio, &self.preview_audio] {
+                    player.set_max_volume(max_volume);
+                }
+                self.save_config();
+                if self.config.volume > max_volume {
+                    return self.update(Message::VolumeChanged(max_volume as f32));
+                }
+            }
+            Message::SetCacheOnDisk(enabled) => {
+                self.config.cache_on_disk = enabled;
+                api::set_disk_cache(enabled);
+                self.save_config();
+            }
+            Message::ApiBaseUrlInputChanged(val) => {
+                self.api_base_url_input = val;
+                self.api_base_url_error = None;
+            }
+            Message::ApplyApiBaseUrl => match api::set_base_url(&self.api_base_url_input) {
+                Ok(()) => {
+                    self.config.api_base_url = self.api_base_url_input.trim().to_string();
+                    self.api_base_url_error = None;
+                    self.save_config();
+                    // Results from the previous directory no longer apply
+                    return self.reload_popular();
+                }
+                Err(e) => {
+                    self.api_base_url_error = Some(url_error_text(e));
+                }
+            },
+            Message::LocationInputChanged(val) => {
+                self.location_input = val;
+                self.location_error = None;
+            }
+            Message::ApplyLocation => {
+                let location = self.location_input.trim();
+                if !location.is_empty() && geo::parse_coordinates(location).is_none() {
+                    self.location_error = Some(fl!("settings-location-invalid"));
+                    return Task::none();
+                }
+                self.config.location = location.to_string();
+                self.save_config();
+                return self.reload_popular();
+            }
+            Message::PopupSizeSelected(index) => {
+                self.config.popup_size = match PopupSize::PRESETS.get(index) {
+                    Some(&size) => size,
+                    // Custom starts out as the size picked before
+                    None => {
+                        let (width, height) = self.config.popup_size.dimensions();
+                        PopupSize::Custom { width, height }
+                    }
+                };
+                self.reset_popup_size_inputs();
+                self.save_config();
+            }
+            Message::PopupWidthChanged(val) => {
+                self.popup_width_input = val;
+                self.popup_size_error = None;
+            }
+            Message::PopupHeightChanged(val) => {
+                self.popup_height_input = val;
+                self.popup_size_error = None;
+            }
+            Message::ApplyPopupSize => {
+                match PopupSize::parse_custom(&self.popup_width_input, &self.popup_height_input) {
+                    Some(size) => {
+                        self.config.popup_size = size;
+                        self.save_config();
+                    }
+                    None => self.popup_size_error = Some(fl!("settings-popup-size-invalid")),
+                }
+            }
+            Message::ProxyInputChanged(val) => {
+                self.proxy_input = val;
+                self.proxy_error = None;
+            }
+            Message::ApplyProxy => match api::set_proxy(&self.proxy_input) {
+                Ok(()) => {
+                    self.config.proxy_url = self.proxy_input.trim().to_string();
+                    self.proxy_error = None;
+                    // Takes effect for mpv on the next station played
+                    let stream_proxy = api::validate_base_url(&self.config.proxy_url).ok();
+                    self.audio.set_http_proxy(stream_proxy.clone());
+                    self.preview_audio.set_http_proxy(stream_proxy);
+                    self.save_config();
+                }
+                Err(e) => {
+                    self.proxy_error = Some(url_error_text(e));
+                }
+            },
+            Message::ListenBrainzTokenChanged(token) => {
+                self.listenbrainz_token_input = token;
+            }
+            Message::ApplyListenBrainzToken => {
+                self.config.listenbrainz_token = self.listenbrainz_token_input.trim().to_string();
+                self.save_config();
+            }
+            Message::RequestTimeoutSelected(index) => {
+                if let Some(&secs) = TIMEOUT_CHOICES.get(index) {
+                    self.config.request_timeout_secs = secs;
+                    api::set_request_timeout(secs);
+                    self.save_config();
+                }
+            }
+            Message::MinBitrateSelected(index) => {
+                if let Some(&kbps) = MIN_BITRATE_CHOICES.get(index) {
+                    self.config.quality.min_bitrate = kbps;
+                    self.save_config();
+                    return self.reload_quality_filtered();
+                }
+            }
+            Message::PreferredCodecSelected(index) => {
+                if let Some(&codec) = PreferredCodec::ALL.get(index) {
+                    self.config.quality.preferred_codec = codec;
+                    self.save_config();
+                    return self.reload_quality_filtered();
+                }
+            }
+            Message::AudioBackendSelected(index) => {
+                let Some(&backend) = AudioBackend::ALL.get(index) else {
+                    return Task::none();
+                };
+                self.config.audio_backend = backend;
+                self.save_config();
+                self.apply_playback_settings();
+            }
+            Message::LiteModeSelected(index) => {
+                let Some(&mode) = LiteMode::ALL.get(index) else {
+                    return Task::none();
+                };
+                self.config.lite_mode = mode;
+                self.set_lite(mode.is_active(self.low_memory));
+                self.save_config();
+                self.apply_playback_settings();
+            }
+            Message::SubscriptionNameChanged(val) => {
+                self.subscription_name_input = val;
+            }
+            Message::SubscriptionUrlChanged(val) => {
+                self.subscription_url_input = val;
+                self.subscription_error = None;
+            }
+            Message::AddSubscription => {
+                let list_url = match api::validate_base_url(&self.subscription_url_input) {
+                    Ok(url) => url,
+                    Err(e) => {
+                        self.subscription_error = Some(url_error_text(e));
+                        return Task::none();
+                    }
+                };
+                if self.config.subscriptions.iter().any(|s| s.url == list_url) {
+                    self.subscription_error = Some(fl!("subscription-exists"));
+                    return Task::none();
+                }
+                let name = match self.subscription_name_input.trim() {
+                    "" => url::Url::parse(&list_url)
+                        .ok()
+                        .and_then(|u| u.host_str().map(str::to_string))
+                        .unwrap_or_else(|| list_url.clone()),
+                    name => name.to_string(),
+                };
+                info!("Subscribed to '{}' at {}", name, list_url);
+                self.config.subscriptions.push(config::Subscription {
+                    name,
+                    url: list_url.clone(),
+                    ..Default::default()
+                });
+                self.subscription_name_input.clear();
+                self.subscription_url_input.clear();
+                self.save_config();
+                return fetch_subscription(list_url);
+            }
+            Message::RemoveSubscription(index) => {
+                if index < self.config.subscriptions.len() {
+                    let removed = self.config.subscriptions.remove(index);
+                    debug!("Unsubscribed from '{}'", removed.name);
+                    self.save_config();
+                }
+            }
+            Message::RefreshSubscriptions => {
+                return self.refresh_subscriptions();
+            }
+            Message::SubscriptionFetched(url, result) => {
+                let Some(subscription) =
+                    self.config.subscriptions.iter_mut().find(|s| s.url == url)
+                else {
+                    return Task::none();
+                };
+                match result {
+                    Ok(stations) => {
+                        let first_refresh = subscription.last_updated == 0;
+                        let changes = subscription.update(stations, journal::now());
+                        debug!(
+                            "Subscription '{}' refreshed: {} stations, {} changes",
+                            subscription.name,
+                            subscription.stations.len(),
+                            changes.len()
+                        );
+                        if !first_refresh && !changes.is_empty() {
+                            self.show_toast(
+                                Severity::Info,
+                                fl!(
+                                    "subscription-updated",
+                                    name = subscription.name.clone(),
+                                    count = changes.len()
+                                ),
+                                None,
+                            );
+                        }
+                        self.save_config();
+                    }
+                    Err(e) => warn!("Refreshing subscription {} failed: {}", url, e),
+                }
+            }
+            Message::ShareStation(id) => {
+                if let Some(station) = self.stations.get(&id) {
+                    self.share = Some(ShareDialog::new(station.clone(), false));
+                }
+            }
+            Message::SetShareHomepage(homepage) => {
+                if let Some(share) = self.share.take() {
+                    self.share = Some(ShareDialog::new(share.station, homepage));
+                }
+            }
+            Message::CloseShare => {
+                self.share = None;
+            }
+            Message::HideStation(id) => {
+                let Some(station) = self.stations.get(&id).cloned() else {
+                    return Task::none();
+                };
+                if self.config.hide(&station) {
+                    info!("Hiding station {}", station.name);
+                    for list in [
+                        &mut self.search_results,
+                        &mut self.popular_voted,
+                        &mut self.popular_clicked,
+                        &mut self.popular_nearby,
+                    ] {
+                        list.retain(|s| !s.is_same(&station));
+                    }
+                    self.search_groups = dedupe::group(&self.search_results);
+                    self.save_config();
+                }
+            }
+            Message::UnhideStation(stationuuid) => {
+                self.config.unhide(&stationuuid);
+                self.save_config();
+            }
+            Message::DismissToast => {
+                self.toast = None;
+            }
+            Message::ToastTick => {
+                if self
+                    .toast
+                    .as_ref()
+                    .is_some_and(|t| t.expires.is_some_and(|at| at <= Instant::now()))
+                {
+                    self.toast = None;
+                }
+            }
+            Message::RevertJournalEntry(index) => {
+                let Some(entry) = self.config.favorites_journal.get(index).cloned() else {
+                    return Task::none();
+                };
+                if entry.revert(&mut self.config.favorites) {
+                    info!("Reverted journal entry for {}", entry.station_name());
+                    self.save_config();
+                }
+            }
+            Message::HistoryLimitSelected(index) => {
+                if let Some(&entries) = history::MAX_ENTRIES_CHOICES.get(index) {
+                    self.config.history_max_entries = entries;
+                    self.config.history.truncate(entries);
+                    self.save_config();
+                }
+            }
+            Message::LeftClickSelected(index) => {
+                if let Some(&action) = ClickAction::LEFT.get(index) {
+                    self.config.left_click = action;
+                    self.save_config();
+                }
+            }
+            Message::MiddleClickSelected(index) => {
+                if let Some(&action) = ClickAction::MIDDLE.get(index) {
+                    self.config.middle_click = action;
+                    self.save_config();
+                }
+            }
+            Message::SetScrollVolume(enabled) => {
+                self.config.scroll_volume = enabled;
+                self.save_config();
+            }
+            Message::ScrollStepSelected(index) => {
+                if let Some(&step) = panel::SCROLL_STEP_CHOICES.get(index) {
+                    self.config.scroll_volume_step = step;
+                    self.save_config();
+                }
+            }
+            Message::PanelTextSelected(index) => {
+                if let Some(&text) = PanelText::ALL.get(index) {
+                    self.config.panel_text = text;
+                    self.save_config();
+                }
+            }
+            Message::PanelTextWidthSelected(index) => {
+                if let Some(&width) = panel::TEXT_WIDTH_CHOICES.get(index) {
+                    self.config.panel_text_width = width;
+                    self.save_config();
+                }
+            }
+            Message::SetPanelTextScroll(enabled) => {
+                self.config.panel_text_scroll = enabled;
+                self.marquee_offset = 0;
+                self.save_config();
+            }
+            Message::MarqueeTick => {
+                self.marquee_offset = self.marquee_offset.wrapping_add(1);
+            }
+            Message::ProfileSelected(index) => {
+                let Some(name) = self
+                    .config
+                    .profile_names()
+                    .get(index)
+                    .map(|n| n.to_string())
+                else {
+                    return Task::none();
+                };
+                if self.config.switch_profile(&name) {
+                    info!("Switched to profile '{}'", name);
+                    return self.profile_switched();
+                }
+            }
+            Message::ProfileNameChanged(val) => {
+                self.profile_name_input = val;
+            }
+            Message::AddProfile => {
+                if self.config.add_profile(&self.profile_name_input) {
+                    self.profile_name_input.clear();
+                    self.profile_labels = profile_labels(&self.config);
+                    self.save_config();
+                }
+            }
+            Message::RemoveProfile(name) => {
+                if self.config.remove_profile(&name) {
+                    info!("Removed profile '{}'", name);
+                    self.profile_labels = profile_labels(&self.config);
+                    self.save_config();
+                }
+            }
+            Message::SyncFileInputChanged(val) => {
+                self.sync_file_input = val;
+                self.sync_file_error = None;
+            }
+            Message::ApplySyncFile => {
+                let path = self.sync_file_input.trim();
+                let usable = Path::new(path).is_absolute()
+                    && Path::new(path).parent().is_some_and(Path::is_dir);
+                if !path.is_empty() && !usable {
+                    self.sync_file_error = Some(fl!("settings-sync-file-invalid"));
+                    return Task::none();
+                }
+                if path != self.config.sync_file {
+                    // Merging against another file's history would drop stations
+                    self.config.sync_file = path.to_string();
+                    self.config.sync_base.clear();
+                    self.reset_sync();
+                    self.save_config();
+                }
+                return self.update(Message::SyncFileTick);
+            }
+            Message::SyncFileTick => {
+                if !self.syncing() {
+                    return Task::none();
+                }
+                let path = PathBuf::from(&self.config.sync_file);
+                let seen = self.sync_seen;
+                let generation = self.sync_generation;
+                return Task::perform(
+                    async move { sync::read(&path, seen).map_err(Arc::new) },
+                    move |res| Message::SyncFileRead(generation, res),
+                )
+                .map(Into::into);
+            }
+            Message::SyncFileRead(generation, _) if generation != self.sync_generation => {}
+            Message::SyncFileRead(_, res) => match res {
+                Ok(SyncRead::Missing) => {
+                    self.sync_ready = true;
+                    self.write_sync_file();
+                    self.save_config();
+                }
+                Ok(SyncRead::Unchanged) => {}
+                Ok(SyncRead::Changed { modified, text }) => {
+                    self.sync_seen = Some(modified);
+                    let format = SyncFormat::for_path(Path::new(&self.config.sync_file));
+                    match sync::parse(format, &text) {
+                        Ok(remote) => {
+                            if sync::merge(
+                                &mut self.config.favorites,
+                                &remote,
+                                &self.config.sync_base,
+                                format,
+                            ) {
+                                info!("Merged favorites from {}", self.config.sync_file);
+                            }
+                            self.config.sync_base = remote;
+                            self.sync_written = text;
+                            self.sync_ready = true;
+                            self.sync_file_error = None;
+                            // Local additions the file doesn't have yet
+                            self.write_sync_file();
+                            self.save_config();
+                        }
+                        // Left alone until it changes again
+                        Err(e) => {
+                            warn!("Ignoring sync file {}: {}", self.config.sync_file, e);
+                            self.sync_file_error = Some(fl!("sync-file-malformed"));
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!("Reading sync file {} failed: {}", self.config.sync_file, e);
+                    self.sync_file_error = Some(io_error_text(&e));
+                }
+            },
+            Message::ClearHistory => {
+                self.config.history.clear();
+                self.history_playing = None;
+                self.save_config();
+            }
+            Message::TabActivated(entity) => {
+                self.tabs.activate(entity);
+                self.revealed_row = None;
+                self.popup_viewport = None;
+                self.config.last_tab = self.active_tab();
+                self.write_config();
+                return self.restore_scroll();
+            }
+            Message::PopupScrolled(viewport) => {
+                self.scroll_offsets
+                    .insert(self.active_tab(), viewport.relative_offset());
+                self.popup_viewport = Some(viewport);
+            }
+            Message::NearbyLoaded(res) => {
+                self.track_network(&res);
+                match res {
+                    Ok(stations) => {
+                        debug!("Nearby stations loaded: {}", stations.len());
+                        self.popular_nearby = self.visible(stations);
+                        self.sync_stations();
+                    }
+                    // The global lists are still useful, so no error is shown
+                    Err(e) => warn!("Loading nearby stations failed: {}", e),
+                }
+            }
+            Message::PopularLoaded(res) => {
+                self.is_loading_popular = false;
+                self.track_network(&res);
+                match res {
+                    Ok((voted, clicked)) => {
+                        debug!(
+                            "Popular stations loaded: {} voted, {} clicked",
+                            voted.len(),
+                            clicked.len()
+                        );
+                        self.popular_voted = self.visible(voted);
+                        self.popular_clicked = self.visible(clicked);
+                        self.sync_stations();
+                    }
+                    Err(e) => {
+                        error!("Loading popular stations failed: {}", e);
+                        self.show_toast(
+                            Severity::Error,
+                            api_error_text(&e),
+                            Some(RetryAction::Browse),
+                        );
+                    }
+                }
+            }
+            Message::ToggleFavorite(id) => {
+                if let Some(pos) = self.favorites_index.position_of_id(&id) {
+                    let removed = self.config.favorites.remove(pos);
+                    debug!("Removed from favorites: {}", removed.name);
+                    self.show_toast(
+                        Severity::Info,
+                        fl!("favorite-removed", name = removed.display_name()),
+                        None,
+                    );
+                } else if let Some(station) = self.stations.get(&id) {
+                    let mut station = station.clone();
+                    station.ensure_uuid();
+                    station.ensure_id();
+                    debug!("Added to favorites: {} ({})", station.name, station.id);
+                    let folders = self.config.favorite_folders();
+                    self.folder_picker = (!folders.is_empty()).then(|| FolderPicker {
+                        station: station.id.clone(),
+                        station_name: station.name.clone(),
+                        options: std::iter::once(fl!("folder-none"))
+                            .chain(folders.iter().map(|folder| folder.to_string()))
+                            .collect(),
+                    });
+                    self.show_toast(
+                        Severity::Info,
+                        fl!("favorite-added", name = station.display_name()),
+                        None,
+                    );
+                    self.config.favorites.push(station);
+                } else {
+                    warn!("Ignoring favorite toggle for unknown station {}", id);
+                    return Task::none();
+                }
+                self.save_config();
+            }
+            Message::VolumeChanged(vol) => {
+                let volume = (vol.round() as u8).min(self.config.max_volume);
+                self.config.volume = volume;
+                self.audio.set_volume(self.playback_volume() as f32);
+                debug!("Volume changed to {}%", volume);
+                self.publish_playback_state();
+                return self.save_volume();
+            }
+            Message::VolumeUp => {
+                let new_vol = self
+                    .config
+                    .volume
+                    .saturating_add(5)
+                    .min(self.config.max_volume);
+                self.config.volume = new_vol;
+                self.audio.set_volume(self.playback_volume() as f32);
+                debug!("Volume up to {}%", new_vol);
+                self.publish_playback_state();
+                return self.save_volume();
+            }
+            Message::VolumeDown => {
+                let new_vol = (self.config.volume as i16 - 5).max(0) as u8;
+                self.config.volume = new_vol;
+                self.audio.set_volume(self.playback_volume() as f32);
+                debug!("Volume down to {}%", new_vol);
+                self.publish_playback_state();
+                return self.save_volume();
+            }
+            Message::SaveVolume(generation) => {
+                if generation == self.volume_save_generation {
+                    self.volume_save_pending = false;
+                    self.write_volume();
+                }
+            }
+            Message::TogglePlayPause => {
+                if self.is_playing || self.is_connecting {
+                    self.stop_playback();
+                } else {
+                    self.resume_playback();
+                }
+            }
+            Message::SkipFavorite(forward) => {
+                let current = self.current_station.as_ref().map(|s| &s.id);
+                if let Some(station) = self.config.skip_favorite(current, forward, random_seed()) {
+                    return self.update(Message::PlayStation(station.id.clone()));
+                }
+            }
+            Message::MprisEvent(event) => match event {
+                mpris::MprisEvent::Ready(tx) => {
+                    info!("MPRIS server ready");
+                    self.events.connect(tx);
+                    self.publish_playback_state();
+                    self.publish_station_groups();
+                }
+                mpris::MprisEvent::Command(cmd) => match cmd {
+                    mpris::MprisCommand::Play => {
+                        if !(self.is_playing || self.is_connecting) {
+                            debug!("MPRIS: Play");
+                            self.resume_playback();
+                        }
+                    }
+                    mpris::MprisCommand::Pause | mpris::MprisCommand::Stop => {
+                        if self.is_playing || self.is_connecting {
+                            debug!("MPRIS: Stop");
+                            self.stop_playback();
+                        }
+                    }
+                    mpris::MprisCommand::PlayPause => {
+                        return self.update(Message::TogglePlayPause);
+                    }
+                    mpris::MprisCommand::Next => {
+                        return self.update(Message::SkipFavorite(true));
+                    }
+                    mpris::MprisCommand::Previous => {
+                        return self.update(Message::SkipFavorite(false));
+                    }
+                    mpris::MprisCommand::SetShuffle(shuffle) => {
+                        debug!("MPRIS: Shuffle {}", shuffle);
+                        return self.update(Message::SetShuffleFavorites(shuffle));
+                    }
+                    mpris::MprisCommand::SetLooping(looping) => {
+                        debug!("MPRIS: Loop favorites {}", looping);
+                        return self.update(Message::SetLoopFavorites(looping));
+                    }
+                    mpris::MprisCommand::SetVolume(vol) => {
+                        let volume = mpris::volume_from_mpris(vol).min(self.config.max_volume);
+                        self.config.volume = volume;
+                        self.audio.set_volume(self.playback_volume() as f32);
+                        debug!("MPRIS: Volume set to {}%", volume);
+                        self.publish_playback_state();
+                        return self.save_volume();
+                    }
+                    mpris::MprisCommand::Raise => {
+                        if self.popup.is_none() {
+                            return self.show_popup();
+                        }
+                    }
+                    mpris::MprisCommand::Quit => {
+                        // The applet lives in the panel, so Quit only silences it
+                        // and takes its MPRIS server off the bus.
+                        // Nothing is written in the background (no recordings or
+                        // downloads), so there are no files to finalize here.
+                        info!("MPRIS: Quit");
+                        self.stop_preview();
+                        if self.is_playing || self.is_connecting {
+                            self.stop_playback();
+                        }
+                        self.events.publish(AppEvent::Shutdown);
+                        self.mpris_quit = true;
+                    }
+                    mpris::MprisCommand::OpenUri(uri) => {
+                        if deeplink::is_deep_link(&uri) {
+                            return self.open_deep_link(&uri);
+                        }
+                        return self.open_stream_uri(&uri);
+                    }
+                    mpris::MprisCommand::ActivatePlaylist(group) => {
+                        let Some(station) = PlaylistSource::from_id(&group)
+                            .and_then(|source| source.first_station(&self.config))
+                        else {
+                            return Task::none();
+                        };
+                        let already_playing = (self.is_playing || self.is_connecting)
+                            && self
+                                .current_station
+                                .as_ref()
+                                .is_some_and(|s| s.is_same(station));
+                        if !already_playing {
+                            return self.update(Message::PlayStation(station.id.clone()));
+                        }
+                    }
+                },
+            },
+            Message::MockTrackTick => return self.announce_mock_track(),
+            Message::BufferTick => {
+                // Asking mpv can take up to its IPC timeout; keep it off the UI thread
+                let audio = Arc::clone(&self.audio);
+                let generation = self.stream_check_generation;
+                return Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || {
+                            (audio.buffer_state(), audio.stream_title())
+                        })
+                        .await
+                        .unwrap_or_default()
+                    },
+                    move |(state, title)| Message::BufferPolled(generation, state, title),
+                )
+                .map(Into::into);
+            }
+            Message::BufferPolled(generation, state, title) => {
+                // Playback stopped, or another station started, while the
+                // player was being asked
+                if !self.is_playing || generation != self.stream_check_generation {
+                    return Task::none();
+                }
+                self.buffer_state = state;
+                if let Some(title) = title {
+                    return self.announce_track(title);
+                }
+            }
+            Message::NotifyEvent(event) => match event {
+                NotifyEvent::Ready(connection) => self.notify_connection = Some(connection),
+                NotifyEvent::ScreenReader(running) => self.screen_reader = running,
+                NotifyEvent::Action(id, action) => {
+                    // Buttons on notifications since replaced no longer apply
+                    if id != self.notification_id {
+                        return Task::none();
+                    }
+                    match action {
+                        NotificationAction::Stop => {
+                            if self.is_playing || self.is_connecting {
+                                self.stop_playback();
+                            }
+                        }
+                        NotificationAction::Next => {
+                            return self.update(Message::SkipFavorite(true));
+                        }
+                    }
+                }
+            },
+            Message::Sleep(SleepEvent::Suspending(ready)) => {
+                if self.is_playing || self.is_connecting {
+                    info!("Stopping playback for suspend");
+                    self.stop_playback();
+                    self.resume_after_sleep = true;
+                }
+                ready.done();
+            }
+            Message::Sleep(SleepEvent::Resumed) => {
+                if std::mem::take(&mut self.resume_after_sleep) {
+                    return Task::perform(tokio::time::sleep(AUTOPLAY_DELAY), |_| {
+                        Message::Reconnect(0)
+                    })
+                    .map(Into::into);
+                }
+            }
+            Message::Network(NetworkEvent::Offline) => {
+                self.waiting_for_network = true;
+                self.set_online(false);
+                if self.is_playing || self.is_connecting {
+                    info!("Network lost, stopping playback until it is back");
+                    self.stop_playback();
+                    self.when_online = Some(Message::Reconnect(0));
+                    self.show_toast(Severity::Warning, fl!("network-waiting"), None);
+                }
+            }
+            Message::Network(NetworkEvent::Online) => {
+                self.waiting_for_network = false;
+                self.set_online(true);
+                if let Some(message) = self.when_online.take() {
+                    return self.update(message);
+                }
+            }
+            Message::Output(OutputEvent::Disconnected) => {
+                if self.is_playing || self.is_connecting {
+                    info!("Audio output disconnected, stopping playback");
+                    self.stop_playback();
+                    self.paused_for_output = true;
+                    self.show_toast(Severity::Info, fl!("output-disconnected"), None);
+                }
+            }
+            Message::Output(OutputEvent::Reconnected) => {
+                if std::mem::take(&mut self.paused_for_output)
+                    && self.config.resume_on_output_return
+                    && !(self.is_playing || self.is_connecting)
+                {
+                    info!("Audio output is back, resuming playback");
+                    self.resume_playback();
+                }
+            }
+            Message::Duck(DuckEvent::Started) => {
+                if !(self.is_playing || self.is_connecting) {
+                    return Task::none();
+                }
+                match self.config.duck_mode {
+                    DuckMode::Off => {}
+                    DuckMode::Lower => {
+                        info!("Other audio playing, lowering the volume");
+                        self.ducked = Some(DuckMode::Lower);
+                        self.audio.set_volume(self.playback_volume() as f32);
+                    }
+                    DuckMode::Pause => {
+                        info!("Other audio playing, stopping playback");
+                        self.stop_playback();
+                        self.ducked = Some(DuckMode::Pause);
+                        self.show_toast(Severity::Info, fl!("duck-paused"), None);
+                    }
+                }
+            }
+            Message::Duck(DuckEvent::Stopped) => match self.ducked.take() {
+                Some(DuckMode::Lower) if self.is_playing => {
+                    info!("Other audio stopped, restoring the volume");
+                    self.audio.set_volume(self.playback_volume() as f32);
+                }
+                Some(DuckMode::Pause) if !(self.is_playing || self.is_connecting) => {
+                    info!("Other audio stopped, resuming playback");
+                    self.resume_playback();
+                }
+                _ => {}
+            },
+            Message::Scrobble(ScrobbleEvent::Ready(tx)) => {
+                info!("Scrobbling to ListenBrainz");
+                self.events.connect(tx);
+            }
+            Message::NotificationShown(result) => match result {
+                Ok(id) => self.notification_id = id,
+                Err(e) => warn!("Could not show notification: {}", e),
+            },
+            Message::DeepLinkResolved(uuid, result) => match result {
+                Ok(stations) => match stations.into_iter().next() {
+                    Some(station) => return self.play_linked(station),
+                    None => {
+                        warn!("Deep link: no station with uuid {}", uuid);
+                        self.show_toast(Severity::Warning, fl!("deeplink-not-found"), None);
+                    }
+                },
+                Err(e) => {
+                    warn!("Deep link: looking up {} failed: {}", uuid, e);
+                    self.show_toast(Severity::Error, api_error_text(&e), None);
+                }
+            },
+            Message::KeyboardEvent(event) => {
+                if let Event::Keyboard(keyboard::Event::KeyPressed { key, .. }) = event {
+                    if key == Key::Named(Named::Tab) && self.config.row_actions_on_hover {
+                        self.keyboard_focus = true;
+                    }
+                    let Some(action) = shortcut_key(&key).and_then(shortcuts::action_for) else {
+                        return Task::none();
+                    };
+                    match action {
+                        ShortcutAction::PlayPause => {
+                            return self.update(Message::TogglePlayPause);
+                        }
+                        ShortcutAction::VolumeUp => {
+                            return self.update(Message::VolumeUp);
+                        }
+                        ShortcutAction::VolumeDown => {
+                            return self.update(Message::VolumeDown);
+                        }
+                        ShortcutAction::Close => {
+                            // Close the overlay or share dialog before the popup itself
+                            if std::mem::take(&mut self.show_shortcuts) {
+                                return Task::none();
+                            }
+                            if self.share.take().is_some() {
+                                return Task::none();
+                            }
+                            if let Some(p) = self.popup.take() {
+                                return destroy_popup(p);
+                            }
+                        }
+                        ShortcutAction::ToggleHelp => {
+                            return self.update(Message::ToggleShortcutHelp);
+                        }
+                    }
+                }
+            }
+            Message::ToggleShortcutHelp => {
+                self.show_shortcuts = !self.show_shortcuts;
+            }
+        }
+        Task::none()
+    }
+}
+
+impl AppModel {
+    /// Search box with the field and provider pickers, fixed above the
+    /// Search tab's results
+    fn view_search_bar(&self) -> Element<'_, Message> {
+        let search_input = text_input(&fl!("search-placeholder"), &self.search_query)
+            .on_input(Message::SearchInputChanged)
+            .on_submit(Message::PerformSearch)
+            .padding(10);
+
+        let search_btn = cosmic::iced::widget::button(widget::text(fl!("search-button")))
+            .on_press(Message::PerformSearch);
+
+        let search_field = widget::dropdown(
+            &self.search_field_labels,
+            SearchField::ALL
+                .iter()
+                .position(|f| *f == self.config.search_field),
+            Message::SearchFieldSelected,
+        );
+
+        let mut search_row = widget::row().spacing(10).align_y(Alignment::Center);
+        // Only offer a choice once a station list has been subscribed to
+        if self.providers.len() > 1 {
+            search_row = search_row.push(widget::dropdown(
+                &self.provider_labels,
+                Some(self.search_provider),
+                Message::SearchProviderSelected,
+            ));
+        }
+        let search_row = search_row
+            .push(search_field)
+            .push(search_input)
+            .push(search_btn);
+
+        search_row.into()
+    }
+
+    /// The configured genres as chips that search their tag right away
+    fn view_genre_chips(&self) -> Element<'_, Message> {
+        let chips: Vec<Element<'_, Message>> = self
+            .config
+            .genre_chips
+            .iter()
+            .map(|tag| {
+                cosmic::iced::widget::button(widget::text(genre_label(tag)).size(12))
+                    .on_press(Message::SearchGenre(tag.clone()))
+                    .into()
+            })
+            .collect();
+        widget::flex_row(chips)
+            .row_spacing(5)
+            .column_spacing(5)
+            .into()
+    }
+
+    /// Country, language and bitrate dropdowns with the popular tags as
+    /// chips below
+    fn view_search_filters(&self) -> Element<'_, Message> {
+        let filters = &self.config.search_filters;
+        let country = self
+            .filter_countries
+            .iter()
+            .position(|f| f.code.as_deref() == Some(filters.country.as_str()))
+            .map_or(0, |i| i + 1);
+        let language = self
+            .filter_languages
+            .iter()
+            .position(|f| f.name == filters.language)
+            .map_or(0, |i| i + 1);
+        let mut dropdowns = widget::row()
+            .spacing(5)
+            .align_y(Alignment::Center)
+            .push(widget::dropdown(
+                &self.country_labels,
+                Some(country),
+                Message::FilterCountrySelected,
+            ))
+            .push(widget::dropdown(
+                &self.language_labels,
+                Some(language),
+                Message::FilterLanguageSelected,
+            ))
+            .push(widget::dropdown(
+                &self.min_bitrate_labels,
+                MIN_BITRATE_CHOICES
+                    .iter()
+                    .position(|&kbps| kbps == self.config.quality.min_bitrate),
+                Message::MinBitrateSelected,
+            ));
+        if filters.is_active() {
+            dropdowns = dropdowns.push(
+                cosmic::iced::widget::button(widget::text(fl!("filter-clear")).size(12))
+                    .on_press(Message::ClearFilters),
+            );
+        }
+
+        // Selected tags stay visible even if they aren't among the popular ones
+        let mut tags: Vec<&str> = self.filter_tags.iter().map(|f| f.name.as_str()).collect();
+        for tag in &filters.tags {
+            if !tags.contains(&tag.as_str()) {
+                tags.push(tag);
+            }
+        }
+        let chips: Vec<Element<'_, Message>> = tags
+            .into_iter()
+            .map(|tag| {
+                let selected = filters.tags.iter().any(|t| t == tag);
+                let mut chip = widget::row().spacing(3).align_y(Alignment::Center);
+                if selected {
+                    chip = chip.push(icon::from_name("object-select-symbolic").size(12));
+                }
+                cosmic::iced::widget::button(chip.push(widget::text(chip_label(tag)).size(12)))
+                    .on_press(Message::ToggleFilterTag(tag.to_string()))
+                    .into()
+            })
+            .collect();
+
+        widget::column()
+            .spacing(5)
+            .push(dropdowns)
+            .push(widget::flex_row(chips).row_spacing(5).column_spacing(5))
+            .into()
+    }
+
+    fn view_favorites(&self) -> Element<'_, Message> {
+        let manage_label = if self.managing_favorites {
+            fl!("manage-done")
+        } else {
+            fl!("manage-favorites")
+        };
+        let mut list = widget::column().spacing(5).push(
+            widget::row()
+                .spacing(10)
+                .align_y(Alignment::Center)
+                .push(
+                    widget::text(fl!("favorites-header"))
+                        .size(18)
+                        .width(Length::Fill),
+                )
+                .push(
+                    icon_button("list-add-symbolic", fl!("a11y-add-station"))
+                        .on_press(Message::ToggleAddStation),
+                )
+                .push(
+                    cosmic::iced::widget::button(widget::text(manage_label)).on_press_maybe(
+                        (!self.config.favorites.is_empty())
+                            .then_some(Message::ToggleManageFavorites),
+                    ),
+                ),
+        );
+        if self.adding_station {
+            list = list.push(self.view_add_station());
+        }
+        if self.config.active_favorites().next().is_none() {
+            list = list.push(self.view_suggestions());
+        }
+        if self.managing_favorites {
+            list = list.push(self.view_manage_favorites());
+        } else {
+            let count = self.config.active_favorites().count();
+            if count >= FAVORITES_FILTER_MIN {
+                list = list.push(
+                    text_input(&fl!("favorites-filter-placeholder"), &self.favorites_filter)
+                        .on_input(Message::FavoritesFilterChanged),
+                );
+            }
+            let filter = self.favorites_filter.trim();
+            let shown: Vec<&Station> = self
+                .config
+                .active_favorites()
+                .filter(|s| filter.is_empty() || s.matches_filter(filter))
+                .collect();
+            if filter.is_empty() && count >= FAVORITES_FILTER_MIN {
+                list = list.push(self.view_letter_index());
+            } else if shown.is_empty() {
+                list = list.push(widget::text(fl!("favorites-filter-empty")).size(12));
+            }
+            let (page, pages) = self.favorites_page(shown.len());
+            let on_page: Vec<&Station> = shown
+                .into_iter()
+                .skip(page * FAVORITES_PAGE_SIZE)
+                .take(FAVORITES_PAGE_SIZE)
+                .collect();
+            list = list.push(self.view_favorite_folders(&on_page));
+            if pages > 1 {
+                list = list.push(self.view_favorites_pager(page, pages));
+            }
+        }
+        // Subscribed lists are read-only folders below the user's own
+        for subscription in &self.config.subscriptions {
+            if subscription.stations.is_empty() {
+                continue;
+            }
+            list = list.push(widget::text(subscription.name.clone()).size(18));
+            for station in &subscription.stations {
+                list = list.push(self.view_station_row(station, self.is_favorite(station), false));
+            }
+        }
+        list.into()
+    }
+
+    /// A button per letter the favorites' names start with
+    fn view_letter_index(&self) -> Element<'_, Message> {
+        let mut letters: Vec<char> = self
+            .config
+            .active_favorites()
+            .map(Station::index_letter)
+            .collect();
+        letters.sort_unstable();
+        letters.dedup();
+        let buttons: Vec<Element<'_, Message>> = letters
+            .into_iter()
+            .map(|letter| {
+                cosmic::iced::widget::button(widget::text(letter.to_string()).size(11))
+                    .padding([2, 6])
+                    .on_press(Message::JumpToLetter(letter))
+                    .into()
+            })
+            .collect();
+        widget::flex_row(buttons)
+            .row_spacing(2)
+            .column_spacing(2)
+            .into()
+    }
+
+    /// First-run help in place of an empty favorites list: genre shortcuts
+    /// and a few popular stations, nearby ones when the location is known
+    fn view_suggestions(&self) -> Element<'_, Message> {
+        let genres: Vec<Element<'_, Message>> = SUGGESTED_GENRES
+            .iter()
+            .map(|&tag| {
+                cosmic::iced::widget::button(widget::text(genre_label(tag)).size(12))
+                    .on_press(Message::SearchGenre(tag.to_string()))
+                    .into()
+            })
+            .collect();
+        let mut list = widget::column()
+            .spacing(5)
+            .push(widget::text(fl!("no-favorites")))
+            .push(widget::text(fl!("suggestions-genres")).size(14))
+            .push(widget::flex_row(genres).row_spacing(5).column_spacing(5));
+
+        let (header, stations) = if self.popular_nearby.is_empty() {
+            (fl!("suggestions-popular"), &self.popular_voted)
+        } else {
+            (fl!("suggestions-nearby"), &self.popular_nearby)
+        };
+        if self.is_loading_popular {
+            list = list.push(widget::text(fl!("loading-popular")).size(12));
+        } else if !stations.is_empty() {
+            list = list.push(widget::text(header).size(14));
+            for station in stations.iter().take(SUGGESTED_STATIONS) {
+                list = list.push(self.view_station_row(station, self.is_favorite(station), true));
+            }
+        }
+        list.into()
+    }
+
+    fn view_search(&self) -> Element<'_, Message> {
+        let mut list = widget::column().spacing(5);
+        if self.is_searching {
+            let status = match &self.search_progress {
+                None | Some(RequestProgress::Mirror { index: 0, .. }) => fl!("searching-status"),
+                Some(RequestProgress::Mirror { host, .. }) => {
+                    fl!("searching-next-mirror", host = host.clone())
+                }
+                Some(RequestProgress::Retry { host, attempt }) => fl!(
+                    "searching-retry",
+                    host = host.clone(),
+                    attempt = attempt,
+                    max = api::MAX_ATTEMPTS
+                ),
+            };
+            list = list.push(
+                widget::row()
+                    .spacing(10)
+                    .align_y(Alignment::Center)
+                    .push(icon::from_name("process-working-symbolic").size(16))
+                    .push(widget::text(status).size(12).width(Length::Fill))
+                    .push(
+                        cosmic::iced::widget::button(widget::text(fl!("cancel-search")))
+                            .on_press(Message::CancelSearch),
+                    ),
+            );
+            let compact = self.config.list_layout == ListLayout::Compact;
+            for _ in 0..SKELETON_ROWS {
+                list = list.push(view_skeleton_row(compact));
+            }
+            return list.into();
+        }
+        if self.search_query.is_empty() && self.search_results.is_empty() {
+            if self.config.saved_searches.is_empty() {
+                list = list.push(widget::text(fl!("search-hint")).size(12));
+            } else {
+                list = list.push(self.view_saved_searches());
+            }
+            return list.into();
+        }
+
+        let save_row = widget::row()
+            .spacing(5)
+            .align_y(Alignment::Center)
+            .push(
+                text_input(&fl!("save-search-placeholder"), &self.save_search_name)
+                    .on_input(Message::SaveSearchNameChanged)
+                    .on_submit(Message::SaveCurrentSearch)
+                    .width(Length::Fill),
+            )
+            .push(
+                cosmic::iced::widget::button(widget::text(fl!("save-search"))).on_press_maybe(
+                    (!self.search_query.trim().is_empty()).then_some(Message::SaveCurrentSearch),
+                ),
+            )
+            .push(
+                cosmic::iced::widget::button(widget::text(fl!("clear-search")))
+                    .on_press(Message::ClearSearch),
+            );
+
+        list = list
+            .push(save_row)
+            .push(widget::text(fl!("search-results-header")).size(18));
+        if let Some(filter) = self.quality_filter_text() {
+            list = list.push(widget::text(filter).size(11));
+        }
+        for group in &self.search_groups {
+            let station = &self.search_results[group.primary];
+            list = list.push(self.view_station_row(station, self.is_favorite(station), true));
+            if group.variants.is_empty() {
+                continue;
+            }
+            let expanded = self.expanded_groups.contains(&station.id);
+            list = list.push(
+                cosmic::iced::widget::button(
+                    widget::row()
+                        .spacing(5)
+                        .align_y(Alignment::Center)
+                        .push(icon::from_name(if expanded {
+                            "go-down-symbolic"
+                        } else {
+                            "go-next-symbolic"
+                        }))
+                        .push(
+                            widget::text(fl!("station-variants", count = group.variants.len()))
+                                .size(12),
+                        ),
+                )
+                .on_press(Message::ToggleVariants(station.id.clone())),
+            );
+            if expanded {
+                let mut variants = widget::column().spacing(5).padding([0, 0, 0, 20]);
+                for &index in &group.variants {
+                    let variant = &self.search_results[index];
+                    variants = variants.push(self.view_station_row(
+                        variant,
+                        self.is_favorite(variant),
+                        true,
+                    ));
+                }
+                list = list.push(variants);
+            }
+        }
+        list.into()
+    }
+
+    fn view_browse(&self) -> Element<'_, Message> {
+        let mut list = widget::column().spacing(5);
+        if self.is_loading_popular {
+            list = list.push(widget::text(fl!("loading-popular")));
+        }
+        if let Some(filter) = self.quality_filter_text() {
+            list = list.push(widget::text(filter).size(11));
+        }
+        for (header, stations) in [
+            (fl!("popular-nearby"), &self.popular_nearby),
+            (fl!("popular-top-voted"), &self.popular_voted),
+            (fl!("popular-top-clicked"), &self.popular_clicked),
+        ] {
+            if stations.is_empty() {
+                continue;
+            }
+            list = list.push(widget::text(header).size(18));
+            for station in stations {
+                list = list.push(self.view_station_row(station, self.is_favorite(station), true));
+            }
+        }
+        list.into()
+    }
+
+    fn view_station_row<'a>(
+        &self,
+        station: &'a Station,
+        is_fav: bool,
+        with_preview: bool,
+    ) -> Element<'a, Message> {
+        let name = self.station_label(station).to_string();
+        let is_current = self.is_playing
+            && self
+                .current_station
+                .as_ref()
+                .map(|s| s.is_same(station))
+                .unwrap_or(false);
+        let (play_icon, play_label) = if is_current {
+            (
+                "media-playback-pause-symbolic",
+                fl!("a11y-pause", name = name.as_str()),
+            )
+        } else {
+            (
+                "media-playback-start-symbolic",
+                fl!("a11y-play", name = name.as_str()),
+            )
+        };
+
+        let fav_icon = if is_fav {
+            "starred-symbolic"
+        } else {
+            "non-starred-symbolic"
+        };
+
+        // The playing station keeps its pause button at hand
+        let hover_only = self.config.row_actions_on_hover;
+        let show_actions = !hover_only
+            || is_current
+            || self.keyboard_focus
+            || self.hovered_row.as_ref() == Some(&station.id)
+            || self.revealed_row.as_ref() == Some(&station.id);
+
+        let compact = self.config.list_layout == ListLayout::Compact;
+        let mut row = widget::row()
+            .spacing(if compact { 6 } else { 10 })
+            .align_y(Alignment::Center)
+            .push(self.view_favicon(station, if compact { 16 } else { 24 }));
+        // Hidden buttons stay in the tree as blank ones so Tab still reaches them
+        let action = |name: &'static str, label: String| {
+            if show_actions {
+                icon_button(name, label)
+            } else {
+                hidden_icon_button(label)
+            }
+        };
+        row = row
+            .push(action(play_icon, play_label).on_press(Message::PlayStation(station.id.clone())));
+        row = row.push({
+            let label = widget::text(name.clone());
+            let mut info = widget::column().width(cosmic::iced::Length::Fill);
+            if compact {
+                info = info.push(label.size(13));
+            } else {
+                info = info.push(label);
+                if let Some(stats) = self.station_stats(station) {
+                    info = info.push(widget::text(stats).size(11));
+                }
+            }
+            info
+        });
+
+        if with_preview && show_actions {
+            // Press and hold to audition; a plain icon is used because a button
+            // would capture the press before the mouse area sees it
+            let preview_icon = if self.preview_station.as_ref() == Some(&station.id) {
+                "audio-volume-high-symbolic"
+            } else {
+                "audio-headphones-symbolic"
+            };
+            row = row.push(
+                widget::mouse_area(
+                    widget::container(icon::from_name(preview_icon).size(16)).padding(4),
+                )
+                .on_press(Message::StartPreview(station.id.clone()))
+                .on_release(Message::StopPreview),
+            );
+        }
+
+        // Favorites are kept on purpose, so only other stations can be hidden
+        if !is_fav && !station.stationuuid.is_empty() {
+            row = row.push(
+                action(
+                    "view-conceal-symbolic",
+                    fl!("a11y-hide-station", name = name.as_str()),
+                )
+                .on_press(Message::HideStation(station.id.clone())),
+            );
+        }
+
+        row = row
+            .push(
+                action(
+                    "emblem-shared-symbolic",
+                    fl!("a11y-share-station", name = name.as_str()),
+                )
+                .on_press(Message::ShareStation(station.id.clone())),
+            )
+            .push(
+                action(fav_icon, favorite_toggle_label(is_fav, &name))
+                    .on_press(Message::ToggleFavorite(station.id.clone())),
+            );
+
+        if !hover_only {
+            return row.into();
+        }
+        widget::mouse_area(row)
+            .on_enter(Message::RowHovered(station.id.clone()))
+            .on_exit(Message::RowUnhovered(station.id.clone()))
+            .on_press(Message::RowPressed(station.id.clone()))
+            .on_release(Message::RowReleased)
+            .into()
+    }
+
+    /// Overlay listing every entry of the shortcut table
+    fn view_shortcuts(&self) -> Element<'_, Message> {
+        let mut list = widget::column().spacing(8).push(
+            widget::row()
+                .spacing(10)
+                .align_y(Alignment::Center)
+                .push(
+                    widget::text(fl!("shortcuts-title"))
+                        .size(18)
+                        .width(Length::Fill),
+                )
+                .push(
+                    icon_button("window-close-symbolic", fl!("a11y-close"))
+                        .on_press(Message::ToggleShortcutHelp),
+                ),
+        );
+        for binding in shortcuts::BINDINGS {
+            list = list.push(
+                widget::row()
+                    .spacing(10)
+                    .align_y(Alignment::Center)
+                    .push(
+                        widget::container(widget::text(shortcut_key_text(binding.key)).size(14))
+                            .width(Length::Fixed(60.0)),
+                    )
+                    .push(widget::text(shortcut_action_text(binding.action)).size(14)),
+            );
+        }
+        list.into()
+    }
+
+    fn view_share<'a>(&'a self, share: &'a ShareDialog) -> Element<'a, Message> {
+        let mut dialog = widget::column()
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .push(
+                widget::row()
+                    .spacing(10)
+                    .align_y(Alignment::Center)
+                    .push(
+                        widget::text(fl!("share-title", name = share.station.name.clone()))
+                            .size(18)
+                            .width(Length::Fill),
+                    )
+                    .push(
+                        icon_button("window-close-symbolic", fl!("a11y-close"))
+                            .on_press(Message::CloseShare),
+                    ),
+            );
+        dialog = match &share.qr {
+            Some(handle) => dialog.push(widget::image(handle.clone())),
+            None => dialog.push(widget::text(fl!("share-too-long"))),
+        };
+        dialog = dialog.push(widget::text(share.url.clone()).size(11));
+        if !share.station.stationuuid.is_empty() {
+            let link = DeepLink::Play {
+                uuid: share.station.stationuuid.clone(),
+            };
+            dialog = dialog.push(
+                widget::text(fl!("share-deep-link", link = link.to_uri()))
+                    .size(11)
+                    .class(cosmic::theme::Text::Color(cosmic::iced::Color::from_rgb(
+                        0.5, 0.5, 0.5,
+                    ))),
+            );
+        }
+        if !share.station.homepage.trim().is_empty() {
+            dialog = dialog.push(widget::settings::item(
+                fl!("share-homepage"),
+                widget::toggler(share.homepage).on_toggle(Message::SetShareHomepage),
+            ));
+        }
+        dialog.into()
+    }
+
+    fn view_add_station(&self) -> Element<'_, Message> {
+        let mut form = widget::column()
+            .spacing(5)
+            .push(
+                text_input(&fl!("custom-station-name"), &self.custom_name_input)
+                    .on_input(Message::CustomNameChanged),
+            )
+            .push(
+                text_input(&fl!("custom-station-url"), &self.custom_url_input)
+                    .on_input(Message::CustomUrlChanged)
+                    .on_submit(Message::SaveCustomStation),
+            )
+            .push(
+                text_input(&fl!("custom-station-icon"), &self.custom_icon_input)
+                    .on_input(Message::CustomIconChanged)
+                    .on_submit(Message::SaveCustomStation),
+            )
+            .push(
+                widget::row()
+                    .spacing(5)
+                    .push(
+                        cosmic::iced::widget::button(widget::text(fl!("custom-station-cancel")))
+                            .on_press(Message::ToggleAddStation),
+                    )
+                    .push(
+                        cosmic::iced::widget::button(widget::text(fl!("custom-station-add")))
+                            .on_press_maybe(
+                                (!self.custom_url_input.trim().is_empty())
+                                    .then_some(Message::SaveCustomStation),
+                            ),
+                    ),
+            );
+        if let Some(err) = &self.custom_station_error {
+            form = form.push(widget::text(format!("{} {}", fl!("error-message"), err)).size(11));
+        }
+        form.into()
+    }
+
+    fn view_manage_favorites(&self) -> Element<'_, Message> {
+        let has_selection = !self.selected_favorites.is_empty();
+        let tag = self.bulk_tag_input.trim();
+        let tag_edit = |edit: fn(String) -> BulkEdit| {
+            (has_selection && !tag.is_empty())
+                .then(|| Message::ApplyBulkEdit(edit(tag.to_string())))
+        };
+
+        let tag_row = widget::row()
+            .spacing(5)
+            .align_y(Alignment::Center)
+            .push(
+                text_input(&fl!("bulk-tag-placeholder"), &self.bulk_tag_input)
+                    .on_input(Message::BulkTagInputChanged)
+                    .width(Length::Fill),
+            )
+            .push(
+                cosmic::iced::widget::button(widget::text(fl!("bulk-add-tag")))
+                    .on_press_maybe(tag_edit(BulkEdit::AddTag)),
+            )
+            .push(
+                cosmic::iced::widget::button(widget::text(fl!("bulk-remove-tag")))
+                    .on_press_maybe(tag_edit(BulkEdit::RemoveTag)),
+            );
+
+        let folder_row = widget::row()
+            .spacing(5)
+            .align_y(Alignment::Center)
+            .push(
+                text_input(&fl!("bulk-folder-placeholder"), &self.bulk_folder_input)
+                    .on_input(Message::BulkFolderInputChanged)
+                    .width(Length::Fill),
+            )
+            .push(
+                cosmic::iced::widget::button(widget::text(fl!("bulk-move-folder"))).on_press_maybe(
+                    has_selection.then(|| {
+                        Message::ApplyBulkEdit(BulkEdit::MoveToFolder(
+                            self.bulk_folder_input.clone(),
+                        ))
+                    }),
+                ),
+            );
+
+        let archive_btn = cosmic::iced::widget::button(widget::text(fl!("bulk-archive")))
+            .on_press_maybe(
+                has_selection.then_some(Message::ApplyBulkEdit(BulkEdit::SetArchived(true))),
+            );
+
+        let selection_row = if self.confirm_bulk_remove {
+            widget::row()
+                .spacing(10)
+                .align_y(Alignment::Center)
+                .push(
+                    widget::text(fl!(
+                        "bulk-remove-confirm",
+                        count = self.selected_favorites.len()
+                    ))
+                    .size(12)
+                    .width(Length::Fill),
+                )
+                .push(
+                    cosmic::iced::widget::button(widget::text(fl!("bulk-remove")))
+                        .class(cosmic::theme::Button::Destructive)
+                        .on_press(Message::RemoveSelectedFavorites),
+                )
+                .push(
+                    cosmic::iced::widget::button(widget::text(fl!("edit-favorite-cancel")))
+                        .on_press(Message::AskRemoveSelected(false)),
+                )
+        } else {
+            widget::row()
+                .spacing(10)
+                .align_y(Alignment::Center)
+                .push(archive_btn)
+                .push(
+                    cosmic::iced::widget::button(widget::text(fl!("bulk-export")))
+                        .on_press_maybe(has_selection.then_some(Message::ExportSelectedFavorites)),
+                )
+                .push(
+                    cosmic::iced::widget::button(widget::text(fl!("bulk-remove")))
+                        .on_press_maybe(has_selection.then_some(Message::AskRemoveSelected(true))),
+                )
+        };
+
+        let all_selected = self.selected_favorites.len() == self.config.active_favorites().count();
+        let mut list = widget::column()
+            .spacing(5)
+            .push(tag_row)
+            .push(folder_row)
+            .push(selection_row)
+            .push(
+                widget::row()
+                    .spacing(10)
+                    .align_y(Alignment::Center)
+                    .push(
+                        widget::checkbox(fl!("select-all"), all_selected)
+                            .on_toggle(Message::SelectAllFavorites)
+                            .width(Length::Fill),
+                    )
+                    .push(
+                        cosmic::iced::widget::button(widget::text(fl!("refresh-favorites")))
+                            .on_press_maybe(
+                                (!self.is_refreshing_favorites)
+                                    .then_some(Message::RefreshFavorites),
+                            ),
+                    ),
+            );
+
+        let (page, pages) = self.favorites_page(self.config.active_favorites().count());
+        for station in self
+            .config
+            .active_favorites()
+            .skip(page * FAVORITES_PAGE_SIZE)
+            .take(FAVORITES_PAGE_SIZE)
+        {
+            if let Some(edit) = self
+                .editing_favorite
+                .as_ref()
+                .filter(|edit| edit.station == station.id)
+            {
+                list = list.push(self.view_favorite_edit(station, edit));
+                continue;
+            }
+            let label = if station.folder.is_empty() {
+                station.display_name().to_string()
+            } else {
+                format!("{} · {}", station.display_name(), station.folder)
+            };
+            let id = station.id.clone();
+            list = list.push(
+                widget::row()
+                    .spacing(5)
+                    .align_y(Alignment::Center)
+                    .push(
+                        widget::checkbox(label, self.selected_favorites.contains(&station.id))
+                            .on_toggle(move |selected| {
+                                Message::FavoriteSelected(id.clone(), selected)
+                            })
+                            .width(Length::Fill),
+                    )
+                    .push(
+                        icon_button(
+                            "document-edit-symbolic",
+                            fl!("a11y-edit-station", name = station.display_name()),
+                        )
+                        .on_press(Message::EditFavorite(station.id.clone())),
+                    )
+                    .push(
+                        icon_button(
+                            "go-up-symbolic",
+                            fl!("a11y-move-up", name = station.display_name()),
+                        )
+                        .on_press(Message::MoveFavorite(station.id.clone(), MoveDirection::Up)),
+                    )
+                    .push(
+                        icon_button(
+                            "go-down-symbolic",
+                            fl!("a11y-move-down", name = station.display_name()),
+                        )
+                        .on_press(Message::MoveFavorite(
+                            station.id.clone(),
+                            MoveDirection::Down,
+                        )),
+                    ),
+            );
+        }
+        if pages > 1 {
+            list = list.push(self.view_favorites_pager(page, pages));
+        }
+
+        if self.config.archived_favorites().next().is_some() {
+            list = list.push(widget::text(fl!("archived-header")).size(16));
+            for station in self.config.archived_favorites() {
+                list = list.push(
+                    widget::row()
+                        .spacing(10)
+                        .align_y(Alignment::Center)
+                        .push(widget::text(station.display_name()).width(Length::Fill))
+                        .push(
+                            cosmic::iced::widget::button(widget::text(fl!("restore-favorite")))
+                                .on_press(Message::RestoreFavorite(station.id.clone())),
+                        ),
+                );
+            }
+        }
+
+        list.into()
+    }
+
+    /// Display name and stream URL inputs replacing a row in the manage view
+    fn view_favorite_edit<'a>(
+        &'a self,
+        station: &'a Station,
+        edit: &'a FavoriteEdit,
+    ) -> Element<'a, Message> {
+        let mut form = widget::column()
+            .spacing(5)
+            .push(
+                text_input(&station.name, &edit.alias)
+                    .on_input(Message::EditAliasChanged)
+                    .on_submit(Message::ApplyFavoriteEdit),
+            )
+            .push(
+                text_input(&station.url_resolved, &edit.stream_url)
+                    .on_input(Message::EditStreamUrlChanged)
+                    .on_submit(Message::ApplyFavoriteEdit),
+            );
+        if let Some(err) = &edit.error {
+            form = form.push(widget::text(format!("{} {}", fl!("error-message"), err)).size(11));
+        }
+        form.push(
+            widget::row()
+                .spacing(5)
+                .push(
+                    cosmic::iced::widget::button(widget::text(fl!("edit-favorite-save")))
+                        .on_press(Message::ApplyFavoriteEdit),
+                )
+                .push(
+                    cosmic::iced::widget::button(widget::text(fl!("edit-favorite-cancel")))
+                        .on_press(Message::CancelFavoriteEdit),
+                ),
+        )
+        .into()
+    }
+
+    /// Saved searches, listed like folders above the favorites
+    fn view_saved_searches(&self) -> Element<'_, Message> {
+        let mut list = widget::column()
+            .spacing(5)
+            .push(widget::text(fl!("saved-searches-header")).size(16));
+        for (index, saved) in self.config.saved_searches.iter().enumerate() {
+            list = list.push(
+                widget::row()
+                    .spacing(10)
+                    .align_y(Alignment::Center)
+                    .push(
+                        icon_button(
+                            "system-search-symbolic",
+                            fl!("a11y-run-search", name = saved.name.as_str()),
+                        )
+                        .on_press(Message::RunSavedSearch(index)),
+                    )
+                    .push(
+                        widget::column()
+                            .width(Length::Fill)
+                            .push(widget::text(&saved.name))
+                            .push(widget::text(&saved.query).size(11)),
+                    )
+                    .push(
+                        icon_button(
+                            "edit-delete-symbolic",
+                            fl!("a11y-remove", name = saved.name.as_str()),
+                        )
+                        .on_press(Message::DeleteSavedSearch(index)),
+                    ),
+            );
+        }
+        list.into()
+    }
+
+    fn view_settings(&self) -> Element<'_, Message> {
+        let mut endpoint = widget::column().spacing(5).push(
+            widget::row()
+                .spacing(5)
+                .align_y(Alignment::Center)
+                .push(
+                    text_input(
+                        &fl!("settings-api-url-placeholder"),
+                        &self.api_base_url_input,
+                    )
+                    .on_input(Message::ApiBaseUrlInputChanged)
+                    .on_submit(Message::ApplyApiBaseUrl)
+                    .width(Length::Fill),
+                )
+                .push(
+                    cosmic::iced::widget::button(widget::text(fl!("settings-apply")))
+                        .on_press(Message::ApplyApiBaseUrl),
+                ),
+        );
+        if let Some(err) = &self.api_base_url_error {
+            endpoint =
+                endpoint.push(widget::text(format!("{} {}", fl!("error-message"), err)).size(11));
+        }
+
+        let mut proxy = widget::column().spacing(5).push(
+            widget::row()
+                .spacing(5)
+                .align_y(Alignment::Center)
+                .push(
+                    text_input(&fl!("settings-proxy-placeholder"), &self.proxy_input)
+                        .on_input(Message::ProxyInputChanged)
+                        .on_submit(Message::ApplyProxy)
+                        .width(Length::Fill),
+                )
+                .push(
+                    cosmic::iced::widget::button(widget::text(fl!("settings-apply")))
+                        .on_press(Message::ApplyProxy),
+                ),
+        );
+        if let Some(err) = &self.proxy_error {
+            proxy = proxy.push(widget::text(format!("{} {}", fl!("error-message"), err)).size(11));
+        }
+
+        let mut location = widget::column().spacing(5).push(
+            widget::row()
+                .spacing(5)
+                .align_y(Alignment::Center)
+                .push(
+                    text_input(&fl!("settings-location-placeholder"), &self.location_input)
+                        .on_input(Message::LocationInputChanged)
+                        .on_submit(Message::ApplyLocation)
+                        .width(Length::Fill),
+                )
+                .push(
+                    cosmic::iced::widget::button(widget::text(fl!("settings-apply")))
+                        .on_press(Message::ApplyLocation),
+                ),
+        );
+        if let Some(err) = &self.location_error {
+            location =
+                location.push(widget::text(format!("{} {}", fl!("error-message"), err)).size(11));
+        }
+
+        let search = widget::settings::section()
+            .title(fl!("settings-search"))
+            .add(widget::settings::item(fl!("settings-api-url"), endpoint))
+            .add(widget::settings::item(fl!("settings-proxy"), proxy))
+            .add(widget::settings::item(fl!("settings-location"), location))
+            .add(widget::settings::item(
+                fl!("settings-timeout"),
+                widget::dropdown(
+                    &self.timeout_labels,
+                    TIMEOUT_CHOICES
+                        .iter()
+                        .position(|&secs| secs == self.config.request_timeout_secs),
+                    Message::RequestTimeoutSelected,
+                ),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-min-bitrate"),
+                widget::dropdown(
+                    &self.min_bitrate_labels,
+                    MIN_BITRATE_CHOICES
+                        .iter()
+                        .position(|&kbps| kbps == self.config.quality.min_bitrate),
+                    Message::MinBitrateSelected,
+                ),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-preferred-codec"),
+                widget::dropdown(
+                    &self.codec_labels,
+                    PreferredCodec::ALL
+                        .iter()
+                        .position(|&codec| codec == self.config.quality.preferred_codec),
+                    Message::PreferredCodecSelected,
+                ),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-show-station-stats"),
+                widget::toggler(self.config.show_station_stats)
+                    .on_toggle(Message::SetShowStationStats),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-row-actions-on-hover"),
+                widget::toggler(self.config.row_actions_on_hover)
+                    .on_toggle(Message::SetRowActionsOnHover),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-show-broken"),
+                widget::toggler(self.config.show_broken_stations)
+                    .on_toggle(Message::SetShowBrokenStations),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-report-clicks"),
+                widget::toggler(self.config.report_clicks).on_toggle(Message::SetReportClicks),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-cache-on-disk"),
+                widget::toggler(self.config.cache_on_disk).on_toggle(Message::SetCacheOnDisk),
+            ));
+
+        let mut playback = widget::settings::section()
+            .title(fl!("settings-playback"))
+            .add(widget::settings::item(
+                fl!("settings-resume"),
+                widget::toggler(self.config.resume_on_login).on_toggle(Message::SetResumeOnLogin),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-autoplay"),
+                widget::toggler(self.config.autoplay).on_toggle(Message::SetAutoplay),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-shuffle-favorites"),
+                widget::toggler(self.config.shuffle_favorites)
+                    .on_toggle(Message::SetShuffleFavorites),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-loop-favorites"),
+                widget::toggler(self.config.loop_favorites).on_toggle(Message::SetLoopFavorites),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-inhibit-idle"),
+                widget::toggler(self.config.inhibit_idle).on_toggle(Message::SetInhibitIdle),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-inhibit-sleep"),
+                widget::toggler(self.config.inhibit_sleep).on_toggle(Message::SetInhibitSleep),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-pause-on-output-loss"),
+                widget::toggler(self.config.pause_on_output_loss)
+                    .on_toggle(Message::SetPauseOnOutputLoss),
+            ));
+        if self.config.pause_on_output_loss {
+            playback = playback.add(widget::settings::item(
+                fl!("settings-resume-on-output-return"),
+                widget::toggler(self.config.resume_on_output_return)
+                    .on_toggle(Message::SetResumeOnOutputReturn),
+            ));
+        }
+        playback = playback.add(widget::settings::item(
+            fl!("settings-duck"),
+            widget::dropdown(
+                &self.duck_labels,
+                DuckMode::ALL
+                    .iter()
+                    .position(|&m| m == self.config.duck_mode),
+                Message::DuckModeSelected,
+            ),
+        ));
+        if self.config.duck_mode == DuckMode::Lower {
+            playback = playback.add(widget::settings::item(
+                fl!("settings-duck-volume"),
+                widget::dropdown(
+                    &self.duck_volume_labels,
+                    duck::VOLUME_CHOICES
+                        .iter()
+                        .position(|&percent| percent == self.config.duck_volume),
+                    Message::DuckVolumeSelected,
+                ),
+            ));
+        }
+        if self.config.duck_mode != DuckMode::Off {
+            playback = playback.add(widget::settings::item(
+                fl!("settings-duck-delay"),
+                widget::dropdown(
+                    &self.duck_delay_labels,
+                    duck::DELAY_CHOICES
+                        .iter()
+                        .position(|&seconds| seconds == self.config.duck_delay_secs),
+                    Message::DuckDelaySelected,
+                ),
+            ));
+        }
+        if self.config.autoplay {
+            let selected = self
+                .config
+                .autoplay_station
+                .as_ref()
+                .and_then(|id| self.config.active_favorites().position(|s| s.id == *id))
+                .map_or(0, |index| index + 1);
+            playback = playback.add(widget::settings::item(
+                fl!("settings-autoplay-station"),
+                widget::dropdown(
+                    &self.autoplay_labels,
+                    Some(selected),
+                    Message::AutoplayStationSelected,
+                ),
+            ));
+        }
+        playback = playback
+            .add(widget::settings::item(
+                fl!("settings-max-volume"),
+                widget::dropdown(
+                    &self.max_volume_labels,
+                    MAX_VOLUME_CHOICES
+                        .iter()
+                        .position(|&percent| percent == self.config.max_volume),
+                    Message::MaxVolumeSelected,
+                ),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-history-limit"),
+                widget::dropdown(
+                    &self.history_limit_labels,
+                    history::MAX_ENTRIES_CHOICES
+                        .iter()
+                        .position(|&entries| entries == self.config.history_max_entries),
+                    Message::HistoryLimitSelected,
+                ),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-lite"),
+                widget::dropdown(
+                    &self.lite_labels,
+                    LiteMode::ALL
+                        .iter()
+                        .position(|&m| m == self.config.lite_mode),
+                    Message::LiteModeSelected,
+                ),
+            ));
+        // mpv is the only engine unless GStreamer support was compiled in
+        if AudioBackend::GStreamer.is_available() {
+            playback = playback.add(widget::settings::item(
+                fl!("settings-backend"),
+                widget::dropdown(
+                    &self.backend_labels,
+                    AudioBackend::ALL
+                        .iter()
+                        .position(|&b| b == self.config.audio_backend),
+                    Message::AudioBackendSelected,
+                ),
+            ));
+        }
+
+        let notifications = widget::settings::section()
+            .title(fl!("settings-notifications"))
+            .add(widget::settings::item(
+                fl!("settings-notify-station"),
+                widget::toggler(self.config.notify_station).on_toggle(Message::SetNotifyStation),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-notify-song"),
+                widget::toggler(self.config.notify_song).on_toggle(Message::SetNotifySong),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-notify-errors"),
+                widget::toggler(self.config.notify_errors).on_toggle(Message::SetNotifyErrors),
+            ));
+
+        let scrobbling = widget::settings::section()
+            .title(fl!("settings-scrobbling"))
+            .add(widget::settings::item(
+                fl!("settings-listenbrainz-token"),
+                widget::row()
+                    .spacing(5)
+                    .align_y(Alignment::Center)
+                    .push(
+                        text_input(
+                            &fl!("settings-listenbrainz-placeholder"),
+                            &self.listenbrainz_token_input,
+                        )
+                        .secure(true)
+                        .on_input(Message::ListenBrainzTokenChanged)
+                        .on_submit(Message::ApplyListenBrainzToken)
+                        .width(Length::Fill),
+                    )
+                    .push(
+                        cosmic::iced::widget::button(widget::text(fl!("settings-apply")))
+                            .on_press(Message::ApplyListenBrainzToken),
+                    ),
+            ));
+
+        let panel_icon = widget::settings::section()
+            .title(fl!("settings-panel"))
+            .add(widget::settings::item(
+                fl!("settings-left-click"),
+                widget::dropdown(
+                    &self.left_click_labels,
+                    ClickAction::LEFT
+                        .iter()
+                        .position(|&a| a == self.config.left_click),
+                    Message::LeftClickSelected,
+                ),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-middle-click"),
+                widget::dropdown(
+                    &self.middle_click_labels,
+                    ClickAction::MIDDLE
+                        .iter()
+                        .position(|&a| a == self.config.middle_click),
+                    Message::MiddleClickSelected,
+                ),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-scroll-volume"),
+                widget::toggler(self.config.scroll_volume).on_toggle(Message::SetScrollVolume),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-scroll-step"),
+                widget::dropdown(
+                    &self.scroll_step_labels,
+                    panel::SCROLL_STEP_CHOICES
+                        .iter()
+                        .position(|&step| step == self.config.scroll_volume_step),
+                    Message::ScrollStepSelected,
+                ),
+            ))
+            .add(widget::settings::item(
+                fl!("settings-panel-text"),
+                widget::dropdown(
+                    &self.panel_text_labels,
+                    PanelText::ALL
+                        .iter()
+                        .position(|&t| t == self.config.panel_text),
+                    Message::PanelTextSelected,
+                ),
+            ));
+        let panel_icon = if self.config.panel_text == PanelText::Off {
+            panel_icon
+        } else if !self.core.applet.is_horizontal() {
+            panel_icon.add(widget::text(fl!("settings-panel-text-vertical")).size(11))
+        } else {
+            panel_icon
+                .add(widget::settings::item(
+                    fl!("settings-panel-text-width"),
+                    widget::dropdown(
+                        &self.panel_width_labels,
+                        panel::TEXT_WIDTH_CHOICES
+                            .iter()
+                            .position(|&w| w == self.config.panel_text_width),
+                        Message::PanelTextWidthSelected,
+                    ),
+                ))
+                .add(widget::settings::item(
+                    fl!("settings-panel-text-scroll"),
+                    widget::toggler(self.config.panel_text_scroll)
+                        .on_toggle(Message::SetPanelTextScroll),
+                ))
+        };
+
+        let mut popup = widget::settings::section()
+            .title(fl!("settings-popup"))
+            .add(widget::settings::item(
+                fl!("settings-popup-size"),
+                widget::dropdown(
+                    &self.popup_size_labels,
+                    Some(
+                        PopupSize::PRESETS
+                            .iter()
+                            .position(|&size| size == self.config.popup_size)
+                            .unwrap_or(PopupSize::PRESETS.len()),
+                    ),
+                    Message::PopupSizeSelected,
+                ),
+            ));
+        if matches!(self.config.popup_size, PopupSize::Custom { .. }) {
+            let mut custom = widget::column().spacing(5).push(
+                widget::row()
+                    .spacing(5)
+                    .align_y(Alignment::Center)
+                    .push(
+                        text_input(&fl!("settings-popup-width"), &self.popup_width_input)
+                            .on_input(Message::PopupWidthChanged)
+                            .on_submit(Message::ApplyPopupSize)
+                            .width(Length::Fixed(70.0)),
+                    )
+                    .push(widget::text("×"))
+                    .push(
+                        text_input(&fl!("settings-popup-height"), &self.popup_height_input)
+                            .on_input(Message::PopupHeightChanged)
+                            .on_submit(Message::ApplyPopupSize)
+                            .width(Length::Fixed(70.0)),
+                    )
+                    .push(
+                        cosmic::iced::widget::button(widget::text(fl!("settings-apply")))
+                            .on_press(Message::ApplyPopupSize),
+                    ),
+            );
+            if let Some(err) = &self.popup_size_error {
+                custom =
+                    custom.push(widget::text(format!("{} {}", fl!("error-message"), err)).size(11));
+            }
+            popup = popup.add(widget::settings::item(fl!("settings-popup-custom"), custom));
+        }
+
+        let mut sync_file = widget::column().spacing(5).push(
+            widget::row()
+                .spacing(5)
+                .align_y(Alignment::Center)
+                .push(
+                    text_input(
+                        &fl!("settings-sync-file-placeholder"),
+                        &self.sync_file_input,
+                    )
+                    .on_input(Message::SyncFileInputChanged)
+                    .on_submit(Message::ApplySyncFile)
+                    .width(Length::Fill),
+                )
+                .push(
+                    cosmic::iced::widget::button(widget::text(fl!("settings-apply")))
+                        .on_press(Message::ApplySyncFile),
+                ),
+        );
+        if let Some(err) = &self.sync_file_error {
+            sync_file =
+                sync_file.push(widget::text(format!("{} {}", fl!("error-message"), err)).size(11));
+        } else if !self.config.sync_file.is_empty() && !self.config.active_profile.is_empty() {
+            sync_file = sync_file.push(widget::text(fl!("settings-sync-file-paused")).size(11));
+        }
+
+        widget::column()
+            .spacing(10)
+            .push(search)
+            .push(playback)
+            .push(notifications)
+            .push(scrobbling)
+            .push(panel_icon)
+            .push(popup)
+            .push(
+                widget::settings::section()
+                    .title(fl!("settings-favorites"))
+                    .add(widget::settings::item(
+                        fl!("import-favorites"),
+                        cosmic::iced::widget::button(widget::text(fl!("import-choose-file")))
+                            .on_press(Message::ImportFavorites),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("export-favorites"),
+                        cosmic::iced::widget::button(widget::text(fl!("export-choose-file")))
+                            .on_press_maybe(
+                                (!self.config.favorites.is_empty())
+                                    .then_some(Message::ExportFavorites),
+                            ),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("export-playlist"),
+                        widget::row()
+                            .spacing(8)
+                            .push(self.export_playlist_button(PlaylistFormat::M3u8))
+                            .push(self.export_playlist_button(PlaylistFormat::Pls)),
+                    ))
+                    .add(widget::settings::item(fl!("settings-sync-file"), sync_file)),
+            )
+            .push(self.view_profiles())
+            .push(self.view_backup())
+            .push(self.view_genre_chip_settings())
+            .push(self.view_hidden_stations())
+            .push(self.view_subscriptions())
+            .push(self.view_journal())
+            .into()
+    }
+
+    fn view_profiles(&self) -> Element<'_, Message> {
+        let mut section = widget::settings::section()
+            .title(fl!("settings-profiles"))
+            .add(widget::settings::item(
+                fl!("profile-add"),
+                widget::row()
+                    .spacing(5)
+                    .align_y(Alignment::Center)
+                    .push(
+                        text_input(&fl!("profile-name-placeholder"), &self.profile_name_input)
+                            .on_input(Message::ProfileNameChanged)
+                            .on_submit(Message::AddProfile)
+                            .width(Length::Fill),
+                    )
+                    .push(
+                        icon_button("list-add-symbolic", fl!("a11y-add-profile")).on_press_maybe(
+                            (!self.profile_name_input.trim().is_empty())
+                                .then_some(Message::AddProfile),
+                        ),
+                    ),
+            ));
+        // The active profile and the default one can't be removed
+        for name in self.config.profiles.keys().filter(|name| !name.is_empty()) {
+            section = section.add(widget::settings::item(
+                name.clone(),
+                icon_button(
+                    "edit-delete-symbolic",
+                    fl!("a11y-remove", name = name.as_str()),
+                )
+                .on_press(Message::RemoveProfile(name.clone())),
+            ));
+        }
+        section.into()
+    }
+
+    fn view_backup(&self) -> Element<'_, Message> {
+        let mut section = widget::settings::section()
+            .title(fl!("settings-backup"))
+            .add(widget::settings::item(
+                fl!("backup-settings"),
+                cosmic::iced::widget::button(widget::text(fl!("export-choose-file")))
+                    .on_press(Message::BackupSettings),
+            ))
+            .add(widget::settings::item(
+                fl!("restore-settings"),
+                cosmic::iced::widget::button(widget::text(fl!("import-choose-file")))
+                    .on_press(Message::RestoreSettings),
+            ));
+        if let Some(restored) = &self.pending_restore {
+            section = section.add(
+                widget::column()
+                    .spacing(6)
+                    .push(
+                        widget::text(fl!("restore-confirm", count = restored.favorites.len()))
+                            .size(12),
+                    )
+                    .push(
+                        widget::row()
+                            .spacing(8)
+                            .push(
+                                cosmic::iced::widget::button(widget::text(fl!("restore-merge")))
+                                    .on_press(Message::ApplyRestore(RestoreMode::Merge)),
+                            )
+                            .push(
+                                cosmic::iced::widget::button(widget::text(fl!("restore-replace")))
+                                    .on_press(Message::ApplyRestore(RestoreMode::Overwrite)),
+                            )
+                            .push(
+                                cosmic::iced::widget::button(widget::text(fl!("restore-cancel")))
+                                    .on_press(Message::CancelRestore),
+                            ),
+                    ),
+            );
+        }
+        section.into()
+    }
+
+    fn export_playlist_button(&self, format: PlaylistFormat) -> Element<'_, Message> {
+        let has_active = self.config.active_favorites().next().is_some();
+        cosmic::iced::widget::button(widget::text(format!(".{}", format.extension())))
+            .on_press_maybe(has_active.then_some(Message::ExportPlaylist(format)))
+            .into()
+    }
+
+    fn view_hidden_stations(&self) -> Element<'_, Message> {
+        let mut section = widget::settings::section().title(fl!("hidden-stations-header"));
+        if self.config.hidden_stations.is_empty() {
+            return section
+                .add(widget::text(fl!("hidden-stations-empty")).size(11))
+                .into();
+        }
+        for hidden in &self.config.hidden_stations {
+            section = section.add(widget::settings::item(
+                hidden.name.clone(),
+                cosmic::iced::widget::button(widget::text(fl!("hidden-station-unhide")))
+                    .on_press(Message::UnhideStation(hidden.stationuuid.clone())),
+            ));
+        }
+        section.into()
+    }
+
+    fn view_genre_chip_settings(&self) -> Element<'_, Message> {
+        let mut section = widget::settings::section().title(fl!("genre-chips-header"));
+        for (index, tag) in self.config.genre_chips.iter().enumerate() {
+            let label = genre_label(tag);
+            section = section.add(widget::settings::item(
+                label.clone(),
+                icon_button(
+                    "edit-delete-symbolic",
+                    fl!("a11y-remove", name = label.as_str()),
+                )
+                .on_press(Message::RemoveGenreChip(index)),
+            ));
+        }
+        section
+            .add(
+                widget::row()
+                    .spacing(5)
+                    .align_y(Alignment::Center)
+                    .push(
+                        text_input(&fl!("genre-chip-placeholder"), &self.genre_chip_input)
+                            .on_input(Message::GenreChipInputChanged)
+                            .on_submit(Message::AddGenreChip)
+                            .width(Length::Fill),
+                    )
+                    .push(
+                        cosmic::iced::widget::button(widget::text(fl!("genre-chip-add")))
+                            .on_press_maybe(
+                                (!self.genre_chip_input.trim().is_empty())
+                                    .then_some(Message::AddGenreChip),
+                            ),
+                    ),
+            )
+            .into()
+    }
+
+    fn view_subscriptions(&self) -> Element<'_, Message> {
+        let mut section = widget::settings::section().title(fl!("subscriptions-header"));
+        for (index, subscription) in self.config.subscriptions.iter().enumerate() {
+            let status = if subscription.last_updated == 0 {
+                fl!("subscription-pending")
+            } else {
+                format!(
+                    "{} · {}",
+                    fl!("subscription-stations", count = subscription.stations.len()),
+                    journal::format_timestamp(subscription.last_updated)
+                )
+            };
+            section = section.add(
+                widget::row()
+                    .spacing(5)
+                    .align_y(Alignment::Center)
+                    .push(
+                        widget::column()
+                            .push(widget::text(subscription.name.clone()))
+                            .push(widget::text(status).size(11))
+                            .width(Length::Fill),
+                    )
+                    .push(
+                        icon_button(
+                            "edit-delete-symbolic",
+                            fl!("a11y-remove", name = subscription.name.as_str()),
+                        )
+                        .on_press(Message::RemoveSubscription(index)),
+                    ),
+            );
+        }
+
+        let mut form = widget::column().spacing(5).push(
+            widget::row()
+                .spacing(5)
+                .align_y(Alignment::Center)
+                .push(
+                    text_input(
+                        &fl!("subscription-name-placeholder"),
+                        &self.subscription_name_input,
+                    )
+                    .on_input(Message::SubscriptionNameChanged)
+                    .width(Length::FillPortion(1)),
+                )
+                .push(
+                    text_input(
+                        &fl!("subscription-url-placeholder"),
+                        &self.subscription_url_input,
+                    )
+                    .on_input(Message::SubscriptionUrlChanged)
+                    .on_submit(Message::AddSubscription)
+                    .width(Length::FillPortion(2)),
+                )
+                .push(
+                    cosmic::iced::widget::button(widget::text(fl!("subscription-add")))
+                        .on_press_maybe(
+                            (!self.subscription_url_input.trim().is_empty())
+                                .then_some(Message::AddSubscription),
+                        ),
+                ),
+        );
+        if let Some(err) = &self.subscription_error {
+            form = form.push(widget::text(format!("{} {}", fl!("error-message"), err)).size(11));
+        }
+        section.add(form).into()
+    }
+
+    fn view_journal(&self) -> Element<'_, Message> {
+        let journal = &self.config.favorites_journal;
+        let mut section = widget::settings::section().title(fl!("journal-header"));
+        if journal.is_empty() {
+            return section
+                .add(widget::text(fl!("journal-empty")).size(12))
+                .into();
+        }
+
+        for (index, entry) in journal.iter().enumerate().rev().take(JOURNAL_VIEW_LIMIT) {
+            let (label, detail) = match &entry.change {
+                FavoriteChange::Added { station } => (fl!("journal-added"), station.name.clone()),
+                FavoriteChange::Removed { station } => {
+                    (fl!("journal-removed"), station.name.clone())
+                }
+                FavoriteChange::Renamed { from, to, .. } => {
+                    (fl!("journal-renamed"), format!("{from} → {to}"))
+                }
+            };
+            section = section.add(
+                widget::row()
+                    .spacing(5)
+                    .align_y(Alignment::Center)
+                    .push(
+                        widget::column()
+                            .push(widget::text(detail))
+                            .push(
+                                widget::text(format!(
+                                    "{} · {}",
+                                    label,
+                                    journal::format_timestamp(entry.timestamp)
+                                ))
+                                .size(11),
+                            )
+                            .width(Length::Fill),
+                    )
+                    .push(
+                        cosmic::iced::widget::button(widget::text(fl!("journal-revert")))
+                            .on_press(Message::RevertJournalEntry(index)),
+                    ),
+            );
+        }
+        section.into()
+    }
+
+    /// One-click buttons for the last few stations, wrapping onto a second
+    /// line in narrow popups
+    fn view_recent_stations(&self) -> Element<'_, Message> {
+        let chips = self
+            .config
+            .recent_stations
+            .iter()
+            .map(|station| {
+                let playing = self.is_playing
+                    && self
+                        .current_station
+                        .as_ref()
+                        .is_some_and(|current| current.is_same(station));
+                cosmic::iced::widget::button(
+                    widget::text(chip_label(self.station_label(station))).size(12),
+                )
+                .on_press_maybe((!playing).then(|| Message::PlayStation(station.id.clone())))
+                .into()
+            })
+            .collect();
+        widget::flex_row(chips)
+            .row_spacing(5)
+            .column_spacing(5)
+            .into()
+    }
+
+    fn view_history(&self) -> Element<'_, Message> {
+        let mut list = widget::column().spacing(5).push(
+            widget::row()
+                .spacing(10)
+                .align_y(Alignment::Center)
+                .push(
+                    widget::text(fl!("history-header"))
+                        .size(18)
+                        .width(Length::Fill),
+                )
+                .push(
+                    cosmic::iced::widget::button(widget::text(fl!("history-clear")))
+                        .on_press_maybe(
+                            (!self.config.history.is_empty()).then_some(Message::ClearHistory),
+                        ),
+                ),
+        );
+        if self.config.history.is_empty() {
+            return list.push(widget::text(fl!("history-empty"))).into();
+        }
+
+        for (index, entry) in self.config.history.iter().enumerate() {
+            let playing = index == 0 && self.history_playing.as_ref() == Some(&entry.station.id);
+            let listened = if playing {
+                fl!("history-playing")
+            } else if entry.duration_secs == 0 {
+                fl!("history-unknown-duration")
+            } else {
+                fl!(
+                    "history-minutes",
+                    minutes = entry.duration_secs.div_ceil(60)
+                )
+            };
+            list = list.push(
+                widget::row()
+                    .spacing(10)
+                    .align_y(Alignment::Center)
+                    .push(
+                        icon_button(
+                            "media-playback-start-symbolic",
+                            fl!("a11y-play", name = self.station_label(&entry.station)),
+                        )
+                        .on_press_maybe(
+                            (!playing).then(|| Message::PlayStation(entry.station.id.clone())),
+                        ),
+                    )
+                    .push(
+                        widget::column()
+                            .push(widget::text(self.station_label(&entry.station)))
+                            .push(
+                                widget::text(format!(
+                                    "{} · {}",
+                                    journal::format_timestamp(entry.started),
+                                    listened
+                                ))
+                                .size(11),
+                            )
+                            .width(Length::Fill),
+                    ),
+            );
+        }
+        list.into()
+    }
+
+    /// Name to show for `station`: a favorite's alias wherever the station
+    /// appears, otherwise the directory name
+    /// Secondary text of a station row: country flag, codec and bitrate,
+    /// votes and plays, whichever are known
+    fn station_stats(&self, station: &Station) -> Option<String> {
+        if !self.config.show_station_stats {
+            return None;
+        }
+        let parts: Vec<String> = station
+            .country_flag()
+            .into_iter()
+            .chain(station.quality_label())
+            .chain((station.votes > 0).then(|| fl!("station-votes", count = station.votes)))
+            .chain(
+                (station.clickcount > 0).then(|| fl!("station-clicks", count = station.clickcount)),
+            )
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" · "))
+    }
+
+    fn station_label<'a>(&'a self, station: &'a Station) -> &'a str {
+        self.favorites_index
+            .position(station)
+            .and_then(|pos| self.config.favorites.get(pos))
+            .map_or_else(|| station.display_name(), Station::display_name)
+    }
+
+    /// Forget which row shows its buttons by hover, long press or keyboard
+    fn clear_row_actions(&mut self) {
+        self.hovered_row = None;
+        self.pressed_row = None;
+        self.revealed_row = None;
+        self.keyboard_focus = false;
+    }
+
+    /// Show the favorite count on the Favorites tab
+    fn refresh_favorites_tab(&mut self) {
+        let label = favorites_tab_label(self.config.active_favorites().count());
+        let entity = self
+            .tabs
+            .iter()
+            .find(|&entity| self.tabs.data::<PopupTab>(entity) == Some(&PopupTab::Favorites));
+        if let Some(entity) = entity {
+            self.tabs.text_set(entity, label);
+        }
+    }
+
+    fn active_tab(&self) -> PopupTab {
+        self.tabs
+            .active_data::<PopupTab>()
+            .copied()
+            .unwrap_or(PopupTab::Favorites)
+    }
+
+    /// Play the station a `cosmicradio://` link points at, looking it up in
+    /// the directory if it isn't in any local list
+    fn open_deep_link(&mut self, uri: &str) -> Task<cosmic::Action<Message>> {
+        let uuid = match DeepLink::parse(uri) {
+            Ok(DeepLink::Play { uuid }) => uuid,
+            Ok(DeepLink::PlayNamed { name }) => {
+                let Some(station) = self.config.favorite_named(&name).cloned() else {
+                    warn!("Deep link: no favorite called {}", name);
+                    self.show_toast(
+                        Severity::Warning,
+                        fl!("deeplink-no-favorite", name = name),
+                        None,
+                    );
+                    return Task::none();
+                };
+                info!("Deep link: play {}", station.name);
+                return self.play_linked(station);
+            }
+            Err(e) => {
+                warn!("Ignoring deep link {}: {}", uri, e);
+                self.show_toast(Severity::Warning, fl!("deeplink-invalid"), None);
+                return Task::none();
+            }
+        };
+        info!("Deep link: play {}", uuid);
+
+        let known = self
+            .config
+            .favorites
+            .iter()
+            .chain(self.config.subscriptions.iter().flat_map(|s| &s.stations))
+            .chain(&self.search_results)
+            .chain(&self.popular_voted)
+            .chain(&self.popular_clicked)
+            .find(|s| s.stationuuid == uuid)
+            .cloned();
+        if let Some(station) = known {
+            return self.play_linked(station);
+        }
+
+        Task::perform(
+            async move {
+                let result = api::stations_by_uuid(std::slice::from_ref(&uuid))
+                    .await
+                    .map_err(Arc::new);
+                (uuid, result)
+            },
+            |(uuid, result)| Message::DeepLinkResolved(uuid, result),
+        )
+        .map(Into::into)
+    }
+
+    /// Play a stream URL handed over through MPRIS `OpenUri`, as the
+    /// favorite with that stream if there is one, or else as a custom
+    /// station that is only kept in the history
+    fn open_stream_uri(&mut self, uri: &str) -> Task<cosmic::Action<Message>> {
+        let station = match Station::custom("", uri, "") {
+            Ok(station) => station,
+            Err(e) => {
+                warn!("Ignoring stream URI {}: {}", uri, e);
+                self.show_toast(Severity::Warning, fl!("open-uri-invalid"), None);
+                return Task::none();
+            }
+        };
+        info!("Opening stream {}", uri);
+        let favorite = self
+            .config
+            .favorites
+            .iter()
+            .find(|s| s.stationuuid == station.stationuuid)
+            .cloned();
+        self.play_linked(favorite.unwrap_or(station))
+    }
+
+    /// Like `PlayStation`, but never toggles an already playing station off
+    fn play_linked(&mut self, station: Station) -> Task<cosmic::Action<Message>> {
+        let already_playing = (self.is_playing || self.is_connecting)
+            && self
+                .current_station
                 .as_ref()
-                .map(|s| s.stationuuid == station.stationuuid)
-                .unwrap_or(false)
+                .is_some_and(|current| current.is_same(&station));
+        if already_playing {
+            return Task::none();
+        }
+        let id = self.stations.insert(station);
+        self.update(Message::PlayStation(id))
+    }
+
+    fn is_favorite(&self, station: &Station) -> bool {
+        self.favorites_index.contains(station)
+    }
+
+    /// The current favorites page, clamped to the list's length,
+    /// and the number of pages
+    fn favorites_page(&self, len: usize) -> (usize, usize) {
+        let pages = len.div_ceil(FAVORITES_PAGE_SIZE).max(1);
+        (self.favorites_page.min(pages - 1), pages)
+    }
+
+    /// Favorites outside any folder, then a collapsible section per folder
+    fn view_favorite_folders<'a>(&'a self, stations: &[&'a Station]) -> Element<'a, Message> {
+        let unfiled: Vec<&Station> = stations
+            .iter()
+            .copied()
+            .filter(|s| s.folder.is_empty())
+            .collect();
+        let mut list = widget::column()
+            .spacing(5)
+            .push(self.view_favorite_group(&unfiled));
+
+        let mut folders: Vec<&str> = Vec::new();
+        for station in stations {
+            if !station.folder.is_empty() && !folders.contains(&station.folder.as_str()) {
+                folders.push(&station.folder);
+            }
+        }
+        for folder in folders {
+            let members: Vec<&Station> = stations
+                .iter()
+                .copied()
+                .filter(|s| s.folder == folder)
+                .collect();
+            let collapsed = self.collapsed_folders.contains(folder);
+            list = list.push(
+                cosmic::iced::widget::button(
+                    widget::row()
+                        .spacing(5)
+                        .align_y(Alignment::Center)
+                        .push(icon::from_name(if collapsed {
+                            "go-next-symbolic"
+                        } else {
+                            "go-down-symbolic"
+                        }))
+                        .push(widget::text(format!("{} ({})", folder, members.len())).size(16)),
+                )
+                .on_press(Message::ToggleFolder(folder.to_string())),
+            );
+            if !collapsed {
+                list = list.push(
+                    widget::container(self.view_favorite_group(&members)).padding([0, 0, 0, 20]),
+                );
+            }
+        }
+        list.into()
+    }
+
+    /// Favorites as rows, or as tiles in the grid layout
+    fn view_favorite_group<'a>(&'a self, stations: &[&'a Station]) -> Element<'a, Message> {
+        if self.config.list_layout != ListLayout::Grid {
+            let mut rows = widget::column().spacing(5);
+            for station in stations {
+                rows = rows.push(self.view_station_row(station, true, false));
+            }
+            return rows.into();
+        }
+        let tiles: Vec<Element<'a, Message>> = stations
+            .iter()
+            .map(|station| {
+                cosmic::iced::widget::button(
+                    widget::column()
+                        .spacing(4)
+                        .align_x(Alignment::Center)
+                        .push(self.view_favicon(station, 48))
+                        .push(widget::text(chip_label(self.station_label(station))).size(11)),
+                )
+                .width(Length::Fixed(96.0))
+                .on_press(Message::PlayStation(station.id.clone()))
+                .into()
+            })
+            .collect();
+        widget::flex_row(tiles)
+            .row_spacing(5)
+            .column_spacing(5)
+            .into()
+    }
+
+    fn view_favorites_pager(&self, page: usize, pages: usize) -> Element<'_, Message> {
+        widget::row()
+            .spacing(10)
+            .align_y(Alignment::Center)
+            .push(
+                icon_button("go-previous-symbolic", fl!("a11y-previous-page"))
+                    .on_press_maybe((page > 0).then(|| Message::FavoritesPage(page - 1))),
+            )
+            .push(
+                widget::text(fl!("favorites-page", page = page + 1, pages = pages))
+                    .size(12)
+                    .width(Length::Fill)
+                    .align_x(Alignment::Center),
+            )
+            .push(
+                icon_button("go-next-symbolic", fl!("a11y-next-page"))
+                    .on_press_maybe((page + 1 < pages).then(|| Message::FavoritesPage(page + 1))),
+            )
+            .into()
+    }
+
+    /// Fetch the popular lists once per session, when the popup first opens
+    fn load_popular(&mut self) -> Task<cosmic::Action<Message>> {
+        if self.is_loading_popular || !self.popular_voted.is_empty() {
+            return Task::none();
+        }
+        self.is_loading_popular = true;
+        let hide_broken = !self.config.show_broken_stations;
+        let min_bitrate = self.config.quality.min_bitrate;
+        let nearby = match Nearby::resolve(&self.config.location) {
+            Some(near) => Task::perform(
+                async move {
+                    api::nearby_stations(near, POPULAR_LIMIT, hide_broken, min_bitrate)
+                        .await
+                        .map_err(Arc::new)
+                },
+                Message::NearbyLoaded,
+            )
+            .map(Into::into),
+            None => Task::none(),
+        };
+        let popular = Task::perform(
+            async move {
+                let (voted, clicked) = futures::join!(
+                    api::top_voted_stations(POPULAR_LIMIT, hide_broken),
+                    api::top_clicked_stations(POPULAR_LIMIT, hide_broken)
+                );
+                match (voted, clicked) {
+                    (Ok(voted), Ok(clicked)) => Ok((voted, clicked)),
+                    (Err(e), _) | (_, Err(e)) => Err(Arc::new(e)),
+                }
+            },
+            Message::PopularLoaded,
+        )
+        .map(Into::into);
+        Task::batch([popular, nearby])
+    }
+
+    /// Drop the popular lists and fetch them again, e.g. after a filter changed
+    fn reload_popular(&mut self) -> Task<cosmic::Action<Message>> {
+        self.popular_voted.clear();
+        self.popular_clicked.clear();
+        self.popular_nearby.clear();
+        self.load_popular()
+    }
+
+    fn stop_preview(&mut self) {
+        if self.preview_station.take().is_some() {
+            self.preview_audio.stop();
+            if self.is_playing {
+                self.audio.set_volume(self.playback_volume() as f32);
+            }
+            debug!("Preview stopped");
+        }
+    }
+
+    /// Search sources: the directory, then every subscribed list
+    fn rebuild_providers(&mut self) {
+        let lists = self.config.subscriptions.iter().map(|subscription| {
+            Arc::new(StationList {
+                name: subscription.name.clone(),
+                url: subscription.url.clone(),
+            }) as Arc<dyn StationProvider>
+        });
+        self.providers = std::iter::once(Arc::new(RadioBrowser) as Arc<dyn StationProvider>)
+            .chain(lists)
+            .collect();
+        self.provider_labels = self.providers.iter().map(|p| p.name()).collect();
+        if self.search_provider >= self.providers.len() {
+            self.search_provider = 0;
+        }
+    }
+
+    /// `stations` as the user wants to see them: without hidden stations,
+    /// filtered and ordered by the quality preferences
+    fn visible(&self, stations: Vec<Station>) -> Vec<Station> {
+        self.config
+            .quality
+            .apply(self.config.without_hidden(stations))
+    }
+
+    /// Describes the active quality preferences, if any
+    fn quality_filter_text(&self) -> Option<String> {
+        let quality = &self.config.quality;
+        if !quality.is_active() {
+            return None;
+        }
+        let parts: Vec<String> = (quality.min_bitrate > 0)
+            .then(|| fl!("quality-min-bitrate", kbps = quality.min_bitrate))
+            .into_iter()
+            .chain(
+                quality
+                    .preferred_codec
+                    .name()
+                    .map(|codec| fl!("quality-codec-first", codec = codec)),
+            )
+            .collect();
+        Some(fl!("quality-filter-active", filter = parts.join(" · ")))
+    }
+
+    /// Fetch the current lists again so changed quality preferences apply
+    fn reload_quality_filtered(&mut self) -> Task<cosmic::Action<Message>> {
+        let popular = self.reload_popular();
+        if self.search_query.trim().is_empty() && !self.config.search_filters.is_active() {
+            return popular;
+        }
+        Task::batch([popular, self.update(Message::PerformSearch)])
+    }
+
+    /// Fetch the countries, languages and tags offered as search filters,
+    /// once per session
+    fn load_filter_choices(&mut self) -> Task<cosmic::Action<Message>> {
+        if self.filter_choices_requested {
+            return Task::none();
+        }
+        self.filter_choices_requested = true;
+        Task::batch(
+            [
+                (FacetKind::Country, FILTER_CHOICES_LIMIT),
+                (FacetKind::Language, FILTER_CHOICES_LIMIT),
+                (FacetKind::Tag, FILTER_TAGS_LIMIT),
+            ]
+            .map(|(kind, limit)| {
+                Task::perform(
+                    async move { api::facets(kind, limit).await.map_err(Arc::new) },
+                    move |res| Message::FilterChoicesLoaded(kind, res),
+                )
+                .map(Into::into)
+            }),
+        )
+    }
+
+    /// Dropdown labels for the search filters. A saved choice missing from
+    /// the fetched lists (or before they arrive) is kept selectable.
+    fn rebuild_filter_labels(&mut self) {
+        let filters = &self.config.search_filters;
+        if !filters.country.is_empty()
+            && !self
+                .filter_countries
+                .iter()
+                .any(|f| f.code.as_deref() == Some(filters.country.as_str()))
+        {
+            self.filter_countries.insert(
+                0,
+                Facet {
+                    name: filters.country.clone(),
+                    code: Some(filters.country.clone()),
+                    stationcount: 0,
+                },
+            );
+        }
+        if !filters.language.is_empty()
+            && !self
+                .filter_languages
+                .iter()
+                .any(|f| f.name == filters.language)
         {
-            "media-playback-pause-symbolic"
+            self.filter_languages.insert(
+                0,
+                Facet {
+                    name: filters.language.clone(),
+                    ..Default::default()
+                },
+            );
+        }
+        self.country_labels = std::iter::once(fl!("filter-any-country"))
+            .chain(self.filter_countries.iter().map(|f| f.name.clone()))
+            .collect();
+        self.language_labels = std::iter::once(fl!("filter-any-language"))
+            .chain(self.filter_languages.iter().map(|f| f.name.clone()))
+            .collect();
+    }
+
+    /// Open the popup and refresh the lists it shows
+    fn show_popup(&mut self) -> Task<cosmic::Action<Message>> {
+        match self.open_popup() {
+            Some(open) => Task::batch([open, self.load_popular(), self.load_filter_choices()]),
+            None => Task::none(),
+        }
+    }
+
+    /// Create the popup window. Unless pinned it grabs input, so the
+    /// compositor closes it once focus moves elsewhere.
+    fn open_popup(&mut self) -> Option<Task<cosmic::Action<Message>>> {
+        let Some(main_id) = self.core.main_window_id() else {
+            warn!("No main window ID available");
+            return None;
+        };
+        let new_id = Id::unique();
+        self.popup.replace(new_id);
+        let mut popup_settings = self
+            .core
+            .applet
+            .get_popup_settings(main_id, new_id, None, None, None);
+        // The list's height follows the config in the view
+        let (width, _) = self.config.popup_size.dimensions();
+        popup_settings.positioner.size_limits = Limits::NONE
+            .min_width(width as f32)
+            .max_width(width as f32)
+            .min_height(200.0)
+            .max_height(1600.0);
+        popup_settings.grab = !self.popup_pinned;
+        if let Some(toast) = &mut self.toast {
+            toast.start_expiry();
+        }
+        Some(get_popup(popup_settings).chain(self.restore_scroll()))
+    }
+
+    /// Scroll the popup list back to where the active tab was left
+    fn restore_scroll(&self) -> Task<cosmic::Action<Message>> {
+        let offset = self
+            .scroll_offsets
+            .get(&self.active_tab())
+            .copied()
+            .unwrap_or_default();
+        scrollable::snap_to(popup_scroll_id(), offset)
+    }
+
+    /// Stop the search in flight, if any; its results would be dropped
+    /// anyway once the generation moves on
+    fn abort_search(&mut self) {
+        self.search_generation = self.search_generation.wrapping_add(1);
+        self.is_searching = false;
+        self.search_progress = None;
+        if let Some(handle) = self.search_handle.take() {
+            handle.abort();
+        }
+    }
+
+    /// Show the configured popup size in the custom size fields
+    fn reset_popup_size_inputs(&mut self) {
+        let (width, height) = self.config.popup_size.dimensions();
+        self.popup_width_input = width.to_string();
+        self.popup_height_input = height.to_string();
+        self.popup_size_error = None;
+    }
+
+    /// Save changed search filters and run the search again with them
+    fn filters_changed(&mut self) -> Task<cosmic::Action<Message>> {
+        self.rebuild_filter_labels();
+        self.save_config();
+        if self.search_query.trim().is_empty() && !self.config.search_filters.is_active() {
+            self.search_results.clear();
+            self.search_groups.clear();
+            return Task::none();
+        }
+        self.update(Message::PerformSearch)
+    }
+
+    /// Fetch the current station's favicon for the now-playing header ahead
+    /// of the queue. Lite mode sticks to the generic icon.
+    fn load_artwork(&mut self) -> Task<cosmic::Action<Message>> {
+        let Some(url) = self.current_station.as_ref().map(|s| s.favicon.clone()) else {
+            return Task::none();
+        };
+        if self.lite || url.is_empty() {
+            return Task::none();
+        }
+        if let Some(pos) = self.favicon_queue.iter().position(|queued| *queued == url) {
+            self.favicon_queue.remove(pos);
+        } else if !self.favicons_requested.insert(url.clone()) {
+            // Loading, loaded or failed already
+            return Task::none();
+        }
+        fetch_favicon(url)
+    }
+
+    /// Switch lite mode. Its generic icons replace the loaded favicons,
+    /// which are requested again once it's off.
+    fn set_lite(&mut self, lite: bool) {
+        if lite == self.lite {
+            return;
+        }
+        self.lite = lite;
+        self.favicon_queue.clear();
+        self.favicons_requested.clear();
+        if lite {
+            self.favicons.clear();
+            self.favicons_loaded.clear();
         } else {
-            "media-playback-start-symbolic"
+            self.queue_favicons();
+        }
+    }
+
+    /// Queue the favicons of every listed station that hasn't been requested
+    fn queue_favicons(&mut self) {
+        if self.lite {
+            return;
+        }
+        for station in self.stations.iter() {
+            let url = &station.favicon;
+            if !url.is_empty() && self.favicons_requested.insert(url.clone()) {
+                self.favicon_queue.push_back(url.clone());
+            }
+        }
+    }
+
+    /// The station's favicon at `size` pixels, or a generic radio icon while
+    /// it's missing
+    fn view_favicon<'a>(&self, station: &Station, size: u16) -> Element<'a, Message> {
+        match self.favicons.get(&station.favicon) {
+            Some(handle) => widget::image(handle.clone())
+                .width(Length::Fixed(f32::from(size)))
+                .height(Length::Fixed(f32::from(size)))
+                .into(),
+            None => icon::from_name("multimedia-player-symbolic")
+                .size(size)
+                .into(),
+        }
+    }
+
+    /// Have a running screen reader say `text`, through a transient
+    /// notification since the popup may be closed or out of focus
+    fn announce(&self, text: String) -> Task<cosmic::Action<Message>> {
+        let Some(connection) = self.notify_connection.clone() else {
+            return Task::none();
+        };
+        if !self.screen_reader {
+            return Task::none();
+        }
+        let notification = Notification {
+            summary: text,
+            transient: true,
+            ..Default::default()
         };
+        Task::future(async move {
+            if let Err(e) = notify::show(connection, notification).await {
+                debug!("Could not announce: {}", e);
+            }
+        })
+        .discard()
+    }
 
-        let fav_icon = if is_fav {
-            "starred-symbolic"
+    /// Show and publish the simulated stream's next track
+    fn announce_mock_track(&mut self) -> Task<cosmic::Action<Message>> {
+        let Some(title) = self.audio.next_mock_track() else {
+            return Task::none();
+        };
+        debug!("Mock track: {}", title);
+        self.announce_track(title)
+    }
+
+    /// Show and publish `title` as the current track, unless it already is
+    fn announce_track(&mut self, title: String) -> Task<cosmic::Action<Message>> {
+        let Some(station) = self.current_station.clone() else {
+            return Task::none();
+        };
+        if self.track_title.as_ref() == Some(&title) {
+            return Task::none();
+        }
+        self.track_title = Some(title.clone());
+        let notification = if self.config.notify_song {
+            self.notify(&station, station.display_name().to_string(), title.clone())
         } else {
-            "non-starred-symbolic"
+            Task::none()
         };
+        self.events.publish(AppEvent::Metadata {
+            station: Box::new(station),
+            title,
+        });
+        notification
+    }
 
-        widget::row()
-            .spacing(10)
-            .align_y(Alignment::Center)
-            .push(
-                cosmic::iced::widget::button(icon::from_name(play_icon))
-                    .on_press(Message::PlayStation(station.clone())),
-            )
-            .push(widget::text(&station.name).width(cosmic::iced::Length::Fill))
-            .push(
-                cosmic::iced::widget::button(icon::from_name(fav_icon))
-                    .on_press(Message::ToggleFavorite(station.clone())),
+    /// Show a desktop notification about `station`, in place of the last
+    /// one. Offers to stop playback while it plays, and to skip to the next
+    /// favorite when there is one.
+    fn notify(
+        &self,
+        station: &Station,
+        summary: String,
+        body: String,
+    ) -> Task<cosmic::Action<Message>> {
+        let Some(connection) = self.notify_connection.clone() else {
+            return Task::none();
+        };
+        let mut actions = Vec::new();
+        if self.is_playing {
+            actions.push((NotificationAction::Stop, fl!("notification-stop")));
+        }
+        if self.can_skip(true) {
+            actions.push((NotificationAction::Next, fl!("notification-next")));
+        }
+        let notification = Notification {
+            summary,
+            body,
+            image: favicon::cached_path(&station.favicon),
+            actions,
+            replaces: self.notification_id,
+            transient: false,
+        };
+        Task::perform(notify::show(connection, notification), |result| {
+            Message::NotificationShown(result.map_err(Arc::new))
+        })
+        .map(Into::into)
+    }
+
+    /// Tell the desktop that `station` could not be played, if asked to
+    fn notify_stream_error(
+        &self,
+        station: &Station,
+        reason: String,
+    ) -> Task<cosmic::Action<Message>> {
+        if !self.config.notify_errors {
+            return Task::none();
+        }
+        self.notify(
+            station,
+            fl!("notification-stream-failed", name = station.display_name()),
+            reason,
+        )
+    }
+
+    /// Offer favorites, each favorites folder and the history as groups
+    /// MPRIS clients can start
+    fn publish_station_groups(&mut self) {
+        let folders = self.config.favorite_folders();
+        let groups = std::iter::once((PlaylistSource::Favorites, fl!("tab-favorites")))
+            .chain(
+                folders
+                    .iter()
+                    .enumerate()
+                    .map(|(index, folder)| (PlaylistSource::Folder(index), folder.to_string())),
             )
-            .into()
+            .chain(std::iter::once((
+                PlaylistSource::History,
+                fl!("tab-history"),
+            )))
+            .map(|(source, name)| StationCollection {
+                id: source.id(),
+                name,
+            })
+            .collect();
+        self.events.publish(AppEvent::StationCollections(groups));
     }
 
-    fn push_mpris_state(&self) {
-        if let Some(tx) = &self.mpris_tx {
-            let update = if self.is_playing {
-                if let Some(station) = &self.current_station {
-                    MprisStateUpdate::Playing {
-                        station: Box::new(station.clone()),
-                    }
-                } else {
-                    MprisStateUpdate::Stopped
-                }
+    /// Start the current station again, without the check a newly picked
+    /// one gets
+    fn resume_playback(&mut self) {
+        let Some(station) = self.current_station.clone() else {
+            return;
+        };
+        if !self.start_stream(&station) {
+            return;
+        }
+        self.is_playing = true;
+        self.paused_for_output = false;
+        self.buffer_state = Some(BufferState::Buffering(0));
+        debug!("Resumed playback: {}", station.name);
+        self.publish_playback_state();
+    }
+
+    /// Stop the stream, or the check before one starts
+    fn stop_playback(&mut self) {
+        if self.is_connecting {
+            // Abandon the pending pre-flight check
+            self.stream_check_generation = self.stream_check_generation.wrapping_add(1);
+            self.is_connecting = false;
+        }
+        if self.is_playing {
+            self.audio.stop();
+            self.is_playing = false;
+            debug!("Stopped playback");
+        }
+        // The next stream starts at full volume
+        if self.ducked == Some(DuckMode::Lower) {
+            self.ducked = None;
+        }
+        self.publish_playback_state();
+    }
+
+    /// The volume the main stream plays at: muted during a preview and
+    /// turned down while other audio plays
+    fn playback_volume(&self) -> u8 {
+        if self.preview_station.is_some() {
+            return 0;
+        }
+        match self.ducked {
+            Some(DuckMode::Lower) => duck::lowered(self.config.volume, self.config.duck_volume),
+            _ => self.config.volume,
+        }
+    }
+
+    fn publish_playback_state(&mut self) {
+        if self.is_playing && std::mem::take(&mut self.mpris_quit) {
+            // The new server reports `MprisEvent::Ready` and gets the state
+            // then
+            self.mpris_session = self.mpris_session.wrapping_add(1);
+        }
+        if !self.is_playing {
+            // A stopped stream has no current track
+            self.track_title = None;
+        }
+        let playback = match &self.current_station {
+            Some(station) if self.is_playing => PlaybackEvent::Started {
+                station: Box::new(station.clone()),
+            },
+            _ => PlaybackEvent::Stopped,
+        };
+        self.events.publish(AppEvent::Playback(playback));
+        self.events
+            .publish(AppEvent::Playback(PlaybackEvent::Volume(
+                self.config.volume,
+            )));
+        self.publish_navigation();
+        self.remember_playback();
+    }
+
+    /// Whether skipping in this direction leads to a favorite
+    fn can_skip(&self, forward: bool) -> bool {
+        let current = self.current_station.as_ref().map(|s| &s.id);
+        self.config.skip_favorite(current, forward, 0).is_some()
+    }
+
+    fn publish_navigation(&mut self) {
+        let navigation = Navigation {
+            shuffle: self.config.shuffle_favorites,
+            looping: self.config.loop_favorites,
+            can_go_next: self.can_skip(true),
+            can_go_previous: self.can_skip(false),
+        };
+        self.events
+            .publish(AppEvent::Playback(PlaybackEvent::Navigation(navigation)));
+    }
+
+    /// Persist the current station and whether it plays, so the next start
+    /// can show or resume it, and record the play in the history
+    fn remember_playback(&mut self) {
+        let history_changed = self.record_history();
+        if !history_changed
+            && self.config.last_station == self.current_station
+            && self.config.was_playing == self.is_playing
+        {
+            return;
+        }
+        if history_changed {
+            self.sync_stations();
+        }
+        self.config.last_station = self.current_station.clone();
+        self.config.was_playing = self.is_playing;
+        self.write_config();
+    }
+
+    /// Close the running history entry and open one for the station now
+    /// playing, if that changed. Returns whether the history was touched.
+    fn record_history(&mut self) -> bool {
+        let playing = self
+            .current_station
+            .as_ref()
+            .filter(|_| self.is_playing)
+            .map(|s| s.id.clone());
+        if playing == self.history_playing {
+            return false;
+        }
+        let now = journal::now();
+        if let Some(previous) = &self.history_playing {
+            history::finish(&mut self.config.history, previous, now);
+        }
+        if let (Some(station), Some(_)) = (&self.current_station, &playing) {
+            history::start(
+                &mut self.config.history,
+                station,
+                now,
+                self.config.history_max_entries,
+            );
+            history::push_recent(&mut self.config.recent_stations, station);
+        }
+        self.history_playing = playing;
+        true
+    }
+
+    /// Publish a network event when a directory request's outcome changes
+    /// whether the servers look reachable. Error responses still count as
+    /// online; only failures to connect at all mean offline.
+    fn track_network<T>(&mut self, result: &Result<T, Arc<ApiError>>) {
+        let online = match result {
+            Ok(_) => true,
+            Err(e) => !matches!(**e, ApiError::RequestFailed(_) | ApiError::Timeout(_)),
+        };
+        if online == self.online {
+            return;
+        }
+        self.online = online;
+        // One slow mirror doesn't mean the stream is cut off; with
+        // NetworkManager watching, only the banner follows requests
+        if !self.network_monitored {
+            let event = network_event(online);
+            self.events.publish(AppEvent::Network(event));
+        }
+    }
+
+    /// Connectivity as NetworkManager reports it, which from now on is the
+    /// only source of network events
+    fn set_online(&mut self, online: bool) {
+        self.network_monitored = true;
+        self.online = online;
+        let event = network_event(online);
+        self.events.publish(AppEvent::Network(event));
+    }
+
+    /// Take over a config written by someone else. This instance's own
+    /// playback state wins; the lists and preferences are replaced.
+    fn reload_config(&mut self, mut config: Config) -> Task<cosmic::Action<Message>> {
+        config.last_station = self.config.last_station.clone();
+        config.was_playing = self.config.was_playing;
+        if self.volume_save_pending {
+            // Our newer volume just hasn't been written yet
+            config.volume = self.config.volume;
+        }
+        if config == self.config {
+            // Our own write coming back
+            return Task::none();
+        }
+        info!("Config changed on disk, reloading");
+        let old = std::mem::replace(&mut self.config, config);
+        self.apply_replaced_config();
+
+        // The current station follows edits made to its favorite elsewhere
+        let playing_url = self
+            .current_station
+            .as_ref()
+            .map(|s| s.stream_url().to_string());
+        if let Some(current) = &mut self.current_station {
+            if let Some(favorite) = self.config.favorites.iter().find(|s| s.is_same(current)) {
+                current.alias.clone_from(&favorite.alias);
+                current
+                    .stream_override
+                    .clone_from(&favorite.stream_override);
+            }
+        }
+        let stream_changed =
+            playing_url.as_deref() != self.current_station.as_ref().map(Station::stream_url);
+        if stream_changed
+            || old.audio_backend != self.config.audio_backend
+            || old.lite_mode != self.config.lite_mode
+            || old.proxy_url != self.config.proxy_url
+            || old.gst_buffer_ms != self.config.gst_buffer_ms
+        {
+            self.apply_playback_settings();
+        }
+
+        let favorite_ids: HashSet<&StationId> =
+            self.config.favorites.iter().map(|s| &s.id).collect();
+        self.selected_favorites
+            .retain(|id| favorite_ids.contains(id));
+        if self
+            .editing_favorite
+            .as_ref()
+            .is_some_and(|edit| !favorite_ids.contains(&edit.station))
+        {
+            self.editing_favorite = None;
+        }
+        // Changes made elsewhere were journaled there
+        self.journaled_favorites = self.config.favorites.clone();
+        self.reindex_stations();
+        self.events.publish(AppEvent::ConfigChanged);
+        self.publish_station_groups();
+        self.publish_playback_state();
+
+        if old.quality != self.config.quality
+            || old.show_broken_stations != self.config.show_broken_stations
+            || old.hidden_stations != self.config.hidden_stations
+        {
+            return self.reload_quality_filtered();
+        }
+        Task::none()
+    }
+
+    fn refresh_subscriptions(&self) -> Task<cosmic::Action<Message>> {
+        Task::batch(
+            self.config
+                .subscriptions
+                .iter()
+                .map(|subscription| fetch_subscription(subscription.url.clone())),
+        )
+    }
+
+    /// Push every runtime setting from a restored or reloaded config to the
+    /// API client and the players. Playback isn't restarted.
+    fn apply_replaced_config(&mut self) {
+        if let Err(e) = api::set_base_url(&self.config.api_base_url) {
+            warn!(
+                "Ignoring invalid API endpoint '{}': {}",
+                self.config.api_base_url, e
+            );
+        }
+        if let Err(e) = api::set_proxy(&self.config.proxy_url) {
+            warn!("Ignoring invalid proxy '{}': {}", self.config.proxy_url, e);
+        }
+        api::set_request_timeout(self.config.request_timeout_secs);
+        api::set_disk_cache(self.config.cache_on_disk);
+        self.api_base_url_input = self.config.api_base_url.clone();
+        self.proxy_input = self.config.proxy_url.clone();
+        self.listenbrainz_token_input = self.config.listenbrainz_token.clone();
+
+        let stream_proxy = api::validate_base_url(&self.config.proxy_url).ok();
+        for player in [&self.audio, &self.preview_audio] {
+            player.set_http_proxy(stream_proxy.clone());
+            player.set_buffer_ms(self.config.gst_buffer_ms);
+            player.set_max_volume(self.config.max_volume);
+        }
+        self.config.volume = self.config.volume.min(self.config.max_volume);
+        self.audio.set_volume(self.playback_volume() as f32);
+        self.set_lite(self.config.lite_mode.is_active(self.low_memory));
+        self.location_input = self.config.location.clone();
+        self.profile_labels = profile_labels(&self.config);
+        self.autoplay_labels = autoplay_labels(&self.config);
+        self.rebuild_filter_labels();
+        self.sync_file_input = self.config.sync_file.clone();
+        self.reset_popup_size_inputs();
+        // The sync file is re-read on the next poll
+        self.reset_sync();
+    }
+
+    /// Catch up with the favorites, volume and filters of the profile just
+    /// switched to
+    fn profile_switched(&mut self) -> Task<cosmic::Action<Message>> {
+        // Only the default profile syncs; coming back to it re-reads the file
+        self.reset_sync();
+        // Another profile's favorites aren't changes to journal
+        self.journaled_favorites = self.config.favorites.clone();
+        self.selected_favorites.clear();
+        self.editing_favorite = None;
+        self.favorites_page = 0;
+        self.config.volume = self.config.volume.min(self.config.max_volume);
+        self.audio.set_volume(self.playback_volume() as f32);
+        self.profile_labels = profile_labels(&self.config);
+        self.save_config();
+        self.publish_playback_state();
+        let sync_file = self.update(Message::SyncFileTick);
+        Task::batch([self.reload_quality_filtered(), sync_file])
+    }
+
+    /// Push the backend and lite settings to both players, restarting the
+    /// current station so the change is heard right away
+    fn apply_playback_settings(&mut self) {
+        let backend = playback_backend(self.config.audio_backend, self.lite);
+        for player in [&self.audio, &self.preview_audio] {
+            player.set_backend(backend);
+            player.set_lite(self.lite);
+        }
+        if self.is_playing {
+            if let Some(station) = self.current_station.clone() {
+                self.start_stream(&station);
+            }
+        }
+    }
+
+    /// Hand `station` to the main player. When it won't play, the player
+    /// is left stopped and the reason shown.
+    fn start_stream(&mut self, station: &Station) -> bool {
+        let url = station.stream_url().to_string();
+        let res = self
+            .audio
+            .play(url, self.playback_volume(), station.has_user_stream());
+        let Err(e) = res else {
+            return true;
+        };
+        warn!("Not playing {}: {}", station.name, e);
+        self.show_toast(
+            Severity::Error,
+            audio_error_text(&e),
+            Some(RetryAction::Playback),
+        );
+        if self.is_playing {
+            self.is_playing = false;
+            self.buffer_state = None;
+            self.publish_playback_state();
+        }
+        false
+    }
+
+    /// Re-index every list a station can be clicked in. Favorites go last so
+    /// the user's own copy (renamed, tagged) wins over directory results.
+    fn sync_stations(&mut self) {
+        let subscribed = self.config.subscriptions.iter().flat_map(|s| &s.stations);
+        self.stations.rebuild(
+            self.search_results
+                .iter()
+                .chain(&self.popular_voted)
+                .chain(&self.popular_clicked)
+                .chain(&self.popular_nearby)
+                .chain(subscribed)
+                .chain(&self.current_station)
+                .chain(self.config.history.iter().map(|e| &e.station))
+                .chain(&self.config.recent_stations)
+                .chain(&self.config.favorites),
+        );
+        self.queue_favicons();
+    }
+
+    fn save_config(&mut self) {
+        // Most settings leave the station lists alone; only re-index when
+        // the favorites or a subscribed list changed
+        let favorites_changed = self.config.favorites != self.indexed_favorites;
+        if favorites_changed || self.config.subscriptions != self.indexed_subscriptions {
+            self.reindex_stations();
+        }
+        // The sync file mirrors nothing but the favorites
+        if favorites_changed {
+            self.write_sync_file();
+        }
+        self.write_config();
+    }
+
+    /// Rebuild everything derived from the favorites and subscribed lists,
+    /// journaling what changed in the favorites
+    fn reindex_stations(&mut self) {
+        self.favorites_index = StationIndex::build(&self.config.favorites);
+        self.refresh_favorites_tab();
+        self.autoplay_labels = autoplay_labels(&self.config);
+        self.sync_stations();
+        self.rebuild_providers();
+        let changes = journal::diff_favorites(
+            &self.journaled_favorites,
+            &self.config.favorites,
+            journal::now(),
+        );
+        if !changes.is_empty() {
+            let retained = if self.lite {
+                journal::LITE_JOURNAL_ENTRIES
             } else {
-                MprisStateUpdate::Stopped
+                journal::MAX_JOURNAL_ENTRIES
             };
-            let _ = tx.send(update);
-            let _ = tx.send(MprisStateUpdate::Volume(self.config.volume));
+            journal::append(&mut self.config.favorites_journal, changes, retained);
+            self.journaled_favorites = self.config.favorites.clone();
+        }
+        self.indexed_favorites = self.config.favorites.clone();
+        self.indexed_subscriptions = self.config.subscriptions.clone();
+    }
+
+    /// Write the volume once it stops changing
+    fn save_volume(&mut self) -> Task<cosmic::Action<Message>> {
+        self.volume_save_generation = self.volume_save_generation.wrapping_add(1);
+        self.volume_save_pending = true;
+        let generation = self.volume_save_generation;
+        Task::perform(tokio::time::sleep(VOLUME_SAVE_DELAY), move |_| {
+            Message::SaveVolume(generation)
+        })
+        .map(Into::into)
+    }
+
+    /// Write just the volume; the rest of the config is unchanged
+    fn write_volume(&mut self) {
+        if let Err(e) = self.config_handler.set("volume", self.config.volume) {
+            error!("Failed to save volume: {:?}", e);
+        }
+    }
+
+    /// Text shown next to the panel icon while a station plays. Panels
+    /// along the side of the screen have no room for it.
+    fn panel_text(&self) -> Option<String> {
+        if !self.core.applet.is_horizontal() || !(self.is_playing || self.is_connecting) {
+            return None;
+        }
+        let station = self.current_station.as_ref()?;
+        self.config
+            .panel_text
+            .label(Some(station.display_name()), self.track_title.as_deref())
+    }
+
+    /// Show `text` at the top of the popup, replacing any earlier toast
+    fn show_toast(&mut self, severity: Severity, text: String, retry: Option<RetryAction>) {
+        let mut toast = Toast {
+            severity,
+            text,
+            retry,
+            expires: None,
+        };
+        if self.popup.is_some() {
+            toast.start_expiry();
+        }
+        self.toast = Some(toast);
+    }
+
+    /// Drop the toast if it offers to retry `action`, which is running again
+    fn dismiss_toast(&mut self, action: RetryAction) {
+        if self.toast.as_ref().is_some_and(|t| t.retry == Some(action)) {
+            self.toast = None;
+        }
+    }
+
+    /// Favorites are mirrored to the sync file only in the default profile
+    fn syncing(&self) -> bool {
+        !self.config.sync_file.is_empty() && self.config.active_profile.is_empty()
+    }
+
+    /// Forget what was read from the sync file and hold off writing it
+    /// until it has been read again
+    fn reset_sync(&mut self) {
+        self.sync_generation = self.sync_generation.wrapping_add(1);
+        self.sync_ready = false;
+        self.sync_seen = None;
+        self.sync_written.clear();
+        self.sync_file_error = None;
+    }
+
+    /// Mirror the favorites to the sync file if they differ from what it holds
+    fn write_sync_file(&mut self) {
+        if !self.sync_ready || !self.syncing() {
+            return;
+        }
+        let path = PathBuf::from(&self.config.sync_file);
+        let format = SyncFormat::for_path(&path);
+        let text = match sync::render(format, &self.config.favorites) {
+            Ok(text) => text,
+            Err(e) => {
+                error!("Failed to serialize favorites for syncing: {}", e);
+                return;
+            }
+        };
+        if text == self.sync_written {
+            return;
+        }
+        match safe_write::write_atomic(&path, text.as_bytes()) {
+            Ok(()) => {
+                self.sync_generation = self.sync_generation.wrapping_add(1);
+                self.sync_seen = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+                self.config.sync_base = sync::snapshot(format, &self.config.favorites);
+                self.sync_written = text;
+                self.sync_file_error = None;
+            }
+            Err(e) => {
+                warn!("Writing sync file {} failed: {}", path.display(), e);
+                self.sync_file_error = Some(io_error_text(&e));
+            }
         }
     }
 
-    fn save_config(&self) {
+    fn write_config(&mut self) {
         if let Err(e) = self.config.write_entry(&self.config_handler) {
             error!("Failed to save config: {:?}", e);
         } else {
             debug!("Config saved");
+            self.events.publish(AppEvent::ConfigChanged);
+            self.publish_station_groups();
+            self.publish_navigation();
+        }
+    }
+}
+
+fn network_event(online: bool) -> NetworkEvent {
+    if online {
+        NetworkEvent::Online
+    } else {
+        NetworkEvent::Offline
+    }
+}
+
+/// A fresh random number, for picking a shuffled favorite
+fn random_seed() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+fn fetch_subscription(url: String) -> Task<cosmic::Action<Message>> {
+    Task::perform(
+        async move {
+            let result = api::fetch_station_list(url.clone()).await.map_err(Arc::new);
+            (url, result)
+        },
+        |(url, result)| Message::SubscriptionFetched(url, result),
+    )
+    .map(Into::into)
+}
+
+/// Ask for an OPML or JSON station list and read it. `Ok(None)` if the
+/// dialog was cancelled.
+async fn import_station_file() -> Result<Option<Vec<Station>>, Arc<FileError>> {
+    let dialog = file_chooser::open::Dialog::new()
+        .title(fl!("import-favorites"))
+        .filter(FileFilter::new("OPML").glob("*.opml").glob("*.xml"))
+        .filter(FileFilter::new("JSON").glob("*.json"));
+    let response = match dialog.open_file().await {
+        Ok(response) => response,
+        Err(file_chooser::Error::Cancelled) => return Ok(None),
+        Err(e) => return Err(file_error(FileError::Dialog(e.to_string()))),
+    };
+    let path = response
+        .url()
+        .to_file_path()
+        .map_err(|()| file_error(FileError::NotLocal))?;
+    let bytes = std::fs::read(&path).map_err(file_error)?;
+    api::parse_station_list(&bytes)
+        .map(Some)
+        .map_err(file_error)
+}
+
+/// Read a settings backup chosen in the file dialog. `Ok(None)` if the
+/// dialog was cancelled.
+async fn open_backup_file() -> Result<Option<Box<Config>>, Arc<FileError>> {
+    let dialog = file_chooser::open::Dialog::new()
+        .title(fl!("restore-settings"))
+        .filter(FileFilter::new("JSON").glob("*.json"));
+    let response = match dialog.open_file().await {
+        Ok(response) => response,
+        Err(file_chooser::Error::Cancelled) => return Ok(None),
+        Err(e) => return Err(file_error(FileError::Dialog(e.to_string()))),
+    };
+    let path = response
+        .url()
+        .to_file_path()
+        .map_err(|()| file_error(FileError::NotLocal))?;
+    let bytes = std::fs::read(&path).map_err(file_error)?;
+    backup::parse(&bytes)
+        .map(|config| Some(Box::new(config)))
+        .map_err(file_error)
+}
+
+/// Ask where to save an export, suggesting `file_name`, and write
+/// `document` there. `Ok(None)` if the dialog was cancelled.
+async fn save_document(
+    title: String,
+    file_name: String,
+    document: String,
+) -> Result<Option<PathBuf>, Arc<FileError>> {
+    let dialog = file_chooser::save::Dialog::new()
+        .title(title)
+        .file_name(file_name);
+    let response = match dialog.save_file().await {
+        Ok(response) => response,
+        Err(file_chooser::Error::Cancelled) => return Ok(None),
+        Err(e) => return Err(file_error(FileError::Dialog(e.to_string()))),
+    };
+    let Some(url) = response.url() else {
+        return Ok(None);
+    };
+    let path = url
+        .to_file_path()
+        .map_err(|()| file_error(FileError::NotLocal))?;
+    safe_write::write_atomic(&path, document.as_bytes()).map_err(file_error)?;
+    Ok(Some(path))
+}
+
+fn file_error(err: impl Into<FileError>) -> Arc<FileError> {
+    Arc::new(err.into())
+}
+
+/// A short, localized explanation of a failed directory request
+fn api_error_text(err: &ApiError) -> String {
+    match err {
+        ApiError::Timeout(secs) => fl!("error-timeout", seconds = *secs),
+        ApiError::ErrorResponse { status: 429, .. } => fl!("error-rate-limited"),
+        ApiError::ErrorResponse { status, .. } => fl!("error-server", status = *status),
+        ApiError::RequestFailed(_) => fl!("error-network"),
+        ApiError::JsonParseFailed(_) | ApiError::InvalidResponse(_) => fl!("error-bad-response"),
+        ApiError::StationNotFound(_) => fl!("error-station-not-found"),
+        ApiError::InvalidUrl(_) => fl!("error-invalid-url"),
+    }
+}
+
+/// A short, localized explanation of why a stream wasn't played
+fn stream_error_text(err: &StreamError) -> String {
+    match err {
+        StreamError::Offline(status) => fl!("stream-offline", status = *status),
+        StreamError::Timeout(secs) => fl!("stream-timeout", seconds = *secs),
+        StreamError::Tls(_) => fl!("stream-tls"),
+        StreamError::Unreachable(_) => fl!("stream-unreachable"),
+    }
+}
+
+/// A short, localized explanation of why a typed-in URL was refused
+fn url_error_text(err: UrlError) -> String {
+    match err {
+        UrlError::Malformed | UrlError::NotHttp => fl!("error-invalid-url"),
+        UrlError::Icon => fl!("error-invalid-icon-url"),
+    }
+}
+
+/// A short, localized explanation of a failed file operation
+fn io_error_text(err: &io::Error) -> String {
+    match err.kind() {
+        io::ErrorKind::NotFound => fl!("file-not-found"),
+        io::ErrorKind::PermissionDenied => fl!("file-permission-denied"),
+        _ => fl!("file-io-failed"),
+    }
+}
+
+fn backup_error_text(err: &BackupError) -> String {
+    match err {
+        BackupError::NotABackup => fl!("backup-not-a-backup"),
+        BackupError::UnsupportedVersion(_) => fl!("backup-too-new"),
+        BackupError::Parse(_) => fl!("backup-malformed"),
+    }
+}
+
+fn file_error_text(err: &FileError) -> String {
+    match err {
+        FileError::Dialog(_) => fl!("file-dialog-failed"),
+        FileError::NotLocal => fl!("file-not-local"),
+        FileError::Io(e) => io_error_text(e),
+        FileError::StationList(_) => fl!("file-not-station-list"),
+        FileError::Backup(e) => backup_error_text(e),
+    }
+}
+
+/// A short, localized explanation of why the player didn't start
+fn audio_error_text(err: &AudioError) -> String {
+    match err {
+        AudioError::RejectedUrl(_) => fl!("stream-rejected"),
+        _ => fl!("player-failed"),
+    }
+}
+
+/// The configured backend, or the lightest one available in lite mode
+fn playback_backend(configured: AudioBackend, lite: bool) -> AudioBackend {
+    if lite && AudioBackend::GStreamer.is_available() {
+        AudioBackend::GStreamer
+    } else {
+        configured
+    }
+}
+
+fn profile_labels(config: &Config) -> Vec<String> {
+    config
+        .profile_names()
+        .into_iter()
+        .map(|name| {
+            if name.is_empty() {
+                fl!("profile-default")
+            } else {
+                name.to_string()
+            }
+        })
+        .collect()
+}
+
+fn view_toast(toast: &Toast) -> Element<'_, Message> {
+    let mut text = widget::text(toast.text.clone()).width(Length::Fill);
+    if let Some(color) = toast.severity.color() {
+        text = text.class(cosmic::theme::Text::Color(color));
+    }
+    let mut row = widget::row()
+        .spacing(10)
+        .align_y(Alignment::Center)
+        .push(icon::from_name(toast.severity.icon()).size(16))
+        .push(text);
+    if let Some(action) = toast.retry {
+        row = row.push(
+            cosmic::iced::widget::button(widget::text(fl!("retry")))
+                .on_press(Message::Retry(action)),
+        );
+    }
+    row = row.push(
+        icon_button("window-close-symbolic", fl!("a11y-close")).on_press(Message::DismissToast),
+    );
+    widget::container(row)
+        .padding(8)
+        .width(Length::Fill)
+        .class(cosmic::theme::Container::Card)
+        .into()
+}
+
+fn fetch_favicon(url: String) -> Task<cosmic::Action<Message>> {
+    let key = url.clone();
+    Task::perform(
+        async move { favicon::fetch(url).await.map_err(Arc::new) },
+        move |res| Message::FaviconLoaded(key.clone(), res),
+    )
+    .map(Into::into)
+}
+
+fn autoplay_labels(config: &Config) -> Vec<String> {
+    std::iter::once(fl!("autoplay-last-station"))
+        .chain(
+            config
+                .active_favorites()
+                .map(|s| s.display_name().to_string()),
+        )
+        .collect()
+}
+
+fn panel_text_label(text: PanelText) -> String {
+    match text {
+        PanelText::Off => fl!("panel-text-off"),
+        PanelText::Station => fl!("panel-text-station"),
+        PanelText::Song => fl!("panel-text-song"),
+        PanelText::Both => fl!("panel-text-both"),
+    }
+}
+
+fn click_action_label(action: ClickAction) -> String {
+    match action {
+        ClickAction::TogglePopup => fl!("panel-toggle-popup"),
+        ClickAction::PlayPause => fl!("panel-play-pause"),
+        ClickAction::Nothing => fl!("panel-nothing"),
+    }
+}
+
+/// Station name shortened to fit a quick-access button
+fn chip_label(name: &str) -> String {
+    const MAX_CHARS: usize = 18;
+    let name = name.trim();
+    if name.chars().count() <= MAX_CHARS {
+        return name.to_string();
+    }
+    let mut label: String = name.chars().take(MAX_CHARS - 1).collect();
+    label.truncate(label.trim_end().len());
+    label.push('…');
+    label
+}
+
+/// The popup's scrolling tab content
+/// Grey stand-in for a station row, sized like the real ones
+fn view_skeleton_row(compact: bool) -> Element<'static, Message> {
+    let size = if compact { 16.0 } else { 24.0 };
+    let bar = |width, height| {
+        widget::container(widget::horizontal_space())
+            .width(width)
+            .height(Length::Fixed(height))
+            .class(cosmic::theme::Container::Card)
+    };
+    widget::row()
+        .spacing(if compact { 6 } else { 10 })
+        .align_y(Alignment::Center)
+        .padding([4, 0])
+        .push(bar(Length::Fixed(size), size))
+        .push(bar(Length::Fill, size * 0.6))
+        .into()
+}
+
+/// Icon-only button, named `label` for screen readers
+fn icon_button<'a>(name: &'static str, label: String) -> widget::Button<'a, Message> {
+    widget::button::icon(icon::from_name(name).handle()).name(label)
+}
+
+/// An icon button's blank stand-in, focusable and named but drawn empty
+fn hidden_icon_button<'a>(label: String) -> widget::Button<'a, Message> {
+    widget::button::custom(
+        widget::horizontal_space()
+            .width(Length::Fixed(16.0))
+            .height(Length::Fixed(16.0)),
+    )
+    .class(cosmic::theme::Button::Icon)
+    .name(label)
+}
+
+/// The popup shortcut `key` stands for, if any
+fn shortcut_key(key: &Key) -> Option<ShortcutKey> {
+    match key {
+        Key::Named(Named::Space) => Some(ShortcutKey::Space),
+        Key::Named(Named::ArrowUp) => Some(ShortcutKey::Up),
+        Key::Named(Named::ArrowDown) => Some(ShortcutKey::Down),
+        Key::Named(Named::Escape) => Some(ShortcutKey::Escape),
+        Key::Character(text) => {
+            let mut chars = text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(ShortcutKey::Char(c)),
+                _ => None,
+            }
         }
+        _ => None,
+    }
+}
+
+fn shortcut_key_text(key: ShortcutKey) -> String {
+    match key {
+        ShortcutKey::Space => fl!("shortcut-key-space"),
+        key => key.label(),
+    }
+}
+
+fn shortcut_action_text(action: ShortcutAction) -> String {
+    match action {
+        ShortcutAction::PlayPause => fl!("shortcut-play-pause"),
+        ShortcutAction::VolumeUp => fl!("shortcut-volume-up"),
+        ShortcutAction::VolumeDown => fl!("shortcut-volume-down"),
+        ShortcutAction::Close => fl!("shortcut-close"),
+        ShortcutAction::ToggleHelp => fl!("shortcut-help"),
+    }
+}
+
+/// Tooltips open away from the screen edge the panel is on
+fn tooltip_position(anchor: PanelAnchor) -> widget::tooltip::Position {
+    match anchor {
+        PanelAnchor::Top => widget::tooltip::Position::Bottom,
+        PanelAnchor::Bottom => widget::tooltip::Position::Top,
+        PanelAnchor::Left => widget::tooltip::Position::Right,
+        PanelAnchor::Right => widget::tooltip::Position::Left,
+    }
+}
+
+fn favorite_toggle_label(is_fav: bool, name: &str) -> String {
+    if is_fav {
+        fl!("a11y-remove-favorite", name = name)
+    } else {
+        fl!("a11y-add-favorite", name = name)
+    }
+}
+
+fn volume_icon(volume: u8) -> &'static str {
+    if volume == 0 {
+        "audio-volume-muted-symbolic"
+    } else if volume < 33 {
+        "audio-volume-low-symbolic"
+    } else if volume < 66 {
+        "audio-volume-medium-symbolic"
+    } else {
+        "audio-volume-high-symbolic"
+    }
+}
+
+fn popup_scroll_id() -> scrollable::Id {
+    scrollable::Id::new("popup-list")
+}
+
+fn genre_label(tag: &str) -> String {
+    match tag {
+        "pop" => fl!("genre-pop"),
+        "rock" => fl!("genre-rock"),
+        "jazz" => fl!("genre-jazz"),
+        "news" => fl!("genre-news"),
+        "classical" => fl!("genre-classical"),
+        "electronic" => fl!("genre-electronic"),
+        "lofi" => fl!("genre-lofi"),
+        other => other.to_string(),
+    }
+}
+
+fn tab_label(tab: PopupTab) -> String {
+    match tab {
+        PopupTab::Favorites => fl!("tab-favorites"),
+        PopupTab::Search => fl!("tab-search"),
+        PopupTab::Browse => fl!("tab-browse"),
+        PopupTab::History => fl!("tab-history"),
+        PopupTab::Settings => fl!("tab-settings"),
+    }
+}
+
+fn favorites_tab_label(count: usize) -> String {
+    if count == 0 {
+        fl!("tab-favorites")
+    } else {
+        fl!("tab-favorites-count", count = count)
+    }
+}
+
+fn select_tab(tabs: &mut segmented_button::SingleSelectModel, tab: PopupTab) {
+    let entity = tabs
+        .iter()
+        .find(|&entity| tabs.data::<PopupTab>(entity) == Some(&tab));
+    if let Some(entity) = entity {
+        tabs.activate(entity);
     }
 }
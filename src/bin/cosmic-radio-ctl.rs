@@ -0,0 +1,40 @@
+//! Control the radio applet from scripts and keybindings, e.g.
+//! `cosmic-radio-ctl play "Groove Salad"` or `cosmic-radio-ctl status --json`
+
+use cosmic_ext_applet_radio_lib::ctl::{self, Command};
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let command = match Command::parse(std::env::args().skip(1)) {
+        Ok(Command::Help) => {
+            println!("{}", ctl::USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{e}\n\n{}", ctl::USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let result = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())
+        .and_then(|rt| rt.block_on(ctl::run(&command)).map_err(|e| e.to_string()));
+    match result {
+        Ok(Some(status)) => {
+            if matches!(command, Command::Status { json: true }) {
+                println!("{}", status.to_json());
+            } else {
+                println!("{status}");
+            }
+            ExitCode::SUCCESS
+        }
+        Ok(None) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Could not reach the radio applet, is it on the panel? {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Cache of directory responses
+//!
+//! Results are keyed by endpoint and query, served without a request while
+//! fresh, and kept a while longer as a fallback when every mirror is
+//! unreachable. The cache can optionally be persisted so it survives restarts.
+
+use crate::api::Station;
+use crate::safe_write;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+
+/// Responses younger than this are used without asking the network
+pub const FRESH_SECS: u64 = 10 * 60;
+
+/// Older responses are only used offline, and dropped after this long
+pub const MAX_STALE_SECS: u64 = 24 * 60 * 60;
+
+/// Oldest responses are evicted beyond this many
+const MAX_ENTRIES: usize = 64;
+
+const CACHE_FILE: &str = "api-cache.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    /// Seconds since the Unix epoch
+    stored_at: u64,
+    stations: Vec<Station>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResponseCache {
+    entries: HashMap<String, CacheEntry>,
+}
+
+impl ResponseCache {
+    /// Cache key for a request; parameter order doesn't matter
+    pub fn key(path: &str, params: &[(&str, &str)]) -> String {
+        let mut params: Vec<String> = params.iter().map(|(k, v)| format!("{k}={v}")).collect();
+        params.sort_unstable();
+        format!("{}?{}", path, params.join("&"))
+    }
+
+    pub fn get_fresh(&self, key: &str, now: u64) -> Option<Vec<Station>> {
+        self.get_within(key, now, FRESH_SECS)
+    }
+
+    /// Any response not yet expired, for when the network is unavailable
+    pub fn get_stale(&self, key: &str, now: u64) -> Option<Vec<Station>> {
+        self.get_within(key, now, MAX_STALE_SECS)
+    }
+
+    fn get_within(&self, key: &str, now: u64, max_age: u64) -> Option<Vec<Station>> {
+        self.entries
+            .get(key)
+            .filter(|entry| now.saturating_sub(entry.stored_at) < max_age)
+            .map(|entry| entry.stations.clone())
+    }
+
+    pub fn insert(&mut self, key: String, stations: Vec<Station>, now: u64) {
+        self.entries.insert(
+            key,
+            CacheEntry {
+                stored_at: now,
+                stations,
+            },
+        );
+        self.prune(now);
+    }
+
+    /// Drop expired entries, then the oldest ones beyond [`MAX_ENTRIES`]
+    fn prune(&mut self, now: u64) {
+        self.entries
+            .retain(|_, entry| now.saturating_sub(entry.stored_at) < MAX_STALE_SECS);
+        while self.entries.len() > MAX_ENTRIES {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored_at)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(key) => self.entries.remove(&key),
+                None => break,
+            };
+        }
+    }
+
+    /// Merge entries from another cache, keeping whichever copy is newer
+    pub fn merge(&mut self, other: ResponseCache, now: u64) {
+        for (key, entry) in other.entries {
+            let newer = self
+                .entries
+                .get(&key)
+                .is_none_or(|existing| existing.stored_at < entry.stored_at);
+            if newer {
+                self.entries.insert(key, entry);
+            }
+        }
+        self.prune(now);
+    }
+
+    pub fn load(path: &Path) -> Self {
+        match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+                warn!(
+                    "Ignoring unreadable response cache {}: {}",
+                    path.display(),
+                    e
+                );
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) {
+        match safe_write::write_json_atomic(path, self) {
+            Ok(()) => debug!("Response cache saved to {}", path.display()),
+            Err(e) => warn!("Failed to save response cache to {}: {}", path.display(), e),
+        }
+    }
+}
+
+/// `$XDG_CACHE_HOME/cosmic-ext-applet-radio`, or `~/.cache/...` without it
+pub fn cache_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(base.join("cosmic-ext-applet-radio"))
+}
+
+pub fn cache_file() -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join(CACHE_FILE))
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stations(name: &str) -> Vec<Station> {
+        vec![Station {
+            name: name.to_string(),
+            ..Default::default()
+        }]
+    }
+
+    #[test]
+    fn test_key_ignores_param_order() {
+        assert_eq!(
+            ResponseCache::key("json/stations/search", &[("name", "jazz"), ("limit", "20")]),
+            ResponseCache::key("json/stations/search", &[("limit", "20"), ("name", "jazz")])
+        );
+        assert_ne!(
+            ResponseCache::key("json/stations/search", &[("name", "jazz")]),
+            ResponseCache::key("json/stations/search", &[("name", "rock")])
+        );
+    }
+
+    #[test]
+    fn test_fresh_and_stale() {
+        let mut cache = ResponseCache::default();
+        cache.insert("k".to_string(), stations("A"), 1000);
+
+        assert!(cache.get_fresh("k", 1000 + FRESH_SECS - 1).is_some());
+        assert!(cache.get_fresh("k", 1000 + FRESH_SECS).is_none());
+        assert!(cache.get_stale("k", 1000 + FRESH_SECS).is_some());
+        assert!(cache.get_stale("k", 1000 + MAX_STALE_SECS).is_none());
+        assert!(cache.get_fresh("missing", 1000).is_none());
+    }
+
+    #[test]
+    fn test_prune_evicts_oldest() {
+        let mut cache = ResponseCache::default();
+        for i in 0..=MAX_ENTRIES as u64 {
+            cache.insert(format!("k{i}"), stations("A"), 1000 + i);
+        }
+        assert_eq!(cache.entries.len(), MAX_ENTRIES);
+        assert!(!cache.entries.contains_key("k0"));
+        assert!(cache.entries.contains_key(&format!("k{MAX_ENTRIES}")));
+    }
+
+    #[test]
+    fn test_merge_keeps_newer() {
+        let mut memory = ResponseCache::default();
+        memory.insert("k".to_string(), stations("new"), 2000);
+        let mut disk = ResponseCache::default();
+        disk.insert("k".to_string(), stations("old"), 1000);
+        disk.insert("other".to_string(), stations("B"), 1000);
+
+        memory.merge(disk, 2000);
+        assert_eq!(memory.get_stale("k", 2000).unwrap()[0].name, "new");
+        assert!(memory.get_stale("other", 2000).is_some());
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let dir =
+            std::env::temp_dir().join(format!("cosmic-ext-radio-cache-{}", std::process::id()));
+        let path = dir.join(CACHE_FILE);
+        let mut cache = ResponseCache::default();
+        cache.insert("k".to_string(), stations("A"), 1000);
+        cache.save(&path);
+
+        let loaded = ResponseCache::load(&path);
+        assert_eq!(loaded.get_stale("k", 1000).unwrap()[0].name, "A");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,22 +1,839 @@
-use crate::api::Station;
+use crate::api::{self, SearchField, SearchFilters, Station};
+use crate::audio::{self, AudioBackend};
+use crate::duck::{self, DuckMode};
+use crate::history::{self, HistoryEntry};
+use crate::journal::{self, JournalEntry};
+use crate::panel::{self, ClickAction, PanelText};
+use crate::profile::LiteMode;
+use crate::quality::QualityFilter;
+use crate::station::StationId;
 use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, CosmicConfigEntry};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 
 #[derive(Debug, Clone, CosmicConfigEntry, Eq, PartialEq, Serialize, Deserialize)]
 #[version = 9]
 pub struct Config {
     #[serde(default)]
     pub favorites: Vec<Station>,
+    /// When favorites were last refreshed from the directory, in seconds
+    /// since the Unix epoch
+    #[serde(default)]
+    pub favorites_refreshed_at: u64,
     #[serde(default)]
     pub volume: u8, // 0-100
+    /// Ceiling for the volume slider, MPRIS and the players
+    #[serde(default = "default_max_volume")]
+    pub max_volume: u8,
+    /// Station playing or last played, shown as now playing after a restart
+    #[serde(default)]
+    pub last_station: Option<Station>,
+    /// Whether `last_station` was still playing when the applet last ran
+    #[serde(default)]
+    pub was_playing: bool,
+    /// Start `last_station` again on startup if it was playing
+    #[serde(default)]
+    pub resume_on_login: bool,
+    /// Start a station every time the applet starts, playing or not at
+    /// the last logout
+    #[serde(default)]
+    pub autoplay: bool,
+    /// Favorite `autoplay` starts; `None` for `last_station`
+    #[serde(default)]
+    pub autoplay_station: Option<StationId>,
+    /// Send a click to radio-browser.info when a station starts playing
+    #[serde(default = "default_true")]
+    pub report_clicks: bool,
+    /// Field plain words in the search box are matched against
+    #[serde(default)]
+    pub search_field: SearchField,
+    /// Include stations that failed radio-browser's last health check
+    #[serde(default)]
+    pub show_broken_stations: bool,
+    /// Minimum bitrate and preferred codec for every station list
+    #[serde(default)]
+    pub quality: QualityFilter,
+    /// Country, language and tags every search is narrowed by
+    #[serde(default)]
+    pub search_filters: SearchFilters,
+    #[serde(default)]
+    pub saved_searches: Vec<SavedSearch>,
+    /// Self-hosted radio-browser server; empty uses the public mirrors
+    #[serde(default)]
+    pub api_base_url: String,
+    /// HTTP proxy for directory requests and streams; empty uses the environment
+    #[serde(default)]
+    pub proxy_url: String,
+    /// Seconds before a directory request moves on to the next mirror
+    #[serde(default = "default_request_timeout")]
+    pub request_timeout_secs: u64,
+    #[serde(default)]
+    pub audio_backend: AudioBackend,
+    /// Audio buffered by the GStreamer backend before playback starts
+    #[serde(default = "default_buffer_ms")]
+    pub gst_buffer_ms: u32,
+    /// Keep directory responses on disk for offline use
+    #[serde(default)]
+    pub cache_on_disk: bool,
+    #[serde(default)]
+    pub lite_mode: LiteMode,
+    /// "lat, long" for the nearby-stations list; empty uses the locale's country
+    #[serde(default)]
+    pub location: String,
+    /// Stations left out of every search and browse list
+    #[serde(default)]
+    pub hidden_stations: Vec<HiddenStation>,
+    #[serde(default)]
+    pub subscriptions: Vec<Subscription>,
+    /// Recent additions, removals and renames of favorites
+    #[serde(default)]
+    pub favorites_journal: Vec<JournalEntry>,
+    /// Stations played, newest first
+    #[serde(default)]
+    pub history: Vec<HistoryEntry>,
+    #[serde(default = "default_history_max_entries")]
+    pub history_max_entries: usize,
+    /// Last distinct stations played, for the quick-access row
+    #[serde(default)]
+    pub recent_stations: Vec<Station>,
+    /// What a left click on the panel icon does
+    #[serde(default)]
+    pub left_click: ClickAction,
+    #[serde(default = "default_middle_click")]
+    pub middle_click: ClickAction,
+    /// Scrolling over the panel icon changes the volume
+    #[serde(default)]
+    pub scroll_volume: bool,
+    /// Volume change per scroll notch, in percent
+    #[serde(default = "default_scroll_volume_step")]
+    pub scroll_volume_step: u8,
+    /// Station or song shown next to the panel icon
+    #[serde(default)]
+    pub panel_text: PanelText,
+    /// Characters of panel text shown before it is cut or scrolled
+    #[serde(default = "default_panel_text_width")]
+    pub panel_text_width: usize,
+    /// Scroll panel text that doesn't fit instead of cutting it
+    #[serde(default = "default_true")]
+    pub panel_text_scroll: bool,
+    /// Name of the profile whose favorites, volume and filters are the ones
+    /// above; empty for the default profile
+    #[serde(default)]
+    pub active_profile: String,
+    /// The other profiles, by name
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+    /// JSON or OPML file favorites are mirrored to; empty when not syncing
+    #[serde(default)]
+    pub sync_file: String,
+    /// Favorites as last read from or written to the sync file, what
+    /// incoming changes are merged against
+    #[serde(default)]
+    pub sync_base: Vec<Station>,
+    /// Tab the popup opens on, the one last shown
+    #[serde(default)]
+    pub last_tab: PopupTab,
+    #[serde(default)]
+    pub list_layout: ListLayout,
+    /// Votes, plays, bitrate and country under each station's name
+    #[serde(default = "default_true")]
+    pub show_station_stats: bool,
+    /// Show a row's buttons only while it is hovered, long-pressed or reached with Tab
+    #[serde(default)]
+    pub row_actions_on_hover: bool,
+    #[serde(default)]
+    pub popup_size: PopupSize,
+    /// Tags offered as one-tap searches under the search bar
+    #[serde(default = "default_genre_chips")]
+    pub genre_chips: Vec<String>,
+    /// Skipping forward picks a random favorite instead of the next one
+    #[serde(default)]
+    pub shuffle_favorites: bool,
+    /// Skipping past the last favorite starts over at the first
+    #[serde(default = "default_true")]
+    pub loop_favorites: bool,
+    /// Desktop notification when a station starts playing
+    #[serde(default)]
+    pub notify_station: bool,
+    /// Desktop notification when the station announces a new song
+    #[serde(default)]
+    pub notify_song: bool,
+    /// Desktop notification when a stream fails and playback stops
+    #[serde(default)]
+    pub notify_errors: bool,
+    /// Keep the session from idling into suspend while a stream plays
+    #[serde(default = "default_true")]
+    pub inhibit_idle: bool,
+    /// Also block suspending outright while a stream plays
+    #[serde(default)]
+    pub inhibit_sleep: bool,
+    /// Stop when the audio device in use goes away, e.g. unplugged
+    /// headphones
+    #[serde(default = "default_true")]
+    pub pause_on_output_loss: bool,
+    /// Start again when that device comes back
+    #[serde(default)]
+    pub resume_on_output_return: bool,
+    /// What happens to the radio while another app plays audio
+    #[serde(default)]
+    pub duck_mode: DuckMode,
+    /// Percent of the volume kept while ducking
+    #[serde(default = "default_duck_volume")]
+    pub duck_volume: u8,
+    /// Seconds other audio must last before the radio makes way for it, or
+    /// stay quiet before it comes back
+    #[serde(default = "default_duck_delay")]
+    pub duck_delay_secs: u64,
+    /// ListenBrainz user token songs are scrobbled with; empty turns
+    /// scrobbling off
+    #[serde(default)]
+    pub listenbrainz_token: String,
+}
+
+/// Favorites, volume and filters of a profile that isn't active
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default)]
+    pub favorites: Vec<Station>,
+    #[serde(default = "default_volume")]
+    pub volume: u8,
+    #[serde(default)]
+    pub quality: QualityFilter,
+    #[serde(default)]
+    pub show_broken_stations: bool,
+    #[serde(default)]
+    pub hidden_stations: Vec<HiddenStation>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            favorites: Vec::new(),
+            volume: default_volume(),
+            quality: QualityFilter::default(),
+            show_broken_stations: false,
+            hidden_stations: Vec::new(),
+        }
+    }
+}
+
+/// A named query that is re-run every time it is opened
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    /// Search box text, including any `key:value` terms
+    pub query: String,
+}
+
+/// A station the user never wants to see in results again
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct HiddenStation {
+    pub stationuuid: String,
+    /// Name when it was hidden, for the settings list
+    pub name: String,
+}
+
+/// Read-only station list published by someone else (a family member, a
+/// community) and refreshed periodically
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Subscription {
+    pub name: String,
+    /// OPML or JSON list
+    pub url: String,
+    /// Stations from the last successful refresh
+    #[serde(default)]
+    pub stations: Vec<Station>,
+    /// Seconds since the Unix epoch, 0 before the first refresh
+    #[serde(default)]
+    pub last_updated: u64,
+}
+
+impl Subscription {
+    /// Replace the stations with a fresh download and report what changed
+    pub fn update(&mut self, stations: Vec<Station>, now: u64) -> Vec<JournalEntry> {
+        let changes = journal::diff_favorites(&self.stations, &stations, now);
+        self.stations = stations;
+        self.last_updated = now;
+        changes
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             favorites: Vec::new(),
-            volume: 50,
+            favorites_refreshed_at: 0,
+            volume: default_volume(),
+            max_volume: default_max_volume(),
+            last_station: None,
+            was_playing: false,
+            resume_on_login: false,
+            autoplay: false,
+            autoplay_station: None,
+            report_clicks: true,
+            search_field: SearchField::Name,
+            show_broken_stations: false,
+            quality: QualityFilter::default(),
+            search_filters: SearchFilters::default(),
+            saved_searches: Vec::new(),
+            api_base_url: String::new(),
+            proxy_url: String::new(),
+            request_timeout_secs: default_request_timeout(),
+            audio_backend: AudioBackend::default(),
+            gst_buffer_ms: default_buffer_ms(),
+            cache_on_disk: false,
+            lite_mode: LiteMode::default(),
+            location: String::new(),
+            hidden_stations: Vec::new(),
+            subscriptions: Vec::new(),
+            favorites_journal: Vec::new(),
+            history: Vec::new(),
+            history_max_entries: default_history_max_entries(),
+            recent_stations: Vec::new(),
+            left_click: ClickAction::default(),
+            middle_click: default_middle_click(),
+            scroll_volume: false,
+            scroll_volume_step: default_scroll_volume_step(),
+            panel_text: PanelText::default(),
+            panel_text_width: default_panel_text_width(),
+            panel_text_scroll: true,
+            active_profile: String::new(),
+            profiles: BTreeMap::new(),
+            sync_file: String::new(),
+            sync_base: Vec::new(),
+            last_tab: PopupTab::default(),
+            list_layout: ListLayout::default(),
+            show_station_stats: true,
+            row_actions_on_hover: false,
+            popup_size: PopupSize::default(),
+            genre_chips: default_genre_chips(),
+            shuffle_favorites: false,
+            loop_favorites: true,
+            notify_station: false,
+            notify_song: false,
+            notify_errors: false,
+            inhibit_idle: true,
+            inhibit_sleep: false,
+            pause_on_output_loss: true,
+            resume_on_output_return: false,
+            duck_mode: DuckMode::default(),
+            duck_volume: duck::DEFAULT_VOLUME,
+            duck_delay_secs: duck::DEFAULT_DELAY_SECS,
+            listenbrainz_token: String::new(),
+        }
+    }
+}
+
+fn default_volume() -> u8 {
+    50
+}
+
+fn default_max_volume() -> u8 {
+    100
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_request_timeout() -> u64 {
+    api::DEFAULT_REQUEST_TIMEOUT_SECS
+}
+
+fn default_buffer_ms() -> u32 {
+    audio::DEFAULT_BUFFER_MS
+}
+
+fn default_duck_volume() -> u8 {
+    duck::DEFAULT_VOLUME
+}
+
+fn default_duck_delay() -> u64 {
+    duck::DEFAULT_DELAY_SECS
+}
+
+fn default_history_max_entries() -> usize {
+    history::DEFAULT_MAX_ENTRIES
+}
+
+fn default_scroll_volume_step() -> u8 {
+    panel::SCROLL_STEP_CHOICES[2]
+}
+
+fn default_panel_text_width() -> usize {
+    panel::TEXT_WIDTH_CHOICES[1]
+}
+
+/// Genre chips a new config starts with
+pub const DEFAULT_GENRE_CHIPS: [&str; 5] = ["news", "jazz", "lofi", "rock", "classical"];
+
+fn default_genre_chips() -> Vec<String> {
+    DEFAULT_GENRE_CHIPS.map(String::from).to_vec()
+}
+
+fn default_middle_click() -> ClickAction {
+    ClickAction::Nothing
+}
+
+/// An edit applied to several favorites at once
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BulkEdit {
+    AddTag(String),
+    RemoveTag(String),
+    /// Move into the named folder; an empty name removes the folder
+    MoveToFolder(String),
+    SetArchived(bool),
+}
+
+/// Top-level sections of the popup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PopupTab {
+    #[default]
+    Favorites,
+    Search,
+    /// Popular and nearby stations
+    Browse,
+    History,
+    Settings,
+}
+
+impl PopupTab {
+    pub const ALL: [Self; 5] = [
+        Self::Favorites,
+        Self::Search,
+        Self::Browse,
+        Self::History,
+        Self::Settings,
+    ];
+}
+
+/// How station lists are laid out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ListLayout {
+    /// Small icons and names only, to fit more rows
+    Compact,
+    #[default]
+    Comfortable,
+    /// Favorites as tiles with a big favicon and the name underneath
+    Grid,
+}
+
+impl ListLayout {
+    /// The layout the header's layout button switches to
+    pub fn next(self) -> Self {
+        match self {
+            Self::Compact => Self::Comfortable,
+            Self::Comfortable => Self::Grid,
+            Self::Grid => Self::Compact,
+        }
+    }
+}
+
+/// Size of the popup and its station list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PopupSize {
+    Small,
+    #[default]
+    Medium,
+    Large,
+    /// Popup width and list height in pixels
+    Custom {
+        width: u32,
+        height: u32,
+    },
+}
+
+impl PopupSize {
+    pub const PRESETS: [Self; 3] = [Self::Small, Self::Medium, Self::Large];
+    pub const WIDTHS: std::ops::RangeInclusive<u32> = 300..=1200;
+    pub const HEIGHTS: std::ops::RangeInclusive<u32> = 150..=1200;
+
+    /// Popup width and the height of the scrolling station list, in
+    /// pixels. Custom sizes edited out of range are clamped.
+    pub fn dimensions(self) -> (u32, u32) {
+        match self {
+            Self::Small => (360, 200),
+            Self::Medium => (420, 250),
+            Self::Large => (560, 450),
+            Self::Custom { width, height } => (
+                width.clamp(*Self::WIDTHS.start(), *Self::WIDTHS.end()),
+                height.clamp(*Self::HEIGHTS.start(), *Self::HEIGHTS.end()),
+            ),
+        }
+    }
+
+    /// A custom size from the settings' text fields, if both are in range
+    pub fn parse_custom(width: &str, height: &str) -> Option<Self> {
+        let width: u32 = width.trim().parse().ok()?;
+        let height: u32 = height.trim().parse().ok()?;
+        (Self::WIDTHS.contains(&width) && Self::HEIGHTS.contains(&height))
+            .then_some(Self::Custom { width, height })
+    }
+}
+
+/// `stations` as the favorites list shows them: those without a folder
+/// first, then each folder's in order of first appearance
+pub fn in_list_order<'a>(stations: &[&'a Station]) -> Vec<&'a Station> {
+    let mut ordered: Vec<&Station> = stations
+        .iter()
+        .copied()
+        .filter(|s| s.folder.is_empty())
+        .collect();
+    for station in stations {
+        if !station.folder.is_empty() && !ordered.iter().any(|s| s.folder == station.folder) {
+            ordered.extend(
+                stations
+                    .iter()
+                    .copied()
+                    .filter(|s| s.folder == station.folder),
+            );
+        }
+    }
+    ordered
+}
+
+/// The line the first of `stations` under `letter` is drawn on and how many
+/// lines the list takes, as the favorites list lays them out: folderless
+/// stations, then per folder a header line and, unless `collapsed`, its
+/// members. `per_line` is how many stations share a line (tiles in the grid)
+pub fn letter_line(
+    stations: &[&Station],
+    letter: char,
+    collapsed: impl Fn(&str) -> bool,
+    per_line: usize,
+) -> Option<(usize, usize)> {
+    let per_line = per_line.max(1);
+    let ordered = in_list_order(stations);
+    let mut groups: Vec<(&str, Vec<&Station>)> = Vec::new();
+    for station in ordered {
+        match groups.last_mut() {
+            Some((folder, members)) if *folder == station.folder => members.push(station),
+            _ => groups.push((&station.folder, vec![station])),
+        }
+    }
+    if groups.first().is_some_and(|(folder, _)| !folder.is_empty()) {
+        // The folderless group is drawn even when it is empty
+        groups.insert(0, ("", Vec::new()));
+    }
+
+    let mut lines = 0;
+    let mut found = None;
+    for (folder, members) in groups {
+        if !folder.is_empty() {
+            lines += 1;
+            if collapsed(folder) {
+                continue;
+            }
+        }
+        if found.is_none() {
+            if let Some(pos) = members.iter().position(|s| s.index_letter() == letter) {
+                found = Some(lines + pos / per_line);
+            }
+        }
+        lines += members.len().div_ceil(per_line);
+    }
+    found.map(|line| (line, lines))
+}
+
+/// Where a favorite moves in the list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveDirection {
+    Up,
+    Down,
+}
+
+impl Config {
+    /// Favorites shown in the main list, i.e. everything not archived
+    pub fn active_favorites(&self) -> impl Iterator<Item = &Station> {
+        self.favorites.iter().filter(|s| !s.archived)
+    }
+
+    /// The active favorite called `name`, by alias or directory name and
+    /// ignoring case
+    pub fn favorite_named(&self, name: &str) -> Option<&Station> {
+        let name = name.trim();
+        self.active_favorites()
+            .find(|s| s.display_name().eq_ignore_ascii_case(name))
+            .or_else(|| {
+                self.active_favorites()
+                    .find(|s| s.name.trim().eq_ignore_ascii_case(name))
+            })
+    }
+
+    /// Station autoplay starts: the chosen favorite, or the last station
+    /// played if none is chosen or it is no longer an active favorite
+    pub fn autoplay_target(&self) -> Option<&Station> {
+        self.autoplay_station
+            .as_ref()
+            .and_then(|id| self.active_favorites().find(|s| s.id == *id))
+            .or(self.last_station.as_ref())
+    }
+
+    pub fn archived_favorites(&self) -> impl Iterator<Item = &Station> {
+        self.favorites.iter().filter(|s| s.archived)
+    }
+
+    /// Folder names of the active favorites, in order of first appearance
+    pub fn favorite_folders(&self) -> Vec<&str> {
+        let mut folders: Vec<&str> = Vec::new();
+        for station in self.active_favorites() {
+            if !station.folder.is_empty() && !folders.contains(&station.folder.as_str()) {
+                folders.push(&station.folder);
+            }
+        }
+        folders
+    }
+
+    /// Active favorites in the order the list shows them
+    pub fn favorites_in_list_order(&self) -> Vec<&Station> {
+        in_list_order(&self.active_favorites().collect::<Vec<_>>())
+    }
+
+    /// The favorite after (or before) `current` in list order, wrapping
+    /// around if `loop_favorites` is set. Starts at the first (or last) one
+    /// if `current` isn't a favorite.
+    pub fn adjacent_favorite(
+        &self,
+        current: Option<&StationId>,
+        forward: bool,
+    ) -> Option<&Station> {
+        let stations = self.favorites_in_list_order();
+        let len = stations.len();
+        let position = current.and_then(|id| stations.iter().position(|s| s.id == *id));
+        let index = match (position, forward) {
+            (Some(i), true) if i + 1 < len => i + 1,
+            (Some(i), false) if i > 0 => i - 1,
+            (Some(_), true) if self.loop_favorites => 0,
+            (Some(_), false) if self.loop_favorites => len - 1,
+            (Some(_), _) => return None,
+            (None, true) => 0,
+            (None, false) => len.checked_sub(1)?,
+        };
+        stations.get(index).copied()
+    }
+
+    /// Where skipping from `current` leads: a random other favorite, picked
+    /// by `seed`, when skipping forward with `shuffle_favorites` set, and
+    /// the adjacent one otherwise
+    pub fn skip_favorite(
+        &self,
+        current: Option<&StationId>,
+        forward: bool,
+        seed: u64,
+    ) -> Option<&Station> {
+        if !(forward && self.shuffle_favorites) {
+            return self.adjacent_favorite(current, forward);
+        }
+        let others: Vec<&Station> = self
+            .favorites_in_list_order()
+            .into_iter()
+            .filter(|s| Some(&s.id) != current)
+            .collect();
+        let len = u64::try_from(others.len()).ok().filter(|&len| len > 0)?;
+        others.get(usize::try_from(seed % len).ok()?).copied()
+    }
+
+    pub fn is_hidden(&self, station: &Station) -> bool {
+        !station.stationuuid.is_empty()
+            && self
+                .hidden_stations
+                .iter()
+                .any(|hidden| hidden.stationuuid == station.stationuuid)
+    }
+
+    /// Hide `station` from results. Returns false for stations without a
+    /// directory UUID and ones already hidden.
+    pub fn hide(&mut self, station: &Station) -> bool {
+        if station.stationuuid.is_empty() || self.is_hidden(station) {
+            return false;
+        }
+        self.hidden_stations.push(HiddenStation {
+            stationuuid: station.stationuuid.clone(),
+            name: station.name.clone(),
+        });
+        true
+    }
+
+    pub fn unhide(&mut self, stationuuid: &str) {
+        self.hidden_stations
+            .retain(|hidden| hidden.stationuuid != stationuuid);
+    }
+
+    /// `stations` without the hidden ones
+    pub fn without_hidden(&self, mut stations: Vec<Station>) -> Vec<Station> {
+        stations.retain(|station| !self.is_hidden(station));
+        stations
+    }
+
+    /// Update favorites from their current directory entries, matched by
+    /// UUID. Returns how many favorites changed.
+    pub fn refresh_favorites(&mut self, fresh: &[Station]) -> usize {
+        self.favorites
+            .iter_mut()
+            .filter(|favorite| !favorite.stationuuid.is_empty())
+            .filter_map(|favorite| {
+                let entry = fresh
+                    .iter()
+                    .find(|entry| entry.stationuuid == favorite.stationuuid)?;
+                Some(favorite.refresh_from(entry))
+            })
+            .filter(|changed| *changed)
+            .count()
+    }
+
+    /// Swap a favorite with its visible neighbour; archived favorites in
+    /// between keep their place. Returns false at either end of the list.
+    pub fn move_favorite(&mut self, id: &StationId, direction: MoveDirection) -> bool {
+        let Some(from) = self.favorites.iter().position(|s| &s.id == id) else {
+            return false;
+        };
+        let is_active = |index: &usize| !self.favorites[*index].archived;
+        let to = match direction {
+            MoveDirection::Up => (0..from).rev().find(is_active),
+            MoveDirection::Down => (from + 1..self.favorites.len()).find(is_active),
+        };
+        match to {
+            Some(to) => {
+                self.favorites.swap(from, to);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// All profile names, the default profile (empty name) first
+    pub fn profile_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = std::iter::once(self.active_profile.as_str())
+            .chain(self.profiles.keys().map(String::as_str))
+            .chain(std::iter::once(""))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Create an empty profile. Returns false for a blank or taken name.
+    pub fn add_profile(&mut self, name: &str) -> bool {
+        let name = name.trim();
+        if name.is_empty() || self.profile_names().contains(&name) {
+            return false;
+        }
+        self.profiles.insert(name.to_string(), Profile::default());
+        true
+    }
+
+    /// Delete an inactive profile with its favorites. The default profile
+    /// stays.
+    pub fn remove_profile(&mut self, name: &str) -> bool {
+        !name.is_empty() && self.profiles.remove(name).is_some()
+    }
+
+    /// Put the active profile away and load `name` in its place. Returns
+    /// false if it is already active or doesn't exist.
+    pub fn switch_profile(&mut self, name: &str) -> bool {
+        if name == self.active_profile {
+            return false;
+        }
+        let next = match self.profiles.remove(name) {
+            Some(profile) => profile,
+            // The default profile exists even before it was first stashed
+            None if name.is_empty() => Profile::default(),
+            None => return false,
+        };
+        let previous = Profile {
+            favorites: std::mem::take(&mut self.favorites),
+            volume: self.volume,
+            quality: self.quality,
+            show_broken_stations: self.show_broken_stations,
+            hidden_stations: std::mem::take(&mut self.hidden_stations),
+        };
+        let previous_name = std::mem::replace(&mut self.active_profile, name.to_string());
+        self.profiles.insert(previous_name, previous);
+
+        self.favorites = next.favorites;
+        self.volume = next.volume;
+        self.quality = next.quality;
+        self.show_broken_stations = next.show_broken_stations;
+        self.hidden_stations = next.hidden_stations;
+        true
+    }
+
+    /// Give a favorite a display name; an empty alias or the original name
+    /// removes it. Returns whether the favorite changed.
+    pub fn set_alias(&mut self, id: &StationId, alias: &str) -> bool {
+        let Some(station) = self.favorites.iter_mut().find(|s| &s.id == id) else {
+            return false;
+        };
+        let alias = alias.trim();
+        let alias = if alias == station.name.trim() {
+            ""
+        } else {
+            alias
+        };
+        if station.alias == alias {
+            return false;
+        }
+        station.alias = alias.to_string();
+        true
+    }
+
+    /// Apply `edit` to every favorite whose id is in `ids`.
+    /// Returns how many favorites changed.
+    pub fn apply_bulk_edit(&mut self, ids: &HashSet<StationId>, edit: &BulkEdit) -> usize {
+        self.favorites
+            .iter_mut()
+            .filter(|s| ids.contains(&s.id))
+            .map(|station| match edit {
+                BulkEdit::AddTag(tag) => station.add_tag(tag),
+                BulkEdit::RemoveTag(tag) => station.remove_tag(tag),
+                BulkEdit::MoveToFolder(folder) => {
+                    let folder = folder.trim();
+                    if station.folder == folder {
+                        false
+                    } else {
+                        station.folder = folder.to_string();
+                        true
+                    }
+                }
+                BulkEdit::SetArchived(archived) => {
+                    let changed = station.archived != *archived;
+                    station.archived = *archived;
+                    changed
+                }
+            })
+            .filter(|changed| *changed)
+            .count()
+    }
+
+    /// Delete the favorites in `ids`. Returns how many were removed.
+    pub fn remove_favorites(&mut self, ids: &HashSet<StationId>) -> usize {
+        let before = self.favorites.len();
+        self.favorites.retain(|station| !ids.contains(&station.id));
+        before - self.favorites.len()
+    }
+
+    /// The favorites in `ids`, in list order
+    pub fn selected_favorites(&self, ids: &HashSet<StationId>) -> Vec<Station> {
+        self.favorites
+            .iter()
+            .filter(|station| ids.contains(&station.id))
+            .cloned()
+            .collect()
+    }
+
+    /// Add `tag` to the genre chips, lowercased. Returns false if it is
+    /// blank or already there.
+    pub fn add_genre_chip(&mut self, tag: &str) -> bool {
+        let tag = tag
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        if tag.is_empty() || self.genre_chips.contains(&tag) {
+            return false;
         }
+        self.genre_chips.push(tag);
+        true
     }
 }
 
@@ -43,6 +860,50 @@ mod tests {
         assert!(config.favorites.is_empty());
     }
 
+    #[test]
+    fn test_config_reports_clicks_by_default() {
+        assert!(Config::default().report_clicks);
+        let config: Config = serde_json::from_str(r#"{"favorites": [], "volume": 40}"#).unwrap();
+        assert!(config.report_clicks);
+    }
+
+    #[test]
+    fn test_config_request_timeout_default() {
+        let config: Config = serde_json::from_str(r#"{"favorites": [], "volume": 40}"#).unwrap();
+        assert_eq!(
+            config.request_timeout_secs,
+            api::DEFAULT_REQUEST_TIMEOUT_SECS
+        );
+        assert_eq!(
+            Config::default().request_timeout_secs,
+            config.request_timeout_secs
+        );
+    }
+
+    #[test]
+    fn test_config_panel_defaults_keep_old_behavior() {
+        let config: Config = serde_json::from_str(r#"{"favorites": [], "volume": 40}"#).unwrap();
+        assert_eq!(config.left_click, ClickAction::TogglePopup);
+        assert_eq!(config.middle_click, ClickAction::Nothing);
+        assert!(!config.scroll_volume);
+        assert_eq!(config.scroll_volume_step, 5);
+        assert_eq!(config.middle_click, Config::default().middle_click);
+    }
+
+    #[test]
+    fn test_saved_searches_roundtrip() {
+        let config = Config {
+            saved_searches: vec![SavedSearch {
+                name: "Lo-fi HQ".to_string(),
+                query: "tag:lofi bitrate>128 order:votes".to_string(),
+            }],
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        let back: Config = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.saved_searches, config.saved_searches);
+    }
+
     #[test]
     fn test_config_with_favorites() {
         let station = Station {
@@ -55,6 +916,7 @@ mod tests {
         let config = Config {
             favorites: vec![station.clone()],
             volume: 75,
+            ..Default::default()
         };
 
         assert_eq!(config.favorites.len(), 1);
@@ -71,6 +933,7 @@ mod tests {
         let config1 = Config {
             favorites: vec![station],
             volume: 60,
+            ..Default::default()
         };
         let config2 = config1.clone();
 
@@ -87,14 +950,17 @@ mod tests {
         let config1 = Config {
             favorites: vec![station.clone()],
             volume: 50,
+            ..Default::default()
         };
         let config2 = Config {
             favorites: vec![station],
             volume: 50,
+            ..Default::default()
         };
         let config3 = Config {
             favorites: vec![],
             volume: 50,
+            ..Default::default()
         };
 
         assert_eq!(config1, config2);
@@ -112,6 +978,7 @@ mod tests {
         let config = Config {
             favorites: vec![station],
             volume: 80,
+            ..Default::default()
         };
 
         let serialized = serde_json::to_string(&config).unwrap();
@@ -125,10 +992,12 @@ mod tests {
         let config_min = Config {
             favorites: vec![],
             volume: 0,
+            ..Default::default()
         };
         let config_max = Config {
             favorites: vec![],
             volume: 100,
+            ..Default::default()
         };
 
         assert_eq!(config_min.volume, 0);
@@ -153,6 +1022,7 @@ mod tests {
         let config = Config {
             favorites: vec![station1, station2, station3],
             volume: 50,
+            ..Default::default()
         };
 
         assert_eq!(config.favorites.len(), 3);
@@ -160,4 +1030,434 @@ mod tests {
         assert_eq!(config.favorites[1].name, "Station 2");
         assert_eq!(config.favorites[2].name, "Station 3");
     }
+
+    #[test]
+    fn test_hidden_stations_are_filtered() {
+        let station = |uuid: &str| Station {
+            stationuuid: uuid.to_string(),
+            name: format!("Station {uuid}"),
+            ..Default::default()
+        };
+        let mut config = Config::default();
+        assert!(config.hide(&station("a")));
+        assert!(!config.hide(&station("a")));
+        assert!(!config.hide(&station("")));
+        assert_eq!(config.hidden_stations[0].name, "Station a");
+
+        let visible = config.without_hidden(vec![station("a"), station("b"), station("")]);
+        let uuids: Vec<&str> = visible.iter().map(|s| s.stationuuid.as_str()).collect();
+        assert_eq!(uuids, ["b", ""]);
+
+        config.unhide("a");
+        assert!(!config.is_hidden(&station("a")));
+    }
+
+    #[test]
+    fn test_refresh_favorites_matches_uuid() {
+        let mut config = bulk_fixture();
+        let fresh = [Station {
+            stationuuid: "b".to_string(),
+            favicon: "https://example.com/b.png".to_string(),
+            ..Default::default()
+        }];
+
+        assert_eq!(config.refresh_favorites(&fresh), 1);
+        assert_eq!(config.favorites[1].favicon, "https://example.com/b.png");
+        assert!(config.favorites[0].favicon.is_empty());
+        assert_eq!(config.refresh_favorites(&fresh), 0);
+    }
+
+    fn bulk_fixture() -> Config {
+        let mut favorites = Vec::new();
+        for (uuid, tags) in [("a", "jazz"), ("b", "rock"), ("c", "")] {
+            let mut station = Station {
+                stationuuid: uuid.to_string(),
+                tags: tags.to_string(),
+                ..Default::default()
+            };
+            station.ensure_id();
+            favorites.push(station);
+        }
+        Config {
+            favorites,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_favorite_folders_in_order() {
+        let mut config = bulk_fixture();
+        config.favorites[0].folder = "Jazz".to_string();
+        config.favorites[1].folder = "News".to_string();
+        config.favorites[2].folder = "Jazz".to_string();
+        assert_eq!(config.favorite_folders(), ["Jazz", "News"]);
+
+        config.favorites[1].archived = true;
+        assert_eq!(config.favorite_folders(), ["Jazz"]);
+    }
+
+    #[test]
+    fn test_letter_line_skips_collapsed_folders() {
+        let mut config = bulk_fixture();
+        for (station, name) in config
+            .favorites
+            .iter_mut()
+            .zip(["Alpha", "Bravo", "Charlie"])
+        {
+            station.name = name.to_string();
+        }
+        config.favorites[0].folder = "Jazz".to_string();
+        config.favorites[2].folder = "News".to_string();
+        let stations: Vec<&Station> = config.favorites.iter().collect();
+        let open = |_: &str| false;
+
+        // Bravo, the Jazz header, Alpha, the News header, Charlie
+        assert_eq!(letter_line(&stations, 'B', open, 1), Some((0, 5)));
+        assert_eq!(letter_line(&stations, 'A', open, 1), Some((2, 5)));
+        assert_eq!(letter_line(&stations, 'C', open, 1), Some((4, 5)));
+        assert_eq!(letter_line(&stations, 'Z', open, 1), None);
+
+        let jazz_closed = |folder: &str| folder == "Jazz";
+        assert_eq!(letter_line(&stations, 'C', jazz_closed, 1), Some((3, 4)));
+        assert_eq!(letter_line(&stations, 'A', jazz_closed, 1), None);
+
+        // Tiles share lines in the grid
+        config.favorites[0].folder.clear();
+        config.favorites[2].folder.clear();
+        let stations: Vec<&Station> = config.favorites.iter().collect();
+        assert_eq!(letter_line(&stations, 'C', open, 2), Some((1, 2)));
+    }
+
+    #[test]
+    fn test_popup_size() {
+        assert_eq!(PopupSize::default().dimensions(), (420, 250));
+        assert_eq!(
+            PopupSize::parse_custom(" 640 ", "480"),
+            Some(PopupSize::Custom {
+                width: 640,
+                height: 480
+            })
+        );
+        assert_eq!(PopupSize::parse_custom("100", "480"), None);
+        assert_eq!(PopupSize::parse_custom("640", "tall"), None);
+        let edited = PopupSize::Custom {
+            width: 5000,
+            height: 10,
+        };
+        assert_eq!(edited.dimensions(), (1200, 150));
+    }
+
+    #[test]
+    fn test_list_layout_cycles_through_all() {
+        let mut layout = ListLayout::default();
+        assert_eq!(layout, ListLayout::Comfortable);
+        let mut seen = Vec::new();
+        for _ in 0..3 {
+            layout = layout.next();
+            seen.push(layout);
+        }
+        assert_eq!(
+            seen,
+            [
+                ListLayout::Grid,
+                ListLayout::Compact,
+                ListLayout::Comfortable
+            ]
+        );
+    }
+
+    #[test]
+    fn test_adjacent_favorite_follows_list_order() {
+        let mut config = bulk_fixture();
+        config.favorites[0].folder = "Jazz".to_string();
+        let ids: Vec<StationId> = config.favorites.iter().map(|s| s.id.clone()).collect();
+        let uuid = |station: Option<&Station>| station.unwrap().stationuuid.clone();
+
+        // Listed as b, c, then the Jazz folder with a
+        assert_eq!(uuid(config.adjacent_favorite(Some(&ids[1]), true)), "c");
+        assert_eq!(uuid(config.adjacent_favorite(Some(&ids[2]), true)), "a");
+        assert_eq!(uuid(config.adjacent_favorite(Some(&ids[0]), true)), "b");
+        assert_eq!(uuid(config.adjacent_favorite(Some(&ids[1]), false)), "a");
+        assert_eq!(uuid(config.adjacent_favorite(None, true)), "b");
+        assert_eq!(uuid(config.adjacent_favorite(None, false)), "a");
+
+        config.loop_favorites = false;
+        assert!(config.adjacent_favorite(Some(&ids[0]), true).is_none());
+        assert!(config.adjacent_favorite(Some(&ids[1]), false).is_none());
+        assert_eq!(uuid(config.adjacent_favorite(Some(&ids[1]), true)), "c");
+
+        config.favorites.clear();
+        assert!(config.adjacent_favorite(None, true).is_none());
+    }
+
+    #[test]
+    fn test_favorite_named_prefers_alias() {
+        let mut config = bulk_fixture();
+        config.favorites[0].name = "Groove Salad".to_string();
+        config.favorites[0].alias = "Morning chill".to_string();
+        config.favorites[1].name = "Rock Antenne".to_string();
+        config.favorites[1].alias = "groove salad".to_string();
+
+        let uuid = |name| config.favorite_named(name).map(|s| s.stationuuid.clone());
+        assert_eq!(uuid("Groove Salad").as_deref(), Some("b"));
+        assert_eq!(uuid(" rock antenne ").as_deref(), Some("b"));
+        assert_eq!(uuid("morning chill").as_deref(), Some("a"));
+        assert_eq!(uuid("Jazz FM"), None);
+    }
+
+    #[test]
+    fn test_shuffle_skips_to_another_favorite() {
+        let mut config = bulk_fixture();
+        config.shuffle_favorites = true;
+        let current = config.favorites[0].id.clone();
+        for seed in 0..10 {
+            let station = config.skip_favorite(Some(&current), true, seed).unwrap();
+            assert_ne!(station.id, current);
+        }
+        // Going back still follows the list
+        assert_eq!(
+            config.skip_favorite(Some(&current), false, 7),
+            config.adjacent_favorite(Some(&current), false)
+        );
+
+        config.favorites.truncate(1);
+        assert!(config.skip_favorite(Some(&current), true, 3).is_none());
+    }
+
+    #[test]
+    fn test_move_favorite_skips_archived() {
+        let mut config = bulk_fixture();
+        config.favorites[1].archived = true;
+        let order = |config: &Config| -> Vec<String> {
+            config
+                .favorites
+                .iter()
+                .map(|s| s.stationuuid.clone())
+                .collect()
+        };
+
+        let last = config.favorites[2].id.clone();
+        assert!(config.move_favorite(&last, MoveDirection::Up));
+        assert_eq!(order(&config), ["c", "b", "a"]);
+        assert!(!config.move_favorite(&last, MoveDirection::Up));
+        assert!(config.move_favorite(&last, MoveDirection::Down));
+        assert_eq!(order(&config), ["a", "b", "c"]);
+        assert!(!config.move_favorite(&last, MoveDirection::Down));
+    }
+
+    #[test]
+    fn test_switch_profiles_keeps_each_profiles_settings() {
+        let mut config = bulk_fixture();
+        config.volume = 80;
+        assert!(config.add_profile(" Kids "));
+        assert!(!config.add_profile("Kids"));
+        assert!(!config.add_profile("  "));
+        assert_eq!(config.profile_names(), ["", "Kids"]);
+
+        assert!(config.switch_profile("Kids"));
+        assert_eq!(config.active_profile, "Kids");
+        assert!(config.favorites.is_empty());
+        assert_eq!(config.volume, 50);
+        config.volume = 30;
+        config.show_broken_stations = true;
+        assert!(!config.remove_profile("Kids"));
+
+        assert!(config.switch_profile(""));
+        assert_eq!(config.favorites.len(), 3);
+        assert_eq!(config.volume, 80);
+        assert!(!config.show_broken_stations);
+
+        assert!(config.switch_profile("Kids"));
+        assert_eq!(config.volume, 30);
+        assert!(config.show_broken_stations);
+        assert!(!config.switch_profile("Kids"));
+        assert!(!config.switch_profile("Work"));
+        assert_eq!(config.profile_names(), ["", "Kids"]);
+    }
+
+    #[test]
+    fn test_remove_profile() {
+        let mut config = Config::default();
+        config.add_profile("Work");
+        assert!(config.remove_profile("Work"));
+        assert!(!config.remove_profile("Work"));
+        assert_eq!(config.profile_names(), [""]);
+
+        config.add_profile("Work");
+        config.switch_profile("Work");
+        assert!(!config.remove_profile(""));
+    }
+
+    #[test]
+    fn test_set_alias() {
+        let mut config = bulk_fixture();
+        config.favorites[0].name = "JAZZ FM 24/7 LIVE".to_string();
+        let id = config.favorites[0].id.clone();
+
+        assert!(config.set_alias(&id, " Jazz "));
+        assert_eq!(config.favorites[0].alias, "Jazz");
+        assert!(!config.set_alias(&id, "Jazz"));
+
+        // The original name or nothing removes the alias
+        assert!(config.set_alias(&id, "JAZZ FM 24/7 LIVE"));
+        assert!(config.favorites[0].alias.is_empty());
+        assert!(!config.set_alias(&StationId::new("custom", "missing"), "X"));
+
+        // A directory refresh keeps the alias
+        config.set_alias(&id, "Jazz");
+        let fresh = [Station {
+            stationuuid: "a".to_string(),
+            name: "JAZZ FM".to_string(),
+            favicon: "https://example.com/a.png".to_string(),
+            ..Default::default()
+        }];
+        config.refresh_favorites(&fresh);
+        assert_eq!(config.favorites[0].display_name(), "Jazz");
+    }
+
+    #[test]
+    fn test_bulk_add_tag_only_touches_selection() {
+        let mut config = bulk_fixture();
+        let ids: HashSet<StationId> = config.favorites[..2].iter().map(|s| s.id.clone()).collect();
+
+        let changed = config.apply_bulk_edit(&ids, &BulkEdit::AddTag("jazz".to_string()));
+        assert_eq!(changed, 1);
+        assert_eq!(config.favorites[0].tags, "jazz");
+        assert_eq!(config.favorites[1].tags, "rock,jazz");
+        assert_eq!(config.favorites[2].tags, "");
+    }
+
+    #[test]
+    fn test_bulk_remove_tag() {
+        let mut config = bulk_fixture();
+        let ids: HashSet<StationId> = config.favorites.iter().map(|s| s.id.clone()).collect();
+
+        let changed = config.apply_bulk_edit(&ids, &BulkEdit::RemoveTag("Rock".to_string()));
+        assert_eq!(changed, 1);
+        assert_eq!(config.favorites[1].tags, "");
+    }
+
+    #[test]
+    fn test_bulk_move_to_folder() {
+        let mut config = bulk_fixture();
+        let ids: HashSet<StationId> = [config.favorites[2].id.clone()].into_iter().collect();
+
+        let changed = config.apply_bulk_edit(&ids, &BulkEdit::MoveToFolder(" Kids ".to_string()));
+        assert_eq!(changed, 1);
+        assert_eq!(config.favorites[2].folder, "Kids");
+        assert_eq!(
+            config.apply_bulk_edit(&ids, &BulkEdit::MoveToFolder("Kids".to_string())),
+            0
+        );
+        assert!(config.favorites[0].folder.is_empty());
+    }
+
+    #[test]
+    fn test_bulk_archive_and_restore() {
+        let mut config = bulk_fixture();
+        let ids: HashSet<StationId> = [config.favorites[1].id.clone()].into_iter().collect();
+
+        assert_eq!(
+            config.apply_bulk_edit(&ids, &BulkEdit::SetArchived(true)),
+            1
+        );
+        assert_eq!(config.active_favorites().count(), 2);
+        assert_eq!(config.archived_favorites().count(), 1);
+        // Archiving keeps the entry and its position
+        assert_eq!(config.favorites.len(), 3);
+        assert!(config.favorites[1].archived);
+
+        assert_eq!(
+            config.apply_bulk_edit(&ids, &BulkEdit::SetArchived(false)),
+            1
+        );
+        assert_eq!(config.archived_favorites().count(), 0);
+    }
+
+    #[test]
+    fn test_bulk_remove_and_export_selection() {
+        let mut config = bulk_fixture();
+        let ids: HashSet<StationId> = [
+            config.favorites[2].id.clone(),
+            config.favorites[0].id.clone(),
+        ]
+        .into_iter()
+        .collect();
+
+        let selected = config.selected_favorites(&ids);
+        assert_eq!(
+            selected,
+            [config.favorites[0].clone(), config.favorites[2].clone()]
+        );
+
+        assert_eq!(config.remove_favorites(&ids), 2);
+        assert_eq!(config.favorites.len(), 1);
+        assert!(!ids.contains(&config.favorites[0].id));
+        assert_eq!(config.remove_favorites(&ids), 0);
+    }
+
+    #[test]
+    fn test_add_genre_chip() {
+        let mut config = Config::default();
+        assert_eq!(config.genre_chips, DEFAULT_GENRE_CHIPS);
+        assert!(config.add_genre_chip("  Hip   Hop "));
+        assert_eq!(config.genre_chips.last().unwrap(), "hip hop");
+        assert!(!config.add_genre_chip("JAZZ"));
+        assert!(!config.add_genre_chip("   "));
+        assert_eq!(config.genre_chips.len(), DEFAULT_GENRE_CHIPS.len() + 1);
+    }
+
+    #[test]
+    fn test_subscription_update_reports_changes() {
+        let station = |uuid: &str, name: &str| {
+            let mut s = Station {
+                stationuuid: uuid.to_string(),
+                name: name.to_string(),
+                ..Default::default()
+            };
+            s.ensure_id();
+            s
+        };
+        let mut sub = Subscription {
+            name: "Family".to_string(),
+            url: "https://example.com/list.opml".to_string(),
+            ..Default::default()
+        };
+
+        let first = sub.update(vec![station("a", "A")], 100);
+        assert_eq!(first.len(), 1);
+        assert_eq!(sub.last_updated, 100);
+
+        let second = sub.update(vec![station("a", "A"), station("b", "B")], 200);
+        assert_eq!(second.len(), 1);
+        assert_eq!(sub.stations.len(), 2);
+        assert!(sub.update(sub.stations.clone(), 300).is_empty());
+    }
+
+    #[test]
+    fn test_autoplay_target_falls_back_to_last_station() {
+        let station = |uuid: &str| {
+            let mut s = Station {
+                stationuuid: uuid.to_string(),
+                name: uuid.to_uppercase(),
+                ..Default::default()
+            };
+            s.ensure_id();
+            s
+        };
+        let mut config = Config {
+            favorites: vec![station("a"), station("b")],
+            last_station: Some(station("c")),
+            ..Default::default()
+        };
+        assert_eq!(config.autoplay_target().unwrap().name, "C");
+
+        config.autoplay_station = Some(config.favorites[1].id.clone());
+        assert_eq!(config.autoplay_target().unwrap().name, "B");
+
+        config.favorites[1].archived = true;
+        assert_eq!(config.autoplay_target().unwrap().name, "C");
+        config.last_station = None;
+        assert!(config.autoplay_target().is_none());
+    }
 }
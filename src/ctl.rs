@@ -0,0 +1,348 @@
+//! `cosmic-radio-ctl`, the command-line companion
+//!
+//! Every command is an MPRIS call on the running applet's bus name, so
+//! scripts and custom keybindings drive it the way media keys do. Stations
+//! are started through `OpenUri` with a [`DeepLink`], leaving the applet to
+//! look the name or UUID up.
+
+use crate::deeplink::DeepLink;
+use crate::mpris::{self, BUS_NAME, OBJECT_PATH, PLAYER_INTERFACE};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use uuid::Uuid;
+use zbus::zvariant::{OwnedValue, Value};
+use zbus::Connection;
+
+const PROPERTIES_INTERFACE: &str = "org.freedesktop.DBus.Properties";
+
+pub const USAGE: &str = "\
+Usage: cosmic-radio-ctl <command>
+
+Commands:
+  play [NAME|UUID]  Resume playback, or start a favorite by name or a station by UUID
+  stop              Stop playback
+  next              Skip to the next favorite
+  volume PERCENT    Set the volume, from 0 to 100
+  status [--json]   Show what is playing";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Resume the current station, or start the named one
+    Play(Option<String>),
+    Stop,
+    Next,
+    Volume(u8),
+    Status {
+        json: bool,
+    },
+    Help,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum UsageError {
+    #[error("Missing command")]
+    MissingCommand,
+    #[error("Unknown command: {0}")]
+    UnknownCommand(String),
+    #[error("Volume must be a number from 0 to 100, not {0:?}")]
+    InvalidVolume(String),
+    #[error("Unexpected argument: {0}")]
+    UnexpectedArgument(String),
+}
+
+impl Command {
+    /// Parse the arguments after the program name
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, UsageError> {
+        let mut args = args.into_iter();
+        let name = args.next().ok_or(UsageError::MissingCommand)?;
+        let rest: Vec<String> = args.collect();
+        let command = match name.as_str() {
+            // A station name may be several words
+            "play" => {
+                let target = rest.join(" ");
+                let target = target.trim();
+                return Ok(Self::Play((!target.is_empty()).then(|| target.to_string())));
+            }
+            "stop" => Self::Stop,
+            "next" => Self::Next,
+            "volume" => {
+                let value = rest
+                    .first()
+                    .ok_or_else(|| UsageError::InvalidVolume(String::new()))?;
+                let percent = value
+                    .trim_end_matches('%')
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|&percent| percent <= 100)
+                    .ok_or_else(|| UsageError::InvalidVolume(value.clone()))?;
+                return match rest.get(1) {
+                    Some(extra) => Err(UsageError::UnexpectedArgument(extra.clone())),
+                    None => Ok(Self::Volume(percent)),
+                };
+            }
+            "status" => match rest.first().map(String::as_str) {
+                Some("--json") => {
+                    return match rest.get(1) {
+                        Some(extra) => Err(UsageError::UnexpectedArgument(extra.clone())),
+                        None => Ok(Self::Status { json: true }),
+                    };
+                }
+                _ => Self::Status { json: false },
+            },
+            "help" | "--help" | "-h" => Self::Help,
+            other => return Err(UsageError::UnknownCommand(other.to_string())),
+        };
+        match rest.first() {
+            Some(extra) => Err(UsageError::UnexpectedArgument(extra.clone())),
+            None => Ok(command),
+        }
+    }
+}
+
+/// The link that starts `target`: a station UUID, or else a favorite's name
+pub fn play_link(target: &str) -> DeepLink {
+    match Uuid::parse_str(target) {
+        Ok(uuid) => DeepLink::Play {
+            uuid: uuid.hyphenated().to_string(),
+        },
+        Err(_) => DeepLink::PlayNamed {
+            name: target.to_string(),
+        },
+    }
+}
+
+/// What the applet reports through MPRIS
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Status {
+    /// `Playing`, `Paused` or `Stopped`
+    pub status: String,
+    pub station: Option<String>,
+    pub artist: Option<String>,
+    /// Song on the station, when it announces one
+    pub title: Option<String>,
+    pub volume: u8,
+}
+
+impl Status {
+    /// Build from the MPRIS properties. Metadata titles the station until a
+    /// song is known; then the song is the title and the station the album.
+    pub fn new(
+        status: String,
+        volume: f64,
+        title: Option<String>,
+        album: Option<String>,
+        artists: Vec<String>,
+    ) -> Self {
+        let (station, title) = match album {
+            Some(album) => (Some(album), title),
+            None => (title, None),
+        };
+        let artists = artists.join(", ");
+        Self {
+            status,
+            station,
+            artist: (!artists.is_empty()).then_some(artists),
+            title,
+            volume: mpris::volume_from_mpris(volume),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.status)?;
+        if let Some(station) = &self.station {
+            write!(f, ": {station}")?;
+        }
+        match (&self.artist, &self.title) {
+            (Some(artist), Some(title)) => write!(f, " - {artist} - {title}")?,
+            (None, Some(title)) => write!(f, " - {title}")?,
+            _ => {}
+        }
+        write!(f, " (volume {}%)", self.volume)
+    }
+}
+
+/// Send `command` to the running applet. Only `status` has something to
+/// return.
+pub async fn run(command: &Command) -> zbus::Result<Option<Status>> {
+    let connection = Connection::session().await?;
+    match command {
+        Command::Play(None) => call_player(&connection, "Play", &()).await?,
+        Command::Play(Some(target)) => {
+            let uri = play_link(target).to_uri();
+            call_player(&connection, "OpenUri", &(uri,)).await?;
+        }
+        Command::Stop => call_player(&connection, "Stop", &()).await?,
+        Command::Next => call_player(&connection, "Next", &()).await?,
+        Command::Volume(percent) => {
+            let volume = Value::from(mpris::volume_to_mpris(*percent));
+            connection
+                .call_method(
+                    Some(BUS_NAME),
+                    OBJECT_PATH,
+                    Some(PROPERTIES_INTERFACE),
+                    "Set",
+                    &(PLAYER_INTERFACE, "Volume", volume),
+                )
+                .await?;
+        }
+        Command::Status { .. } => return status(&connection).await.map(Some),
+        Command::Help => {}
+    }
+    Ok(None)
+}
+
+async fn call_player<B>(connection: &Connection, method: &str, body: &B) -> zbus::Result<()>
+where
+    B: serde::Serialize + zbus::zvariant::DynamicType,
+{
+    connection
+        .call_method(
+            Some(BUS_NAME),
+            OBJECT_PATH,
+            Some(PLAYER_INTERFACE),
+            method,
+            body,
+        )
+        .await?;
+    Ok(())
+}
+
+async fn status(connection: &Connection) -> zbus::Result<Status> {
+    let reply = connection
+        .call_method(
+            Some(BUS_NAME),
+            OBJECT_PATH,
+            Some(PROPERTIES_INTERFACE),
+            "GetAll",
+            &(PLAYER_INTERFACE,),
+        )
+        .await?;
+    let mut properties: HashMap<String, OwnedValue> = reply.body().deserialize()?;
+    let text = |value: OwnedValue| String::try_from(value).ok();
+
+    let mut metadata: HashMap<String, OwnedValue> = properties
+        .remove("Metadata")
+        .and_then(|value| HashMap::try_from(value).ok())
+        .unwrap_or_default();
+    Ok(Status::new(
+        properties
+            .remove("PlaybackStatus")
+            .and_then(text)
+            .unwrap_or_default(),
+        properties
+            .remove("Volume")
+            .and_then(|value| f64::try_from(value).ok())
+            .unwrap_or_default(),
+        metadata.remove("xesam:title").and_then(text),
+        metadata.remove("xesam:album").and_then(text),
+        metadata
+            .remove("xesam:artist")
+            .and_then(|value| Vec::try_from(value).ok())
+            .unwrap_or_default(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, UsageError> {
+        Command::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(parse(&["play"]), Ok(Command::Play(None)));
+        assert_eq!(
+            parse(&["play", "Groove", "Salad"]),
+            Ok(Command::Play(Some("Groove Salad".to_string())))
+        );
+        assert_eq!(parse(&["stop"]), Ok(Command::Stop));
+        assert_eq!(parse(&["next"]), Ok(Command::Next));
+        assert_eq!(parse(&["volume", "70"]), Ok(Command::Volume(70)));
+        assert_eq!(parse(&["volume", "70%"]), Ok(Command::Volume(70)));
+        assert_eq!(parse(&["status"]), Ok(Command::Status { json: false }));
+        assert_eq!(
+            parse(&["status", "--json"]),
+            Ok(Command::Status { json: true })
+        );
+        assert_eq!(parse(&["--help"]), Ok(Command::Help));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse(&[]), Err(UsageError::MissingCommand));
+        assert_eq!(
+            parse(&["rewind"]),
+            Err(UsageError::UnknownCommand("rewind".to_string()))
+        );
+        assert_eq!(
+            parse(&["volume", "loud"]),
+            Err(UsageError::InvalidVolume("loud".to_string()))
+        );
+        assert_eq!(
+            parse(&["volume", "170"]),
+            Err(UsageError::InvalidVolume("170".to_string()))
+        );
+        assert_eq!(
+            parse(&["stop", "now"]),
+            Err(UsageError::UnexpectedArgument("now".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_play_link() {
+        assert_eq!(
+            play_link("96202C39-0601-11E8-AE97-52543BE04C81"),
+            DeepLink::Play {
+                uuid: "96202c39-0601-11e8-ae97-52543be04c81".to_string()
+            }
+        );
+        assert_eq!(
+            play_link("Groove Salad"),
+            DeepLink::PlayNamed {
+                name: "Groove Salad".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_status_from_metadata() {
+        let station_only = Status::new(
+            "Playing".to_string(),
+            0.7,
+            Some("Groove Salad".to_string()),
+            None,
+            Vec::new(),
+        );
+        assert_eq!(station_only.station.as_deref(), Some("Groove Salad"));
+        assert_eq!(station_only.title, None);
+        assert_eq!(
+            station_only.to_string(),
+            "Playing: Groove Salad (volume 70%)"
+        );
+
+        let song = Status::new(
+            "Playing".to_string(),
+            0.5,
+            Some("Alpha Beta Gaga".to_string()),
+            Some("Groove Salad".to_string()),
+            vec!["Air".to_string()],
+        );
+        assert_eq!(
+            song.to_string(),
+            "Playing: Groove Salad - Air - Alpha Beta Gaga (volume 50%)"
+        );
+        assert_eq!(
+            song.to_json(),
+            r#"{"status":"Playing","station":"Groove Salad","artist":"Air","title":"Alpha Beta Gaga","volume":50}"#
+        );
+    }
+}
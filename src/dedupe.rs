@@ -0,0 +1,208 @@
+//! Collapsing duplicate stations in search results
+//!
+//! Popular stations are often listed many times with slightly different
+//! names or URLs. Entries with the same normalized name, codec and bitrate,
+//! or the same stream host, path and query, are grouped behind the first of
+//! them.
+
+use crate::api::Station;
+use std::collections::HashMap;
+use url::Url;
+
+/// A station and the indices of its duplicates, all into the original list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StationGroup {
+    pub primary: usize,
+    pub variants: Vec<usize>,
+}
+
+/// Group `stations` by duplicate, keeping the original order of the first
+/// entry of each group
+pub fn group(stations: &[Station]) -> Vec<StationGroup> {
+    let mut parent: Vec<usize> = (0..stations.len()).collect();
+    let mut first_by_key: HashMap<String, usize> = HashMap::new();
+
+    for (index, station) in stations.iter().enumerate() {
+        for key in keys(station) {
+            match first_by_key.get(&key) {
+                Some(&other) => union(&mut parent, index, other),
+                None => {
+                    first_by_key.insert(key, index);
+                }
+            }
+        }
+    }
+
+    let mut groups: Vec<StationGroup> = Vec::new();
+    let mut group_of_root: HashMap<usize, usize> = HashMap::new();
+    for index in 0..stations.len() {
+        let root = find(&mut parent, index);
+        match group_of_root.get(&root) {
+            Some(&g) => groups[g].variants.push(index),
+            None => {
+                group_of_root.insert(root, groups.len());
+                groups.push(StationGroup {
+                    primary: index,
+                    variants: Vec::new(),
+                });
+            }
+        }
+    }
+    groups
+}
+
+fn keys(station: &Station) -> Vec<String> {
+    let mut keys = Vec::with_capacity(2);
+    let name = normalize_name(&station.name);
+    if !name.is_empty() {
+        keys.push(format!(
+            "name:{}|{}|{}",
+            name,
+            station.codec.trim().to_lowercase(),
+            station.bitrate
+        ));
+    }
+    let url = if station.url_resolved.is_empty() {
+        &station.url
+    } else {
+        &station.url_resolved
+    };
+    if let Some(stream) = stream_key(url) {
+        keys.push(format!("stream:{stream}"));
+    }
+    keys
+}
+
+/// Lowercase alphanumeric words, so "Radio  X!" and "radio x" match
+fn normalize_name(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Host, path and query, ignoring scheme, port and a trailing slash. The
+/// query stays: many servers pick the stream by it (`listen.php?id=3`).
+fn stream_key(url: &str) -> Option<String> {
+    let url = Url::parse(url.trim()).ok()?;
+    let host = url.host_str()?.to_lowercase();
+    let path = url.path().trim_end_matches('/');
+    Some(match url.query() {
+        Some(query) => format!("{host}{path}?{query}"),
+        None => format!("{host}{path}"),
+    })
+}
+
+fn find(parent: &mut [usize], mut index: usize) -> usize {
+    while parent[index] != index {
+        parent[index] = parent[parent[index]];
+        index = parent[index];
+    }
+    index
+}
+
+/// Merge two sets, keeping the smaller (earlier) index as the root
+fn union(parent: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parent, a), find(parent, b));
+    if a != b {
+        parent[a.max(b)] = a.min(b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn station(name: &str, url: &str, codec: &str, bitrate: u32) -> Station {
+        Station {
+            name: name.to_string(),
+            url_resolved: url.to_string(),
+            codec: codec.to_string(),
+            bitrate,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_groups_same_name_codec_bitrate() {
+        let stations = [
+            station("Radio X", "https://a.example.com/x", "MP3", 128),
+            station("radio  x!", "https://b.example.com/x", "mp3", 128),
+            station("Radio X", "https://c.example.com/x", "AAC", 64),
+        ];
+        let groups = group(&stations);
+        assert_eq!(
+            groups,
+            vec![
+                StationGroup {
+                    primary: 0,
+                    variants: vec![1]
+                },
+                StationGroup {
+                    primary: 2,
+                    variants: vec![]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_groups_same_stream_host_and_path() {
+        let stations = [
+            station(
+                "Jazz FM",
+                "http://stream.example.com:8000/jazz/",
+                "MP3",
+                128,
+            ),
+            station(
+                "Jazz FM (HQ)",
+                "https://STREAM.example.com/jazz",
+                "AAC",
+                256,
+            ),
+            station("Other", "https://stream.example.com/rock", "MP3", 128),
+        ];
+        let groups = group(&stations);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].variants, vec![1]);
+        assert_eq!(groups[1].primary, 2);
+    }
+
+    #[test]
+    fn test_different_queries_stay_separate() {
+        let stations = [
+            station("Rock", "https://example.com/listen.php?id=1", "MP3", 128),
+            station("Jazz", "https://example.com/listen.php?id=2", "MP3", 128),
+            station("Rock 2", "https://example.com/listen.php?id=1", "AAC", 64),
+        ];
+        let groups = group(&stations);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].variants, vec![2]);
+    }
+
+    #[test]
+    fn test_transitive_duplicates_share_a_group() {
+        // 0 and 1 share a name, 1 and 2 share a stream
+        let stations = [
+            station("A", "https://one.example.com/a", "MP3", 128),
+            station("A", "https://two.example.com/b", "MP3", 128),
+            station("B", "https://two.example.com/b", "AAC", 64),
+        ];
+        let groups = group(&stations);
+        assert_eq!(
+            groups,
+            vec![StationGroup {
+                primary: 0,
+                variants: vec![1, 2]
+            }]
+        );
+    }
+
+    #[test]
+    fn test_unnamed_stations_without_url_stay_separate() {
+        let stations = [Station::default(), Station::default()];
+        assert_eq!(group(&stations).len(), 2);
+    }
+}
@@ -0,0 +1,161 @@
+//! `cosmicradio://` deep links
+//!
+//! Links such as `cosmicradio://play?uuid=<stationuuid>` in chats or notes
+//! start a station in the applet; `cosmicradio://play?name=<name>` starts a
+//! favorite by name. The system hands them to a new process
+//! (see the scheme handler desktop entry), which forwards them to the
+//! running applet through MPRIS `OpenUri`.
+
+use url::{form_urlencoded, Url};
+
+pub const SCHEME: &str = "cosmicradio";
+
+/// Action requested by a deep link
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeepLink {
+    /// Play the station with this radio-browser (or custom) UUID
+    Play { uuid: String },
+    /// Play the favorite with this name or alias
+    PlayNamed { name: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DeepLinkError {
+    #[error("Not a {SCHEME}:// link")]
+    WrongScheme,
+    #[error("Unknown action: {0}")]
+    UnknownAction(String),
+    #[error("Missing station uuid or name")]
+    MissingUuid,
+}
+
+impl DeepLink {
+    pub fn parse(uri: &str) -> Result<Self, DeepLinkError> {
+        let url = Url::parse(uri.trim()).map_err(|_| DeepLinkError::WrongScheme)?;
+        if url.scheme() != SCHEME {
+            return Err(DeepLinkError::WrongScheme);
+        }
+
+        // `cosmicradio://play?..` puts the action in the host,
+        // `cosmicradio:play?..` in the path
+        let action = url
+            .host_str()
+            .filter(|host| !host.is_empty())
+            .unwrap_or_else(|| url.path().trim_matches('/'));
+        match action {
+            "play" => {
+                let param = |name: &str| {
+                    url.query_pairs()
+                        .find(|(key, _)| key == name)
+                        .map(|(_, value)| value.trim().to_string())
+                        .filter(|value| !value.is_empty())
+                };
+                if let Some(uuid) = param("uuid") {
+                    Ok(Self::Play { uuid })
+                } else if let Some(name) = param("name") {
+                    Ok(Self::PlayNamed { name })
+                } else {
+                    Err(DeepLinkError::MissingUuid)
+                }
+            }
+            other => Err(DeepLinkError::UnknownAction(other.to_string())),
+        }
+    }
+
+    pub fn to_uri(&self) -> String {
+        match self {
+            Self::Play { uuid } => format!("{SCHEME}://play?uuid={uuid}"),
+            Self::PlayNamed { name } => {
+                let name: String = form_urlencoded::byte_serialize(name.as_bytes()).collect();
+                format!("{SCHEME}://play?name={name}")
+            }
+        }
+    }
+}
+
+/// Whether `uri` is meant for this applet rather than being a stream URL
+pub fn is_deep_link(uri: &str) -> bool {
+    uri.trim()
+        .get(..SCHEME.len() + 1)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case(&format!("{SCHEME}:")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_play() {
+        let link = DeepLink::parse("cosmicradio://play?uuid=96202c39-0601-11e8-ae97-52543be04c81")
+            .unwrap();
+        assert_eq!(
+            link,
+            DeepLink::Play {
+                uuid: "96202c39-0601-11e8-ae97-52543be04c81".to_string()
+            }
+        );
+        assert_eq!(
+            DeepLink::parse("cosmicradio:play?uuid=abc").unwrap(),
+            DeepLink::Play {
+                uuid: "abc".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_play_named() {
+        assert_eq!(
+            DeepLink::parse("cosmicradio://play?name=Groove%20Salad").unwrap(),
+            DeepLink::PlayNamed {
+                name: "Groove Salad".to_string()
+            }
+        );
+        assert_eq!(
+            DeepLink::parse("cosmicradio://play?name=x&uuid=abc").unwrap(),
+            DeepLink::Play {
+                uuid: "abc".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            DeepLink::parse("https://play?uuid=abc"),
+            Err(DeepLinkError::WrongScheme)
+        );
+        assert_eq!(
+            DeepLink::parse("cosmicradio://play"),
+            Err(DeepLinkError::MissingUuid)
+        );
+        assert_eq!(
+            DeepLink::parse("cosmicradio://play?uuid="),
+            Err(DeepLinkError::MissingUuid)
+        );
+        assert_eq!(
+            DeepLink::parse("cosmicradio://delete?uuid=abc"),
+            Err(DeepLinkError::UnknownAction("delete".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let link = DeepLink::Play {
+            uuid: "abc-123".to_string(),
+        };
+        assert_eq!(DeepLink::parse(&link.to_uri()).unwrap(), link);
+
+        let link = DeepLink::PlayNamed {
+            name: "Groove Salad & Friends?".to_string(),
+        };
+        assert_eq!(DeepLink::parse(&link.to_uri()).unwrap(), link);
+    }
+
+    #[test]
+    fn test_is_deep_link() {
+        assert!(is_deep_link("cosmicradio://play?uuid=abc"));
+        assert!(is_deep_link("CosmicRadio:play"));
+        assert!(!is_deep_link("https://stream.example.com"));
+        assert!(!is_deep_link("cosmic"));
+    }
+}
@@ -0,0 +1,204 @@
+//! Making way for other audio
+//!
+//! While another app plays sound, such as a call or a video, the radio can
+//! turn itself down ("ducking") or stop, and come back once that app goes
+//! quiet. `pactl subscribe` reports playback streams (sink inputs) coming,
+//! going and changing; after each, `pactl list sink-inputs` tells whether a
+//! stream other than the radio's own is playing. [`subscription`] only
+//! reports a change that lasts, so a notification sound doesn't duck the
+//! radio.
+
+use crate::audio::AUDIO_CLIENT_NAME;
+use crate::output::{parse_event, watch_pactl};
+use futures::SinkExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+/// Percent of the normal volume offered for ducking
+pub const VOLUME_CHOICES: [u8; 4] = [10, 20, 30, 50];
+pub const DEFAULT_VOLUME: u8 = 30;
+
+/// Seconds other audio must keep playing, or stay quiet, before the radio
+/// makes way or comes back
+pub const DELAY_CHOICES: [u64; 4] = [1, 2, 5, 10];
+pub const DEFAULT_DELAY_SECS: u64 = 2;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DuckMode {
+    /// Keep playing as is
+    #[default]
+    Off,
+    /// Turn the volume down
+    Lower,
+    /// Stop the stream
+    Pause,
+}
+
+impl DuckMode {
+    pub const ALL: [Self; 3] = [Self::Off, Self::Lower, Self::Pause];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuckEvent {
+    /// Another app has been playing audio for the delay
+    Started,
+    /// It has been quiet again for the delay
+    Stopped,
+}
+
+/// `volume` turned down to `percent` of itself
+pub fn lowered(volume: u8, percent: u8) -> u8 {
+    (u16::from(volume) * u16::from(percent.min(100)) / 100) as u8
+}
+
+/// Whether `pactl -f json list sink-inputs` output lists a playing stream
+/// of another app. The radio's own streams are told apart by the client
+/// name mpv is given, or by `pid` for GStreamer's, which play in-process.
+pub fn others_playing(sink_inputs: &str, pid: u32) -> bool {
+    let Ok(Value::Array(inputs)) = serde_json::from_str(sink_inputs) else {
+        return false;
+    };
+    let pid = pid.to_string();
+    inputs.iter().any(|input| {
+        let properties = &input["properties"];
+        let property = |key: &str| properties[key].as_str();
+        !input["corked"].as_bool().unwrap_or(false)
+            && property("application.name") != Some(AUDIO_CLIENT_NAME)
+            && property("application.process.id") != Some(pid.as_str())
+            // Notification and other event sounds are over in a moment
+            && property("media.role") != Some("event")
+    })
+}
+
+fn query_others_playing() -> Option<bool> {
+    let output = Command::new("pactl")
+        .args(["-f", "json", "list", "sink-inputs"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let json = String::from_utf8(output.stdout).ok()?;
+    output
+        .status
+        .success()
+        .then(|| others_playing(&json, std::process::id()))
+}
+
+/// Report other audio starting and stopping once either has lasted `delay`
+pub fn subscription(delay: Duration) -> cosmic::iced::Subscription<DuckEvent> {
+    cosmic::iced::Subscription::run_with_id(
+        ("duck", delay),
+        cosmic::iced::stream::channel(4, move |mut output| async move {
+            let (playing_tx, mut playing_rx) = mpsc::unbounded_channel();
+            let Some(mut playing) = query_others_playing() else {
+                warn!("Not watching other audio: pactl can't list sink inputs");
+                return;
+            };
+            let mut last = playing;
+            let _watcher = match watch_pactl(move |line| {
+                if !matches!(parse_event(line), Some((_, "sink-input"))) {
+                    return true;
+                }
+                match query_others_playing() {
+                    Some(now) if now != last => {
+                        last = now;
+                        playing_tx.send(now).is_ok()
+                    }
+                    _ => true,
+                }
+            }) {
+                Ok(watcher) => watcher,
+                Err(e) => {
+                    warn!("Not watching other audio: {}", e);
+                    return;
+                }
+            };
+            let mut ducked = false;
+            loop {
+                let changed = if playing == ducked {
+                    playing_rx.recv().await
+                } else {
+                    match tokio::time::timeout(delay, playing_rx.recv()).await {
+                        Ok(changed) => changed,
+                        Err(_) => {
+                            ducked = playing;
+                            let event = if ducked {
+                                DuckEvent::Started
+                            } else {
+                                DuckEvent::Stopped
+                            };
+                            debug!("Other audio: {:?}", event);
+                            if output.send(event).await.is_err() {
+                                break;
+                            }
+                            continue;
+                        }
+                    }
+                };
+                match changed {
+                    Some(now) => playing = now,
+                    None => break,
+                }
+            }
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SINK_INPUTS: &str = r#"[
+        {
+            "index": 61,
+            "corked": false,
+            "properties": {
+                "application.name": "cosmic-radio-applet",
+                "application.process.id": "4100"
+            }
+        },
+        {
+            "index": 75,
+            "corked": true,
+            "properties": {
+                "application.name": "Firefox",
+                "application.process.id": "2311"
+            }
+        },
+        {
+            "index": 80,
+            "corked": false,
+            "properties": {
+                "application.name": "GNOME Shell",
+                "application.process.id": "1022",
+                "media.role": "event"
+            }
+        }
+    ]"#;
+
+    #[test]
+    fn test_own_paused_and_event_streams_are_not_others_playing() {
+        assert!(!others_playing(SINK_INPUTS, 9999));
+        assert!(!others_playing("[]", 9999));
+        assert!(!others_playing("not json", 9999));
+    }
+
+    #[test]
+    fn test_others_playing() {
+        let unpaused = SINK_INPUTS.replace(r#""corked": true"#, r#""corked": false"#);
+        assert!(others_playing(&unpaused, 9999));
+        // GStreamer streams carry the app's own pid
+        assert!(!others_playing(&unpaused, 2311));
+    }
+
+    #[test]
+    fn test_lowered() {
+        assert_eq!(lowered(80, 30), 24);
+        assert_eq!(lowered(55, 10), 5);
+        assert_eq!(lowered(50, 100), 50);
+        assert_eq!(lowered(50, 150), 50);
+    }
+}
@@ -1,8 +1,10 @@
 //! Custom error types for cosmic-ext-applet-radio
 //!
-//! These error types are defined for future integration. Currently the application
-//! uses simpler error handling, but these types provide a foundation for more
-//! robust error handling as the codebase evolves.
+//! `ApiError` is returned by every directory call in `api.rs`,
+//! `StreamError` by the stream pre-flight check, `AudioError` by
+//! `AudioManager::play` and `BackupError` by settings backups. The other
+//! types provide a foundation for more robust error handling as the
+//! codebase evolves.
 
 #![allow(dead_code)]
 
@@ -53,6 +55,9 @@ pub enum AudioError {
     #[error("Failed to spawn mpv process: {0}")]
     SpawnFailed(#[from] std::io::Error),
 
+    #[error("Refusing stream URL: {0}")]
+    RejectedUrl(&'static str),
+
     #[error("MPV IPC communication failed: {0}")]
     IpcFailed(String),
 
@@ -89,6 +94,90 @@ pub enum ApiError {
 
     #[error("Invalid API response format: {0}")]
     InvalidResponse(String),
+
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(String),
+}
+
+/// Problems found by the pre-flight check of a stream before playback
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum StreamError {
+    #[error("Station appears offline (HTTP {0})")]
+    Offline(u16),
+
+    #[error("Station did not respond within {0}s")]
+    Timeout(u64),
+
+    #[error("Secure connection to the station failed: {0}")]
+    Tls(String),
+
+    #[error("Could not connect to the station: {0}")]
+    Unreachable(String),
+}
+
+impl StreamError {
+    /// Whether the station certainly won't play. A slow answer or a
+    /// certificate the check rejects may still work in mpv, which waits
+    /// longer and doesn't verify certificates.
+    pub fn is_definite(&self) -> bool {
+        matches!(self, Self::Offline(_) | Self::Unreachable(_))
+    }
+}
+
+/// Problems with a URL typed into a form
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UrlError {
+    #[error("Invalid URL format")]
+    Malformed,
+
+    #[error("Only http/https URLs are allowed")]
+    NotHttp,
+
+    #[error("Icon must be an http(s) URL")]
+    Icon,
+}
+
+/// Problems reading a settings backup
+#[derive(Error, Debug)]
+pub enum BackupError {
+    #[error("Not a radio settings backup")]
+    NotABackup,
+
+    #[error("Backup version {0} is newer than this release supports")]
+    UnsupportedVersion(u32),
+
+    #[error("Malformed backup: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// Problems reading the favorites sync file
+#[derive(Error, Debug)]
+pub enum SyncError {
+    #[error("Malformed station list: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Malformed OPML: {0}")]
+    Opml(#[from] roxmltree::Error),
+}
+
+/// Problems with a file picked in the file dialog to import from or
+/// export to
+#[derive(Error, Debug)]
+pub enum FileError {
+    #[error("File dialog failed: {0}")]
+    Dialog(String),
+
+    #[error("Not a local file")]
+    NotLocal,
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("Not a station list: {0}")]
+    StationList(#[from] ApiError),
+
+    #[error(transparent)]
+    Backup(#[from] BackupError),
 }
 
 /// Input validation errors
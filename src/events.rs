@@ -0,0 +1,122 @@
+//! Internal event bus
+//!
+//! The app publishes what happens (playback, track changes, config saves,
+//! connectivity) once on an [`EventBus`], and every interested subsystem
+//! receives its own copy. MPRIS and the ListenBrainz scrobbler consume it;
+//! stats would subscribe the same way instead of getting a dedicated
+//! channel.
+//! Desktop notifications are sent by the app itself, as they carry
+//! localized text.
+
+use crate::api::Station;
+use tokio::sync::mpsc;
+
+#[derive(Debug, Clone)]
+pub enum PlaybackEvent {
+    Started { station: Box<Station> },
+    Stopped,
+    Volume(u8),
+    Navigation(Navigation),
+}
+
+/// How skipping through the favorites behaves, and where it can go
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Navigation {
+    pub shuffle: bool,
+    pub looping: bool,
+    pub can_go_next: bool,
+    pub can_go_previous: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkEvent {
+    /// NetworkManager reports a connection again, or a directory request
+    /// got an answer after earlier ones failed
+    Online,
+    /// NetworkManager reports no connection, or a directory request
+    /// couldn't reach any server
+    Offline,
+}
+
+/// A named set of stations that can be started as a whole, such as a
+/// favorites folder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StationCollection {
+    /// Stable key made of ASCII letters, digits and underscores
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum AppEvent {
+    Playback(PlaybackEvent),
+    /// The playing stream moved on to a new track
+    Metadata {
+        station: Box<Station>,
+        title: String,
+    },
+    /// The config was written, e.g. after a favorites or settings change
+    ConfigChanged,
+    Network(NetworkEvent),
+    /// The station collections on offer, after favorites or history changed
+    StationCollections(Vec<StationCollection>),
+    /// Subsystems should let go of their resources and stop, e.g. after an
+    /// MPRIS client asked the player to quit
+    Shutdown,
+}
+
+/// Fans each published event out to every connected subscriber
+#[derive(Debug, Default)]
+pub struct EventBus {
+    subscribers: Vec<mpsc::UnboundedSender<AppEvent>>,
+}
+
+impl EventBus {
+    /// Start delivering events to `subscriber`. Subscribers that go away
+    /// are dropped on the next publish.
+    pub fn connect(&mut self, subscriber: mpsc::UnboundedSender<AppEvent>) {
+        self.subscribers.push(subscriber);
+    }
+
+    pub fn publish(&mut self, event: AppEvent) {
+        self.subscribers
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_reaches_every_subscriber() {
+        let mut bus = EventBus::default();
+        let (tx_a, mut rx_a) = mpsc::unbounded_channel();
+        let (tx_b, mut rx_b) = mpsc::unbounded_channel();
+        bus.connect(tx_a);
+        bus.connect(tx_b);
+
+        bus.publish(AppEvent::Playback(PlaybackEvent::Volume(40)));
+        for rx in [&mut rx_a, &mut rx_b] {
+            assert!(matches!(
+                rx.try_recv(),
+                Ok(AppEvent::Playback(PlaybackEvent::Volume(40)))
+            ));
+            assert!(rx.try_recv().is_err());
+        }
+    }
+
+    #[test]
+    fn test_publish_drops_closed_subscribers() {
+        let mut bus = EventBus::default();
+        let (tx_open, mut rx_open) = mpsc::unbounded_channel();
+        let (tx_closed, rx_closed) = mpsc::unbounded_channel();
+        bus.connect(tx_open);
+        bus.connect(tx_closed);
+        drop(rx_closed);
+
+        bus.publish(AppEvent::ConfigChanged);
+        assert_eq!(bus.subscribers.len(), 1);
+        assert!(matches!(rx_open.try_recv(), Ok(AppEvent::ConfigChanged)));
+    }
+}
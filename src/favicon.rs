@@ -0,0 +1,146 @@
+//! Station favicon cache
+//!
+//! Favicons are downloaded in the background and kept on disk under
+//! `$XDG_CACHE_HOME/cosmic-ext-applet-radio/favicons`, one file per URL, so
+//! lists show them right away after a restart. Oversized images are refused
+//! by the download, and the oldest files are evicted once the cache outgrows
+//! its budget.
+
+use crate::api;
+use crate::cache;
+use crate::error::ApiError;
+use crate::safe_write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+use uuid::Uuid;
+
+/// Disk space the cached favicons may take together
+pub const MAX_CACHE_BYTES: u64 = 16 * 1024 * 1024;
+
+pub fn cache_dir() -> Option<PathBuf> {
+    cache::cache_dir().map(|dir| dir.join("favicons"))
+}
+
+fn file_name(url: &str) -> String {
+    Uuid::new_v5(&Uuid::NAMESPACE_URL, url.as_bytes())
+        .simple()
+        .to_string()
+}
+
+/// The cache file holding the favicon at `url`, if it has been downloaded
+pub fn cached_path(url: &str) -> Option<PathBuf> {
+    let path = cache_dir()?.join(file_name(url));
+    path.is_file().then_some(path)
+}
+
+pub fn load(dir: &Path, url: &str) -> Option<Vec<u8>> {
+    fs::read(dir.join(file_name(url))).ok()
+}
+
+/// Cache `bytes` as the favicon at `url`, evicting old entries if the
+/// cache grew past its budget
+pub fn store(dir: &Path, url: &str, bytes: &[u8]) -> io::Result<()> {
+    safe_write::write_atomic(&dir.join(file_name(url)), bytes)?;
+    prune(dir, MAX_CACHE_BYTES)?;
+    Ok(())
+}
+
+/// Delete the oldest files until the cache fits in `budget` bytes. Returns
+/// how many were deleted.
+pub fn prune(dir: &Path, budget: u64) -> io::Result<usize> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        if meta.is_file() {
+            files.push((meta.modified()?, meta.len(), entry.path()));
+        }
+    }
+    let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+    files.sort();
+
+    let mut removed = 0;
+    for (_, len, path) in files {
+        if total <= budget {
+            break;
+        }
+        fs::remove_file(path)?;
+        total -= len;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// The favicon at `url`, from the disk cache or else downloaded and cached.
+/// The disk is only touched on blocking threads.
+pub async fn fetch(url: String) -> Result<Vec<u8>, ApiError> {
+    let dir = cache_dir();
+    if let Some(dir) = dir.clone() {
+        let key = url.clone();
+        let cached = tokio::task::spawn_blocking(move || load(&dir, &key))
+            .await
+            .ok()
+            .flatten();
+        if let Some(bytes) = cached {
+            return Ok(bytes);
+        }
+    }
+    let bytes = api::fetch_favicon(url.clone()).await?;
+    if let Some(dir) = dir {
+        let copy = bytes.clone();
+        let _ = tokio::task::spawn_blocking(move || {
+            if let Err(e) = store(&dir, &url, &copy) {
+                warn!("Failed to cache favicon {}: {}", url, e);
+            }
+        })
+        .await;
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "cosmic-ext-radio-favicon-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_store_and_load() {
+        let dir = scratch_dir("roundtrip");
+        store(&dir, "http://example.com/a.png", b"png").unwrap();
+        assert_eq!(load(&dir, "http://example.com/a.png").unwrap(), b"png");
+        assert!(load(&dir, "http://example.com/b.png").is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_prune_evicts_oldest_first() {
+        let dir = scratch_dir("prune");
+        for (n, url) in ["a", "b", "c"].into_iter().enumerate() {
+            safe_write::write_atomic(&dir.join(file_name(url)), &[0; 10]).unwrap();
+            let file = fs::File::options()
+                .write(true)
+                .open(dir.join(file_name(url)))
+                .unwrap();
+            let age = std::time::Duration::from_secs(100 - n as u64 * 10);
+            file.set_modified(std::time::SystemTime::now() - age)
+                .unwrap();
+        }
+
+        assert_eq!(prune(&dir, 25).unwrap(), 1);
+        assert!(load(&dir, "a").is_none());
+        assert!(load(&dir, "b").is_some());
+        assert_eq!(prune(&dir, 25).unwrap(), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,85 @@
+//! "Stations near me"
+//!
+//! Uses coordinates the user entered when available (radio-browser filters
+//! stations by distance from them), otherwise the country of the desktop
+//! locale.
+
+/// Search radius around the user's coordinates, in meters
+pub const NEARBY_RADIUS_M: u32 = 100_000;
+
+/// Where to look for nearby stations
+#[derive(Debug, Clone, PartialEq)]
+pub enum Nearby {
+    Coordinates { lat: f64, long: f64 },
+    Country(String),
+}
+
+impl Nearby {
+    /// The configured location, or the locale's country if it is empty or
+    /// unparsable. `None` when neither is known.
+    pub fn resolve(location: &str) -> Option<Self> {
+        parse_coordinates(location)
+            .map(|(lat, long)| Self::Coordinates { lat, long })
+            .or_else(|| locale_country_code().map(Self::Country))
+    }
+}
+
+/// Parse "lat, long" in decimal degrees, e.g. "52.52, 13.405"
+pub fn parse_coordinates(text: &str) -> Option<(f64, f64)> {
+    let (lat, long) = text.split_once(',')?;
+    let lat: f64 = lat.trim().parse().ok()?;
+    let long: f64 = long.trim().parse().ok()?;
+    ((-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&long)).then_some((lat, long))
+}
+
+/// Country of the desktop locale, from the usual environment variables
+pub fn locale_country_code() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|locale| country_from_locale(&locale))
+}
+
+/// "pt_BR.UTF-8" -> "BR"; plain "C"/"POSIX" or "en" have no country
+fn country_from_locale(locale: &str) -> Option<String> {
+    let locale = locale.split(['.', '@']).next()?;
+    let (_, country) = locale.split_once('_')?;
+    (country.len() == 2 && country.chars().all(|c| c.is_ascii_alphabetic()))
+        .then(|| country.to_ascii_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_coordinates() {
+        assert_eq!(parse_coordinates("52.52, 13.405"), Some((52.52, 13.405)));
+        assert_eq!(parse_coordinates(" -33.9,18.4 "), Some((-33.9, 18.4)));
+        assert_eq!(parse_coordinates(""), None);
+        assert_eq!(parse_coordinates("52.52"), None);
+        assert_eq!(parse_coordinates("95, 10"), None);
+        assert_eq!(parse_coordinates("north, south"), None);
+    }
+
+    #[test]
+    fn test_country_from_locale() {
+        assert_eq!(country_from_locale("pt_BR.UTF-8"), Some("BR".to_string()));
+        assert_eq!(country_from_locale("de_DE@euro"), Some("DE".to_string()));
+        assert_eq!(country_from_locale("en_gb"), Some("GB".to_string()));
+        assert_eq!(country_from_locale("C.UTF-8"), None);
+        assert_eq!(country_from_locale("en"), None);
+    }
+
+    #[test]
+    fn test_resolve_prefers_coordinates() {
+        assert_eq!(
+            Nearby::resolve("52.52, 13.405"),
+            Some(Nearby::Coordinates {
+                lat: 52.52,
+                long: 13.405
+            })
+        );
+    }
+}
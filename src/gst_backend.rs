@@ -0,0 +1,164 @@
+//! In-process GStreamer playback (`--features gstreamer`)
+//!
+//! Runs `souphttpsrc ! queue2 ! decodebin ! audioconvert ! audioresample !
+//! volume ! pipewiresink` inside the applet instead of spawning mpv, which
+//! is noticeably lighter on low-power devices. Errors and the end of the
+//! stream go out on the channel given to [`GstPlayer::new`].
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+/// Name of the volume element, looked up again for live volume changes
+const VOLUME_ELEMENT: &str = "volume";
+
+pub struct GstPlayer {
+    pipeline: Mutex<Option<gst::Pipeline>>,
+    buffer_ms: AtomicU32,
+    /// Why the playing pipeline stopped by itself
+    ended: broadcast::Sender<String>,
+}
+
+impl GstPlayer {
+    pub fn new(ended: broadcast::Sender<String>) -> Self {
+        Self {
+            pipeline: Mutex::new(None),
+            buffer_ms: AtomicU32::new(crate::audio::DEFAULT_BUFFER_MS),
+            ended,
+        }
+    }
+
+    /// How much audio `queue2` holds before playback starts
+    pub fn set_buffer_ms(&self, buffer_ms: u32) {
+        self.buffer_ms.store(buffer_ms, Ordering::Relaxed);
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.pipeline.lock().map(|p| p.is_some()).unwrap_or(false)
+    }
+
+    pub fn play(&self, url: &str, volume: u8, proxy: Option<&str>) -> Result<(), String> {
+        self.stop();
+        gst::init().map_err(|e| e.to_string())?;
+
+        let pipeline = self
+            .build_pipeline(url, volume, proxy)
+            .map_err(|e| e.to_string())?;
+        self.watch_bus(&pipeline);
+        pipeline
+            .set_state(gst::State::Playing)
+            .map_err(|e| e.to_string())?;
+        debug!("GStreamer pipeline playing {}", url);
+
+        if let Ok(mut guard) = self.pipeline.lock() {
+            *guard = Some(pipeline);
+        }
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        if let Ok(mut guard) = self.pipeline.lock() {
+            if let Some(pipeline) = guard.take() {
+                // Tearing down isn't the stream ending
+                if let Some(bus) = pipeline.bus() {
+                    bus.unset_sync_handler();
+                }
+                if let Err(e) = pipeline.set_state(gst::State::Null) {
+                    warn!("Failed to stop GStreamer pipeline: {}", e);
+                }
+            }
+        }
+    }
+
+    pub fn set_volume(&self, volume: f32) {
+        if let Ok(guard) = self.pipeline.lock() {
+            if let Some(element) = guard.as_ref().and_then(|p| p.by_name(VOLUME_ELEMENT)) {
+                element.set_property("volume", f64::from(volume.clamp(0.0, 100.0)) / 100.0);
+            }
+        }
+    }
+
+    /// Report errors and the end of the stream from `pipeline`'s bus.
+    /// Nothing runs a GLib main loop here, so messages are looked at on the
+    /// streaming threads that post them.
+    fn watch_bus(&self, pipeline: &gst::Pipeline) {
+        let Some(bus) = pipeline.bus() else {
+            return;
+        };
+        let ended = self.ended.clone();
+        bus.set_sync_handler(move |_, message| {
+            let reason = match message.view() {
+                gst::MessageView::Eos(_) => Some("end of stream".to_string()),
+                gst::MessageView::Error(err) => Some(err.error().to_string()),
+                _ => None,
+            };
+            if let Some(reason) = reason {
+                warn!("GStreamer pipeline stopped: {}", reason);
+                // Nobody listening is fine
+                let _ = ended.send(reason);
+            }
+            gst::BusSyncReply::Drop
+        });
+    }
+
+    fn build_pipeline(
+        &self,
+        url: &str,
+        volume: u8,
+        proxy: Option<&str>,
+    ) -> Result<gst::Pipeline, gst::glib::BoolError> {
+        let buffer_ns = u64::from(self.buffer_ms.load(Ordering::Relaxed)) * 1_000_000;
+
+        let mut src = gst::ElementFactory::make("souphttpsrc")
+            .property("location", url)
+            .property("is-live", true);
+        if let Some(proxy) = proxy {
+            src = src.property("proxy", proxy);
+        }
+        let src = src.build()?;
+        let queue = gst::ElementFactory::make("queue2")
+            .property("use-buffering", true)
+            .property("max-size-time", buffer_ns)
+            .build()?;
+        let decode = gst::ElementFactory::make("decodebin").build()?;
+        let convert = gst::ElementFactory::make("audioconvert").build()?;
+        let resample = gst::ElementFactory::make("audioresample").build()?;
+        let level = gst::ElementFactory::make("volume")
+            .name(VOLUME_ELEMENT)
+            .property("volume", f64::from(volume) / 100.0)
+            .build()?;
+        let sink = gst::ElementFactory::make("pipewiresink").build()?;
+
+        let pipeline = gst::Pipeline::new();
+        pipeline.add_many([&src, &queue, &decode, &convert, &resample, &level, &sink])?;
+        gst::Element::link_many([&src, &queue, &decode])?;
+        gst::Element::link_many([&convert, &resample, &level, &sink])?;
+
+        // decodebin only exposes its audio pad once it knows the stream format
+        let convert_weak = convert.downgrade();
+        decode.connect_pad_added(move |_, pad| {
+            let Some(convert) = convert_weak.upgrade() else {
+                return;
+            };
+            let Some(sink_pad) = convert.static_pad("sink") else {
+                return;
+            };
+            if !sink_pad.is_linked() {
+                if let Err(e) = pad.link(&sink_pad) {
+                    debug!("Not linking decoder pad: {:?}", e);
+                }
+            }
+        });
+
+        Ok(pipeline)
+    }
+}
+
+impl Drop for GstPlayer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
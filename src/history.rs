@@ -0,0 +1,122 @@
+//! Listening history
+//!
+//! Every play is recorded with its start time, newest first. The duration
+//! is filled in when playback stops or moves on to another station; an
+//! entry the applet quit in the middle of keeps a duration of 0.
+//!
+//! Separately, the last few distinct stations are kept for the quick-access
+//! row; clearing the history leaves them alone.
+
+use crate::api::Station;
+use crate::station::StationId;
+use serde::{Deserialize, Serialize};
+
+/// Entries kept unless configured otherwise
+pub const DEFAULT_MAX_ENTRIES: usize = 100;
+
+/// History sizes offered in the settings tab
+pub const MAX_ENTRIES_CHOICES: [usize; 4] = [25, 50, 100, 200];
+
+/// Stations in the quick-access row
+pub const RECENT_LIMIT: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub station: Station,
+    /// Seconds since the Unix epoch
+    pub started: u64,
+    /// Seconds listened; 0 while still playing
+    #[serde(default)]
+    pub duration_secs: u64,
+}
+
+/// Record `station` starting at `now`, dropping the oldest entries beyond
+/// `max_entries`
+pub fn start(history: &mut Vec<HistoryEntry>, station: &Station, now: u64, max_entries: usize) {
+    history.insert(
+        0,
+        HistoryEntry {
+            station: station.clone(),
+            started: now,
+            duration_secs: 0,
+        },
+    );
+    history.truncate(max_entries);
+}
+
+/// Fill in the duration of the newest entry if it is the still running play
+/// of `playing`
+pub fn finish(history: &mut [HistoryEntry], playing: &StationId, now: u64) {
+    if let Some(entry) = history.first_mut() {
+        if entry.station.id == *playing && entry.duration_secs == 0 {
+            entry.duration_secs = now.saturating_sub(entry.started);
+        }
+    }
+}
+
+/// Move `station` to the front of the recently played stations
+pub fn push_recent(recent: &mut Vec<Station>, station: &Station) {
+    recent.retain(|s| !s.is_same(station));
+    recent.insert(0, station.clone());
+    recent.truncate(RECENT_LIMIT);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn station(uuid: &str) -> Station {
+        let mut station = Station {
+            name: uuid.to_uppercase(),
+            stationuuid: uuid.to_string(),
+            ..Default::default()
+        };
+        station.ensure_id();
+        station
+    }
+
+    #[test]
+    fn test_start_and_finish() {
+        let mut history = Vec::new();
+        let a = station("a");
+        start(&mut history, &a, 1000, 10);
+        finish(&mut history, &a.id, 1300);
+        assert_eq!(history[0].duration_secs, 300);
+
+        // Finishing again doesn't stretch a closed entry
+        finish(&mut history, &a.id, 5000);
+        assert_eq!(history[0].duration_secs, 300);
+    }
+
+    #[test]
+    fn test_newest_first_and_capped() {
+        let mut history = Vec::new();
+        for (n, uuid) in ["a", "b", "c"].into_iter().enumerate() {
+            start(&mut history, &station(uuid), n as u64, 2);
+        }
+        let names: Vec<&str> = history.iter().map(|e| e.station.name.as_str()).collect();
+        assert_eq!(names, ["C", "B"]);
+    }
+
+    #[test]
+    fn test_finish_ignores_other_station() {
+        let mut history = Vec::new();
+        start(&mut history, &station("a"), 1000, 10);
+        finish(&mut history, &station("b").id, 1300);
+        assert_eq!(history[0].duration_secs, 0);
+
+        // Cleared history has nothing to finish
+        let mut empty: Vec<HistoryEntry> = Vec::new();
+        finish(&mut empty, &station("a").id, 1300);
+    }
+
+    #[test]
+    fn test_push_recent_dedupes_and_caps() {
+        let mut recent = Vec::new();
+        for uuid in ["a", "b", "c", "d", "e", "f", "b"] {
+            push_recent(&mut recent, &station(uuid));
+        }
+        let names: Vec<&str> = recent.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["B", "F", "E", "D", "C"]);
+    }
+}
@@ -30,4 +30,35 @@ pub static LANGUAGE_LOADER: LazyLock<FluentLanguageLoader> = LazyLock::new(|| {
 #[macro_export]
 macro_rules! fl {
     ($message_id:literal) => { i18n_embed_fl::fl!($crate::i18n::LANGUAGE_LOADER, $message_id) };
+    ($message_id:literal, $($args:expr),*) => {
+        i18n_embed_fl::fl!($crate::i18n::LANGUAGE_LOADER, $message_id, $($args), *)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn message_ids(locale: &str) -> BTreeSet<String> {
+        let file = Localizations::get(&format!("{locale}/cosmic_ext_applet_radio.ftl"))
+            .expect("locale file");
+        String::from_utf8_lossy(&file.data)
+            .lines()
+            .filter_map(|line| line.split_once(" ="))
+            .map(|(id, _)| id)
+            .filter(|id| !id.is_empty() && !id.starts_with([' ', '#', '*', '[']))
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_locales_have_every_message() {
+        let english = message_ids("en");
+        for locale in
+            Localizations::iter().filter_map(|path| Some(path.split_once('/')?.0.to_string()))
+        {
+            assert_eq!(message_ids(&locale), english, "{locale}");
+        }
+    }
 }
@@ -0,0 +1,84 @@
+//! Keeping the session awake while the radio plays
+//!
+//! systemd-logind keeps an inhibitor lock for as long as the file
+//! descriptor it hands out stays open. [`subscription`] takes one and holds
+//! it until the subscription is dropped, so the app runs it only while a
+//! stream plays and stopping releases the lock.
+
+use std::future;
+use tracing::{debug, warn};
+use zbus::zvariant::OwnedFd;
+use zbus::Connection;
+
+pub const LOGIND_NAME: &str = "org.freedesktop.login1";
+pub const LOGIND_PATH: &str = "/org/freedesktop/login1";
+pub const LOGIND_MANAGER_INTERFACE: &str = "org.freedesktop.login1.Manager";
+
+const WHO: &str = "Radio for COSMIC";
+const WHY: &str = "Playing radio";
+
+/// The `what` argument of logind's `Inhibit`, or `None` if there is
+/// nothing to inhibit
+pub fn inhibited(idle: bool, sleep: bool) -> Option<String> {
+    let what: Vec<&str> = [(idle, "idle"), (sleep, "sleep")]
+        .into_iter()
+        .filter_map(|(on, name)| on.then_some(name))
+        .collect();
+    (!what.is_empty()).then(|| what.join(":"))
+}
+
+/// Take a blocking inhibitor lock on `what`, held until the returned file
+/// descriptor is dropped
+pub async fn inhibit(what: &str) -> zbus::Result<OwnedFd> {
+    lock(what, "block").await
+}
+
+/// Take a delay lock on `what`: logind waits for it to be dropped, up to a
+/// few seconds, before going ahead
+pub async fn delay(what: &str) -> zbus::Result<OwnedFd> {
+    lock(what, "delay").await
+}
+
+async fn lock(what: &str, mode: &str) -> zbus::Result<OwnedFd> {
+    let connection = Connection::system().await?;
+    let reply = connection
+        .call_method(
+            Some(LOGIND_NAME),
+            LOGIND_PATH,
+            Some(LOGIND_MANAGER_INTERFACE),
+            "Inhibit",
+            &(what, WHO, WHY, mode),
+        )
+        .await?;
+    reply.body().deserialize()
+}
+
+/// Hold an inhibitor lock on `what` for as long as the subscription runs.
+/// It never yields anything.
+pub fn subscription<T: Send + 'static>(what: String) -> cosmic::iced::Subscription<T> {
+    cosmic::iced::Subscription::run_with_id(
+        what.clone(),
+        cosmic::iced::stream::channel(1, |_output| async move {
+            match inhibit(&what).await {
+                Ok(_lock) => {
+                    debug!("Inhibiting {} while playing", what);
+                    future::pending::<()>().await;
+                }
+                Err(e) => warn!("Could not inhibit {}: {}", what, e),
+            }
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inhibited() {
+        assert_eq!(inhibited(true, false).as_deref(), Some("idle"));
+        assert_eq!(inhibited(true, true).as_deref(), Some("idle:sleep"));
+        assert_eq!(inhibited(false, true).as_deref(), Some("sleep"));
+        assert_eq!(inhibited(false, false), None);
+    }
+}
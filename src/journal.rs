@@ -0,0 +1,226 @@
+//! Change journal for the favorites list
+//!
+//! Every save is diffed against the previous favorites so unexpected
+//! changes (for example from a sync on another machine) can be seen and
+//! reverted.
+
+use crate::api::Station;
+use crate::station::StationId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Oldest entries are dropped beyond this many
+pub const MAX_JOURNAL_ENTRIES: usize = 200;
+
+/// Shorter retention for the lite profile
+pub const LITE_JOURNAL_ENTRIES: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FavoriteChange {
+    Added {
+        station: Station,
+    },
+    Removed {
+        station: Station,
+    },
+    Renamed {
+        id: StationId,
+        from: String,
+        to: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub change: FavoriteChange,
+}
+
+impl JournalEntry {
+    /// Undo this change on `favorites`. Returns false if there was nothing to undo.
+    pub fn revert(&self, favorites: &mut Vec<Station>) -> bool {
+        match &self.change {
+            FavoriteChange::Added { station } => {
+                let before = favorites.len();
+                favorites.retain(|s| s.id != station.id);
+                favorites.len() != before
+            }
+            FavoriteChange::Removed { station } => {
+                if favorites.iter().any(|s| s.id == station.id) {
+                    return false;
+                }
+                favorites.push(station.clone());
+                true
+            }
+            FavoriteChange::Renamed { id, from, .. } => {
+                match favorites.iter_mut().find(|s| &s.id == id) {
+                    Some(station) if &station.name != from => {
+                        station.name.clone_from(from);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+        }
+    }
+
+    /// Name of the station the entry is about
+    pub fn station_name(&self) -> &str {
+        match &self.change {
+            FavoriteChange::Added { station } | FavoriteChange::Removed { station } => {
+                &station.name
+            }
+            FavoriteChange::Renamed { to, .. } => to,
+        }
+    }
+}
+
+/// Compute the changes that turn `old` into `new`, matched by station id
+pub fn diff_favorites(old: &[Station], new: &[Station], timestamp: u64) -> Vec<JournalEntry> {
+    let old_by_id: HashMap<&StationId, &Station> = old.iter().map(|s| (&s.id, s)).collect();
+    let new_by_id: HashMap<&StationId, &Station> = new.iter().map(|s| (&s.id, s)).collect();
+
+    let removed = old
+        .iter()
+        .filter(|s| !new_by_id.contains_key(&s.id))
+        .map(|s| FavoriteChange::Removed { station: s.clone() });
+
+    let added_or_renamed = new.iter().filter_map(|s| match old_by_id.get(&s.id) {
+        None => Some(FavoriteChange::Added { station: s.clone() }),
+        Some(previous) if previous.name != s.name => Some(FavoriteChange::Renamed {
+            id: s.id.clone(),
+            from: previous.name.clone(),
+            to: s.name.clone(),
+        }),
+        Some(_) => None,
+    });
+
+    removed
+        .chain(added_or_renamed)
+        .map(|change| JournalEntry { timestamp, change })
+        .collect()
+}
+
+/// Append entries, dropping the oldest beyond `max_entries`
+pub fn append(journal: &mut Vec<JournalEntry>, entries: Vec<JournalEntry>, max_entries: usize) {
+    journal.extend(entries);
+    if journal.len() > max_entries {
+        let excess = journal.len() - max_entries;
+        journal.drain(..excess);
+    }
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Format a Unix timestamp as `YYYY-MM-DD HH:MM` (UTC)
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = timestamp / 86_400;
+    let secs_of_day = timestamp % 86_400;
+
+    // Civil-from-days (Howard Hinnant), valid for all dates after 1970
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        (secs_of_day % 3600) / 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn station(uuid: &str, name: &str) -> Station {
+        let mut s = Station {
+            stationuuid: uuid.to_string(),
+            name: name.to_string(),
+            ..Default::default()
+        };
+        s.ensure_id();
+        s
+    }
+
+    #[test]
+    fn test_diff_detects_add_remove_rename() {
+        let old = vec![station("a", "Alpha"), station("b", "Beta")];
+        let new = vec![station("b", "Beta FM"), station("c", "Gamma")];
+
+        let entries = diff_favorites(&old, &new, 42);
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().all(|e| e.timestamp == 42));
+        assert!(
+            matches!(&entries[0].change, FavoriteChange::Removed { station } if station.name == "Alpha")
+        );
+        assert!(
+            matches!(&entries[1].change, FavoriteChange::Renamed { from, to, .. } if from == "Beta" && to == "Beta FM")
+        );
+        assert!(
+            matches!(&entries[2].change, FavoriteChange::Added { station } if station.name == "Gamma")
+        );
+    }
+
+    #[test]
+    fn test_diff_unchanged_is_empty() {
+        let list = vec![station("a", "Alpha")];
+        assert!(diff_favorites(&list, &list, 0).is_empty());
+    }
+
+    #[test]
+    fn test_revert_each_kind() {
+        let old = vec![station("a", "Alpha"), station("b", "Beta")];
+        let mut favorites = vec![station("b", "Beta FM"), station("c", "Gamma")];
+        let entries = diff_favorites(&old, &favorites, 0);
+
+        for entry in &entries {
+            assert!(entry.revert(&mut favorites));
+        }
+        let mut names: Vec<&str> = favorites.iter().map(|s| s.name.as_str()).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["Alpha", "Beta"]);
+
+        // Reverting twice is a no-op
+        assert!(!entries[0].revert(&mut favorites));
+    }
+
+    #[test]
+    fn test_append_caps_length() {
+        let mut journal = Vec::new();
+        let entries: Vec<JournalEntry> = (0..MAX_JOURNAL_ENTRIES as u64 + 5)
+            .map(|i| JournalEntry {
+                timestamp: i,
+                change: FavoriteChange::Added {
+                    station: Station::default(),
+                },
+            })
+            .collect();
+        append(&mut journal, entries, MAX_JOURNAL_ENTRIES);
+        assert_eq!(journal.len(), MAX_JOURNAL_ENTRIES);
+        assert_eq!(journal[0].timestamp, 5);
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00");
+        assert_eq!(format_timestamp(1_790_000_000), "2026-09-21 14:13");
+    }
+}
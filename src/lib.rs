@@ -1,10 +1,44 @@
 // Library exports for testing
 pub mod api;
 pub mod audio;
+pub mod backup;
+pub mod cache;
 pub mod config;
+pub mod ctl;
+pub mod dedupe;
+pub mod deeplink;
+pub mod duck;
+pub mod error;
+pub mod events;
+pub mod favicon;
+pub mod geo;
+#[cfg(feature = "gstreamer")]
+pub mod gst_backend;
+pub mod history;
+pub mod inhibit;
+pub mod journal;
+pub mod migration;
+pub mod mock_audio;
 pub mod mpris;
+pub mod network;
+pub mod notify;
+pub mod opml;
+pub mod output;
+pub mod panel;
+pub mod playlist;
+pub mod profile;
+pub mod provider;
+pub mod qr;
+pub mod quality;
+pub mod safe_write;
+pub mod scrobble;
+pub mod shortcuts;
+pub mod sleep;
+pub mod station;
+pub mod store;
+pub mod sync;
 
 // Re-export commonly used items for easier testing
-pub use api::{search_stations, Station};
+pub use api::{search_stations, SearchField, SearchOrder, SearchParams, Station};
 pub use audio::AudioManager;
 pub use config::Config;
@@ -1,13 +1,69 @@
 mod api;
 mod app;
 mod audio;
+mod backup;
+mod cache;
 mod config;
+mod dedupe;
+mod deeplink;
+mod duck;
 mod error;
+mod events;
+mod favicon;
+mod geo;
+#[cfg(feature = "gstreamer")]
+mod gst_backend;
+mod history;
 mod i18n;
+mod inhibit;
+mod journal;
+mod migration;
+mod mock_audio;
 mod mpris;
+mod network;
+mod notify;
+mod opml;
+mod output;
+mod panel;
+mod playlist;
+mod profile;
+mod provider;
+mod qr;
+mod quality;
+mod safe_write;
+mod scrobble;
+mod shortcuts;
+mod sleep;
+mod station;
+mod store;
+mod sync;
 
 fn main() -> cosmic::iced::Result {
+    // Launched as the cosmicradio:// handler: pass the link to the running applet
+    if let Some(uri) = std::env::args()
+        .nth(1)
+        .filter(|arg| deeplink::is_deep_link(arg))
+    {
+        forward_deep_link(&uri);
+        return Ok(());
+    }
+
     let requested_languages = i18n_embed::DesktopLanguageRequester::requested_languages();
     i18n::init(&requested_languages);
     cosmic::applet::run::<app::AppModel>(())
 }
+
+fn forward_deep_link(uri: &str) {
+    let result = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())
+        .and_then(|rt| {
+            rt.block_on(mpris::forward_uri(uri))
+                .map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        eprintln!("Could not pass {uri} to the radio applet: {e}");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,212 @@
+//! Config migrations
+//!
+//! cosmic-config keeps each `Config::VERSION` in its own `v<N>` directory
+//! with one RON file per field, so a version bump would otherwise start over
+//! from defaults. On startup, when the current version has no fields yet,
+//! the newest older version is copied forward field by field, applying the
+//! renames and removals in [`STEPS`] on the way.
+//!
+//! Whenever fields are about to be rewritten (a migration, or a config with
+//! fields that no longer deserialize and are reset to their defaults), the
+//! version's files are first copied to `backups/v<N>-<time>` next to them.
+
+use crate::safe_write;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Field changes needed to reach `version` from the one before it
+pub struct Step {
+    pub version: u64,
+    pub renamed: &'static [(&'static str, &'static str)],
+    pub removed: &'static [&'static str],
+}
+
+/// Every step so far, oldest first. Fields that were only added need no
+/// step; their serde default fills them in. A field whose type changes
+/// should be renamed, so the old value is dropped instead of failing to
+/// load.
+pub const STEPS: &[Step] = &[];
+
+/// `$XDG_CONFIG_HOME/cosmic/<app_id>`, or `~/.config/...` without it; where
+/// cosmic-config keeps the version directories
+pub fn config_root(app_id: &str) -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("cosmic").join(app_id))
+}
+
+fn version_dir(root: &Path, version: u64) -> PathBuf {
+    root.join(format!("v{version}"))
+}
+
+/// Field name to RON text for every field stored in `dir`
+fn read_fields(dir: &Path) -> io::Result<BTreeMap<String, String>> {
+    let mut fields = BTreeMap::new();
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(fields),
+        Err(e) => return Err(e),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+        // Skip leftovers of interrupted writes
+        if name.starts_with('.') || !entry.file_type()?.is_file() {
+            continue;
+        }
+        fields.insert(name, fs::read_to_string(entry.path())?);
+    }
+    Ok(fields)
+}
+
+fn write_fields(dir: &Path, fields: &BTreeMap<String, String>) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    for (name, text) in fields {
+        safe_write::write_atomic(&dir.join(name), text.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Versions below `current` that have a directory, newest first
+fn older_versions(root: &Path, current: u64) -> io::Result<Vec<u64>> {
+    let mut versions: Vec<u64> = match fs::read_dir(root) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                name.strip_prefix('v')?.parse().ok()
+            })
+            .filter(|&version| version < current)
+            .collect(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    versions.sort_unstable_by(|a, b| b.cmp(a));
+    Ok(versions)
+}
+
+/// Apply the steps taking `fields` from version `from` to `to`
+pub fn upgrade(fields: &mut BTreeMap<String, String>, from: u64, to: u64, steps: &[Step]) {
+    for step in steps.iter().filter(|s| s.version > from && s.version <= to) {
+        for &(old, new) in step.renamed {
+            if let Some(text) = fields.remove(old) {
+                fields.insert(new.to_string(), text);
+            }
+        }
+        for &name in step.removed {
+            fields.remove(name);
+        }
+    }
+}
+
+/// Copy the fields of `version` to a timestamped backup directory.
+/// Returns `None` if there was nothing to back up.
+pub fn backup(root: &Path, version: u64, now: u64) -> io::Result<Option<PathBuf>> {
+    let fields = read_fields(&version_dir(root, version))?;
+    if fields.is_empty() {
+        return Ok(None);
+    }
+    let dest = root.join("backups").join(format!("v{version}-{now}"));
+    write_fields(&dest, &fields)?;
+    Ok(Some(dest))
+}
+
+/// Bring the newest older config forward to `current` if `current` has no
+/// fields yet. Returns the version migrated from.
+pub fn migrate(root: &Path, current: u64, now: u64) -> io::Result<Option<u64>> {
+    let target = version_dir(root, current);
+    if !read_fields(&target)?.is_empty() {
+        return Ok(None);
+    }
+    for from in older_versions(root, current)? {
+        let mut fields = read_fields(&version_dir(root, from))?;
+        if fields.is_empty() {
+            continue;
+        }
+        backup(root, from, now)?;
+        upgrade(&mut fields, from, current, STEPS);
+        write_fields(&target, &fields)?;
+        return Ok(Some(from));
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "cosmic-ext-radio-migration-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn fields(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|&(name, text)| (name.to_string(), text.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_upgrade_applies_steps_in_range() {
+        let steps = [
+            Step {
+                version: 3,
+                renamed: &[("fav", "favorites")],
+                removed: &[],
+            },
+            Step {
+                version: 5,
+                renamed: &[],
+                removed: &["legacy"],
+            },
+        ];
+        let mut old = fields(&[("fav", "[]"), ("legacy", "true"), ("volume", "40")]);
+        upgrade(&mut old, 2, 4, &steps);
+        assert_eq!(
+            old,
+            fields(&[("favorites", "[]"), ("legacy", "true"), ("volume", "40")])
+        );
+
+        upgrade(&mut old, 4, 9, &steps);
+        assert_eq!(old, fields(&[("favorites", "[]"), ("volume", "40")]));
+    }
+
+    #[test]
+    fn test_migrate_copies_newest_older_version() {
+        let root = scratch_dir("forward");
+        write_fields(&version_dir(&root, 3), &fields(&[("volume", "10")])).unwrap();
+        write_fields(&version_dir(&root, 7), &fields(&[("volume", "70")])).unwrap();
+        fs::create_dir_all(version_dir(&root, 8)).unwrap();
+
+        assert_eq!(migrate(&root, 9, 1000).unwrap(), Some(7));
+        assert_eq!(
+            read_fields(&version_dir(&root, 9)).unwrap(),
+            fields(&[("volume", "70")])
+        );
+        assert!(root.join("backups/v7-1000/volume").is_file());
+
+        // Already migrated
+        assert_eq!(migrate(&root, 9, 2000).unwrap(), None);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_nothing_to_migrate_or_back_up() {
+        let root = scratch_dir("fresh");
+        assert_eq!(migrate(&root, 9, 1000).unwrap(), None);
+        assert_eq!(backup(&root, 9, 1000).unwrap(), None);
+        assert!(!root.exists());
+    }
+}
@@ -0,0 +1,135 @@
+//! Simulated playback for demos and testing
+//!
+//! With `COSMIC_RADIO_MOCK_AUDIO=1` in the environment nothing is actually
+//! played or downloaded: [`MockAudioBackend`] only remembers what would be
+//! playing, and the app announces a made-up track every [`TRACK_INTERVAL`]
+//! so the UI, MPRIS and everything else on the event bus can be exercised
+//! on machines without speakers or network.
+
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::debug;
+
+pub const ENV_VAR: &str = "COSMIC_RADIO_MOCK_AUDIO";
+
+/// How often a "playing" mock station moves on to its next track
+pub const TRACK_INTERVAL: Duration = Duration::from_secs(20);
+
+const ARTISTS: [&str; 5] = [
+    "The Test Patterns",
+    "Null Island",
+    "Loopback Orchestra",
+    "Carrier Wave",
+    "Dead Air",
+];
+const TITLES: [&str; 7] = [
+    "Signal Found",
+    "Buffer Underrun",
+    "Night Shift",
+    "Station Identification",
+    "Long Wave",
+    "Static Bloom",
+    "Sign Off",
+];
+
+/// Whether `COSMIC_RADIO_MOCK_AUDIO` asks for simulated playback
+pub fn enabled() -> bool {
+    is_enabled_value(std::env::var(ENV_VAR).ok().as_deref())
+}
+
+fn is_enabled_value(value: Option<&str>) -> bool {
+    value.is_some_and(|value| !matches!(value.trim(), "" | "0" | "false" | "no"))
+}
+
+/// The `n`th synthetic track, "Artist - Title"
+pub fn track_title(n: u32) -> String {
+    let n = n as usize;
+    format!(
+        "{} - {}",
+        ARTISTS[n % ARTISTS.len()],
+        TITLES[n % TITLES.len()]
+    )
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    url: Option<String>,
+    /// Tracks announced since the current stream started
+    tracks: u32,
+}
+
+/// Stands in for mpv/GStreamer when [`enabled`]
+#[derive(Debug, Default)]
+pub struct MockAudioBackend {
+    state: Mutex<MockState>,
+}
+
+impl MockAudioBackend {
+    pub fn play(&self, url: &str, volume: u8) {
+        debug!("Mock audio: playing {} at {}%", url, volume);
+        if let Ok(mut state) = self.state.lock() {
+            *state = MockState {
+                url: Some(url.to_string()),
+                tracks: 0,
+            };
+        }
+    }
+
+    pub fn stop(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            if let Some(url) = state.url.take() {
+                debug!("Mock audio: stopped {}", url);
+            }
+        }
+    }
+
+    pub fn set_volume(&self, volume: f32) {
+        debug!("Mock audio: volume {}%", volume);
+    }
+
+    /// Move on to the next synthetic track; `None` while nothing plays
+    pub fn next_track(&self) -> Option<String> {
+        let mut state = self.state.lock().ok()?;
+        state.url.as_ref()?;
+        let title = track_title(state.tracks);
+        state.tracks = state.tracks.wrapping_add(1);
+        Some(title)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enabled_values() {
+        assert!(is_enabled_value(Some("1")));
+        assert!(is_enabled_value(Some("yes")));
+        assert!(!is_enabled_value(Some("0")));
+        assert!(!is_enabled_value(Some(" ")));
+        assert!(!is_enabled_value(None));
+    }
+
+    #[test]
+    fn test_tracks_only_while_playing() {
+        let backend = MockAudioBackend::default();
+        assert_eq!(backend.next_track(), None);
+
+        backend.play("http://example.com/stream", 40);
+        assert_eq!(backend.next_track(), Some(track_title(0)));
+        assert_eq!(backend.next_track(), Some(track_title(1)));
+
+        // A new stream starts its track list over
+        backend.play("http://example.com/other", 40);
+        assert_eq!(backend.next_track(), Some(track_title(0)));
+
+        backend.stop();
+        assert_eq!(backend.next_track(), None);
+    }
+
+    #[test]
+    fn test_track_titles_vary() {
+        assert_ne!(track_title(0), track_title(1));
+        assert!(track_title(0).contains(" - "));
+    }
+}
@@ -1,8 +1,19 @@
 use crate::api::Station;
+use crate::config::Config;
+use crate::deeplink;
+use crate::events::{AppEvent, Navigation, NetworkEvent, PlaybackEvent, StationCollection};
+use crate::favicon;
+use crate::station::StationId;
 use futures::SinkExt;
-use mpris_server::{Metadata, PlaybackStatus, Player, TrackId};
+use mpris_server::{LoopStatus, Metadata, MetadataBuilder, PlaybackStatus, Player, Time, TrackId};
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
+use url::Url;
+use zbus::object_server::SignalEmitter;
+use zbus::zvariant::{ObjectPath, OwnedObjectPath};
 
 /// Commands from D-Bus clients to the app
 #[derive(Debug, Clone)]
@@ -11,26 +22,296 @@ pub enum MprisCommand {
     Pause,
     PlayPause,
     Stop,
+    /// Skip to the next (or a random) favorite
+    Next,
+    Previous,
     SetVolume(f64),
+    SetShuffle(bool),
+    /// Whether skipping wraps around the favorites list
+    SetLooping(bool),
     Raise,
     Quit,
+    /// A `cosmicradio://` link to act on, or an http(s) stream to play
+    OpenUri(String),
+    /// Start the first station of the [`StationCollection`] with this id
+    ActivatePlaylist(String),
 }
 
 /// State updates from the app to the MPRIS server
 #[derive(Debug, Clone)]
 pub enum MprisStateUpdate {
     Playing { station: Box<Station> },
+    /// A new track on the playing station
+    Track {
+        station: Box<Station>,
+        title: String,
+    },
     Stopped,
     Volume(u8),
+    Navigation(Navigation),
+    Playlists(Vec<StationCollection>),
+    /// Whether stations can be reached, and so started
+    Online(bool),
+    /// Leave the bus and end the server thread
+    Shutdown,
+}
+
+impl MprisStateUpdate {
+    /// The update an app event implies for MPRIS clients, if any
+    pub fn from_event(event: &AppEvent) -> Option<Self> {
+        match event {
+            AppEvent::Playback(PlaybackEvent::Started { station }) => Some(Self::Playing {
+                station: station.clone(),
+            }),
+            AppEvent::Playback(PlaybackEvent::Stopped) => Some(Self::Stopped),
+            AppEvent::Playback(PlaybackEvent::Volume(volume)) => Some(Self::Volume(*volume)),
+            AppEvent::Playback(PlaybackEvent::Navigation(navigation)) => {
+                Some(Self::Navigation(*navigation))
+            }
+            AppEvent::Metadata { station, title } => Some(Self::Track {
+                station: station.clone(),
+                title: title.clone(),
+            }),
+            AppEvent::StationCollections(groups) => Some(Self::Playlists(groups.clone())),
+            AppEvent::Network(event) => Some(Self::Online(*event == NetworkEvent::Online)),
+            AppEvent::Shutdown => Some(Self::Shutdown),
+            AppEvent::ConfigChanged => None,
+        }
+    }
 }
 
 /// Events yielded by the MPRIS subscription
 #[derive(Debug, Clone)]
 pub enum MprisEvent {
-    Ready(mpsc::UnboundedSender<MprisStateUpdate>),
+    /// Connect this sender to the app's event bus
+    Ready(mpsc::UnboundedSender<AppEvent>),
     Command(MprisCommand),
 }
 
+/// Name the player registers under, after `org.mpris.MediaPlayer2.`
+const PLAYER_NAME: &str = "cosmic_ext_applet_radio";
+pub const BUS_NAME: &str = "org.mpris.MediaPlayer2.cosmic_ext_applet_radio";
+
+pub const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+
+pub const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+/// How often the stored Position catches up with the time played. Clients
+/// read it on demand and extrapolate from the Rate in between.
+const POSITION_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Where playlist object paths live; MPRIS only asks that they are unique
+const PLAYLIST_PATH_PREFIX: &str = "/com/marcos/RadioApplet/Playlist";
+
+/// `Orderings` offered by the Playlists interface
+const PLAYLIST_ORDERINGS: [&str; 2] = ["UserDefined", "Alphabetical"];
+
+/// Which stations a playlist stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaylistSource {
+    /// Every favorite, in list order
+    Favorites,
+    /// The favorites folder at this index of [`Config::favorite_folders`]
+    Folder(usize),
+    /// Stations played, newest first
+    History,
+}
+
+impl PlaylistSource {
+    pub fn id(self) -> String {
+        match self {
+            Self::Favorites => "favorites".to_string(),
+            Self::Folder(index) => format!("folder_{index}"),
+            Self::History => "history".to_string(),
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "favorites" => Some(Self::Favorites),
+            "history" => Some(Self::History),
+            _ => id.strip_prefix("folder_")?.parse().ok().map(Self::Folder),
+        }
+    }
+
+    /// The station activating the playlist starts
+    pub fn first_station(self, config: &Config) -> Option<&Station> {
+        match self {
+            Self::Favorites => config.favorites_in_list_order().first().copied(),
+            Self::Folder(index) => {
+                let folder = *config.favorite_folders().get(index)?;
+                config
+                    .favorites_in_list_order()
+                    .into_iter()
+                    .find(|station| station.folder == folder)
+            }
+            Self::History => config.history.first().map(|entry| &entry.station),
+        }
+    }
+}
+
+/// Object path of the playlist for `group`
+pub fn playlist_path(group: &StationCollection) -> String {
+    format!("{PLAYLIST_PATH_PREFIX}/{}", group.id)
+}
+
+/// The slice of `groups` a `GetPlaylists` call asks for. Unknown orderings
+/// fall back to the user-defined one.
+pub fn playlist_page<'a>(
+    groups: &'a [StationCollection],
+    index: u32,
+    max_count: u32,
+    order: &str,
+    reverse: bool,
+) -> Vec<&'a StationCollection> {
+    let mut page: Vec<&StationCollection> = groups.iter().collect();
+    if order == "Alphabetical" {
+        page.sort_by_cached_key(|group| group.name.to_lowercase());
+    }
+    if reverse {
+        page.reverse();
+    }
+    page.into_iter()
+        .skip(index as usize)
+        .take(max_count as usize)
+        .collect()
+}
+
+/// A playlist as the Playlists interface sends it: path, name and icon
+type DbusPlaylist = (OwnedObjectPath, String, String);
+
+fn dbus_playlist(group: &StationCollection) -> Option<DbusPlaylist> {
+    let path = OwnedObjectPath::try_from(playlist_path(group)).ok()?;
+    Some((path, group.name.clone(), String::new()))
+}
+
+/// `org.mpris.MediaPlayer2.Playlists`, served on the player's connection
+/// next to the interfaces `mpris_server` provides
+struct PlaylistsInterface {
+    groups: Vec<StationCollection>,
+    /// Id of the group last activated, while it still exists
+    active: Option<String>,
+    cmd_tx: mpsc::UnboundedSender<MprisCommand>,
+}
+
+#[zbus::interface(name = "org.mpris.MediaPlayer2.Playlists")]
+impl PlaylistsInterface {
+    async fn activate_playlist(
+        &mut self,
+        playlist_id: ObjectPath<'_>,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+    ) -> zbus::fdo::Result<()> {
+        let group = self
+            .groups
+            .iter()
+            .find(|group| playlist_path(group) == playlist_id.as_str())
+            .ok_or_else(|| zbus::fdo::Error::InvalidArgs(format!("No playlist {playlist_id}")))?;
+        self.active = Some(group.id.clone());
+        let _ = self
+            .cmd_tx
+            .send(MprisCommand::ActivatePlaylist(group.id.clone()));
+        self.active_playlist_changed(&emitter).await?;
+        Ok(())
+    }
+
+    async fn get_playlists(
+        &self,
+        index: u32,
+        max_count: u32,
+        order: String,
+        reverse_order: bool,
+    ) -> Vec<DbusPlaylist> {
+        playlist_page(&self.groups, index, max_count, &order, reverse_order)
+            .into_iter()
+            .filter_map(dbus_playlist)
+            .collect()
+    }
+
+    #[zbus(signal)]
+    async fn playlist_changed(
+        emitter: &SignalEmitter<'_>,
+        playlist: DbusPlaylist,
+    ) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    async fn playlist_count(&self) -> u32 {
+        u32::try_from(self.groups.len()).unwrap_or(u32::MAX)
+    }
+
+    #[zbus(property)]
+    async fn orderings(&self) -> Vec<String> {
+        PLAYLIST_ORDERINGS.map(String::from).to_vec()
+    }
+
+    #[zbus(property)]
+    async fn active_playlist(&self) -> (bool, DbusPlaylist) {
+        let active = self
+            .active
+            .as_ref()
+            .and_then(|id| self.groups.iter().find(|group| &group.id == id))
+            .and_then(dbus_playlist);
+        match active {
+            Some(playlist) => (true, playlist),
+            // Spec: the playlist is ignored unless the flag is set
+            None => (
+                false,
+                (
+                    ObjectPath::from_static_str_unchecked("/").into(),
+                    String::new(),
+                    String::new(),
+                ),
+            ),
+        }
+    }
+}
+
+/// Offer `groups` as the playlists, telling clients what changed
+async fn update_playlists(
+    connection: &zbus::Connection,
+    groups: Vec<StationCollection>,
+) -> zbus::Result<()> {
+    let iface = connection
+        .object_server()
+        .interface::<_, PlaylistsInterface>(OBJECT_PATH)
+        .await?;
+    let mut playlists = iface.get_mut().await;
+    if playlists.groups == groups {
+        return Ok(());
+    }
+    let emitter = iface.signal_emitter();
+    for group in &groups {
+        let renamed = playlists
+            .groups
+            .iter()
+            .any(|old| old.id == group.id && old.name != group.name);
+        if let Some(playlist) = dbus_playlist(group).filter(|_| renamed) {
+            PlaylistsInterface::playlist_changed(emitter, playlist).await?;
+        }
+    }
+    let count_changed = playlists.groups.len() != groups.len();
+    playlists.groups = groups;
+    if count_changed {
+        playlists.playlist_count_changed(emitter).await?;
+    }
+    playlists.active_playlist_changed(emitter).await?;
+    Ok(())
+}
+
+/// `LoopStatus` for whether skipping wraps around the favorites; a radio
+/// stream has no track to repeat
+pub fn loop_status(looping: bool) -> LoopStatus {
+    if looping {
+        LoopStatus::Playlist
+    } else {
+        LoopStatus::None
+    }
+}
+
+/// Whether a `LoopStatus` set by a client asks for wrapping around
+pub fn is_looping(status: LoopStatus) -> bool {
+    status != LoopStatus::None
+}
+
 /// Convert app volume (0-100 u8) to MPRIS volume (0.0-1.0 f64)
 #[must_use]
 pub fn volume_to_mpris(vol: u8) -> f64 {
@@ -44,9 +325,40 @@ pub fn volume_from_mpris(vol: f64) -> u8 {
     (vol.clamp(0.0, 1.0) * 100.0).round() as u8
 }
 
-/// Build MPRIS metadata from a Station
+/// Build MPRIS metadata from a Station, titled with its alias if it has one
 pub fn build_metadata(station: &Station) -> Metadata {
-    let mut builder = Metadata::builder().title(&station.name);
+    station_metadata(station)
+        .title(station.display_name())
+        .build()
+}
+
+/// Metadata for a track playing on `station`, which becomes the album
+pub fn build_track_metadata(station: &Station, stream_title: &str) -> Metadata {
+    let (artist, title) = split_stream_title(stream_title);
+    let mut builder = station_metadata(station)
+        .title(title)
+        .album(station.display_name());
+    if let Some(artist) = artist {
+        builder = builder.artist([artist]);
+    }
+    builder.build()
+}
+
+/// Artist and title of an ICY `StreamTitle`, which stations mostly send as
+/// "Artist - Title". Anything else is all title.
+pub fn split_stream_title(stream_title: &str) -> (Option<&str>, &str) {
+    let stream_title = stream_title.trim();
+    match stream_title.split_once(" - ") {
+        Some((artist, title)) if !artist.trim().is_empty() && !title.trim().is_empty() => {
+            (Some(artist.trim()), title.trim())
+        }
+        _ => (None, stream_title),
+    }
+}
+
+/// Everything but the title
+fn station_metadata(station: &Station) -> MetadataBuilder {
+    let mut builder = Metadata::builder();
 
     if !station.stationuuid.is_empty() {
         let sanitized = station.stationuuid.replace('-', "_");
@@ -57,7 +369,8 @@ pub fn build_metadata(station: &Station) -> Metadata {
     }
 
     if !station.favicon.is_empty() {
-        builder = builder.art_url(&station.favicon);
+        let cached = favicon::cached_path(&station.favicon);
+        builder = builder.art_url(art_url(&station.favicon, cached.as_deref()));
     }
 
     if !station.homepage.is_empty() {
@@ -69,16 +382,93 @@ pub fn build_metadata(station: &Station) -> Metadata {
         builder = builder.genre(genres);
     }
 
-    builder.build()
+    builder
+}
+
+/// `file://` URL of the cached copy of a favicon if there is one, since
+/// lock screens often can't fetch remote art, or else the remote URL
+fn art_url(favicon: &str, cached: Option<&Path>) -> String {
+    cached
+        .and_then(|path| Url::from_file_path(path).ok())
+        .map_or_else(|| favicon.to_string(), String::from)
+}
+
+/// Download the favicon at `url` into the cache. Returns whether a copy
+/// is there now.
+async fn cache_art(url: String) -> bool {
+    match favicon::fetch(url.clone()).await {
+        Ok(_) => favicon::cached_path(&url).is_some(),
+        Err(e) => {
+            debug!("No local MPRIS art from {}: {}", url, e);
+            false
+        }
+    }
+}
+
+/// Favicons being downloaded for local art, and those that could not be
+/// and aren't tried again this session
+#[derive(Debug, Default)]
+struct ArtFetches {
+    pending: HashSet<String>,
+    failed: HashSet<String>,
+}
+
+impl ArtFetches {
+    /// Whether to download `url` now: it isn't cached, underway or known
+    /// to fail
+    fn start(&mut self, url: &str, cached: bool) -> bool {
+        if url.is_empty() || cached || self.failed.contains(url) {
+            return false;
+        }
+        self.pending.insert(url.to_string())
+    }
+
+    fn finish(&mut self, url: &str, stored: bool) {
+        self.pending.remove(url);
+        if !stored {
+            self.failed.insert(url.to_string());
+        }
+    }
+}
+
+/// Time the current stream has been playing, reported as the MPRIS
+/// Position. A live stream has no length, so this only ever counts up.
+#[derive(Debug, Default)]
+struct Elapsed {
+    since: Option<(StationId, Instant)>,
+}
+
+impl Elapsed {
+    /// Note that `station` plays. Returns true if the clock started over,
+    /// for a new station or after a stop, and false if it was already
+    /// running for it.
+    fn play(&mut self, station: &StationId, now: Instant) -> bool {
+        if self.since.as_ref().is_some_and(|(id, _)| id == station) {
+            return false;
+        }
+        self.since = Some((station.clone(), now));
+        true
+    }
+
+    fn stop(&mut self) {
+        self.since = None;
+    }
+
+    fn position(&self, now: Instant) -> Time {
+        let elapsed = self.since.as_ref().map_or(Duration::ZERO, |(_, start)| {
+            now.saturating_duration_since(*start)
+        });
+        Time::from_micros(i64::try_from(elapsed.as_micros()).unwrap_or(i64::MAX))
+    }
 }
 
 /// Spawn the MPRIS server on a dedicated OS thread.
 ///
-/// Returns a sender for pushing state updates to the MPRIS server.
+/// Returns a sender for feeding app events to the MPRIS server.
 /// Commands from D-Bus clients are forwarded via `cmd_tx`.
 fn spawn_mpris_thread(
     cmd_tx: mpsc::UnboundedSender<MprisCommand>,
-) -> mpsc::UnboundedSender<MprisStateUpdate> {
+) -> mpsc::UnboundedSender<AppEvent> {
     let (state_tx, state_rx) = mpsc::unbounded_channel();
 
     std::thread::spawn(move || {
@@ -103,17 +493,28 @@ fn spawn_mpris_thread(
 /// Run the MPRIS server event loop (must be called on a `LocalSet`)
 async fn run_mpris_server(
     cmd_tx: mpsc::UnboundedSender<MprisCommand>,
-    mut state_rx: mpsc::UnboundedReceiver<MprisStateUpdate>,
+    mut state_rx: mpsc::UnboundedReceiver<AppEvent>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let player = Player::builder("cosmic_ext_applet_radio")
+    let player = Player::builder(PLAYER_NAME)
         .identity("Radio for COSMIC")
         .desktop_entry("com.marcos.RadioApplet")
         .can_play(true)
         .can_pause(true)
         .can_control(true)
         .can_seek(false)
+        .rate(1.0)
+        .minimum_rate(1.0)
+        .maximum_rate(1.0)
         .can_go_next(false)
         .can_go_previous(false)
+        .can_raise(true)
+        .can_quit(true)
+        .loop_status(LoopStatus::Playlist)
+        .supported_uri_schemes(vec![
+            deeplink::SCHEME.to_string(),
+            "http".to_string(),
+            "https".to_string(),
+        ])
         .build()
         .await?;
 
@@ -142,12 +543,36 @@ async fn run_mpris_server(
             let _ = tx.send(MprisCommand::Stop);
         });
     }
+    {
+        let tx = cmd_tx.clone();
+        player.connect_next(move |_| {
+            let _ = tx.send(MprisCommand::Next);
+        });
+    }
+    {
+        let tx = cmd_tx.clone();
+        player.connect_previous(move |_| {
+            let _ = tx.send(MprisCommand::Previous);
+        });
+    }
     {
         let tx = cmd_tx.clone();
         player.connect_set_volume(move |_, vol| {
             let _ = tx.send(MprisCommand::SetVolume(vol));
         });
     }
+    {
+        let tx = cmd_tx.clone();
+        player.connect_set_shuffle(move |_, shuffle| {
+            let _ = tx.send(MprisCommand::SetShuffle(shuffle));
+        });
+    }
+    {
+        let tx = cmd_tx.clone();
+        player.connect_set_loop_status(move |_, status| {
+            let _ = tx.send(MprisCommand::SetLooping(is_looping(status)));
+        });
+    }
     {
         let tx = cmd_tx.clone();
         player.connect_raise(move |_| {
@@ -155,21 +580,86 @@ async fn run_mpris_server(
         });
     }
     {
-        let tx = cmd_tx;
+        let tx = cmd_tx.clone();
         player.connect_quit(move |_| {
             let _ = tx.send(MprisCommand::Quit);
         });
     }
+    {
+        let tx = cmd_tx.clone();
+        player.connect_open_uri(move |_, uri| {
+            let _ = tx.send(MprisCommand::OpenUri(uri.to_string()));
+        });
+    }
+
+    let playlists = PlaylistsInterface {
+        groups: Vec::new(),
+        active: None,
+        cmd_tx,
+    };
+    if let Err(e) = player
+        .connection()
+        .object_server()
+        .at(OBJECT_PATH, playlists)
+        .await
+    {
+        warn!("Failed to serve MPRIS playlists: {}", e);
+    }
 
     debug!("MPRIS server started on D-Bus");
 
     // Run the D-Bus event loop as a background local task
     tokio::task::spawn_local(player.run());
 
-    // Process state updates from the app
-    while let Some(update) = state_rx.recv().await {
+    // Process state updates from the app, keeping Position current
+    let mut elapsed = Elapsed::default();
+    let mut position_tick = tokio::time::interval(POSITION_INTERVAL);
+    // Favicons are downloaded on the side and come back here to switch
+    // the art over to the local copy
+    let (art_tx, mut art_rx) = mpsc::unbounded_channel::<(String, bool)>();
+    let mut art = ArtFetches::default();
+    // The station and track the metadata describes
+    let mut showing: Option<(Box<Station>, Option<String>)> = None;
+    loop {
+        let event = tokio::select! {
+            event = state_rx.recv() => match event {
+                Some(event) => event,
+                None => break,
+            },
+            _ = position_tick.tick() => {
+                player.set_position(elapsed.position(Instant::now()));
+                continue;
+            }
+            Some((url, stored)) = art_rx.recv() => {
+                art.finish(&url, stored);
+                let Some((station, title)) = showing.as_ref().filter(|(s, _)| s.favicon == url)
+                else {
+                    continue;
+                };
+                if stored {
+                    let metadata = match title {
+                        Some(title) => build_track_metadata(station, title),
+                        None => build_metadata(station),
+                    };
+                    if let Err(e) = player.set_metadata(metadata).await {
+                        warn!("Failed to set MPRIS metadata: {}", e);
+                    }
+                }
+                continue;
+            }
+        };
+        let Some(update) = MprisStateUpdate::from_event(&event) else {
+            continue;
+        };
         match update {
             MprisStateUpdate::Playing { station } => {
+                // Tell clients the position jumped back, e.g. after a reconnect
+                if elapsed.play(&station.id, Instant::now()) {
+                    player.set_position(Time::ZERO);
+                    if let Err(e) = player.seeked(Time::ZERO).await {
+                        warn!("Failed to emit MPRIS Seeked: {}", e);
+                    }
+                }
                 let metadata = build_metadata(station.as_ref());
                 if let Err(e) = player.set_metadata(metadata).await {
                     warn!("Failed to set MPRIS metadata: {}", e);
@@ -180,8 +670,26 @@ async fn run_mpris_server(
                 {
                     warn!("Failed to set MPRIS playback status: {}", e);
                 }
+                let url = station.favicon.clone();
+                if art.start(&url, favicon::cached_path(&url).is_some()) {
+                    let art_tx = art_tx.clone();
+                    tokio::task::spawn_local(async move {
+                        let stored = cache_art(url.clone()).await;
+                        let _ = art_tx.send((url, stored));
+                    });
+                }
+                showing = Some((station, None));
+            }
+            MprisStateUpdate::Track { station, title } => {
+                let metadata = build_track_metadata(station.as_ref(), &title);
+                if let Err(e) = player.set_metadata(metadata).await {
+                    warn!("Failed to set MPRIS metadata: {}", e);
+                }
+                showing = Some((station, Some(title)));
             }
             MprisStateUpdate::Stopped => {
+                elapsed.stop();
+                player.set_position(Time::ZERO);
                 if let Err(e) = player
                     .set_playback_status(PlaybackStatus::Stopped)
                     .await
@@ -194,15 +702,58 @@ async fn run_mpris_server(
                     warn!("Failed to set MPRIS volume: {}", e);
                 }
             }
+            MprisStateUpdate::Navigation(navigation) => {
+                let result = async {
+                    player.set_shuffle(navigation.shuffle).await?;
+                    player
+                        .set_loop_status(loop_status(navigation.looping))
+                        .await?;
+                    player.set_can_go_next(navigation.can_go_next).await?;
+                    player.set_can_go_previous(navigation.can_go_previous).await
+                };
+                if let Err(e) = result.await {
+                    warn!("Failed to set MPRIS navigation state: {}", e);
+                }
+            }
+            MprisStateUpdate::Playlists(groups) => {
+                if let Err(e) = update_playlists(player.connection(), groups).await {
+                    warn!("Failed to update MPRIS playlists: {}", e);
+                }
+            }
+            MprisStateUpdate::Online(online) => {
+                if let Err(e) = player.set_can_play(online).await {
+                    warn!("Failed to set MPRIS CanPlay: {}", e);
+                }
+            }
+            // Dropping the player releases the bus name
+            MprisStateUpdate::Shutdown => break,
         }
     }
 
     Ok(())
 }
 
-/// Create an iced Subscription that runs the MPRIS server and forwards events
-pub fn mpris_subscription() -> cosmic::iced::Subscription<MprisEvent> {
-    cosmic::iced::Subscription::run(|| {
+/// Hand `uri` to the running applet through its MPRIS `OpenUri` method
+pub async fn forward_uri(uri: &str) -> Result<(), mpris_server::zbus::Error> {
+    let connection = mpris_server::zbus::Connection::session().await?;
+    connection
+        .call_method(
+            Some(BUS_NAME),
+            OBJECT_PATH,
+            Some(PLAYER_INTERFACE),
+            "OpenUri",
+            &(uri,),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Create an iced Subscription that runs the MPRIS server and forwards
+/// events. The server ends on [`AppEvent::Shutdown`]; a new `session`
+/// starts another.
+pub fn mpris_subscription(session: u32) -> cosmic::iced::Subscription<MprisEvent> {
+    cosmic::iced::Subscription::run_with_id(
+        ("mpris", session),
         cosmic::iced::stream::channel(100, |mut output| async move {
             let (cmd_tx, mut cmd_rx) = mpsc::unbounded_channel();
             let state_tx = spawn_mpris_thread(cmd_tx);
@@ -216,8 +767,8 @@ pub fn mpris_subscription() -> cosmic::iced::Subscription<MprisEvent> {
                     break;
                 }
             }
-        })
-    })
+        }),
+    )
 }
 
 #[cfg(test)]
@@ -259,6 +810,10 @@ mod tests {
     #[test]
     fn test_build_metadata_full_station() {
         let station = Station {
+            id: crate::station::StationId::new(
+                crate::station::PROVIDER_RADIO_BROWSER,
+                "96202c39-0601-11e8-ae97-52543be04c81",
+            ),
             stationuuid: "96202c39-0601-11e8-ae97-52543be04c81".to_string(),
             name: "SomaFM - Groove Salad".to_string(),
             url: "https://somafm.com/groovesalad/".to_string(),
@@ -268,12 +823,107 @@ mod tests {
             tags: "ambient,electronic,chillout".to_string(),
             country: "USA".to_string(),
             language: "English".to_string(),
+            ..Default::default()
         };
 
         let metadata = build_metadata(&station);
         assert!(format!("{metadata:?}").contains("SomaFM"));
     }
 
+    #[test]
+    fn test_build_metadata_uses_alias() {
+        let station = Station {
+            name: "RADIO GROOVE SALAD 24/7 LIVE".to_string(),
+            alias: "Groove Salad".to_string(),
+            ..Default::default()
+        };
+        let metadata = format!("{:?}", build_metadata(&station));
+        assert!(metadata.contains("Groove Salad"));
+        assert!(!metadata.contains("LIVE"));
+    }
+
+    #[test]
+    fn test_elapsed_restarts_for_new_station() {
+        let jazz = StationId::new(crate::station::PROVIDER_CUSTOM, "jazz");
+        let news = StationId::new(crate::station::PROVIDER_CUSTOM, "news");
+        let start = Instant::now();
+        let mut elapsed = Elapsed::default();
+        assert_eq!(elapsed.position(start), Time::ZERO);
+
+        assert!(elapsed.play(&jazz, start));
+        assert!(!elapsed.play(&jazz, start + Duration::from_secs(5)));
+        assert_eq!(
+            elapsed.position(start + Duration::from_secs(90)),
+            Time::from_secs(90)
+        );
+
+        assert!(elapsed.play(&news, start + Duration::from_secs(100)));
+        assert_eq!(
+            elapsed.position(start + Duration::from_secs(130)),
+            Time::from_secs(30)
+        );
+
+        elapsed.stop();
+        assert!(elapsed.play(&news, start + Duration::from_secs(200)));
+    }
+
+    #[test]
+    fn test_art_url_prefers_cached_file() {
+        let remote = "https://somafm.com/favicon.ico";
+        assert_eq!(art_url(remote, None), remote);
+        assert_eq!(
+            art_url(remote, Some(Path::new("/tmp/favicons/ab12"))),
+            "file:///tmp/favicons/ab12"
+        );
+    }
+
+    #[test]
+    fn test_art_fetches_once_and_remembers_failures() {
+        let mut art = ArtFetches::default();
+        let url = "https://example.com/icon.png";
+        assert!(!art.start("", false));
+        assert!(!art.start(url, true));
+
+        assert!(art.start(url, false));
+        assert!(!art.start(url, false), "already underway");
+        art.finish(url, false);
+        assert!(!art.start(url, false), "failed before");
+
+        let other = "https://example.com/other.png";
+        assert!(art.start(other, false));
+        art.finish(other, true);
+        assert!(art.start(other, false), "gone from the cache since");
+    }
+
+    #[test]
+    fn test_split_stream_title() {
+        assert_eq!(
+            split_stream_title("Boards of Canada - Roygbiv "),
+            (Some("Boards of Canada"), "Roygbiv")
+        );
+        assert_eq!(
+            split_stream_title("Jay-Z - Song - Live Edit"),
+            (Some("Jay-Z"), "Song - Live Edit")
+        );
+        assert_eq!(
+            split_stream_title("Station jingle"),
+            (None, "Station jingle")
+        );
+        assert_eq!(split_stream_title(" - Untitled"), (None, "- Untitled"));
+
+        let station = Station {
+            name: "Groove Salad".to_string(),
+            ..Default::default()
+        };
+        let metadata = format!(
+            "{:?}",
+            build_track_metadata(&station, "Air - Alpha Beta Gaga")
+        );
+        assert!(metadata.contains("Alpha Beta Gaga"));
+        assert!(metadata.contains("Groove Salad"));
+        assert!(!metadata.contains("Air - "));
+    }
+
     #[test]
     fn test_build_metadata_empty_station() {
         let station = Station::default();
@@ -306,4 +956,105 @@ mod tests {
         let update = MprisStateUpdate::Volume(50);
         assert!(format!("{update:?}").contains("50"));
     }
+
+    #[test]
+    fn test_state_update_from_event() {
+        let started = AppEvent::Playback(PlaybackEvent::Started {
+            station: Box::new(Station {
+                name: "Jazz".to_string(),
+                ..Default::default()
+            }),
+        });
+        assert!(matches!(
+            MprisStateUpdate::from_event(&started),
+            Some(MprisStateUpdate::Playing { station }) if station.name == "Jazz"
+        ));
+        assert!(matches!(
+            MprisStateUpdate::from_event(&AppEvent::Playback(PlaybackEvent::Volume(30))),
+            Some(MprisStateUpdate::Volume(30))
+        ));
+        assert!(matches!(
+            MprisStateUpdate::from_event(&AppEvent::Metadata {
+                station: Box::default(),
+                title: "Song".to_string(),
+            }),
+            Some(MprisStateUpdate::Track { title, .. }) if title == "Song"
+        ));
+        assert!(MprisStateUpdate::from_event(&AppEvent::ConfigChanged).is_none());
+        assert!(matches!(
+            MprisStateUpdate::from_event(&AppEvent::Network(NetworkEvent::Offline)),
+            Some(MprisStateUpdate::Online(false))
+        ));
+        assert!(matches!(
+            MprisStateUpdate::from_event(&AppEvent::Shutdown),
+            Some(MprisStateUpdate::Shutdown)
+        ));
+    }
+
+    #[test]
+    fn test_loop_status_roundtrip() {
+        for looping in [true, false] {
+            assert_eq!(is_looping(loop_status(looping)), looping);
+        }
+        assert!(is_looping(LoopStatus::Track));
+    }
+
+    fn group(id: &str, name: &str) -> StationCollection {
+        StationCollection {
+            id: id.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_playlist_page() {
+        let groups = [
+            group("favorites", "Favorites"),
+            group("folder_0", "jazz"),
+            group("history", "History"),
+        ];
+        let ids = |page: Vec<&StationCollection>| -> Vec<String> {
+            page.into_iter().map(|g| g.id.clone()).collect()
+        };
+        assert_eq!(
+            ids(playlist_page(&groups, 0, 10, "UserDefined", false)),
+            ["favorites", "folder_0", "history"]
+        );
+        assert_eq!(
+            ids(playlist_page(&groups, 1, 1, "Alphabetical", false)),
+            ["history"]
+        );
+        assert_eq!(
+            ids(playlist_page(&groups, 0, 2, "LastPlayDate", true)),
+            ["history", "folder_0"]
+        );
+        assert_eq!(
+            playlist_path(&groups[1]),
+            "/com/marcos/RadioApplet/Playlist/folder_0"
+        );
+    }
+
+    #[test]
+    fn test_playlist_source_first_station() {
+        let favorite = |name: &str, folder: &str| Station {
+            name: name.to_string(),
+            folder: folder.to_string(),
+            ..Default::default()
+        };
+        let config = Config {
+            favorites: vec![favorite("News", ""), favorite("Jazz FM", "jazz")],
+            ..Default::default()
+        };
+        let first = |id: &str| {
+            PlaylistSource::from_id(id)
+                .and_then(|source| source.first_station(&config))
+                .map(|station| station.name.clone())
+        };
+        assert_eq!(PlaylistSource::Folder(0).id(), "folder_0");
+        assert_eq!(first("folder_0").as_deref(), Some("Jazz FM"));
+        assert_eq!(first("folder_1"), None);
+        assert_eq!(first("history"), None);
+        assert!(first("favorites").is_some());
+        assert_eq!(PlaylistSource::from_id("nonsense"), None);
+    }
 }
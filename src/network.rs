@@ -0,0 +1,91 @@
+//! Network connectivity from NetworkManager
+//!
+//! [`subscription`] reports whether the machine is online, first as it
+//! stands and then on every `StateChanged` signal that flips it. Without
+//! NetworkManager it ends quietly and the app keeps judging the network by
+//! its own requests.
+
+use crate::events::NetworkEvent;
+use futures::{SinkExt, StreamExt};
+use tracing::warn;
+use zbus::message::Type;
+use zbus::{Connection, MatchRule, MessageStream};
+
+const NM_NAME: &str = "org.freedesktop.NetworkManager";
+const NM_PATH: &str = "/org/freedesktop/NetworkManager";
+const NM_INTERFACE: &str = "org.freedesktop.NetworkManager";
+
+/// `NM_STATE_CONNECTED_LOCAL`, the lowest connected state. Local and site
+/// connectivity count as online: streams on the LAN still play, and NM's
+/// internet check can't tell when it is disabled or blocked.
+const NM_STATE_CONNECTED_LOCAL: u32 = 50;
+
+/// What a NetworkManager state means for reaching streams, or `None` while
+/// it doesn't know either
+pub fn from_state(state: u32) -> Option<NetworkEvent> {
+    match state {
+        0 => None,
+        state if state >= NM_STATE_CONNECTED_LOCAL => Some(NetworkEvent::Online),
+        _ => Some(NetworkEvent::Offline),
+    }
+}
+
+pub fn subscription() -> cosmic::iced::Subscription<NetworkEvent> {
+    cosmic::iced::Subscription::run(|| {
+        cosmic::iced::stream::channel(4, |mut output| async move {
+            let result = async {
+                let connection = Connection::system().await?;
+                let rule = MatchRule::builder()
+                    .msg_type(Type::Signal)
+                    .interface(NM_INTERFACE)?
+                    .member("StateChanged")?
+                    .build();
+                let mut signals = MessageStream::for_match_rule(rule, &connection, None).await?;
+                let reply = connection
+                    .call_method(Some(NM_NAME), NM_PATH, Some(NM_INTERFACE), "state", &())
+                    .await?;
+                let mut last = from_state(reply.body().deserialize()?);
+                if let Some(event) = last {
+                    if output.send(event).await.is_err() {
+                        return Ok(());
+                    }
+                }
+                while let Some(signal) = signals.next().await {
+                    let event = from_state(signal?.body().deserialize()?);
+                    // Connecting steps through several offline states
+                    if event.is_none() || event == last {
+                        continue;
+                    }
+                    last = event;
+                    if let Some(event) = event {
+                        if output.send(event).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+                Ok::<(), zbus::Error>(())
+            };
+            if let Err(e) = result.await {
+                warn!("Not watching NetworkManager: {}", e);
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_state() {
+        // Connected to the local network, a site, or the internet
+        for state in [50, 60, 70] {
+            assert_eq!(from_state(state), Some(NetworkEvent::Online));
+        }
+        // Asleep, disconnected, disconnecting and connecting
+        for state in [10, 20, 30, 40] {
+            assert_eq!(from_state(state), Some(NetworkEvent::Offline));
+        }
+        assert_eq!(from_state(0), None);
+    }
+}
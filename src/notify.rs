@@ -0,0 +1,214 @@
+//! Desktop notifications
+//!
+//! Notifications go to the freedesktop notification service over the
+//! session bus, each with Stop and Next buttons. [`subscription`] owns the
+//! bus connection: it hands it to the app to send on, and reports the
+//! buttons pressed, which servers only signal to the sending connection.
+//! It also says whether a screen reader is running, for which playback
+//! changes are announced with transient notifications that it reads out.
+
+use futures::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tracing::{debug, warn};
+use zbus::message::Type;
+use zbus::zvariant::Value;
+use zbus::{Connection, MatchRule, MessageStream};
+
+const NOTIFICATIONS_NAME: &str = "org.freedesktop.Notifications";
+const NOTIFICATIONS_PATH: &str = "/org/freedesktop/Notifications";
+const NOTIFICATIONS_INTERFACE: &str = "org.freedesktop.Notifications";
+
+const A11Y_NAME: &str = "org.a11y.Bus";
+const A11Y_PATH: &str = "/org/a11y/bus";
+const A11Y_STATUS_INTERFACE: &str = "org.a11y.Status";
+
+const APP_NAME: &str = "Radio for COSMIC";
+const APP_ICON: &str = "com.marcos.RadioApplet";
+
+/// Let the server pick how long a notification stays up
+const DEFAULT_TIMEOUT: i32 = -1;
+
+/// A notification button
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationAction {
+    Stop,
+    Next,
+}
+
+impl NotificationAction {
+    fn key(self) -> &'static str {
+        match self {
+            Self::Stop => "stop",
+            Self::Next => "next",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        [Self::Stop, Self::Next]
+            .into_iter()
+            .find(|action| action.key() == key)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Notification {
+    pub summary: String,
+    pub body: String,
+    /// Local image shown with it, such as the cached station favicon
+    pub image: Option<PathBuf>,
+    /// Buttons, with their labels
+    pub actions: Vec<(NotificationAction, String)>,
+    /// Id of an earlier notification this one takes the place of, or 0
+    pub replaces: u32,
+    /// An announcement: low urgency and kept out of the notification history
+    pub transient: bool,
+}
+
+impl Notification {
+    /// The `actions` argument of `Notify`: each key followed by its label
+    fn action_list(&self) -> Vec<&str> {
+        self.actions
+            .iter()
+            .flat_map(|(action, label)| [action.key(), label.as_str()])
+            .collect()
+    }
+
+    /// The `hints` argument of `Notify`
+    fn hints(&self) -> HashMap<&'static str, Value<'_>> {
+        let mut hints = HashMap::new();
+        hints.insert("desktop-entry", Value::from(APP_ICON));
+        if let Some(image) = self.image.as_deref().and_then(|path| path.to_str()) {
+            hints.insert("image-path", Value::from(image));
+        }
+        if self.transient {
+            hints.insert("transient", Value::from(true));
+            hints.insert("urgency", Value::from(0u8));
+        }
+        hints
+    }
+}
+
+/// Show `notification`. Returns its id, for replacing it later and for
+/// matching the buttons pressed on it.
+pub async fn show(connection: Connection, notification: Notification) -> zbus::Result<u32> {
+    let reply = connection
+        .call_method(
+            Some(NOTIFICATIONS_NAME),
+            NOTIFICATIONS_PATH,
+            Some(NOTIFICATIONS_INTERFACE),
+            "Notify",
+            &(
+                APP_NAME,
+                notification.replaces,
+                APP_ICON,
+                notification.summary.as_str(),
+                notification.body.as_str(),
+                notification.action_list(),
+                notification.hints(),
+                DEFAULT_TIMEOUT,
+            ),
+        )
+        .await?;
+    reply.body().deserialize()
+}
+
+/// Whether the accessibility bus reports a screen reader running
+async fn screen_reader_enabled(connection: &Connection) -> zbus::Result<bool> {
+    let reply = connection
+        .call_method(
+            Some(A11Y_NAME),
+            A11Y_PATH,
+            Some("org.freedesktop.DBus.Properties"),
+            "Get",
+            &(A11Y_STATUS_INTERFACE, "ScreenReaderEnabled"),
+        )
+        .await?;
+    let value: zbus::zvariant::OwnedValue = reply.body().deserialize()?;
+    Ok(bool::try_from(value)?)
+}
+
+/// Events yielded by the notification subscription
+#[derive(Debug, Clone)]
+pub enum NotifyEvent {
+    /// Send notifications on this connection
+    Ready(Connection),
+    /// Whether a screen reader is running
+    ScreenReader(bool),
+    /// A button was pressed on the notification with this id
+    Action(u32, NotificationAction),
+}
+
+pub fn subscription() -> cosmic::iced::Subscription<NotifyEvent> {
+    cosmic::iced::Subscription::run(|| {
+        cosmic::iced::stream::channel(10, |mut output| async move {
+            let result = async {
+                let connection = Connection::session().await?;
+                let rule = MatchRule::builder()
+                    .msg_type(Type::Signal)
+                    .interface(NOTIFICATIONS_INTERFACE)?
+                    .member("ActionInvoked")?
+                    .build();
+                let mut signals = MessageStream::for_match_rule(rule, &connection, None).await?;
+                let reader = screen_reader_enabled(&connection)
+                    .await
+                    .unwrap_or_else(|e| {
+                        debug!("No screen reader status: {}", e);
+                        false
+                    });
+                if output.send(NotifyEvent::Ready(connection)).await.is_err()
+                    || output
+                        .send(NotifyEvent::ScreenReader(reader))
+                        .await
+                        .is_err()
+                {
+                    return Ok(());
+                }
+                while let Some(signal) = signals.next().await {
+                    let (id, key): (u32, String) = signal?.body().deserialize()?;
+                    let Some(action) = NotificationAction::from_key(&key) else {
+                        continue;
+                    };
+                    if output.send(NotifyEvent::Action(id, action)).await.is_err() {
+                        break;
+                    }
+                }
+                Ok::<(), zbus::Error>(())
+            };
+            if let Err(e) = result.await {
+                warn!("Desktop notifications unavailable: {}", e);
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_list_pairs_keys_with_labels() {
+        let notification = Notification {
+            actions: vec![
+                (NotificationAction::Stop, "Stop".to_string()),
+                (NotificationAction::Next, "Next".to_string()),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(notification.action_list(), ["stop", "Stop", "next", "Next"]);
+        for (action, _) in &notification.actions {
+            assert_eq!(NotificationAction::from_key(action.key()), Some(*action));
+        }
+        assert_eq!(NotificationAction::from_key("default"), None);
+    }
+
+    #[test]
+    fn test_announcements_are_transient() {
+        let mut notification = Notification::default();
+        assert!(!notification.hints().contains_key("transient"));
+        notification.transient = true;
+        let hints = notification.hints();
+        assert_eq!(hints.get("transient"), Some(&Value::from(true)));
+        assert_eq!(hints.get("urgency"), Some(&Value::from(0u8)));
+    }
+}
@@ -0,0 +1,183 @@
+//! OPML station lists
+//!
+//! Stations are `<outline>` elements carrying a stream `URL`; outlines
+//! without one act as folders for the stations nested inside them. This is
+//! the format Shortwave and RadioTray-NG exchange station lists in.
+
+use crate::api::Station;
+
+/// Parse every station in an OPML document. Stations nested in a folder
+/// outline get that folder's title as their `folder`.
+pub fn parse(text: &str) -> Result<Vec<Station>, roxmltree::Error> {
+    let doc = roxmltree::Document::parse(text)?;
+
+    let stations = doc
+        .descendants()
+        .filter(|node| node.has_tag_name("outline"))
+        .filter_map(|node| {
+            let url = ["URL", "url", "xmlUrl"]
+                .iter()
+                .find_map(|&attr| node.attribute(attr))?
+                .trim()
+                .to_string();
+            if url.is_empty() {
+                return None;
+            }
+            let name = node
+                .attribute("text")
+                .or_else(|| node.attribute("title"))
+                .unwrap_or(url.as_str())
+                .trim()
+                .to_string();
+            let folder = node
+                .parent_element()
+                .filter(|parent| parent.has_tag_name("outline"))
+                .and_then(|parent| {
+                    parent
+                        .attribute("text")
+                        .or_else(|| parent.attribute("title"))
+                })
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+
+            Some(Station {
+                name,
+                url_resolved: url.clone(),
+                url,
+                homepage: node.attribute("htmlUrl").unwrap_or_default().to_string(),
+                favicon: node.attribute("image").unwrap_or_default().to_string(),
+                tags: node.attribute("category").unwrap_or_default().to_string(),
+                folder,
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    Ok(stations)
+}
+
+/// Write `stations` as an OPML document titled `title`, nesting stations
+/// that have a folder inside an outline named after it
+pub fn export(title: &str, stations: &[Station]) -> String {
+    let mut out =
+        String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n");
+    out.push_str(&format!(
+        "  <head><title>{}</title></head>\n  <body>\n",
+        escape(title)
+    ));
+
+    for station in stations.iter().filter(|s| s.folder.is_empty()) {
+        push_station(&mut out, station, 2);
+    }
+    let mut folders: Vec<&str> = Vec::new();
+    for station in stations {
+        if !station.folder.is_empty() && !folders.contains(&station.folder.as_str()) {
+            folders.push(&station.folder);
+        }
+    }
+    for folder in folders {
+        out.push_str(&format!("    <outline text=\"{}\">\n", escape(folder)));
+        for station in stations.iter().filter(|s| s.folder == folder) {
+            push_station(&mut out, station, 3);
+        }
+        out.push_str("    </outline>\n");
+    }
+
+    out.push_str("  </body>\n</opml>\n");
+    out
+}
+
+fn push_station(out: &mut String, station: &Station, depth: usize) {
+    out.push_str(&"  ".repeat(depth));
+    out.push_str(&format!(
+        "<outline type=\"audio\" text=\"{}\" URL=\"{}\"",
+        escape(&station.name),
+        escape(&station.url)
+    ));
+    for (attr, value) in [
+        ("htmlUrl", &station.homepage),
+        ("image", &station.favicon),
+        ("category", &station.tags),
+    ] {
+        if !value.is_empty() {
+            out.push_str(&format!(" {}=\"{}\"", attr, escape(value)));
+        }
+    }
+    out.push_str(" />\n");
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<opml version="2.0">
+  <head><title>Family radio</title></head>
+  <body>
+    <outline text="Jazz">
+      <outline type="audio" text="Jazz &amp; Blues" URL="http://jazz.example.com/live" />
+    </outline>
+    <outline type="link" title="News" url="https://news.example.com/stream" htmlUrl="https://news.example.com" />
+    <outline text="Empty folder" />
+  </body>
+</opml>"#;
+
+    #[test]
+    fn test_parse_outlines() {
+        let stations = parse(SAMPLE).unwrap();
+        assert_eq!(stations.len(), 2);
+
+        assert_eq!(stations[0].name, "Jazz & Blues");
+        assert_eq!(stations[0].url, "http://jazz.example.com/live");
+        assert_eq!(stations[0].url_resolved, stations[0].url);
+        assert_eq!(stations[0].folder, "Jazz");
+
+        assert_eq!(stations[1].name, "News");
+        assert_eq!(stations[1].homepage, "https://news.example.com");
+        assert!(stations[1].folder.is_empty());
+    }
+
+    #[test]
+    fn test_export_roundtrip() {
+        let stations = vec![
+            Station {
+                name: "News <24/7>".to_string(),
+                url: "https://news.example.com/stream?a=1&b=2".to_string(),
+                homepage: "https://news.example.com".to_string(),
+                tags: "news,talk".to_string(),
+                ..Default::default()
+            },
+            Station {
+                name: "Jazz \"Live\"".to_string(),
+                url: "http://jazz.example.com/live".to_string(),
+                favicon: "http://jazz.example.com/icon.png".to_string(),
+                folder: "Music".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let back = parse(&export("Favorites", &stations)).unwrap();
+        assert_eq!(back.len(), 2);
+        assert_eq!(back[0].name, "News <24/7>");
+        assert_eq!(back[0].url, "https://news.example.com/stream?a=1&b=2");
+        assert_eq!(back[0].homepage, "https://news.example.com");
+        assert_eq!(back[0].tags, "news,talk");
+        assert_eq!(back[1].name, "Jazz \"Live\"");
+        assert_eq!(back[1].favicon, "http://jazz.example.com/icon.png");
+        assert_eq!(back[1].folder, "Music");
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed() {
+        assert!(parse("<opml><body><outline></body>").is_err());
+    }
+}
@@ -0,0 +1,374 @@
+//! Watching the audio output device
+//!
+//! `pactl subscribe`, which PipeWire answers as well as PulseAudio, reports
+//! sinks coming and going. When removing one (unplugged headphones, a
+//! Bluetooth headset out of range) moves the default sink elsewhere,
+//! [`SinkWatch`] calls it a disconnect, so the app can stop before the
+//! stream comes out of the laptop speakers; the lost sink becoming the
+//! default again is a reconnect. Switching the default by hand is neither.
+//!
+//! Headphones in a laptop's jack are a port of the same sink as its
+//! speakers, so unplugging them only changes the sink. `pactl -f json list
+//! sinks` tells which port is active and which have nothing plugged in; the
+//! active port going unplugged counts as a disconnect too.
+
+use futures::SinkExt;
+use serde_json::Value;
+use std::io::{self, BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputEvent {
+    /// The default sink went away and another took over
+    Disconnected,
+    /// The sink lost earlier is the default again
+    Reconnected,
+}
+
+/// A `pactl subscribe` event that may have moved the default sink
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SinkChange {
+    Added,
+    Removed,
+    /// A sink changed, its active port among others
+    Changed,
+    /// Server settings changed, the default sink among them
+    Server,
+}
+
+/// The kind and facility of a `pactl subscribe` line, e.g. `remove` and
+/// `sink` for `Event 'remove' on sink #68`
+pub fn parse_event(line: &str) -> Option<(&str, &str)> {
+    let rest = line.trim().strip_prefix("Event '")?;
+    let (kind, rest) = rest.split_once("' on ")?;
+    Some((kind, rest.split(" #").next()?))
+}
+
+impl SinkChange {
+    pub fn parse(line: &str) -> Option<Self> {
+        match parse_event(line)? {
+            ("new", "sink") => Some(Self::Added),
+            ("remove", "sink") => Some(Self::Removed),
+            ("change", "sink") => Some(Self::Changed),
+            ("change", "server") => Some(Self::Server),
+            _ => None,
+        }
+    }
+}
+
+/// Where the sound goes: a sink and, if it has any, its active port
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Output {
+    pub sink: String,
+    pub port: Option<String>,
+}
+
+/// The default sink as `pactl` reports it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefaultOutput {
+    pub output: Output,
+    /// Ports of the sink with nothing plugged in
+    pub unplugged: Vec<String>,
+}
+
+/// The active port and unplugged ports of `sink` in `pactl -f json list
+/// sinks` output
+pub fn sink_ports(sinks: &str, sink: &str) -> Option<(Option<String>, Vec<String>)> {
+    let Ok(Value::Array(sinks)) = serde_json::from_str(sinks) else {
+        return None;
+    };
+    let found = sinks.iter().find(|s| s["name"].as_str() == Some(sink))?;
+    let active = found["active_port"].as_str().map(str::to_string);
+    let unplugged = found["ports"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|port| port["availability"].as_str() == Some("not available"))
+        .filter_map(|port| port["name"].as_str().map(str::to_string))
+        .collect();
+    Some((active, unplugged))
+}
+
+/// Follows the default sink and its port to tell disconnects from switches
+/// by hand
+#[derive(Debug)]
+pub struct SinkWatch {
+    default: Output,
+    /// A sink was just removed, and the default may follow in the next
+    /// event
+    removed: bool,
+    /// The default output before a disconnect
+    lost: Option<Output>,
+}
+
+impl SinkWatch {
+    pub fn new(default: Output) -> Self {
+        Self {
+            default,
+            removed: false,
+            lost: None,
+        }
+    }
+
+    /// Take in `change`, after which `current` is the default sink
+    pub fn update(&mut self, change: SinkChange, current: DefaultOutput) -> Option<OutputEvent> {
+        let DefaultOutput { output, unplugged } = current;
+        let removed = match change {
+            SinkChange::Removed => true,
+            SinkChange::Added => false,
+            SinkChange::Server => self.removed,
+            // The same sink moved off a port that was just unplugged
+            SinkChange::Changed => {
+                output.sink == self.default.sink
+                    && self
+                        .default
+                        .port
+                        .as_ref()
+                        .is_some_and(|port| unplugged.contains(port))
+            }
+        };
+        self.removed = false;
+        if output == self.default {
+            self.removed = change == SinkChange::Removed;
+            return None;
+        }
+        let previous = std::mem::replace(&mut self.default, output);
+        if removed {
+            // Losing the fallback too still waits for the first sink
+            self.lost.get_or_insert(previous);
+            return Some(OutputEvent::Disconnected);
+        }
+        if self.lost.take().is_some_and(|lost| lost == self.default) {
+            return Some(OutputEvent::Reconnected);
+        }
+        None
+    }
+}
+
+/// Name of the default sink, if `pactl` can tell
+fn default_sink_name() -> Option<String> {
+    let output = Command::new("pactl")
+        .arg("get-default-sink")
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let sink = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (output.status.success() && !sink.is_empty()).then_some(sink)
+}
+
+/// The default sink with its ports. A `pactl` too old for JSON output
+/// leaves the port out, which only loses telling unplugged jacks apart.
+fn default_sink() -> Option<DefaultOutput> {
+    let sink = default_sink_name()?;
+    let (port, unplugged) = Command::new("pactl")
+        .args(["-f", "json", "list", "sinks"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .and_then(|json| sink_ports(&json, &sink))
+        .unwrap_or_default();
+    Some(DefaultOutput {
+        output: Output { sink, port },
+        unplugged,
+    })
+}
+
+/// A running `pactl subscribe`. Dropping it kills pactl, which also ends
+/// the thread reading from it.
+pub struct PactlWatcher(Child);
+
+impl Drop for PactlWatcher {
+    fn drop(&mut self) {
+        if let Err(e) = self.0.kill() {
+            debug!("Failed to kill pactl: {}", e);
+        }
+        let _ = self.0.wait();
+    }
+}
+
+/// Start `pactl subscribe` and hand its lines to `on_line` on a thread of
+/// their own, until `on_line` returns false or pactl ends
+pub fn watch_pactl<F>(mut on_line: F) -> io::Result<PactlWatcher>
+where
+    F: FnMut(&str) -> bool + Send + 'static,
+{
+    let mut child = Command::new("pactl")
+        .arg("subscribe")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let stdout = child.stdout.take().ok_or(io::ErrorKind::BrokenPipe)?;
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if !on_line(&line) {
+                break;
+            }
+        }
+    });
+    Ok(PactlWatcher(child))
+}
+
+/// Watch the default sink, sending [`OutputEvent`]s to `events`
+fn spawn_watcher(events: mpsc::UnboundedSender<OutputEvent>) -> io::Result<PactlWatcher> {
+    let default =
+        default_sink().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no default sink"))?;
+    let mut watch = SinkWatch::new(default.output);
+    watch_pactl(move |line| {
+        let Some(change) = SinkChange::parse(line) else {
+            return true;
+        };
+        let Some(default) = default_sink() else {
+            return true;
+        };
+        match watch.update(change, default) {
+            Some(event) => events.send(event).is_ok(),
+            None => true,
+        }
+    })
+}
+
+pub fn subscription() -> cosmic::iced::Subscription<OutputEvent> {
+    cosmic::iced::Subscription::run(|| {
+        cosmic::iced::stream::channel(4, |mut output| async move {
+            let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+            let _watcher = match spawn_watcher(event_tx) {
+                Ok(watcher) => watcher,
+                Err(e) => {
+                    warn!("Not watching the audio output: {}", e);
+                    return;
+                }
+            };
+            while let Some(event) = event_rx.recv().await {
+                if output.send(event).await.is_err() {
+                    break;
+                }
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sink(name: &str) -> Output {
+        Output {
+            sink: name.to_string(),
+            port: None,
+        }
+    }
+
+    fn on(name: &str) -> DefaultOutput {
+        DefaultOutput {
+            output: sink(name),
+            unplugged: Vec::new(),
+        }
+    }
+
+    fn port(active: &str, unplugged: &[&str]) -> DefaultOutput {
+        DefaultOutput {
+            output: Output {
+                sink: "analog".to_string(),
+                port: Some(active.to_string()),
+            },
+            unplugged: unplugged.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_sink_changes() {
+        assert_eq!(
+            SinkChange::parse("Event 'remove' on sink #68"),
+            Some(SinkChange::Removed)
+        );
+        assert_eq!(
+            SinkChange::parse("Event 'new' on sink #70"),
+            Some(SinkChange::Added)
+        );
+        assert_eq!(
+            SinkChange::parse("Event 'change' on server #-1"),
+            Some(SinkChange::Server)
+        );
+        assert_eq!(
+            SinkChange::parse("Event 'change' on sink #68"),
+            Some(SinkChange::Changed)
+        );
+        assert_eq!(SinkChange::parse("Event 'remove' on sink-input #12"), None);
+    }
+
+    #[test]
+    fn test_unplug_and_replug() {
+        let mut watch = SinkWatch::new(sink("headphones"));
+        assert_eq!(watch.update(SinkChange::Removed, on("headphones")), None);
+        assert_eq!(
+            watch.update(SinkChange::Server, on("speakers")),
+            Some(OutputEvent::Disconnected)
+        );
+        assert_eq!(
+            watch.update(SinkChange::Added, on("headphones")),
+            Some(OutputEvent::Reconnected)
+        );
+    }
+
+    #[test]
+    fn test_switching_by_hand_is_not_a_disconnect() {
+        let mut watch = SinkWatch::new(sink("speakers"));
+        assert_eq!(watch.update(SinkChange::Server, on("hdmi")), None);
+
+        // Removing a sink that isn't the default changes nothing
+        assert_eq!(watch.update(SinkChange::Removed, on("hdmi")), None);
+        assert_eq!(watch.update(SinkChange::Server, on("hdmi")), None);
+
+        // Picking another sink after a disconnect gives up on the lost one
+        assert_eq!(
+            watch.update(SinkChange::Removed, on("speakers")),
+            Some(OutputEvent::Disconnected)
+        );
+        assert_eq!(watch.update(SinkChange::Server, on("bluetooth")), None);
+        assert_eq!(watch.update(SinkChange::Added, on("hdmi")), None);
+    }
+
+    #[test]
+    fn test_sink_ports() {
+        let sinks = r#"[{"name":"hdmi","active_port":null,"ports":[]},
+            {"name":"analog","active_port":"analog-output-speaker","ports":[
+                {"name":"analog-output-speaker","availability":"availability unknown"},
+                {"name":"analog-output-headphones","availability":"not available"}]}]"#;
+        assert_eq!(
+            sink_ports(sinks, "analog"),
+            Some((
+                Some("analog-output-speaker".to_string()),
+                vec!["analog-output-headphones".to_string()]
+            ))
+        );
+        assert_eq!(sink_ports(sinks, "hdmi"), Some((None, Vec::new())));
+        assert_eq!(sink_ports(sinks, "bluetooth"), None);
+        assert_eq!(sink_ports("not json", "analog"), None);
+    }
+
+    #[test]
+    fn test_unplug_and_replug_a_port() {
+        let mut watch = SinkWatch::new(port("headphones", &[]).output);
+        assert_eq!(
+            watch.update(SinkChange::Changed, port("speaker", &["headphones"])),
+            Some(OutputEvent::Disconnected)
+        );
+        assert_eq!(
+            watch.update(SinkChange::Changed, port("headphones", &[])),
+            Some(OutputEvent::Reconnected)
+        );
+
+        // Picking the speaker by hand leaves the headphones plugged in
+        assert_eq!(
+            watch.update(SinkChange::Changed, port("speaker", &[])),
+            None
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! Panel icon behavior
+//!
+//! What clicking the applet's panel icon does is configurable, and
+//! scrolling over it can change the volume, briefly showing the new level
+//! next to it, or switch favorites when scrolled sideways. Hovering it shows
+//! what is playing, and the station or song can also be shown as text next
+//! to it, scrolling when it is wider than the space given.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Volume changes per scroll notch offered, in percent
+pub const SCROLL_STEP_CHOICES: [u8; 4] = [1, 2, 5, 10];
+
+/// How long the volume stays shown next to the icon after scrolling
+pub const VOLUME_HINT_DURATION: Duration = Duration::from_millis(1500);
+
+/// Touchpad pixels that count as one scroll notch
+const PIXELS_PER_NOTCH: f32 = 40.0;
+
+/// Panel text widths offered, in characters
+pub const TEXT_WIDTH_CHOICES: [usize; 4] = [16, 24, 32, 48];
+
+/// How often scrolling panel text moves by one character
+pub const MARQUEE_INTERVAL: Duration = Duration::from_millis(300);
+
+/// Blank space between the end of scrolling text and its start
+const MARQUEE_GAP: &str = "   ";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClickAction {
+    #[default]
+    TogglePopup,
+    /// Play or stop the current (or last) station
+    PlayPause,
+    Nothing,
+}
+
+impl ClickAction {
+    /// Choices for the left button; one of them always reaches the popup
+    /// or playback
+    pub const LEFT: [Self; 2] = [Self::TogglePopup, Self::PlayPause];
+    pub const MIDDLE: [Self; 3] = [Self::Nothing, Self::TogglePopup, Self::PlayPause];
+}
+
+/// Text shown next to the panel icon
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PanelText {
+    /// Icon only
+    #[default]
+    Off,
+    Station,
+    /// The song, or the station while the stream announces none
+    Song,
+    Both,
+}
+
+impl PanelText {
+    pub const ALL: [Self; 4] = [Self::Off, Self::Station, Self::Song, Self::Both];
+
+    /// The text to show for `station` playing `track`
+    pub fn label(self, station: Option<&str>, track: Option<&str>) -> Option<String> {
+        let station = station.map(str::trim).filter(|s| !s.is_empty());
+        let track = track.map(str::trim).filter(|t| !t.is_empty());
+        let text = match (self, station, track) {
+            (Self::Off, _, _) => return None,
+            (Self::Station, station, _) => station?.to_string(),
+            (Self::Song, _, Some(track)) => track.to_string(),
+            (Self::Song, station, None) => station?.to_string(),
+            (Self::Both, Some(station), Some(track)) => format!("{station} \u{b7} {track}"),
+            (Self::Both, station, track) => station.or(track)?.to_string(),
+        };
+        Some(text)
+    }
+}
+
+/// Sums scroll events into whole notches, so a touchpad's many small
+/// deltas move the volume as steadily as a wheel
+#[derive(Debug, Default)]
+pub struct ScrollAccumulator {
+    pending: f32,
+}
+
+impl ScrollAccumulator {
+    /// Add a wheel movement in lines. Returns the whole notches (positive
+    /// is up) ready to apply.
+    pub fn add_lines(&mut self, lines: f32) -> i16 {
+        self.pending += lines;
+        let notches = self.pending.trunc();
+        self.pending -= notches;
+        notches as i16
+    }
+
+    pub fn add_pixels(&mut self, pixels: f32) -> i16 {
+        self.add_lines(pixels / PIXELS_PER_NOTCH)
+    }
+}
+
+/// `volume` moved by `notches` scroll steps of `step` percent, kept
+/// within 0-100
+pub fn scrolled_volume(volume: u8, notches: i16, step: u8) -> u8 {
+    (i16::from(volume) + notches * i16::from(step)).clamp(0, 100) as u8
+}
+
+/// `text` cut to `width` characters, ending in an ellipsis if shortened
+pub fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(width.saturating_sub(1)).collect();
+    cut.truncate(cut.trim_end().len());
+    cut.push('\u{2026}');
+    cut
+}
+
+/// The `width` characters of `text` visible after scrolling it `offset`
+/// characters to the left, wrapping around; short text stays put
+pub fn marquee(text: &str, width: usize, offset: usize) -> String {
+    let len = text.chars().count();
+    if len <= width {
+        return text.to_string();
+    }
+    let cycle = len + MARQUEE_GAP.len();
+    text.chars()
+        .chain(MARQUEE_GAP.chars())
+        .cycle()
+        .skip(offset % cycle)
+        .take(width)
+        .collect()
+}
+
+/// Panel tooltip: the applet name, then the station and the current song
+/// when there are any
+pub fn tooltip_text(app: &str, station: Option<&str>, track: Option<&str>) -> String {
+    let mut text = app.to_string();
+    let station = station.map(str::trim).filter(|s| !s.is_empty());
+    let track = track.map(str::trim).filter(|t| !t.is_empty());
+    if let Some(station) = station {
+        text.push_str(" \u{2014} ");
+        text.push_str(station);
+        if let Some(track) = track {
+            text.push_str(" \u{b7} ");
+            text.push_str(track);
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrolled_volume_clamps() {
+        assert_eq!(scrolled_volume(50, 1, 5), 55);
+        assert_eq!(scrolled_volume(50, -2, 5), 40);
+        assert_eq!(scrolled_volume(98, 1, 5), 100);
+        assert_eq!(scrolled_volume(3, -1, 5), 0);
+        assert_eq!(scrolled_volume(50, 3, 1), 53);
+        assert_eq!(scrolled_volume(50, -6, 10), 0);
+    }
+
+    #[test]
+    fn test_accumulator_collects_small_deltas() {
+        let mut scroll = ScrollAccumulator::default();
+        assert_eq!(scroll.add_pixels(15.0), 0);
+        assert_eq!(scroll.add_pixels(15.0), 0);
+        assert_eq!(scroll.add_pixels(15.0), 1);
+        assert_eq!(scroll.add_lines(-2.0), -1);
+        assert_eq!(scroll.add_lines(1.0), 0);
+    }
+
+    #[test]
+    fn test_tooltip_text() {
+        assert_eq!(tooltip_text("Radio", None, Some("Song")), "Radio");
+        assert_eq!(
+            tooltip_text("Radio", Some("Groove Salad"), None),
+            "Radio \u{2014} Groove Salad"
+        );
+        assert_eq!(
+            tooltip_text(
+                "Radio",
+                Some("Groove Salad"),
+                Some("Boards of Canada - Roygbiv")
+            ),
+            "Radio \u{2014} Groove Salad \u{b7} Boards of Canada - Roygbiv"
+        );
+        assert_eq!(
+            tooltip_text("Radio", Some("Groove Salad"), Some(" ")),
+            "Radio \u{2014} Groove Salad"
+        );
+    }
+
+    #[test]
+    fn test_panel_text_label() {
+        let station = Some("Groove Salad");
+        let song = Some("Roygbiv");
+        assert_eq!(PanelText::Off.label(station, song), None);
+        assert_eq!(
+            PanelText::Station.label(station, song).unwrap(),
+            "Groove Salad"
+        );
+        assert_eq!(PanelText::Song.label(station, song).unwrap(), "Roygbiv");
+        assert_eq!(
+            PanelText::Song.label(station, None).unwrap(),
+            "Groove Salad"
+        );
+        assert_eq!(
+            PanelText::Both.label(station, song).unwrap(),
+            "Groove Salad \u{b7} Roygbiv"
+        );
+        assert_eq!(PanelText::Both.label(None, None), None);
+    }
+
+    #[test]
+    fn test_truncate_and_marquee() {
+        assert_eq!(truncate("Jazz", 8), "Jazz");
+        assert_eq!(truncate("Smooth Jazz", 8), "Smooth\u{2026}");
+        assert_eq!(marquee("Jazz", 8, 3), "Jazz");
+        assert_eq!(marquee("Groove Salad", 6, 0), "Groove");
+        assert_eq!(marquee("Groove Salad", 6, 7), "Salad ");
+        assert_eq!(marquee("Groove Salad", 6, 12), "   Gro");
+        assert_eq!(marquee("Groove Salad", 6, 15), "Groove");
+    }
+}
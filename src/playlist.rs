@@ -0,0 +1,127 @@
+//! M3U and PLS playlists
+//!
+//! Favorites written as a playlist play in VLC, car head units and most
+//! other players, with each station's name as the track title.
+
+use crate::api::Station;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaylistFormat {
+    /// Extended M3U in UTF-8
+    M3u8,
+    Pls,
+}
+
+impl PlaylistFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::M3u8 => "m3u8",
+            Self::Pls => "pls",
+        }
+    }
+
+    pub fn render(self, stations: &[&Station]) -> String {
+        match self {
+            Self::M3u8 => to_m3u8(stations),
+            Self::Pls => to_pls(stations),
+        }
+    }
+}
+
+fn to_m3u8(stations: &[&Station]) -> String {
+    let mut out = String::from("#EXTM3U\n");
+    for station in stations {
+        out.push_str(&format!(
+            "#EXTINF:-1,{}\n{}\n",
+            single_line(&station.name),
+            stream_url(station)
+        ));
+    }
+    out
+}
+
+fn to_pls(stations: &[&Station]) -> String {
+    let mut out = String::from("[playlist]\n");
+    for (n, station) in (1..).zip(stations) {
+        out.push_str(&format!(
+            "File{n}={}\nTitle{n}={}\nLength{n}=-1\n",
+            stream_url(station),
+            single_line(&station.name)
+        ));
+    }
+    out.push_str(&format!("NumberOfEntries={}\nVersion=2\n", stations.len()));
+    out
+}
+
+/// The direct stream where known; `url` may itself be a playlist
+fn stream_url(station: &Station) -> &str {
+    if station.stream_url().trim().is_empty() {
+        station.url.trim()
+    } else {
+        station.stream_url().trim()
+    }
+}
+
+/// Both formats are line based, so names must not contain line breaks
+fn single_line(value: &str) -> String {
+    value
+        .split(['\r', '\n'])
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stations() -> Vec<Station> {
+        vec![
+            Station {
+                name: "Jazz FM".to_string(),
+                url: "http://jazz.example.com/listen.pls".to_string(),
+                url_resolved: "http://jazz.example.com/live.mp3".to_string(),
+                ..Default::default()
+            },
+            Station {
+                name: "News\nRadio".to_string(),
+                url: "https://news.example.com/stream".to_string(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_m3u8() {
+        let stations = stations();
+        let refs: Vec<&Station> = stations.iter().collect();
+        assert_eq!(
+            PlaylistFormat::M3u8.render(&refs),
+            "#EXTM3U\n\
+             #EXTINF:-1,Jazz FM\nhttp://jazz.example.com/live.mp3\n\
+             #EXTINF:-1,News Radio\nhttps://news.example.com/stream\n"
+        );
+    }
+
+    #[test]
+    fn test_pls() {
+        let stations = stations();
+        let refs: Vec<&Station> = stations.iter().collect();
+        assert_eq!(
+            PlaylistFormat::Pls.render(&refs),
+            "[playlist]\n\
+             File1=http://jazz.example.com/live.mp3\nTitle1=Jazz FM\nLength1=-1\n\
+             File2=https://news.example.com/stream\nTitle2=News Radio\nLength2=-1\n\
+             NumberOfEntries=2\nVersion=2\n"
+        );
+    }
+
+    #[test]
+    fn test_empty_playlists() {
+        assert_eq!(PlaylistFormat::M3u8.render(&[]), "#EXTM3U\n");
+        assert!(PlaylistFormat::Pls
+            .render(&[])
+            .contains("NumberOfEntries=0"));
+    }
+}
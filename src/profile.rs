@@ -0,0 +1,67 @@
+//! Lite profile for low-power devices
+//!
+//! On small single-board computers the applet trims optional work: no
+//! loudness normalization, the lightest playback backend, no artwork and
+//! shorter retained histories.
+
+use serde::{Deserialize, Serialize};
+
+/// Systems with less memory than this use the lite profile in `Auto` mode
+const LOW_MEMORY_KB: u64 = 2 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LiteMode {
+    /// Lite on low-memory systems only
+    #[default]
+    Auto,
+    On,
+    Off,
+}
+
+impl LiteMode {
+    pub const ALL: [Self; 3] = [Self::Auto, Self::On, Self::Off];
+
+    pub fn is_active(self, low_memory: bool) -> bool {
+        match self {
+            Self::Auto => low_memory,
+            Self::On => true,
+            Self::Off => false,
+        }
+    }
+}
+
+/// Whether total RAM is below [`LOW_MEMORY_KB`]; false if it can't be read
+pub fn is_low_memory() -> bool {
+    std::fs::read_to_string("/proc/meminfo")
+        .ok()
+        .and_then(|meminfo| parse_mem_total_kb(&meminfo))
+        .is_some_and(|kb| kb < LOW_MEMORY_KB)
+}
+
+fn parse_mem_total_kb(meminfo: &str) -> Option<u64> {
+    meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemTotal:"))
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|kb| kb.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mem_total() {
+        let meminfo = "MemTotal:        1917428 kB\nMemFree:          123456 kB\n";
+        assert_eq!(parse_mem_total_kb(meminfo), Some(1_917_428));
+        assert_eq!(parse_mem_total_kb("MemFree: 1 kB"), None);
+    }
+
+    #[test]
+    fn test_lite_mode_is_active() {
+        assert!(LiteMode::Auto.is_active(true));
+        assert!(!LiteMode::Auto.is_active(false));
+        assert!(LiteMode::On.is_active(false));
+        assert!(!LiteMode::Off.is_active(true));
+    }
+}
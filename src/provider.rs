@@ -0,0 +1,168 @@
+//! Station directories searches can run against
+//!
+//! radio-browser.info is the default [`StationProvider`]. A station list
+//! published at a URL (OPML or JSON, the formats subscriptions use) can be
+//! searched instead; such lists have no query API, so they are downloaded
+//! and matched locally.
+
+use crate::api::{self, SearchOrder, SearchParams, Station};
+use crate::error::ApiError;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use std::cmp::Reverse;
+
+/// Results kept from a station list search, like the directory's `limit`
+const LIST_SEARCH_LIMIT: usize = 20;
+
+pub type SearchFuture = BoxFuture<'static, Result<Vec<Station>, ApiError>>;
+
+pub trait StationProvider: Send + Sync {
+    /// Shown in the search source dropdown
+    fn name(&self) -> String;
+
+    fn search(&self, params: SearchParams) -> SearchFuture;
+}
+
+/// The public radio-browser.info directory (or the configured mirror)
+pub struct RadioBrowser;
+
+impl StationProvider for RadioBrowser {
+    fn name(&self) -> String {
+        "radio-browser.info".to_string()
+    }
+
+    fn search(&self, params: SearchParams) -> SearchFuture {
+        api::search_stations(params).boxed()
+    }
+}
+
+/// A station list published at a URL
+pub struct StationList {
+    pub name: String,
+    pub url: String,
+}
+
+impl StationProvider for StationList {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn search(&self, params: SearchParams) -> SearchFuture {
+        let url = self.url.clone();
+        async move {
+            if params.is_empty() {
+                return Ok(Vec::new());
+            }
+            let stations = api::fetch_station_list(url).await?;
+            Ok(search_list(stations, &params))
+        }
+        .boxed()
+    }
+}
+
+/// Stations from `stations` matching `params`, ordered like the directory
+/// would order them
+fn search_list(mut stations: Vec<Station>, params: &SearchParams) -> Vec<Station> {
+    stations.retain(|station| matches(station, params));
+    if params.hide_broken {
+        stations.retain(|station| station.lastcheckok != Some(false));
+    }
+    match params.order {
+        Some(SearchOrder::Name) => {
+            stations.sort_by_cached_key(|station| station.name.to_lowercase());
+        }
+        Some(SearchOrder::Votes) => stations.sort_by_key(|station| Reverse(station.votes)),
+        Some(SearchOrder::Clicks) => stations.sort_by_key(|station| Reverse(station.clickcount)),
+        Some(SearchOrder::Bitrate) => stations.sort_by_key(|station| Reverse(station.bitrate)),
+        None => {}
+    }
+    stations.truncate(LIST_SEARCH_LIMIT);
+    stations
+}
+
+fn matches(station: &Station, params: &SearchParams) -> bool {
+    let contains = |haystack: &str, needle: &Option<String>| {
+        needle.as_deref().is_none_or(|needle| {
+            haystack
+                .to_lowercase()
+                .contains(&needle.trim().to_lowercase())
+        })
+    };
+    let country = params.country.as_deref().is_none_or(|country| {
+        let country = country.trim();
+        station.countrycode.eq_ignore_ascii_case(country)
+            || station
+                .country
+                .to_lowercase()
+                .contains(&country.to_lowercase())
+    });
+
+    contains(&station.name, &params.name)
+        && contains(&station.tags, &params.tag)
+        && contains(&station.language, &params.language)
+        && country
+        && params.min_bitrate.is_none_or(|min| station.bitrate >= min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::SearchField;
+
+    fn list() -> Vec<Station> {
+        [
+            ("Jazz FM", "jazz,smooth", "GB", 128, 50),
+            ("Radio Jazz Brasil", "jazz", "BR", 64, 200),
+            ("News 24", "news", "GB", 96, 10),
+        ]
+        .into_iter()
+        .map(|(name, tags, code, bitrate, votes)| Station {
+            name: name.to_string(),
+            tags: tags.to_string(),
+            countrycode: code.to_string(),
+            bitrate,
+            votes,
+            ..Default::default()
+        })
+        .collect()
+    }
+
+    fn names(stations: &[Station]) -> Vec<&str> {
+        stations.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_search_list_by_name() {
+        let params = SearchParams::parse("jazz", SearchField::Name);
+        assert_eq!(
+            names(&search_list(list(), &params)),
+            ["Jazz FM", "Radio Jazz Brasil"]
+        );
+    }
+
+    #[test]
+    fn test_search_list_combines_terms_and_order() {
+        let params = SearchParams::parse("tag:jazz order:votes", SearchField::Name);
+        assert_eq!(
+            names(&search_list(list(), &params)),
+            ["Radio Jazz Brasil", "Jazz FM"]
+        );
+
+        let params = SearchParams::parse("country:gb bitrate>=100", SearchField::Name);
+        assert_eq!(names(&search_list(list(), &params)), ["Jazz FM"]);
+    }
+
+    #[tokio::test]
+    async fn test_station_list_empty_query() {
+        let provider = StationList {
+            name: "Family".to_string(),
+            url: "https://example.com/list.opml".to_string(),
+        };
+        assert!(provider
+            .search(SearchParams::default())
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(provider.name(), "Family");
+    }
+}
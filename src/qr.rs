@@ -0,0 +1,82 @@
+//! QR codes for sharing stations
+//!
+//! Renders a URL into an RGBA bitmap that the share dialog shows as an
+//! image, so a phone camera can pick up the station.
+
+use qrcode::types::QrError;
+use qrcode::{Color, QrCode};
+
+/// Light border around the code, in modules, as the QR spec requires
+const QUIET_ZONE: usize = 4;
+
+const DARK: [u8; 4] = [0, 0, 0, 255];
+const LIGHT: [u8; 4] = [255, 255, 255, 255];
+
+/// A square RGBA bitmap
+#[derive(Debug, Clone)]
+pub struct QrImage {
+    /// Width and height in pixels
+    pub size: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// Encode `data` with each module drawn as a `scale` × `scale` pixel block
+pub fn render(data: &str, scale: usize) -> Result<QrImage, QrError> {
+    let code = QrCode::new(data.as_bytes())?;
+    let modules = code.width();
+    let colors = code.to_colors();
+    let scale = scale.max(1);
+    let size = (modules + 2 * QUIET_ZONE) * scale;
+
+    let mut pixels = Vec::with_capacity(size * size * 4);
+    for y in 0..size {
+        for x in 0..size {
+            let (mx, my) = (x / scale, y / scale);
+            let dark = (QUIET_ZONE..QUIET_ZONE + modules).contains(&mx)
+                && (QUIET_ZONE..QUIET_ZONE + modules).contains(&my)
+                && colors[(my - QUIET_ZONE) * modules + (mx - QUIET_ZONE)] == Color::Dark;
+            pixels.extend_from_slice(if dark { &DARK } else { &LIGHT });
+        }
+    }
+
+    Ok(QrImage {
+        size: size as u32,
+        pixels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_dark(image: &QrImage, x: usize, y: usize) -> bool {
+        let offset = (y * image.size as usize + x) * 4;
+        image.pixels[offset..offset + 4] == DARK
+    }
+
+    #[test]
+    fn test_render_dimensions() {
+        let image = render("https://radio.example.com/live", 3).unwrap();
+        assert_eq!(image.pixels.len(), (image.size * image.size * 4) as usize);
+        assert_eq!(image.size % 3, 0);
+    }
+
+    #[test]
+    fn test_render_quiet_zone_and_finder() {
+        let scale = 2;
+        let image = render("https://radio.example.com/live", scale).unwrap();
+        // Border is light, the top-left finder pattern starts right after it
+        assert!(!is_dark(&image, 0, 0));
+        assert!(!is_dark(
+            &image,
+            QUIET_ZONE * scale - 1,
+            QUIET_ZONE * scale - 1
+        ));
+        assert!(is_dark(&image, QUIET_ZONE * scale, QUIET_ZONE * scale));
+    }
+
+    #[test]
+    fn test_render_rejects_oversized_data() {
+        assert!(render(&"x".repeat(8000), 1).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! Stream quality preferences
+//!
+//! A minimum bitrate is sent to the directory as `bitrateMin` where the
+//! endpoint supports it and enforced again on every list, since the popular
+//! endpoints ignore it. A preferred codec doesn't hide anything, it only
+//! moves matching stations to the front.
+
+use crate::api::Station;
+use serde::{Deserialize, Serialize};
+
+/// Minimum bitrates offered in settings, in kbps; 0 allows any
+pub const MIN_BITRATE_CHOICES: [u32; 5] = [0, 64, 128, 192, 320];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PreferredCodec {
+    #[default]
+    Any,
+    Aac,
+    Mp3,
+    Opus,
+    Flac,
+}
+
+impl PreferredCodec {
+    pub const ALL: [Self; 5] = [Self::Any, Self::Aac, Self::Mp3, Self::Opus, Self::Flac];
+
+    /// Display name; `None` for [`PreferredCodec::Any`]
+    pub fn name(self) -> Option<&'static str> {
+        match self {
+            Self::Any => None,
+            Self::Aac => Some("AAC"),
+            Self::Mp3 => Some("MP3"),
+            Self::Opus => Some("Opus"),
+            Self::Flac => Some("FLAC"),
+        }
+    }
+
+    /// Whether a directory codec string such as "AAC+" is this codec
+    pub fn matches(self, codec: &str) -> bool {
+        let codec = codec.trim().to_ascii_uppercase();
+        match self {
+            Self::Any => false,
+            // Also "AAC+" and "HE-AAC"
+            Self::Aac => codec.contains("AAC"),
+            Self::Mp3 => codec == "MP3",
+            Self::Opus => codec.contains("OPUS"),
+            Self::Flac => codec == "FLAC",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QualityFilter {
+    /// Stations below this many kbps are hidden; 0 allows any
+    #[serde(default)]
+    pub min_bitrate: u32,
+    #[serde(default)]
+    pub preferred_codec: PreferredCodec,
+}
+
+impl QualityFilter {
+    pub fn is_active(&self) -> bool {
+        self.min_bitrate > 0 || self.preferred_codec != PreferredCodec::Any
+    }
+
+    /// Drop stations below the minimum bitrate (including ones that don't
+    /// report a bitrate) and move the preferred codec to the front, keeping
+    /// the order otherwise
+    pub fn apply(&self, mut stations: Vec<Station>) -> Vec<Station> {
+        if self.min_bitrate > 0 {
+            stations.retain(|station| station.bitrate >= self.min_bitrate);
+        }
+        if self.preferred_codec != PreferredCodec::Any {
+            stations.sort_by_key(|station| !self.preferred_codec.matches(&station.codec));
+        }
+        stations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn station(name: &str, codec: &str, bitrate: u32) -> Station {
+        Station {
+            name: name.to_string(),
+            codec: codec.to_string(),
+            bitrate,
+            ..Default::default()
+        }
+    }
+
+    fn names(stations: &[Station]) -> Vec<&str> {
+        stations.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_default_filter_keeps_everything() {
+        let filter = QualityFilter::default();
+        assert!(!filter.is_active());
+        let stations = vec![station("a", "MP3", 0), station("b", "AAC", 64)];
+        assert_eq!(names(&filter.apply(stations)), ["a", "b"]);
+    }
+
+    #[test]
+    fn test_min_bitrate_drops_low_and_unknown() {
+        let filter = QualityFilter {
+            min_bitrate: 128,
+            ..Default::default()
+        };
+        let stations = vec![
+            station("low", "MP3", 64),
+            station("unknown", "MP3", 0),
+            station("exact", "MP3", 128),
+            station("high", "AAC", 256),
+        ];
+        assert_eq!(names(&filter.apply(stations)), ["exact", "high"]);
+    }
+
+    #[test]
+    fn test_preferred_codec_moves_to_front() {
+        let filter = QualityFilter {
+            preferred_codec: PreferredCodec::Aac,
+            ..Default::default()
+        };
+        let stations = vec![
+            station("mp3 1", "MP3", 128),
+            station("aac+", "AAC+", 64),
+            station("mp3 2", "MP3", 128),
+            station("he-aac", "he-aac", 48),
+        ];
+        assert_eq!(
+            names(&filter.apply(stations)),
+            ["aac+", "he-aac", "mp3 1", "mp3 2"]
+        );
+    }
+
+    #[test]
+    fn test_codec_matching() {
+        assert!(PreferredCodec::Mp3.matches(" mp3 "));
+        assert!(!PreferredCodec::Mp3.matches("MP3 AAC"));
+        assert!(PreferredCodec::Opus.matches("OGG OPUS"));
+        assert!(!PreferredCodec::Any.matches("MP3"));
+    }
+}
@@ -0,0 +1,133 @@
+//! Crash-safe file writes
+//!
+//! Every file the applet persists itself (exports, caches, journals) goes
+//! through [`write_atomic`], so a power loss leaves either the old or the new
+//! contents on disk, never a truncated mix. The cosmic-config store handles
+//! its own writes.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Write `contents` to `path` via a temporary file in the same directory,
+/// fsync it, rename it over the target and fsync the directory.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(dir)?;
+
+    let tmp_path = temp_path_for(path);
+    let result = write_and_sync(&tmp_path, contents).and_then(|()| fs::rename(&tmp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+        return result;
+    }
+
+    // Make the rename itself durable
+    File::open(dir)?.sync_all()
+}
+
+/// Serialize `value` as pretty JSON and write it atomically
+pub fn write_json_atomic<T: serde::Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    let json = serde_json::to_vec_pretty(value).map_err(io::Error::other)?;
+    write_atomic(path, &json)
+}
+
+fn write_and_sync(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut file = match OpenOptions::new().write(true).create_new(true).open(path) {
+        // Temp names are unique among live writers, so this one was left by
+        // a crashed process that had the same pid
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+            fs::remove_file(path)?;
+            OpenOptions::new().write(true).create_new(true).open(path)?
+        }
+        res => res?,
+    };
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+/// Hidden sibling of `path`, unique per process and write so concurrent
+/// writers don't clobber each other's temp files
+fn temp_path_for(path: &Path) -> PathBuf {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        name,
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "cosmic-ext-radio-safe-write-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_write_atomic_creates_and_replaces() {
+        let dir = test_dir("replace");
+        let path = dir.join("favorites.json");
+
+        write_atomic(&path, b"first").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"first");
+        write_atomic(&path, b"second").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+
+        // No temp files are left behind
+        let entries: Vec<_> = fs::read_dir(&dir).unwrap().collect();
+        assert_eq!(entries.len(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_json_atomic() {
+        let dir = test_dir("json");
+        let path = dir.join("data.json");
+        write_json_atomic(&path, &vec!["a", "b"]).unwrap();
+        let back: Vec<String> = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(back, vec!["a", "b"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_stale_temp_file() {
+        let dir = test_dir("stale");
+        let path = dir.join("cache.json");
+        fs::create_dir_all(&dir).unwrap();
+        let stale = temp_path_for(&path);
+        fs::write(&stale, b"left by a crash").unwrap();
+
+        assert!(write_and_sync(&stale, b"new").is_ok());
+        assert_eq!(fs::read(&stale).unwrap(), b"new");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_temp_path_is_hidden_sibling() {
+        let tmp = temp_path_for(Path::new("/data/radio/favorites.opml"));
+        assert_eq!(tmp.parent(), Some(Path::new("/data/radio")));
+        assert!(tmp
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with(".favorites.opml."));
+        assert_ne!(tmp, temp_path_for(Path::new("/data/radio/favorites.opml")));
+    }
+}
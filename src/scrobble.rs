@@ -0,0 +1,622 @@
+//! Scrobbling the songs stations play
+//!
+//! The scrobbler is an event bus subscriber like MPRIS. [`ListenTracker`]
+//! turns playback and track events into what a scrobble service is told:
+//! the song now playing, and each song heard long enough to count as a
+//! listen. Listens wait in a [`ListenQueue`], kept in the cache directory
+//! across restarts, while offline or after a failed submission. They go out
+//! once the network is back, or after a growing [`Backoff`] when the server
+//! had trouble. ListenBrainz, with the user's token, is where they are sent.
+
+use crate::api;
+use crate::cache;
+use crate::error::ApiError;
+use crate::events::{AppEvent, NetworkEvent, PlaybackEvent};
+use crate::mpris::split_stream_title;
+use crate::safe_write;
+use crate::station::StationId;
+use futures::SinkExt;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+/// A song counts as listened to once it played this long
+pub const MIN_LISTEN: Duration = Duration::from_secs(30);
+
+/// Listens kept while they can't be sent; the oldest go first
+pub const MAX_QUEUED: usize = 500;
+
+/// Listens sent in one request
+const MAX_BATCH: usize = 100;
+
+/// Wait after the first failed submission; doubled for each further one
+const RETRY_DELAY: Duration = Duration::from_secs(60);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// File in the cache directory holding the listens not yet sent
+const QUEUE_FILE: &str = "listenbrainz-queue.json";
+
+const SUBMISSION_CLIENT: &str = "Radio for COSMIC";
+
+/// A song heard on a station
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Listen {
+    pub artist: String,
+    pub track: String,
+    /// When it started, in seconds since the Unix epoch
+    pub listened_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScrobbleAction {
+    /// Show this song as playing right now
+    NowPlaying(Listen),
+    /// Record this song as listened to
+    Submit(Listen),
+}
+
+/// The song playing, and since when
+#[derive(Debug)]
+struct Playing {
+    station: StationId,
+    listen: Listen,
+    since: SystemTime,
+}
+
+/// Follows what is playing to decide what to scrobble
+#[derive(Debug, Default)]
+pub struct ListenTracker {
+    playing: Option<Playing>,
+}
+
+impl ListenTracker {
+    /// What `event`, happening at `now`, means for scrobbling
+    pub fn handle(&mut self, event: &AppEvent, now: SystemTime) -> Vec<ScrobbleAction> {
+        let mut actions = Vec::new();
+        match event {
+            AppEvent::Metadata { station, title } => {
+                actions.extend(self.finish(now));
+                // Only "Artist - Title" names a song; anything else is
+                // usually the station talking about itself
+                if let (Some(artist), track) = split_stream_title(title) {
+                    let listen = Listen {
+                        artist: artist.to_string(),
+                        track: track.to_string(),
+                        listened_at: unix_seconds(now),
+                    };
+                    actions.push(ScrobbleAction::NowPlaying(listen.clone()));
+                    self.playing = Some(Playing {
+                        station: station.id.clone(),
+                        listen,
+                        since: now,
+                    });
+                }
+            }
+            // Also published when only the volume changed
+            AppEvent::Playback(PlaybackEvent::Started { station })
+                if self
+                    .playing
+                    .as_ref()
+                    .is_some_and(|playing| playing.station != station.id) =>
+            {
+                actions.extend(self.finish(now));
+            }
+            // MPRIS Quit only silences the applet, which stays in the panel
+            // and may play again, so scrobbling carries on afterwards
+            AppEvent::Playback(PlaybackEvent::Stopped) | AppEvent::Shutdown => {
+                actions.extend(self.finish(now));
+            }
+            _ => {}
+        }
+        actions
+    }
+
+    /// End the current song, which is a listen if it played long enough
+    fn finish(&mut self, now: SystemTime) -> Option<ScrobbleAction> {
+        let playing = self.playing.take()?;
+        let played = now.duration_since(playing.since).unwrap_or_default();
+        (played >= MIN_LISTEN).then_some(ScrobbleAction::Submit(playing.listen))
+    }
+}
+
+/// Listens not yet sent, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ListenQueue {
+    listens: VecDeque<Listen>,
+}
+
+impl ListenQueue {
+    pub fn push(&mut self, listen: Listen) {
+        if self.listens.len() == MAX_QUEUED {
+            self.listens.pop_front();
+        }
+        self.listens.push_back(listen);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.listens.is_empty()
+    }
+
+    /// The oldest listens, as many as go in one request
+    pub fn batch(&self) -> Vec<Listen> {
+        self.listens.iter().take(MAX_BATCH).cloned().collect()
+    }
+
+    /// Drop the `count` oldest listens, once they were sent
+    pub fn confirm(&mut self, count: usize) {
+        self.listens.drain(..count.min(self.listens.len()));
+    }
+
+    pub fn load(path: &Path) -> Self {
+        match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+                warn!("Ignoring unreadable listen queue {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) {
+        let saved = if self.is_empty() {
+            std::fs::remove_file(path).or_else(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => Ok(()),
+                _ => Err(e),
+            })
+        } else {
+            safe_write::write_json_atomic(path, self)
+        };
+        if let Err(e) = saved {
+            warn!("Failed to save listen queue to {}: {}", path.display(), e);
+        }
+    }
+}
+
+fn queue_file() -> Option<PathBuf> {
+    cache::cache_dir().map(|dir| dir.join(QUEUE_FILE))
+}
+
+/// Read the queue left from earlier sessions, off the async runtime
+async fn load_queue() -> ListenQueue {
+    let Some(path) = queue_file() else {
+        return ListenQueue::default();
+    };
+    tokio::task::spawn_blocking(move || ListenQueue::load(&path))
+        .await
+        .unwrap_or_default()
+}
+
+async fn save_queue(queue: &ListenQueue) {
+    let Some(path) = queue_file() else {
+        return;
+    };
+    let queue = queue.clone();
+    if let Err(e) = tokio::task::spawn_blocking(move || queue.save(&path)).await {
+        warn!("Saving the listen queue failed: {}", e);
+    }
+}
+
+/// Time between failed submissions, doubling up to [`MAX_RETRY_DELAY`]
+#[derive(Debug, Default)]
+pub struct Backoff {
+    failures: u32,
+}
+
+impl Backoff {
+    /// Note another failure; returns how long to wait before the next try
+    pub fn failed(&mut self) -> Duration {
+        let delay = RETRY_DELAY.saturating_mul(1 << self.failures.min(16));
+        self.failures = self.failures.saturating_add(1);
+        delay.min(MAX_RETRY_DELAY)
+    }
+
+    pub fn reset(&mut self) {
+        self.failures = 0;
+    }
+}
+
+/// What a failed submission means for the listens in it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Refusal {
+    /// ListenBrainz won't take these listens as they are; drop them
+    Rejected,
+    /// The token was refused; keep everything until it is changed
+    Unauthorized,
+    /// Server trouble, rate limiting or no connection; try again later
+    Transient,
+}
+
+impl Refusal {
+    pub fn of(err: &ApiError) -> Self {
+        match err {
+            ApiError::ErrorResponse {
+                status: 401 | 403, ..
+            } => Self::Unauthorized,
+            ApiError::ErrorResponse { status, .. }
+                if (400..500).contains(status) && *status != 429 =>
+            {
+                Self::Rejected
+            }
+            _ => Self::Transient,
+        }
+    }
+}
+
+/// Kinds of ListenBrainz submission
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListenType {
+    PlayingNow,
+    Single,
+    Import,
+}
+
+/// Body of a ListenBrainz `submit-listens` request
+#[derive(Debug, Serialize)]
+pub struct Submission<'a> {
+    listen_type: ListenType,
+    payload: Vec<Payload<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Payload<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    listened_at: Option<u64>,
+    track_metadata: TrackMetadata<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct TrackMetadata<'a> {
+    artist_name: &'a str,
+    track_name: &'a str,
+    additional_info: AdditionalInfo,
+}
+
+#[derive(Debug, Serialize)]
+struct AdditionalInfo {
+    media_player: &'static str,
+    submission_client: &'static str,
+    submission_client_version: &'static str,
+}
+
+impl<'a> Submission<'a> {
+    /// Show `listen` as the song playing now
+    pub fn playing_now(listen: &'a Listen) -> Self {
+        Self {
+            listen_type: ListenType::PlayingNow,
+            payload: vec![Payload::new(listen, false)],
+        }
+    }
+
+    /// Record `listens` as listened to
+    pub fn listens(listens: &'a [Listen]) -> Self {
+        Self {
+            listen_type: if listens.len() == 1 {
+                ListenType::Single
+            } else {
+                ListenType::Import
+            },
+            payload: listens
+                .iter()
+                .map(|listen| Payload::new(listen, true))
+                .collect(),
+        }
+    }
+}
+
+impl<'a> Payload<'a> {
+    fn new(listen: &'a Listen, listened: bool) -> Self {
+        Self {
+            listened_at: listened.then_some(listen.listened_at),
+            track_metadata: TrackMetadata {
+                artist_name: &listen.artist,
+                track_name: &listen.track,
+                additional_info: AdditionalInfo {
+                    media_player: SUBMISSION_CLIENT,
+                    submission_client: SUBMISSION_CLIENT,
+                    submission_client_version: env!("CARGO_PKG_VERSION"),
+                },
+            },
+        }
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or_default()
+}
+
+/// Events yielded by the scrobbler subscription
+#[derive(Debug, Clone)]
+pub enum ScrobbleEvent {
+    /// Connect this sender to the app's event bus
+    Ready(mpsc::UnboundedSender<AppEvent>),
+}
+
+/// Scrobble to ListenBrainz with `token` for as long as the subscription
+/// runs
+pub fn subscription(token: String) -> cosmic::iced::Subscription<ScrobbleEvent> {
+    cosmic::iced::Subscription::run_with_id(
+        token.clone(),
+        cosmic::iced::stream::channel(1, |mut output| async move {
+            let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+            if output.send(ScrobbleEvent::Ready(event_tx)).await.is_err() {
+                return;
+            }
+
+            let mut tracker = ListenTracker::default();
+            let mut queue = load_queue().await;
+            let mut online = true;
+            let mut backoff = Backoff::default();
+            let mut retry_at: Option<Instant> = None;
+            // The token was refused; a new one starts a new subscription
+            let mut unauthorized = false;
+            loop {
+                let retry = async {
+                    match retry_at {
+                        Some(at) => tokio::time::sleep_until(at).await,
+                        None => std::future::pending().await,
+                    }
+                };
+                let event = tokio::select! {
+                    event = event_rx.recv() => match event {
+                        Some(event) => Some(event),
+                        None => break,
+                    },
+                    () = retry => None,
+                };
+                let mut changed = false;
+                if let Some(event) = event {
+                    if let AppEvent::Network(network) = event {
+                        online = network == NetworkEvent::Online;
+                        // The failures may have been the network's
+                        retry_at = None;
+                    }
+                    for action in tracker.handle(&event, SystemTime::now()) {
+                        match action {
+                            ScrobbleAction::NowPlaying(listen) if online && !unauthorized => {
+                                let submission = Submission::playing_now(&listen);
+                                if let Err(e) = api::submit_listens(&token, &submission).await {
+                                    debug!("ListenBrainz playing-now failed: {}", e);
+                                }
+                            }
+                            ScrobbleAction::NowPlaying(_) => {}
+                            ScrobbleAction::Submit(listen) => {
+                                queue.push(listen);
+                                changed = true;
+                            }
+                        }
+                    }
+                }
+                let due = retry_at.is_none_or(|at| at <= Instant::now());
+                if online && !unauthorized && due && !queue.is_empty() {
+                    changed = true;
+                    retry_at = None;
+                    match flush(&token, &mut queue).await {
+                        None => backoff.reset(),
+                        Some(Refusal::Unauthorized) => unauthorized = true,
+                        Some(_) => {
+                            let delay = backoff.failed();
+                            debug!("Trying ListenBrainz again in {:?}", delay);
+                            retry_at = Some(Instant::now() + delay);
+                        }
+                    }
+                }
+                if changed {
+                    save_queue(&queue).await;
+                }
+            }
+        }),
+    )
+}
+
+/// Send the queued listens, dropping those ListenBrainz rejects and keeping
+/// the rest for later. Returns why sending stopped, if it did.
+async fn flush(token: &str, queue: &mut ListenQueue) -> Option<Refusal> {
+    while !queue.is_empty() {
+        let batch = queue.batch();
+        match api::submit_listens(token, &Submission::listens(&batch)).await {
+            Ok(()) => {
+                debug!("Submitted {} listens to ListenBrainz", batch.len());
+                queue.confirm(batch.len());
+            }
+            Err(e) => match Refusal::of(&e) {
+                Refusal::Rejected => {
+                    warn!("ListenBrainz rejected {} listens: {}", batch.len(), e);
+                    queue.confirm(batch.len());
+                }
+                Refusal::Unauthorized => {
+                    warn!("ListenBrainz refused the token, keeping listens: {}", e);
+                    return Some(Refusal::Unauthorized);
+                }
+                Refusal::Transient => {
+                    warn!("ListenBrainz submission failed, keeping listens: {}", e);
+                    return Some(Refusal::Transient);
+                }
+            },
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Station;
+
+    fn station(name: &str) -> Box<Station> {
+        let url = format!("http://example.com/{name}");
+        Box::new(Station::custom(name, &url, "").unwrap())
+    }
+
+    fn track(station: &Station, title: &str) -> AppEvent {
+        AppEvent::Metadata {
+            station: Box::new(station.clone()),
+            title: title.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_song_listened_long_enough_is_submitted() {
+        let start = UNIX_EPOCH + Duration::from_secs(1_000);
+        let jazz = station("Jazz");
+        let mut tracker = ListenTracker::default();
+
+        let actions = tracker.handle(&track(&jazz, "Air - Alpha Beta Gaga"), start);
+        let listen = Listen {
+            artist: "Air".to_string(),
+            track: "Alpha Beta Gaga".to_string(),
+            listened_at: 1_000,
+        };
+        assert_eq!(actions, [ScrobbleAction::NowPlaying(listen.clone())]);
+
+        // Volume changes republish Started for the same station
+        let started = AppEvent::Playback(PlaybackEvent::Started {
+            station: jazz.clone(),
+        });
+        assert!(tracker.handle(&started, start + MIN_LISTEN).is_empty());
+
+        let next = tracker.handle(&track(&jazz, "Air - Sexy Boy"), start + MIN_LISTEN);
+        assert_eq!(next[0], ScrobbleAction::Submit(listen));
+        assert!(matches!(next[1], ScrobbleAction::NowPlaying(_)));
+    }
+
+    #[test]
+    fn test_short_or_untitled_songs_are_not_submitted() {
+        let start = UNIX_EPOCH + Duration::from_secs(1_000);
+        let jazz = station("Jazz");
+        let mut tracker = ListenTracker::default();
+
+        tracker.handle(&track(&jazz, "Air - Alpha Beta Gaga"), start);
+        let stopped = AppEvent::Playback(PlaybackEvent::Stopped);
+        assert!(tracker
+            .handle(&stopped, start + Duration::from_secs(10))
+            .is_empty());
+
+        assert!(tracker.handle(&track(&jazz, "Jazz FM"), start).is_empty());
+        assert!(tracker
+            .handle(&stopped, start + Duration::from_secs(300))
+            .is_empty());
+
+        // Switching stations ends the song
+        tracker.handle(&track(&jazz, "Air - Alpha Beta Gaga"), start);
+        let rock = AppEvent::Playback(PlaybackEvent::Started {
+            station: station("Rock"),
+        });
+        assert!(matches!(
+            tracker.handle(&rock, start + MIN_LISTEN)[..],
+            [ScrobbleAction::Submit(_)]
+        ));
+    }
+
+    #[test]
+    fn test_shutdown_ends_the_song() {
+        let start = UNIX_EPOCH + Duration::from_secs(1_000);
+        let jazz = station("Jazz");
+        let mut tracker = ListenTracker::default();
+
+        tracker.handle(&track(&jazz, "Air - Alpha Beta Gaga"), start);
+        assert!(matches!(
+            tracker.handle(&AppEvent::Shutdown, start + MIN_LISTEN)[..],
+            [ScrobbleAction::Submit(_)]
+        ));
+        // Songs after it are tracked as usual
+        assert!(matches!(
+            tracker.handle(&track(&jazz, "Air - Sexy Boy"), start + MIN_LISTEN)[..],
+            [ScrobbleAction::NowPlaying(_)]
+        ));
+    }
+
+    #[test]
+    fn test_queue_keeps_newest() {
+        let mut queue = ListenQueue::default();
+        for listened_at in 0..MAX_QUEUED as u64 + 1 {
+            queue.push(Listen {
+                artist: "Air".to_string(),
+                track: "Playground Love".to_string(),
+                listened_at,
+            });
+        }
+        let batch = queue.batch();
+        assert_eq!(batch.len(), MAX_BATCH);
+        assert_eq!(batch[0].listened_at, 1);
+
+        queue.confirm(MAX_QUEUED);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_queue_save_and_load_roundtrip() {
+        let dir =
+            std::env::temp_dir().join(format!("cosmic-ext-radio-listens-{}", std::process::id()));
+        let path = dir.join(QUEUE_FILE);
+        let mut queue = ListenQueue::default();
+        queue.push(Listen {
+            artist: "Air".to_string(),
+            track: "Playground Love".to_string(),
+            listened_at: 1_000,
+        });
+        queue.save(&path);
+        assert_eq!(ListenQueue::load(&path).batch(), queue.batch());
+
+        // Nothing left to send leaves no file behind
+        queue.confirm(1);
+        queue.save(&path);
+        assert!(!path.exists());
+        assert!(ListenQueue::load(&path).is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_refusals() {
+        let status = |status| ApiError::ErrorResponse {
+            status,
+            message: String::new(),
+        };
+        assert_eq!(Refusal::of(&status(400)), Refusal::Rejected);
+        assert_eq!(Refusal::of(&status(401)), Refusal::Unauthorized);
+        assert_eq!(Refusal::of(&status(429)), Refusal::Transient);
+        assert_eq!(Refusal::of(&status(503)), Refusal::Transient);
+        assert_eq!(Refusal::of(&ApiError::Timeout(10)), Refusal::Transient);
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_limit() {
+        let mut backoff = Backoff::default();
+        assert_eq!(backoff.failed(), RETRY_DELAY);
+        assert_eq!(backoff.failed(), RETRY_DELAY * 2);
+        for _ in 0..20 {
+            backoff.failed();
+        }
+        assert_eq!(backoff.failed(), MAX_RETRY_DELAY);
+        backoff.reset();
+        assert_eq!(backoff.failed(), RETRY_DELAY);
+    }
+
+    #[test]
+    fn test_submission_json() {
+        let listen = Listen {
+            artist: "Air".to_string(),
+            track: "Alpha Beta Gaga".to_string(),
+            listened_at: 1_000,
+        };
+        let now = serde_json::to_value(Submission::playing_now(&listen)).unwrap();
+        assert_eq!(now["listen_type"], "playing_now");
+        assert!(now["payload"][0].get("listened_at").is_none());
+        assert_eq!(now["payload"][0]["track_metadata"]["artist_name"], "Air");
+
+        let single =
+            serde_json::to_value(Submission::listens(std::slice::from_ref(&listen))).unwrap();
+        assert_eq!(single["listen_type"], "single");
+        assert_eq!(single["payload"][0]["listened_at"], 1_000);
+
+        let import = serde_json::to_value(Submission::listens(&[listen.clone(), listen])).unwrap();
+        assert_eq!(import["listen_type"], "import");
+        assert_eq!(import["payload"].as_array().map(Vec::len), Some(2));
+    }
+}
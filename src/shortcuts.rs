@@ -0,0 +1,95 @@
+//! Popup keyboard shortcuts
+//!
+//! [`BINDINGS`] is the single table the popup's key handler looks keys up
+//! in, and the "?" help overlay lists it as is, so the overlay cannot drift
+//! from what the keys actually do.
+
+/// A key the popup reacts to, independent of the toolkit's key type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutKey {
+    Space,
+    Up,
+    Down,
+    Escape,
+    Char(char),
+}
+
+impl ShortcutKey {
+    /// How the key is written in the help overlay
+    pub fn label(self) -> String {
+        match self {
+            Self::Space => "Space".to_string(),
+            Self::Up => "↑".to_string(),
+            Self::Down => "↓".to_string(),
+            Self::Escape => "Esc".to_string(),
+            Self::Char(c) => c.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutAction {
+    PlayPause,
+    VolumeUp,
+    VolumeDown,
+    /// Close the topmost dialog, or else the popup
+    Close,
+    ToggleHelp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    pub key: ShortcutKey,
+    pub action: ShortcutAction,
+}
+
+/// Every popup shortcut, in the order the help overlay lists them
+pub const BINDINGS: &[Binding] = &[
+    Binding {
+        key: ShortcutKey::Space,
+        action: ShortcutAction::PlayPause,
+    },
+    Binding {
+        key: ShortcutKey::Up,
+        action: ShortcutAction::VolumeUp,
+    },
+    Binding {
+        key: ShortcutKey::Down,
+        action: ShortcutAction::VolumeDown,
+    },
+    Binding {
+        key: ShortcutKey::Escape,
+        action: ShortcutAction::Close,
+    },
+    Binding {
+        key: ShortcutKey::Char('?'),
+        action: ShortcutAction::ToggleHelp,
+    },
+];
+
+/// What pressing `key` does in the popup
+pub fn action_for(key: ShortcutKey) -> Option<ShortcutAction> {
+    BINDINGS
+        .iter()
+        .find(|binding| binding.key == key)
+        .map(|binding| binding.action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_each_key_bound_once() {
+        for (i, binding) in BINDINGS.iter().enumerate() {
+            assert!(
+                BINDINGS[i + 1..].iter().all(|b| b.key != binding.key),
+                "{:?} is bound twice",
+                binding.key
+            );
+            assert_eq!(action_for(binding.key), Some(binding.action));
+        }
+        assert_eq!(action_for(ShortcutKey::Char('x')), None);
+        assert_eq!(ShortcutKey::Char('?').label(), "?");
+    }
+}
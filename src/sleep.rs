@@ -0,0 +1,112 @@
+//! Suspend and resume
+//!
+//! logind signals `PrepareForSleep` before the system suspends and again
+//! after it wakes. [`subscription`] holds a delay lock so the app gets to
+//! stop the stream first, instead of mpv waking up to a dead socket, and
+//! lets the lock go once the app reports the stream stopped.
+
+use crate::inhibit::{self, LOGIND_MANAGER_INTERFACE};
+use futures::channel::mpsc;
+use futures::{SinkExt, StreamExt};
+use std::time::Duration;
+use tracing::warn;
+use zbus::message::Type;
+use zbus::{Connection, MatchRule, MessageStream};
+
+/// Longest the suspend is held up for the app; logind itself gives up on
+/// delay locks after `InhibitDelayMaxSec` (5 s by default)
+const STOP_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone)]
+pub enum SleepEvent {
+    /// The system is about to suspend, as soon as the app is ready
+    Suspending(SleepReady),
+    /// The system woke up again
+    Resumed,
+}
+
+/// Lets the suspend go ahead once [`SleepReady::done`] is called or the
+/// last copy is dropped
+#[derive(Debug, Clone)]
+pub struct SleepReady(mpsc::UnboundedSender<()>);
+
+impl SleepReady {
+    /// Playback is stopped, the system may suspend
+    pub fn done(&self) {
+        let _ = self.0.unbounded_send(());
+    }
+}
+
+/// Wait for the app to be done with a [`SleepReady`], at most `limit`.
+/// Returns whether it was in time.
+async fn wait_ready(mut ready: mpsc::UnboundedReceiver<()>, limit: Duration) -> bool {
+    tokio::time::timeout(limit, ready.next()).await.is_ok()
+}
+
+pub fn subscription() -> cosmic::iced::Subscription<SleepEvent> {
+    cosmic::iced::Subscription::run(|| {
+        cosmic::iced::stream::channel(4, |mut output| async move {
+            let result = async {
+                let connection = Connection::system().await?;
+                let rule = MatchRule::builder()
+                    .msg_type(Type::Signal)
+                    .interface(LOGIND_MANAGER_INTERFACE)?
+                    .member("PrepareForSleep")?
+                    .build();
+                let mut signals = MessageStream::for_match_rule(rule, &connection, None).await?;
+                let mut lock = Some(inhibit::delay("sleep").await?);
+                while let Some(signal) = signals.next().await {
+                    let suspending: bool = signal?.body().deserialize()?;
+                    if !suspending {
+                        if output.send(SleepEvent::Resumed).await.is_err() {
+                            break;
+                        }
+                        if lock.is_none() {
+                            lock = Some(inhibit::delay("sleep").await?);
+                        }
+                        continue;
+                    }
+                    let (ready, stopped) = mpsc::unbounded();
+                    if output
+                        .send(SleepEvent::Suspending(SleepReady(ready)))
+                        .await
+                        .is_err()
+                    {
+                        break;
+                    }
+                    if !wait_ready(stopped, STOP_TIMEOUT).await {
+                        warn!("Playback didn't stop in time for suspend");
+                    }
+                    lock = None;
+                }
+                Ok::<(), zbus::Error>(())
+            };
+            if let Err(e) = result.await {
+                warn!("Not watching for suspend: {}", e);
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_ready() {
+        let limit = Duration::from_millis(50);
+
+        let (ready, stopped) = mpsc::unbounded();
+        SleepReady(ready.clone()).done();
+        assert!(wait_ready(stopped, limit).await);
+
+        // Handled without an explicit answer
+        let (ready, stopped) = mpsc::unbounded();
+        drop(SleepReady(ready));
+        assert!(wait_ready(stopped, limit).await);
+
+        let (ready, stopped) = mpsc::unbounded::<()>();
+        assert!(!wait_ready(stopped, limit).await);
+        drop(ready);
+    }
+}
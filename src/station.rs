@@ -0,0 +1,158 @@
+use crate::api::Station;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Provider prefix for stations from radio-browser.info
+pub const PROVIDER_RADIO_BROWSER: &str = "radio-browser";
+
+/// Provider prefix for stations added or imported by the user
+pub const PROVIDER_CUSTOM: &str = "custom";
+
+/// Internal station identifier, independent of any directory's UUID.
+///
+/// It is assigned once when a station is first seen and then kept, so
+/// favorites and anything referencing them stay linked even if the
+/// provider re-issues its own identifier.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct StationId(String);
+
+impl StationId {
+    /// Build an identifier from a provider name and that provider's key
+    pub fn new(provider: &str, key: &str) -> Self {
+        Self(format!("{provider}:{key}"))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for StationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Positions of stations in a list, by internal id and by provider UUID,
+/// so membership checks don't scan the whole list. Rebuild it whenever the
+/// list changes.
+#[derive(Debug, Clone, Default)]
+pub struct StationIndex {
+    by_id: HashMap<StationId, usize>,
+    by_uuid: HashMap<String, usize>,
+}
+
+impl StationIndex {
+    pub fn build(stations: &[Station]) -> Self {
+        let mut index = Self::default();
+        // Iterate backwards so the first of any duplicates wins, like `position`
+        for (pos, station) in stations.iter().enumerate().rev() {
+            if !station.id.is_empty() {
+                index.by_id.insert(station.id.clone(), pos);
+            }
+            if !station.stationuuid.is_empty() {
+                index.by_uuid.insert(station.stationuuid.clone(), pos);
+            }
+        }
+        index
+    }
+
+    /// Position of the entry that [`Station::is_same`] as `station`
+    pub fn position(&self, station: &Station) -> Option<usize> {
+        let by_id = (!station.id.is_empty())
+            .then(|| self.by_id.get(&station.id))
+            .flatten();
+        let by_uuid = (!station.stationuuid.is_empty())
+            .then(|| self.by_uuid.get(&station.stationuuid))
+            .flatten();
+        match (by_id, by_uuid) {
+            (Some(a), Some(b)) => Some(*a.min(b)),
+            (a, b) => a.or(b).copied(),
+        }
+    }
+
+    pub fn position_of_id(&self, id: &StationId) -> Option<usize> {
+        self.by_id.get(id).copied()
+    }
+
+    pub fn contains(&self, station: &Station) -> bool {
+        self.position(station).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_station_id_new() {
+        let id = StationId::new(PROVIDER_RADIO_BROWSER, "abc-123");
+        assert_eq!(id.to_string(), "radio-browser:abc-123");
+        assert!(!id.is_empty());
+    }
+
+    fn station(id: &str, uuid: &str) -> Station {
+        Station {
+            id: if id.is_empty() {
+                StationId::default()
+            } else {
+                StationId::new(PROVIDER_RADIO_BROWSER, id)
+            },
+            stationuuid: uuid.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_station_index_matches_is_same() {
+        let stations = vec![
+            station("a", "uuid-a"),
+            station("", "uuid-b"),
+            station("c", ""),
+        ];
+        let index = StationIndex::build(&stations);
+
+        for probe in [
+            station("a", ""),
+            station("", "uuid-a"),
+            station("x", "uuid-b"),
+            station("c", "other"),
+            station("x", "uuid-x"),
+            station("", ""),
+        ] {
+            assert_eq!(
+                index.position(&probe),
+                stations.iter().position(|s| s.is_same(&probe)),
+                "{probe:?}"
+            );
+        }
+        assert_eq!(
+            index.position_of_id(&StationId::new(PROVIDER_RADIO_BROWSER, "c")),
+            Some(2)
+        );
+        assert!(!index.contains(&station("", "")));
+    }
+
+    #[test]
+    fn test_station_index_prefers_first_duplicate() {
+        let stations = vec![station("a", "u1"), station("a", "u2")];
+        let index = StationIndex::build(&stations);
+        assert_eq!(index.position(&station("a", "")), Some(0));
+        assert_eq!(index.position(&station("", "u2")), Some(1));
+    }
+
+    #[test]
+    fn test_station_id_default_is_empty() {
+        assert!(StationId::default().is_empty());
+    }
+
+    #[test]
+    fn test_station_id_serializes_as_plain_string() {
+        let id = StationId::new(PROVIDER_CUSTOM, "x");
+        assert_eq!(serde_json::to_string(&id).unwrap(), "\"custom:x\"");
+        let back: StationId = serde_json::from_str("\"custom:x\"").unwrap();
+        assert_eq!(back, id);
+    }
+}
@@ -0,0 +1,94 @@
+//! Central lookup of the stations the applet knows about
+//!
+//! Favorites, search results, popular lists and subscriptions are indexed
+//! here by [`StationId`], so messages carry an id and the full station is
+//! looked up only when a handler needs it.
+
+use crate::api::Station;
+use crate::station::StationId;
+use std::collections::HashMap;
+
+#[derive(Debug, Default)]
+pub struct StationStore {
+    stations: HashMap<StationId, Station>,
+}
+
+impl StationStore {
+    /// Replace the contents with the stations in `lists`. For duplicate ids
+    /// the last occurrence wins, so pass the user's own lists last.
+    pub fn rebuild<'a>(&mut self, lists: impl IntoIterator<Item = &'a Station>) {
+        self.stations.clear();
+        for station in lists {
+            if !station.id.is_empty() {
+                self.stations.insert(station.id.clone(), station.clone());
+            }
+        }
+    }
+
+    /// Add or replace a single station, assigning an id if it has none.
+    /// Returns the id to reference it by.
+    pub fn insert(&mut self, mut station: Station) -> StationId {
+        station.ensure_uuid();
+        station.ensure_id();
+        let id = station.id.clone();
+        self.stations.insert(id.clone(), station);
+        id
+    }
+
+    pub fn get(&self, id: &StationId) -> Option<&Station> {
+        self.stations.get(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Station> {
+        self.stations.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::station::PROVIDER_RADIO_BROWSER;
+
+    fn station(uuid: &str, name: &str) -> Station {
+        Station {
+            id: StationId::new(PROVIDER_RADIO_BROWSER, uuid),
+            stationuuid: uuid.to_string(),
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rebuild_last_wins() {
+        let search = [station("a", "Directory name"), station("b", "B")];
+        let favorites = [station("a", "My name")];
+
+        let mut store = StationStore::default();
+        store.rebuild(search.iter().chain(&favorites));
+        assert!(store.get(&search[1].id).is_some());
+        assert_eq!(store.get(&favorites[0].id).unwrap().name, "My name");
+
+        store.rebuild(&favorites);
+        assert!(store.get(&favorites[0].id).is_some());
+        assert!(store.get(&search[1].id).is_none());
+    }
+
+    #[test]
+    fn test_rebuild_skips_stations_without_id() {
+        let mut store = StationStore::default();
+        store.rebuild(&[Station::default()]);
+        assert!(store.stations.is_empty());
+    }
+
+    #[test]
+    fn test_insert_assigns_id() {
+        let mut store = StationStore::default();
+        let id = store.insert(Station {
+            name: "Icecast".to_string(),
+            url: "https://icecast.example.com/live".to_string(),
+            ..Default::default()
+        });
+        assert!(!id.is_empty());
+        assert_eq!(store.get(&id).unwrap().name, "Icecast");
+    }
+}
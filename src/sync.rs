@@ -0,0 +1,243 @@
+//! Favorites sync file
+//!
+//! With a sync file configured, favorites are mirrored to it after every
+//! change and the file is polled for edits made elsewhere, typically another
+//! machine sharing the folder through Syncthing or Nextcloud. A `.opml` path
+//! is written as OPML, anything else as a JSON station list.
+//!
+//! Incoming files are merged against the last synced state: stations removed
+//! from the file since then are removed locally, stations changed in the file
+//! overwrite the local copy, and everything else on either side is kept.
+//! Stations are matched by `stationuuid`, falling back to the stream URL for
+//! OPML, which has no uuid.
+
+use crate::api::Station;
+use crate::error::SyncError;
+use crate::opml;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// How often the sync file is checked for changes
+pub const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+const OPML_TITLE: &str = "COSMIC Radio favorites";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncFormat {
+    Json,
+    Opml,
+}
+
+impl SyncFormat {
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("opml") => Self::Opml,
+            _ => Self::Json,
+        }
+    }
+}
+
+/// What polling the sync file found
+#[derive(Debug, Clone)]
+pub enum SyncRead {
+    /// Nothing there yet; the local favorites get written
+    Missing,
+    /// Same modification time as last seen
+    Unchanged,
+    Changed {
+        modified: SystemTime,
+        text: String,
+    },
+}
+
+/// Serialize `favorites` in `format`
+pub fn render(format: SyncFormat, favorites: &[Station]) -> Result<String, SyncError> {
+    Ok(match format {
+        SyncFormat::Json => serde_json::to_string_pretty(favorites)?,
+        SyncFormat::Opml => opml::export(OPML_TITLE, favorites),
+    })
+}
+
+/// Parse a sync file's contents
+pub fn parse(format: SyncFormat, text: &str) -> Result<Vec<Station>, SyncError> {
+    let mut stations = match format {
+        SyncFormat::Json => serde_json::from_str::<Vec<Station>>(text)?,
+        SyncFormat::Opml => opml::parse(text)?,
+    };
+    for station in &mut stations {
+        station.ensure_uuid();
+        station.ensure_id();
+    }
+    Ok(stations)
+}
+
+/// `favorites` as they look after a trip through the sync file, the form
+/// incoming files are compared against
+pub fn snapshot(format: SyncFormat, favorites: &[Station]) -> Vec<Station> {
+    render(format, favorites)
+        .and_then(|text| parse(format, &text))
+        .unwrap_or_default()
+}
+
+fn matches(a: &Station, b: &Station) -> bool {
+    a.is_same(b) || (!a.url.trim().is_empty() && a.url.trim() == b.url.trim())
+}
+
+/// Copy what `format` carries from `remote` onto `local`
+fn apply(local: &mut Station, remote: &Station, format: SyncFormat) {
+    match format {
+        SyncFormat::Json => *local = remote.clone(),
+        SyncFormat::Opml => {
+            if local.url != remote.url {
+                local.url = remote.url.clone();
+                local.url_resolved = remote.url_resolved.clone();
+            }
+            local.name = remote.name.clone();
+            local.homepage = remote.homepage.clone();
+            local.favicon = remote.favicon.clone();
+            local.tags = remote.tags.clone();
+            local.folder = remote.folder.clone();
+        }
+    }
+}
+
+/// Merge `remote` into `local` relative to `base`, the last synced state.
+/// Returns whether `local` changed.
+pub fn merge(
+    local: &mut Vec<Station>,
+    remote: &[Station],
+    base: &[Station],
+    format: SyncFormat,
+) -> bool {
+    let before = local.clone();
+
+    // Removed from the file since the last sync
+    local.retain(|station| {
+        let synced = base.iter().any(|b| matches(b, station));
+        !synced || remote.iter().any(|r| matches(r, station))
+    });
+
+    for incoming in remote {
+        let previous = base.iter().find(|b| matches(b, incoming));
+        match local.iter_mut().find(|s| matches(s, incoming)) {
+            Some(station) => {
+                if previous != Some(incoming) {
+                    apply(station, incoming, format);
+                }
+            }
+            // Removed here since the last sync; the next write drops it
+            None if previous.is_some() => {}
+            None => local.push(incoming.clone()),
+        }
+    }
+
+    *local != before
+}
+
+/// Check the sync file, reading it when its modification time differs
+/// from `seen`
+pub fn read(path: &Path, seen: Option<SystemTime>) -> std::io::Result<SyncRead> {
+    let modified = match std::fs::metadata(path) {
+        Ok(meta) => meta.modified()?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(SyncRead::Missing),
+        Err(e) => return Err(e),
+    };
+    if seen == Some(modified) {
+        return Ok(SyncRead::Unchanged);
+    }
+    let text = std::fs::read_to_string(path)?;
+    Ok(SyncRead::Changed { modified, text })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn station(name: &str, uuid: &str) -> Station {
+        let mut station = Station {
+            name: name.to_string(),
+            stationuuid: uuid.to_string(),
+            url: format!("http://example.com/{uuid}"),
+            url_resolved: format!("http://example.com/{uuid}"),
+            ..Default::default()
+        };
+        station.ensure_id();
+        station
+    }
+
+    fn names(stations: &[Station]) -> Vec<&str> {
+        stations.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_format_from_extension() {
+        assert_eq!(
+            SyncFormat::for_path(Path::new("/sync/radio.opml")),
+            SyncFormat::Opml
+        );
+        assert_eq!(
+            SyncFormat::for_path(Path::new("/sync/radio.OPML")),
+            SyncFormat::Opml
+        );
+        assert_eq!(
+            SyncFormat::for_path(Path::new("/sync/radio.json")),
+            SyncFormat::Json
+        );
+        assert_eq!(
+            SyncFormat::for_path(Path::new("/sync/radio")),
+            SyncFormat::Json
+        );
+    }
+
+    #[test]
+    fn test_first_sync_is_a_union() {
+        let mut local = vec![station("Jazz", "a"), station("News", "b")];
+        let remote = vec![station("News", "b"), station("Rock", "c")];
+        assert!(merge(&mut local, &remote, &[], SyncFormat::Json));
+        assert_eq!(names(&local), ["Jazz", "News", "Rock"]);
+    }
+
+    #[test]
+    fn test_remote_removal_and_rename() {
+        let base = vec![station("Jazz", "a"), station("News", "b")];
+        let mut local = base.clone();
+        let remote = vec![station("Smooth Jazz", "a")];
+        assert!(merge(&mut local, &remote, &base, SyncFormat::Json));
+        assert_eq!(names(&local), ["Smooth Jazz"]);
+    }
+
+    #[test]
+    fn test_local_changes_survive() {
+        let base = vec![station("Jazz", "a"), station("News", "b")];
+        // Renamed and removed here, nothing changed in the file
+        let mut local = vec![station("My Jazz", "a")];
+        assert!(!merge(&mut local, &base, &base, SyncFormat::Json));
+        assert_eq!(names(&local), ["My Jazz"]);
+    }
+
+    #[test]
+    fn test_opml_matches_by_url_and_keeps_details() {
+        let mut favorite = station("Jazz", "a");
+        favorite.codec = "MP3".to_string();
+        favorite.bitrate = 128;
+        let base = snapshot(SyncFormat::Opml, std::slice::from_ref(&favorite));
+        let mut local = vec![favorite];
+
+        let text = render(SyncFormat::Opml, &local)
+            .unwrap()
+            .replace("Jazz", "Jazz FM");
+        let remote = parse(SyncFormat::Opml, &text).unwrap();
+        assert!(merge(&mut local, &remote, &base, SyncFormat::Opml));
+        assert_eq!(names(&local), ["Jazz FM"]);
+        assert_eq!(local[0].stationuuid, "a");
+        assert_eq!(local[0].bitrate, 128);
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let favorites = vec![station("Jazz", "a"), station("News", "b")];
+        let text = render(SyncFormat::Json, &favorites).unwrap();
+        assert_eq!(parse(SyncFormat::Json, &text).unwrap(), favorites);
+        assert!(parse(SyncFormat::Json, "{}").is_err());
+    }
+}